@@ -5,10 +5,31 @@ use crate::common::*;
 #[derive(Debug, Clone, Copy, PartialEq)]
 /// Resource event shared among tasks.
 pub enum ResourceEvent {
+    /// `format` is the one chosen for the swapchain, to be used as target by the pipelines rendering on it.
     SwapchainCreated {
         external_id: usize,
         swapchain: SwapchainId,
+        format: crate::wgpu::TextureFormat,
     },
     SwapchainDestroyed(SwapchainId),
     SwapchainUpdated(SwapchainId),
+    /// The surface of the swapchain has a zero size, so no frame is acquired until it gets resumed.
+    SwapchainSuspended(SwapchainId),
+    /// The surface of a suspended swapchain got a non-zero size again.
+    SwapchainResumed(SwapchainId),
+    /// Emitted once, when every surface requested so far has a built swapchain.
+    AllSurfacesReady,
+}
+impl ResourceEvent {
+    /// Swapchain the event refers to, if any.
+    pub fn swapchain(&self) -> Option<SwapchainId> {
+        match self {
+            Self::SwapchainCreated { swapchain, .. } => Some(*swapchain),
+            Self::SwapchainDestroyed(swapchain)
+            | Self::SwapchainUpdated(swapchain)
+            | Self::SwapchainSuspended(swapchain)
+            | Self::SwapchainResumed(swapchain) => Some(*swapchain),
+            Self::AllSurfacesReady => None,
+        }
+    }
 }
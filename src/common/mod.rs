@@ -50,6 +50,22 @@ macro_rules! make_id {
 
 pub(crate) use make_id;
 
+/// Check if the resource logs of `level` are emitted with the resource log level `log_level`, see [set_resource_log_level][crate::ResourceManager::set_resource_log_level].
+pub(crate) fn resource_log_enabled(log_level: log::LevelFilter, level: log::Level) -> bool {
+    level <= log_level
+}
+
+/// Emit a resource log only if its level is enabled by the resource log level, see [resource_log_enabled].
+macro_rules! resource_log {
+    ($log_level: expr, $level: expr, target: $target: expr, $($arg: tt)+) => {
+        if $crate::common::resource_log_enabled($log_level, $level) {
+            log::log!(target: $target, $level, $($arg)+);
+        }
+    };
+}
+
+pub(crate) use resource_log;
+
 /// Offset of a 2D element.
 pub struct Offset2D {
     pub x: u32,
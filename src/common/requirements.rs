@@ -1,27 +1,251 @@
 #[derive(Clone)]
-pub struct Requirements(crate::wgpu::Features, crate::wgpu::Limits);
+pub struct Requirements {
+    features: crate::wgpu::Features,
+    limits: crate::wgpu::Limits,
+    minimum_limits: Option<crate::wgpu::Limits>,
+    allow_software_fallback: bool,
+    backends: crate::wgpu::BackendBit,
+    power_preference: Option<crate::wgpu::PowerPreference>,
+    adapter_name: Option<String>,
+}
 impl Requirements {
     pub fn add(&mut self, requirements: (crate::wgpu::Features, crate::wgpu::Limits)) {
-        self.0.insert(requirements.0);
-        self.1 = self.1.clone().max(requirements.1);
+        self.features.insert(requirements.0);
+        self.limits = self.limits.clone().max(requirements.1);
+    }
+
+    /**
+    Set the limits that an adapter must satisfy to be used. Adapters falling short of them are skipped,
+    and if none is left the engine creation fails with
+    [WGpuEngineError::LimitsNotMet][crate::WGpuEngineError::LimitsNotMet].
+    The requested limits are raised to the minimum ones.
+    */
+    pub fn with_minimum_limits(mut self, minimum_limits: crate::wgpu::Limits) -> Self {
+        self.limits = self.limits.clone().max(minimum_limits.clone());
+        self.minimum_limits = Some(minimum_limits);
+        self
+    }
+    pub fn minimum_limits(&self) -> Option<&crate::wgpu::Limits> {
+        self.minimum_limits.as_ref()
+    }
+
+    /**
+    Allow the engine to fall back on a software adapter (like Mesa llvmpipe, through the GL backend)
+    if the main backend has no adapter, like on a headless CI machine without GPU.
+    Software adapters are orders of magnitude slower than the hardware ones, so the fallback
+    is meant for testing, not for rendering interactive content.
+    */
+    pub fn with_software_fallback(mut self, allow_software_fallback: bool) -> Self {
+        self.allow_software_fallback = allow_software_fallback;
+        self
+    }
+    pub fn allow_software_fallback(&self) -> bool {
+        self.allow_software_fallback
+    }
+
+    /**
+    Set the backends the engine looks for adapters on, like [PRIMARY][crate::wgpu::BackendBit::PRIMARY]
+    to use Metal on macOS and DX12 on Windows. Defaults to [VULKAN][crate::wgpu::BackendBit::VULKAN].
+    Every adapter found becomes a device, so requesting several backends can expose the same GPU more than once.
+    */
+    pub fn with_backends(mut self, backends: crate::wgpu::BackendBit) -> Self {
+        self.backends = backends;
+        self
+    }
+    pub fn backends(&self) -> crate::wgpu::BackendBit {
+        self.backends
+    }
+
+    /**
+    Only use the adapters matching the power preference: [HighPerformance][crate::wgpu::PowerPreference::HighPerformance]
+    selects the discrete GPUs, [LowPower][crate::wgpu::PowerPreference::LowPower] the integrated ones.
+    If no adapter matches, the first one is used.
+    */
+    pub fn with_power_preference(mut self, power_preference: crate::wgpu::PowerPreference) -> Self {
+        self.power_preference = Some(power_preference);
+        self
+    }
+    pub fn power_preference(&self) -> Option<crate::wgpu::PowerPreference> {
+        self.power_preference
+    }
+
+    /**
+    Only use the adapters whose name contains `name`, ignoring the case.
+    If no adapter matches, the first one is used.
+    */
+    pub fn with_adapter_name(mut self, name: impl Into<String>) -> Self {
+        self.adapter_name = Some(name.into());
+        self
+    }
+    pub fn adapter_name(&self) -> Option<&str> {
+        self.adapter_name.as_deref()
     }
+
+    /// Check if an adapter matches the requested power preference and name.
+    pub fn matches_adapter(&self, info: &crate::wgpu::AdapterInfo) -> bool {
+        let power_matches = match self.power_preference {
+            Some(crate::wgpu::PowerPreference::HighPerformance) => {
+                info.device_type == crate::wgpu::DeviceType::DiscreteGpu
+            }
+            Some(crate::wgpu::PowerPreference::LowPower) => {
+                info.device_type == crate::wgpu::DeviceType::IntegratedGpu
+            }
+            None => true,
+        };
+        let name_matches = match &self.adapter_name {
+            Some(name) => info.name.to_lowercase().contains(&name.to_lowercase()),
+            None => true,
+        };
+        power_matches && name_matches
+    }
+
+    /**
+    Check the available limits against the minimum ones, returning the first unmet limit
+    as (field, required, available).
+    */
+    pub fn check_minimum_limits(
+        &self,
+        available: &crate::wgpu::Limits,
+    ) -> Result<(), (&'static str, u32, u32)> {
+        let required = match &self.minimum_limits {
+            Some(required) => required,
+            None => return Ok(()),
+        };
+        match unmet_limits(required, available).into_iter().next() {
+            Some(unmet) => Err(unmet),
+            None => Ok(()),
+        }
+    }
+}
+
+/// List every available limit falling short of the required one, as (field, required, available).
+pub fn unmet_limits(
+    required: &crate::wgpu::Limits,
+    available: &crate::wgpu::Limits,
+) -> Vec<(&'static str, u32, u32)> {
+    let limits = [
+        (
+            "max_texture_dimension_1d",
+            required.max_texture_dimension_1d,
+            available.max_texture_dimension_1d,
+        ),
+        (
+            "max_texture_dimension_2d",
+            required.max_texture_dimension_2d,
+            available.max_texture_dimension_2d,
+        ),
+        (
+            "max_texture_dimension_3d",
+            required.max_texture_dimension_3d,
+            available.max_texture_dimension_3d,
+        ),
+        (
+            "max_texture_array_layers",
+            required.max_texture_array_layers,
+            available.max_texture_array_layers,
+        ),
+        (
+            "max_bind_groups",
+            required.max_bind_groups,
+            available.max_bind_groups,
+        ),
+        (
+            "max_dynamic_uniform_buffers_per_pipeline_layout",
+            required.max_dynamic_uniform_buffers_per_pipeline_layout,
+            available.max_dynamic_uniform_buffers_per_pipeline_layout,
+        ),
+        (
+            "max_dynamic_storage_buffers_per_pipeline_layout",
+            required.max_dynamic_storage_buffers_per_pipeline_layout,
+            available.max_dynamic_storage_buffers_per_pipeline_layout,
+        ),
+        (
+            "max_sampled_textures_per_shader_stage",
+            required.max_sampled_textures_per_shader_stage,
+            available.max_sampled_textures_per_shader_stage,
+        ),
+        (
+            "max_samplers_per_shader_stage",
+            required.max_samplers_per_shader_stage,
+            available.max_samplers_per_shader_stage,
+        ),
+        (
+            "max_storage_buffers_per_shader_stage",
+            required.max_storage_buffers_per_shader_stage,
+            available.max_storage_buffers_per_shader_stage,
+        ),
+        (
+            "max_storage_textures_per_shader_stage",
+            required.max_storage_textures_per_shader_stage,
+            available.max_storage_textures_per_shader_stage,
+        ),
+        (
+            "max_uniform_buffers_per_shader_stage",
+            required.max_uniform_buffers_per_shader_stage,
+            available.max_uniform_buffers_per_shader_stage,
+        ),
+        (
+            "max_uniform_buffer_binding_size",
+            required.max_uniform_buffer_binding_size,
+            available.max_uniform_buffer_binding_size,
+        ),
+        (
+            "max_storage_buffer_binding_size",
+            required.max_storage_buffer_binding_size,
+            available.max_storage_buffer_binding_size,
+        ),
+        (
+            "max_vertex_buffers",
+            required.max_vertex_buffers,
+            available.max_vertex_buffers,
+        ),
+        (
+            "max_vertex_attributes",
+            required.max_vertex_attributes,
+            available.max_vertex_attributes,
+        ),
+        (
+            "max_vertex_buffer_array_stride",
+            required.max_vertex_buffer_array_stride,
+            available.max_vertex_buffer_array_stride,
+        ),
+        (
+            "max_push_constant_size",
+            required.max_push_constant_size,
+            available.max_push_constant_size,
+        ),
+    ];
+    limits
+        .iter()
+        .filter(|(_field, required, available)| available < required)
+        .copied()
+        .collect()
 }
 
 impl From<(crate::wgpu::Features, crate::wgpu::Limits)> for Requirements {
     fn from(requirements: (crate::wgpu::Features, crate::wgpu::Limits)) -> Self {
-        Self(requirements.0, requirements.1)
+        Self {
+            features: requirements.0,
+            limits: requirements.1,
+            ..Self::default()
+        }
     }
 }
 impl Into<(crate::wgpu::Features, crate::wgpu::Limits)> for Requirements {
     fn into(self) -> (crate::wgpu::Features, crate::wgpu::Limits) {
-        (self.0, self.1)
+        (self.features, self.limits)
     }
 }
 impl Default for Requirements {
     fn default() -> Self {
-        Requirements(
-            crate::wgpu::Features::default(),
-            crate::wgpu::Limits::default(),
-        )
+        Requirements {
+            features: crate::wgpu::Features::default(),
+            limits: crate::wgpu::Limits::default(),
+            minimum_limits: None,
+            allow_software_fallback: false,
+            backends: crate::wgpu::BackendBit::VULKAN,
+            power_preference: None,
+            adapter_name: None,
+        }
     }
 }
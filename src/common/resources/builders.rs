@@ -6,9 +6,100 @@ use std::borrow::Cow::Borrowed;
 use std::ops::Range;
 use std::sync::{Arc, MutexGuard};
 
+#[derive(Debug, Clone, PartialEq)]
 /// Possible errors related to resource builders.
 pub enum ResourceBuilderError {
     MissingDependencies,
+    InvalidPushConstantStages,
+    /// A push constant range or write is not aligned, or exceeds the device `max_push_constant_size`.
+    InvalidPushConstantRange,
+    InvalidSampleCount,
+    InvalidCubemapCopy,
+    InvalidTextureViewRange,
+    /// The number of color attachments of a render pass differs from the targets of a pipeline set in it.
+    ColorAttachmentsMismatch,
+    /// A [DrawIndexed][RenderCommand::DrawIndexed] has no index buffer bound or exceeds it.
+    InvalidIndexedDraw,
+    /// The arguments of an indirect command are misaligned or out of the buffer, see [validate_indirect][BufferDescriptor::validate_indirect].
+    InvalidIndirectArguments,
+    /// A draw or a dispatch uses a bind group at an index where the bound pipeline expects an incompatible layout.
+    IncompatibleBindGroup(BindGroupId),
+    MissingFeatures(crate::wgpu::Features),
+    /// The commands use resources that lack some usage flags, see [RequiredUsage][crate::RequiredUsage].
+    MissingUsages(Vec<RequiredUsage>),
+    UnsupportedSurface,
+    /// The swapchain exists, but it has no frame acquired for the current dispatch.
+    /// It is usually transient, like right after a resize.
+    SwapchainFrameNotAcquired(SwapchainId),
+    /// A validation warning, turned into an error by the [strict mode][ResourceManager::set_strict].
+    StrictModeWarning,
+    /// The commands reference resources of other devices, as (resource, device).
+    DeviceMismatch(Vec<(EntityId, DeviceId)>),
+    /// The resource belongs to a device that is not one of the task devices.
+    NotTaskDevice(DeviceId),
+    /// A texture imports external memory, which requires the `wgpu_custom` feature.
+    ExternalMemoryUnsupported,
+    /// A render bundle records a command that bundles do not support, see [is_bundle_command][RenderCommand::is_bundle_command].
+    InvalidBundleCommand,
+}
+impl std::fmt::Display for ResourceBuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingDependencies => {
+                write!(f, "some dependencies do not exist or failed to build")
+            }
+            Self::InvalidPushConstantStages => {
+                write!(f, "push constant stages not allowed by the pipeline")
+            }
+            Self::InvalidPushConstantRange => write!(
+                f,
+                "push constant range not aligned or exceeding the device limit"
+            ),
+            Self::InvalidSampleCount => write!(f, "sample count not supported"),
+            Self::InvalidCubemapCopy => write!(
+                f,
+                "copy to a texture that is not a cubemap, or to a face out of range"
+            ),
+            Self::InvalidTextureViewRange => {
+                write!(f, "view range out of the texture mip levels or layers")
+            }
+            Self::ColorAttachmentsMismatch => write!(
+                f,
+                "the color attachments of a render pass differ from the pipeline targets"
+            ),
+            Self::InvalidIndexedDraw => write!(
+                f,
+                "indexed draw without an index buffer bound, or exceeding it"
+            ),
+            Self::InvalidIndirectArguments => write!(
+                f,
+                "indirect arguments misaligned or out of the buffer bounds"
+            ),
+            Self::IncompatibleBindGroup(bind_group) => write!(
+                f,
+                "{} not compatible with the layout of the bound pipeline",
+                bind_group
+            ),
+            Self::MissingFeatures(features) => write!(f, "missing device features {:?}", features),
+            Self::MissingUsages(usages) => write!(f, "resources missing the usages {:?}", usages),
+            Self::UnsupportedSurface => write!(f, "the surface does not support any format"),
+            Self::SwapchainFrameNotAcquired(swapchain) => {
+                write!(f, "no frame acquired for {}", swapchain)
+            }
+            Self::StrictModeWarning => write!(f, "validation warning in strict mode"),
+            Self::DeviceMismatch(resources) => {
+                write!(f, "resources of other devices {:?}", resources)
+            }
+            Self::NotTaskDevice(device) => write!(f, "{} is not one of the task devices", device),
+            Self::ExternalMemoryUnsupported => write!(
+                f,
+                "external memory textures require the wgpu_custom feature (Linux with Vulkan)"
+            ),
+            Self::InvalidBundleCommand => {
+                write!(f, "command not supported by render bundles")
+            }
+        }
+    }
 }
 
 /**
@@ -24,6 +115,7 @@ pub enum ResourceBuilder {
     TextureView(TextureViewBuilder),
     Sampler(SamplerBuilder),
     ShaderModule(ShaderModuleBuilder),
+    QuerySet(QuerySetBuilder),
 
     BindGroupLayout(BindGroupLayoutBuilder),
     BindGroup(BindGroupBuilder),
@@ -31,6 +123,7 @@ pub enum ResourceBuilder {
     PipelineLayout(PipelineLayoutBuilder),
     RenderPipeline(RenderPipelineBuilder),
     ComputePipeline(ComputePipelineBuilder),
+    RenderBundle(RenderBundleBuilder),
     CommandBuffer(CommandBufferBuilder),
 }
 impl ResourceBuilder {
@@ -96,6 +189,13 @@ impl ResourceBuilder {
                     Err(err) => Err(err),
                 }
             }
+            ResourceDescriptor::QuerySet(descriptor) => {
+                let id = QuerySetId::new(id);
+                match QuerySetBuilder::new(resource_manager, id, descriptor) {
+                    Ok(builder) => Ok(Self::QuerySet(builder)),
+                    Err(err) => Err(err),
+                }
+            }
             ResourceDescriptor::BindGroupLayout(descriptor) => {
                 let id = BindGroupLayoutId::new(id);
                 match BindGroupLayoutBuilder::new(resource_manager, id, descriptor) {
@@ -131,6 +231,13 @@ impl ResourceBuilder {
                     Err(err) => Err(err),
                 }
             }
+            ResourceDescriptor::RenderBundle(descriptor) => {
+                let id = RenderBundleId::new(id);
+                match RenderBundleBuilder::new(resource_manager, id, descriptor) {
+                    Ok(builder) => Ok(Self::RenderBundle(builder)),
+                    Err(err) => Err(err),
+                }
+            }
             ResourceDescriptor::CommandBuffer(descriptor) => {
                 let id = CommandBufferId::new(id);
                 match CommandBufferBuilder::new(resource_manager, id, descriptor) {
@@ -140,22 +247,33 @@ impl ResourceBuilder {
             }
         }
     }
-    pub fn build(&self) -> ResourceHandle {
+    /// Build the resource, logging it if enabled by `log_level`, see [resource_log_enabled][crate::common::resource_log_enabled].
+    pub fn build(&self, log_level: log::LevelFilter) -> ResourceHandle {
         match self {
-            Self::Instance(builder) => ResourceHandle::Instance(builder.build()),
-            Self::Device(builder) => ResourceHandle::Device(builder.build()),
-            Self::Swapchain(builder) => ResourceHandle::Swapchain(builder.build()),
-            Self::Buffer(builder) => ResourceHandle::Buffer(builder.build()),
-            Self::Texture(builder) => ResourceHandle::Texture(builder.build()),
+            Self::Instance(builder) => ResourceHandle::Instance(builder.build(log_level)),
+            Self::Device(builder) => ResourceHandle::Device(builder.build(log_level)),
+            Self::Swapchain(builder) => ResourceHandle::Swapchain(builder.build(log_level)),
+            Self::Buffer(builder) => ResourceHandle::Buffer(builder.build(log_level)),
+            Self::Texture(builder) => ResourceHandle::Texture(builder.build(log_level)),
             Self::TextureView(builder) => ResourceHandle::TextureView(builder.build()),
-            Self::Sampler(builder) => ResourceHandle::Sampler(builder.build()),
-            Self::ShaderModule(builder) => ResourceHandle::ShaderModule(builder.build()),
-            Self::BindGroupLayout(builder) => ResourceHandle::BindGroupLayout(builder.build()),
-            Self::BindGroup(builder) => ResourceHandle::BindGroup(builder.build()),
-            Self::PipelineLayout(builder) => ResourceHandle::PipelineLayout(builder.build()),
-            Self::RenderPipeline(builder) => ResourceHandle::RenderPipeline(builder.build()),
-            Self::ComputePipeline(builder) => ResourceHandle::ComputePipeline(builder.build()),
-            Self::CommandBuffer(builder) => ResourceHandle::CommandBuffer(builder.build()),
+            Self::Sampler(builder) => ResourceHandle::Sampler(builder.build(log_level)),
+            Self::ShaderModule(builder) => ResourceHandle::ShaderModule(builder.build(log_level)),
+            Self::QuerySet(builder) => ResourceHandle::QuerySet(builder.build(log_level)),
+            Self::BindGroupLayout(builder) => {
+                ResourceHandle::BindGroupLayout(builder.build(log_level))
+            }
+            Self::BindGroup(builder) => ResourceHandle::BindGroup(builder.build(log_level)),
+            Self::PipelineLayout(builder) => {
+                ResourceHandle::PipelineLayout(builder.build(log_level))
+            }
+            Self::RenderPipeline(builder) => {
+                ResourceHandle::RenderPipeline(builder.build(log_level))
+            }
+            Self::ComputePipeline(builder) => {
+                ResourceHandle::ComputePipeline(builder.build(log_level))
+            }
+            Self::RenderBundle(builder) => ResourceHandle::RenderBundle(builder.build(log_level)),
+            Self::CommandBuffer(builder) => ResourceHandle::CommandBuffer(builder.build(log_level)),
         }
     }
 }
@@ -177,8 +295,8 @@ impl InstanceBuilder {
         let backend = descriptor.backend;
         Ok(Self { id, label, backend })
     }
-    pub fn build(&self) -> InstanceHandle {
-        log::info!(target: "EntityManager","Building {}",self.id);
+    pub fn build(&self, log_level: log::LevelFilter) -> InstanceHandle {
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(crate::wgpu::Instance::new(self.backend))
     }
 }
@@ -193,6 +311,7 @@ pub struct DeviceBuilder {
     pub pci_id: usize,
     pub features: crate::wgpu::Features,
     pub limits: crate::wgpu::Limits,
+    pub tokio: tokio::runtime::Handle,
 }
 impl DeviceBuilder {
     pub fn new(
@@ -213,6 +332,7 @@ impl DeviceBuilder {
         let pci_id = descriptor.pci_id;
         let features = descriptor.features;
         let limits = descriptor.limits.clone();
+        let tokio = resource_manager.runtime_handle().clone();
 
         Ok(Self {
             id,
@@ -222,13 +342,21 @@ impl DeviceBuilder {
             pci_id,
             features,
             limits,
+            tokio,
         })
     }
-    pub fn build(&self) -> DeviceHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> DeviceHandle {
+        // The same GPU can be exposed by several backends of the instance, so the backend must match too.
         let adapter = self
             .instance
             .enumerate_adapters(self.backend)
-            .find(|adapter| adapter.get_info().device == self.pci_id)
+            .find(|adapter| {
+                let info = adapter.get_info();
+                info.device == self.pci_id
+                    && self
+                        .backend
+                        .contains(crate::wgpu::BackendBit::from(info.backend))
+            })
             .unwrap();
 
         let descriptor = crate::wgpu::DeviceDescriptor {
@@ -237,11 +365,9 @@ impl DeviceBuilder {
             limits: self.limits.clone(),
         };
 
-        let (device, queue) = tokio::runtime::Handle::try_current()
-            .unwrap()
-            .block_on(adapter.request_device(&descriptor, None))
-            .unwrap();
-        log::info!(target: "EntityManager","Building {}",self.id);
+        let (device, queue) =
+            crate::utils::block_on(&self.tokio, adapter.request_device(&descriptor, None)).unwrap();
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new((adapter, device, queue))
     }
 }
@@ -252,8 +378,11 @@ pub struct SwapchainBuilder {
     pub label: String,
     pub device: DeviceHandle,
     pub surface: Arc<crate::wgpu::Surface>,
+    pub format: crate::wgpu::TextureFormat,
     pub width: u32,
     pub height: u32,
+    pub present_mode: crate::wgpu::PresentMode,
+    pub clear_color: crate::wgpu::Color,
 }
 impl SwapchainBuilder {
     pub fn new(
@@ -270,21 +399,47 @@ impl SwapchainBuilder {
         };
         let label = descriptor.label.clone();
         let surface = descriptor.surface.clone();
+        let supported_formats = supported_swapchain_formats(&device.0, &surface);
+        let format = match select_swapchain_format(descriptor.format, &supported_formats) {
+            Some(format) => format,
+            None => {
+                log::error!(target: "EntityManager","Failed to build {}: the surface does not support any format",id);
+                return Err(ResourceBuilderError::UnsupportedSurface);
+            }
+        };
+        if !descriptor.composite_alpha_mode.is_supported() {
+            log::error!(target: "EntityManager","Failed to build {}: {:?} composite alpha mode not supported",id,descriptor.composite_alpha_mode);
+            return Err(ResourceBuilderError::UnsupportedSurface);
+        }
         let width = descriptor.width;
         let height = descriptor.height;
+        let present_mode = descriptor.present_mode;
+        let clear_color = descriptor.clear_color;
         Ok(Self {
             id,
             label,
             device,
             surface,
+            format,
             width,
             height,
+            present_mode,
+            clear_color,
         })
     }
-    pub fn build(&self) -> SwapchainHandle {
-        log::info!(target: "EntityManager","Building {}",self.id);
+    pub fn build(&self, log_level: log::LevelFilter) -> SwapchainHandle {
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(
-            Swapchain::new(&self.device, self.surface.clone(), self.width, self.height).unwrap(),
+            Swapchain::new(
+                &self.device,
+                self.surface.clone(),
+                self.format,
+                self.width,
+                self.height,
+                self.present_mode,
+                self.clear_color,
+            )
+            .unwrap(),
         )
     }
 }
@@ -324,14 +479,14 @@ impl BufferBuilder {
             usage,
         })
     }
-    pub fn build(&self) -> BufferHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> BufferHandle {
         let descriptor = crate::wgpu::BufferDescriptor {
             label: Some(self.label.as_str()),
             size: self.size,
             usage: self.usage,
             mapped_at_creation: false,
         };
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_buffer(&descriptor))
     }
 }
@@ -356,6 +511,12 @@ impl TextureBuilder {
         id: TextureId,
         descriptor: &TextureDescriptor,
     ) -> Result<Self, ResourceBuilderError> {
+        #[cfg(not(feature = "wgpu_custom"))]
+        if descriptor.source != TextureSource::Local {
+            log::error!(target: "EntityManager","Failed to gather Texture resources: {} imports external memory, which requires the wgpu_custom feature on Linux with the Vulkan backend",id);
+            return Err(ResourceBuilderError::ExternalMemoryUnsupported);
+        }
+
         let device = match resource_manager.device_handle_ref(&descriptor.device) {
             Some(device) => device.clone(),
             None => {
@@ -364,6 +525,11 @@ impl TextureBuilder {
             }
         };
 
+        let format_features = device.0.get_texture_format_features(descriptor.format);
+        if !descriptor.validate_sample_count(format_features.allowed_usages) {
+            return Err(ResourceBuilderError::InvalidSampleCount);
+        }
+
         let label = descriptor.label.clone();
         let source = descriptor.source.clone();
         let size = descriptor.size;
@@ -386,7 +552,7 @@ impl TextureBuilder {
             usage,
         })
     }
-    pub fn build(&self) -> TextureHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> TextureHandle {
         match &self.source {
             TextureSource::Local => {
                 let descriptor = crate::wgpu::TextureDescriptor {
@@ -398,7 +564,7 @@ impl TextureBuilder {
                     format: self.format,
                     usage: self.usage,
                 };
-                log::info!(target: "EntityManager","Building {}",self.id);
+                resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
                 Arc::new(self.device.1.create_texture(&descriptor))
             }
             #[cfg(feature = "wgpu_custom")]
@@ -411,7 +577,9 @@ impl TextureBuilder {
                     label: Some(self.label.as_str()),
                     external_memory: crate::wgpu::ExternalImageMemory::DmaBuf(
                         (*fd).into(),
-                        drm_properties.clone(),
+                        drm_properties
+                            .as_ref()
+                            .map(|properties| properties.properties().clone()),
                     ),
                     size: self.size,
                     offset: *offset,
@@ -422,7 +590,7 @@ impl TextureBuilder {
                     usage: self.usage,
                 };
 
-                log::info!(target: "EntityManager","Building {}",self.id);
+                resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
                 Arc::new(self.device.1.import_texture(descriptor))
             }
             #[cfg(feature = "wgpu_custom")]
@@ -457,9 +625,13 @@ impl TextureBuilder {
                     format: self.format,
                     usage: self.usage,
                 };
-                log::info!(target: "EntityManager","Building {}",self.id);
+                resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
                 Arc::new(self.device.1.import_texture(descriptor))
             }
+            #[cfg(not(feature = "wgpu_custom"))]
+            TextureSource::DmaBuf { .. } | TextureSource::OpaqueFd { .. } => {
+                unreachable!("External memory textures are refused by TextureBuilder::new")
+            }
         }
     }
 }
@@ -490,6 +662,13 @@ impl TextureViewBuilder {
             log::error!(target: "EntityManager","Failed to gather TextureView resources: Texture {} not found",descriptor.texture);
             return Err(ResourceBuilderError::MissingDependencies);
         };
+        match resource_manager.texture_descriptor_ref(&descriptor.texture) {
+            Some(texture_descriptor) if !descriptor.is_within(texture_descriptor) => {
+                log::error!(target: "EntityManager","Failed to gather TextureView resources: layers {}..{:?} or mip levels {}..{:?} out of the range of Texture {}",descriptor.base_array_layer,descriptor.array_layer_count,descriptor.base_mip_level,descriptor.mip_level_count,descriptor.texture);
+                return Err(ResourceBuilderError::InvalidTextureViewRange);
+            }
+            _ => {}
+        }
 
         let label = descriptor.label.clone();
         let format = descriptor.format;
@@ -569,7 +748,7 @@ impl SamplerBuilder {
         let lod_min_clamp = descriptor.lod_min_clamp;
         let lod_max_clamp = descriptor.lod_max_clamp;
         let compare = descriptor.compare;
-        let anisotropy_clamp = descriptor.anisotropy_clamp;
+        let anisotropy_clamp = descriptor.capped_anisotropy(resource_manager.max_anisotropy());
         let border_color = descriptor.border_color;
 
         Ok(Self {
@@ -589,7 +768,7 @@ impl SamplerBuilder {
             border_color,
         })
     }
-    pub fn build(&self) -> SamplerHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> SamplerHandle {
         let descriptor = crate::wgpu::SamplerDescriptor {
             label: Some(self.label.as_str()),
             address_mode_u: self.address_mode_u,
@@ -604,7 +783,7 @@ impl SamplerBuilder {
             anisotropy_clamp: self.anisotropy_clamp,
             border_color: self.border_color,
         };
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_sampler(&descriptor))
     }
 }
@@ -643,7 +822,7 @@ impl ShaderModuleBuilder {
             flags,
         })
     }
-    pub fn build(&self) -> ShaderModuleHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> ShaderModuleHandle {
         let descriptor = crate::wgpu::ShaderModuleDescriptor {
             label: Some(self.label.as_str()),
             source: match self.source {
@@ -656,11 +835,63 @@ impl ShaderModuleBuilder {
             },
             flags: self.flags,
         };
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_shader_module(&descriptor))
     }
 }
 
+#[derive(Debug, Clone)]
+/// Builder for a [QuerySet][crate::wgpu::QuerySet] object.
+pub struct QuerySetBuilder {
+    pub id: QuerySetId,
+    pub device: DeviceHandle,
+    pub label: String,
+    pub ty: crate::wgpu::QueryType,
+    pub count: u32,
+}
+impl QuerySetBuilder {
+    pub fn new(
+        resource_manager: &ResourceManager,
+        id: QuerySetId,
+        descriptor: &QuerySetDescriptor,
+    ) -> Result<Self, ResourceBuilderError> {
+        let device = match resource_manager.device_handle_ref(&descriptor.device) {
+            Some(device) => device.clone(),
+            None => {
+                log::error!(target: "EntityManager","Failed to gather QuerySet resources: parent Device of {} not found",id);
+                return Err(ResourceBuilderError::MissingDependencies);
+            }
+        };
+
+        let missing_features = descriptor.required_features() - device.1.features();
+        if !missing_features.is_empty() {
+            log::error!(target: "EntityManager","Failed to gather QuerySet resources: the queries of {} require the missing device features {:?}",id,missing_features);
+            return Err(ResourceBuilderError::MissingFeatures(missing_features));
+        }
+
+        let label = descriptor.label.clone();
+        let ty = descriptor.ty.clone();
+        let count = descriptor.count;
+
+        Ok(Self {
+            id,
+            device,
+            label,
+            ty,
+            count,
+        })
+    }
+    pub fn build(&self, log_level: log::LevelFilter) -> QuerySetHandle {
+        let descriptor = crate::wgpu::QuerySetDescriptor {
+            label: Some(self.label.as_str()),
+            ty: self.ty.clone(),
+            count: self.count,
+        };
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
+        Arc::new(self.device.1.create_query_set(&descriptor))
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Builder for a [BindGroupLayout][crate::wgpu::BindGroupLayout] object.
 pub struct BindGroupLayoutBuilder {
@@ -692,12 +923,12 @@ impl BindGroupLayoutBuilder {
             entries,
         })
     }
-    pub fn build(&self) -> BindGroupLayoutHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> BindGroupLayoutHandle {
         let descriptor = crate::wgpu::BindGroupLayoutDescriptor {
             label: Some(self.label.as_str()),
             entries: self.entries.as_slice(),
         };
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_bind_group_layout(&descriptor))
     }
 }
@@ -936,7 +1167,7 @@ impl BindGroupBuilder {
             entries,
         })
     }
-    pub fn build(&self) -> BindGroupHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> BindGroupHandle {
         let mut supports1: Vec<Vec<crate::wgpu::BufferBinding>> = Vec::new();
         let mut supports2: Vec<Vec<&crate::wgpu::TextureView>> = Vec::new();
         self.entries.iter().for_each(|_| {
@@ -959,7 +1190,7 @@ impl BindGroupBuilder {
             layout: self.layout.as_ref(),
             entries: entries.as_slice(),
         };
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_bind_group(&descriptor))
     }
 }
@@ -997,6 +1228,19 @@ impl PipelineLayoutBuilder {
             }
         }
 
+        let alignment = crate::wgpu::PUSH_CONSTANT_ALIGNMENT;
+        let max_push_constant_size = device.1.limits().max_push_constant_size;
+        for range in &descriptor.push_constant_ranges {
+            if range.range.start % alignment != 0 || range.range.end % alignment != 0 {
+                log::error!(target: "EntityManager","Failed to gather PipelineLayout resources: push constant range {:?} of {} is not aligned to {}, see utils::align_push_constant_size",range.range,id,alignment);
+                return Err(ResourceBuilderError::InvalidPushConstantRange);
+            }
+            if range.range.end > max_push_constant_size {
+                log::error!(target: "EntityManager","Failed to gather PipelineLayout resources: push constant range {:?} of {} exceeds the max_push_constant_size of {}",range.range,id,max_push_constant_size);
+                return Err(ResourceBuilderError::InvalidPushConstantRange);
+            }
+        }
+
         let label = descriptor.label.clone();
         let push_constant_ranges = descriptor.push_constant_ranges.clone();
 
@@ -1008,7 +1252,7 @@ impl PipelineLayoutBuilder {
             push_constant_ranges,
         })
     }
-    pub fn build(&self) -> PipelineLayoutHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> PipelineLayoutHandle {
         let bind_group_layouts: Vec<&crate::wgpu::BindGroupLayout> = self
             .bind_group_layouts
             .iter()
@@ -1019,7 +1263,7 @@ impl PipelineLayoutBuilder {
             bind_group_layouts: bind_group_layouts.as_slice(),
             push_constant_ranges: self.push_constant_ranges.as_slice(),
         };
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_pipeline_layout(&descriptor))
     }
 }
@@ -1154,6 +1398,51 @@ pub struct RenderPipelineBuilder {
     pub fragment: Option<FragmentStateBuilder>,
 }
 
+/**
+Warn about the bind group layout entries of the pipeline layout whose visibility does not cover the stages
+of the pipeline using them. The check needs to reflect the shaders, so it is only done in debug builds.
+*/
+fn check_pipeline_visibility(
+    resource_manager: &ResourceManager,
+    id: impl std::fmt::Display,
+    layout: Option<&PipelineLayoutId>,
+    stages: &[(ShaderModuleId, &str)],
+) {
+    let bind_group_layouts =
+        match layout.and_then(|layout| resource_manager.pipeline_layout_descriptor_ref(layout)) {
+            Some(layout) => &layout.bind_group_layouts,
+            None => return,
+        };
+
+    for (group, bind_group_layout) in bind_group_layouts.iter().enumerate() {
+        let entries = match resource_manager.bind_group_layout_descriptor_ref(bind_group_layout) {
+            Some(bind_group_layout) => &bind_group_layout.entries,
+            None => continue,
+        };
+        for (module, entry_point) in stages.iter() {
+            let source = match resource_manager.shader_module_descriptor_ref(module) {
+                Some(shader_module) => &shader_module.source,
+                None => continue,
+            };
+            match crate::utils::reflection::check_visibility(
+                source,
+                &[*entry_point],
+                group as u32,
+                entries,
+            ) {
+                Ok(mismatches) => {
+                    for (binding, missing) in mismatches {
+                        log::warn!(target: "EntityManager","{}: binding {} of group {} is used by {:?} in `{}`, but its visibility does not include it",id,binding,group,missing,entry_point);
+                    }
+                }
+                Err(err) => {
+                    log::debug!(target: "EntityManager","{}: skipping the visibility check of `{}`: {:?}",id,entry_point,err);
+                }
+            }
+        }
+    }
+}
+
 impl RenderPipelineBuilder {
     pub fn new(
         resource_manager: &ResourceManager,
@@ -1243,6 +1532,23 @@ impl RenderPipelineBuilder {
 
         let label = descriptor.label.clone();
 
+        if cfg!(debug_assertions) {
+            let mut stages = vec![(
+                descriptor.vertex.module,
+                descriptor.vertex.entry_point.as_str(),
+            )];
+            if let Some(fragment) = &descriptor.fragment {
+                stages.push((fragment.module, fragment.entry_point.as_str()));
+            }
+            check_pipeline_visibility(resource_manager, id, descriptor.layout.as_ref(), &stages);
+        }
+
+        let missing_features = descriptor.required_features() - device.1.features();
+        if !missing_features.is_empty() {
+            log::error!(target: "EntityManager","Failed to gather RenderPipeline resources: the PrimitiveState of {} requires the missing device features {:?}",id,missing_features);
+            return Err(ResourceBuilderError::MissingFeatures(missing_features));
+        }
+
         let primitive = descriptor.primitive;
         let multisample = descriptor.multisample;
 
@@ -1258,7 +1564,7 @@ impl RenderPipelineBuilder {
             fragment,
         })
     }
-    pub fn build(&self) -> RenderPipelineHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> RenderPipelineHandle {
         let mut support = Vec::new();
         let descriptor = crate::wgpu::RenderPipelineDescriptor {
             label: Some(self.label.as_str()),
@@ -1276,7 +1582,7 @@ impl RenderPipelineBuilder {
                 .map(|fragment_state| fragment_state.build()),
         };
 
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_render_pipeline(&descriptor))
     }
 }
@@ -1316,6 +1622,21 @@ impl ComputePipelineBuilder {
             None => None,
         };
 
+        if let Some(pipeline_layout) = descriptor
+            .layout
+            .as_ref()
+            .and_then(|id| resource_manager.pipeline_layout_descriptor_ref(id))
+        {
+            if let Some(range) = pipeline_layout
+                .push_constant_ranges
+                .iter()
+                .find(|range| range.stages != crate::wgpu::ShaderStage::COMPUTE)
+            {
+                log::error!(target: "EntityManager","Failed to gather ComputePipeline resources: push constant range {:?} of {} declares {:?} stages, only COMPUTE is allowed",range.range,id,range.stages);
+                return Err(ResourceBuilderError::InvalidPushConstantStages);
+            }
+        }
+
         let module = match resource_manager.shader_module_handle_ref(&descriptor.module) {
             Some(module) => module.clone(),
             None => {
@@ -1324,6 +1645,15 @@ impl ComputePipelineBuilder {
             }
         };
 
+        if cfg!(debug_assertions) {
+            check_pipeline_visibility(
+                resource_manager,
+                id,
+                descriptor.layout.as_ref(),
+                &[(descriptor.module, descriptor.entry_point.as_str())],
+            );
+        }
+
         let label = descriptor.label.clone();
         let entry_point = descriptor.entry_point.clone();
 
@@ -1336,7 +1666,7 @@ impl ComputePipelineBuilder {
             entry_point,
         })
     }
-    pub fn build(&self) -> ComputePipelineHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> ComputePipelineHandle {
         let descriptor = crate::wgpu::ComputePipelineDescriptor {
             label: Some(self.label.as_str()),
             layout: self
@@ -1346,24 +1676,138 @@ impl ComputePipelineBuilder {
             module: self.module.as_ref(),
             entry_point: self.entry_point.as_ref(),
         };
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(self.device.1.create_compute_pipeline(&descriptor))
     }
 }
 
+/// Check that a push constant write is aligned to [PUSH_CONSTANT_ALIGNMENT][crate::wgpu::PUSH_CONSTANT_ALIGNMENT].
+fn check_push_constant_write(offset: u32, data: &[u8]) -> Result<(), ResourceBuilderError> {
+    let alignment = crate::wgpu::PUSH_CONSTANT_ALIGNMENT;
+    if offset % alignment != 0 || data.len() as u32 % alignment != 0 {
+        log::error!(target: "EntityManager","Failed to gather SetPushConstants resources: offset {} and size {} must be aligned to {}",offset,data.len(),alignment);
+        return Err(ResourceBuilderError::InvalidPushConstantRange);
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 /// Builder for a [ComputeCommand][ComputeCommand] object.
-/// Never used nor implemented, so it will panic if used.
-pub enum ComputeCommandBuilder {}
+pub enum ComputeCommandBuilder {
+    SetPipeline {
+        pipeline: ComputePipelineHandle,
+    },
+    SetPushConstants {
+        offset: u32,
+        data: Vec<u8>,
+    },
+    SetBindGroup {
+        index: u32,
+        bind_group: BindGroupHandle,
+        offsets: Vec<crate::wgpu::DynamicOffset>,
+    },
+    Dispatch {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    DispatchIndirect {
+        buffer: BufferHandle,
+        offset: crate::wgpu::BufferAddress,
+    },
+}
 impl ComputeCommandBuilder {
     pub fn new(
-        _resource_manager: &ResourceManager,
-        _descriptor: &ComputeCommand,
+        resource_manager: &ResourceManager,
+        descriptor: &ComputeCommand,
     ) -> Result<Self, ResourceBuilderError> {
-        panic!()
+        Ok(match descriptor {
+            ComputeCommand::SetPipeline { pipeline } => {
+                let pipeline = match resource_manager.compute_pipeline_handle_ref(pipeline) {
+                    Some(pipeline) => pipeline.clone(),
+                    None => {
+                        log::error!(target: "EntityManager","Failed to gather ComputeCommand::SetPipeline resources: Pipeline {} not found",pipeline);
+                        return Err(ResourceBuilderError::MissingDependencies);
+                    }
+                };
+                Self::SetPipeline { pipeline }
+            }
+            ComputeCommand::SetPushConstants {
+                stages,
+                offset,
+                data,
+            } => {
+                if *stages != crate::wgpu::ShaderStage::COMPUTE {
+                    log::error!(target: "EntityManager","Failed to gather ComputeCommand::SetPushConstants resources: {:?} stages used, only COMPUTE is allowed",stages);
+                    return Err(ResourceBuilderError::InvalidPushConstantStages);
+                }
+                check_push_constant_write(*offset, data)?;
+                let offset = *offset;
+                let data = data.clone();
+                Self::SetPushConstants { offset, data }
+            }
+            ComputeCommand::SetBindGroup {
+                index,
+                bind_group,
+                offsets,
+            } => {
+                let bind_group = match resource_manager.bind_group_handle_ref(bind_group) {
+                    Some(bind_group) => bind_group.clone(),
+                    None => {
+                        log::error!(target: "EntityManager","Failed to gather ComputeCommand::SetBindGroup resources: BindGroup {} not found",bind_group);
+                        return Err(ResourceBuilderError::MissingDependencies);
+                    }
+                };
+                let index = *index;
+                let offsets = offsets.clone();
+                Self::SetBindGroup {
+                    index,
+                    bind_group,
+                    offsets,
+                }
+            }
+            ComputeCommand::Dispatch { x, y, z } => {
+                let x = *x;
+                let y = *y;
+                let z = *z;
+                Self::Dispatch { x, y, z }
+            }
+            ComputeCommand::DispatchIndirect { buffer, offset } => {
+                let args_size = std::mem::size_of::<[u32; 3]>() as crate::wgpu::BufferAddress;
+                match resource_manager.buffer_descriptor_ref(buffer) {
+                    Some(descriptor) if !descriptor.validate_indirect(*offset, args_size) => {
+                        log::error!(target: "EntityManager","Failed to gather ComputeCommand::DispatchIndirect resources: invalid indirect arguments in Buffer {}",buffer);
+                        return Err(ResourceBuilderError::InvalidIndirectArguments);
+                    }
+                    _ => {}
+                }
+                let buffer = match resource_manager.buffer_handle_ref(buffer) {
+                    Some(buffer) => buffer.clone(),
+                    None => {
+                        log::error!(target: "EntityManager","Failed to gather ComputeCommand::DispatchIndirect resources: Buffer {} not found",buffer);
+                        return Err(ResourceBuilderError::MissingDependencies);
+                    }
+                };
+                let offset = *offset;
+                Self::DispatchIndirect { buffer, offset }
+            }
+        })
     }
-    pub fn build<'a>(&'a self, _encoder: &mut crate::wgpu::ComputePass<'a>) -> bool {
-        panic!()
+    pub fn build<'a>(&'a self, encoder: &mut crate::wgpu::ComputePass<'a>) -> bool {
+        match self {
+            Self::SetPipeline { pipeline } => encoder.set_pipeline(pipeline),
+            Self::SetPushConstants { offset, data } => {
+                encoder.set_push_constants(*offset, data.as_slice())
+            }
+            Self::SetBindGroup {
+                index,
+                bind_group,
+                offsets,
+            } => encoder.set_bind_group(*index, bind_group, offsets),
+            Self::Dispatch { x, y, z } => encoder.dispatch(*x, *y, *z),
+            Self::DispatchIndirect { buffer, offset } => encoder.dispatch_indirect(buffer, *offset),
+        }
+        true
     }
 }
 
@@ -1402,8 +1846,57 @@ pub enum RenderCommandBuilder {
         base_vertex: i32,
         instances: Range<u32>,
     },
+    DrawIndirect {
+        buffer: BufferHandle,
+        offset: crate::wgpu::BufferAddress,
+    },
+    DrawIndexedIndirect {
+        buffer: BufferHandle,
+        offset: crate::wgpu::BufferAddress,
+    },
+    SetViewport {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        min_depth: f32,
+        max_depth: f32,
+    },
+    SetScissorRect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    ExecuteBundles {
+        bundles: Vec<RenderBundleHandle>,
+    },
 }
 impl RenderCommandBuilder {
+    /// Validate the arguments of an indirect draw and gather its buffer.
+    fn indirect_buffer(
+        resource_manager: &ResourceManager,
+        command: &str,
+        buffer: &BufferId,
+        offset: crate::wgpu::BufferAddress,
+        args_size: crate::wgpu::BufferAddress,
+    ) -> Result<BufferHandle, ResourceBuilderError> {
+        match resource_manager.buffer_descriptor_ref(buffer) {
+            Some(descriptor) if !descriptor.validate_indirect(offset, args_size) => {
+                log::error!(target: "EntityManager","Failed to gather RenderCommand::{} resources: invalid indirect arguments in Buffer {}",command,buffer);
+                return Err(ResourceBuilderError::InvalidIndirectArguments);
+            }
+            _ => {}
+        }
+        match resource_manager.buffer_handle_ref(buffer) {
+            Some(buffer) => Ok(buffer.clone()),
+            None => {
+                log::error!(target: "EntityManager","Failed to gather RenderCommand::{} resources: Buffer {} not found",command,buffer);
+                Err(ResourceBuilderError::MissingDependencies)
+            }
+        }
+    }
+
     pub fn new(
         resource_manager: &ResourceManager,
         descriptor: &RenderCommand,
@@ -1424,6 +1917,7 @@ impl RenderCommandBuilder {
                 offset,
                 data,
             } => {
+                check_push_constant_write(*offset, data)?;
                 let stages = *stages;
                 let offset = *offset;
                 let data = data.clone();
@@ -1518,6 +2012,71 @@ impl RenderCommandBuilder {
                     instances,
                 }
             }
+            RenderCommand::DrawIndirect { buffer, offset } => {
+                let args_size = std::mem::size_of::<[u32; 4]>() as crate::wgpu::BufferAddress;
+                let buffer = Self::indirect_buffer(
+                    resource_manager,
+                    "DrawIndirect",
+                    buffer,
+                    *offset,
+                    args_size,
+                )?;
+                let offset = *offset;
+                Self::DrawIndirect { buffer, offset }
+            }
+            RenderCommand::DrawIndexedIndirect { buffer, offset } => {
+                let args_size = std::mem::size_of::<[u32; 5]>() as crate::wgpu::BufferAddress;
+                let buffer = Self::indirect_buffer(
+                    resource_manager,
+                    "DrawIndexedIndirect",
+                    buffer,
+                    *offset,
+                    args_size,
+                )?;
+                let offset = *offset;
+                Self::DrawIndexedIndirect { buffer, offset }
+            }
+            RenderCommand::SetViewport {
+                x,
+                y,
+                width,
+                height,
+                min_depth,
+                max_depth,
+            } => Self::SetViewport {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+                min_depth: *min_depth,
+                max_depth: *max_depth,
+            },
+            RenderCommand::SetScissorRect {
+                x,
+                y,
+                width,
+                height,
+            } => Self::SetScissorRect {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+            },
+            RenderCommand::ExecuteBundles { bundles } => {
+                let mut bundle_handles = Vec::new();
+                for bundle in bundles {
+                    match resource_manager.render_bundle_handle_ref(bundle) {
+                        Some(handle) => bundle_handles.push(handle.clone()),
+                        None => {
+                            log::error!(target: "EntityManager","Failed to gather RenderCommand::ExecuteBundles resources: RenderBundle {} not found",bundle);
+                            return Err(ResourceBuilderError::MissingDependencies);
+                        }
+                    }
+                }
+                Self::ExecuteBundles {
+                    bundles: bundle_handles,
+                }
+            }
         })
     }
     pub fn build<'a>(&'a self, encoder: &mut crate::wgpu::RenderPass<'a>) -> bool {
@@ -1552,9 +2111,148 @@ impl RenderCommandBuilder {
                 base_vertex,
                 instances,
             } => encoder.draw_indexed(indices.clone(), *base_vertex, instances.clone()),
+            Self::DrawIndirect { buffer, offset } => encoder.draw_indirect(buffer, *offset),
+            Self::DrawIndexedIndirect { buffer, offset } => {
+                encoder.draw_indexed_indirect(buffer, *offset)
+            }
+            Self::SetViewport {
+                x,
+                y,
+                width,
+                height,
+                min_depth,
+                max_depth,
+            } => encoder.set_viewport(*x, *y, *width, *height, *min_depth, *max_depth),
+            Self::SetScissorRect {
+                x,
+                y,
+                width,
+                height,
+            } => encoder.set_scissor_rect(*x, *y, *width, *height),
+            Self::ExecuteBundles { bundles } => {
+                encoder.execute_bundles(bundles.iter().map(|bundle| bundle.as_ref()))
+            }
         }
         true
     }
+    /// Record the command in a render bundle. Returns false for the commands bundles do not support.
+    pub fn build_bundle<'a>(&'a self, encoder: &mut crate::wgpu::RenderBundleEncoder<'a>) -> bool {
+        match self {
+            Self::SetPipeline { pipeline } => encoder.set_pipeline(pipeline),
+            Self::SetPushConstants {
+                stages,
+                offset,
+                data,
+            } => encoder.set_push_constants(*stages, *offset, data.as_slice()),
+            Self::SetBindGroup {
+                index,
+                bind_group,
+                offsets,
+            } => encoder.set_bind_group(*index, bind_group, offsets),
+            Self::SetVertexBuffer {
+                slot,
+                buffer,
+                slice,
+            } => encoder.set_vertex_buffer(*slot, buffer.slice(slice.clone())),
+            Self::SetIndexBuffer {
+                index_format,
+                buffer,
+                slice,
+            } => encoder.set_index_buffer(buffer.slice(slice.clone()), *index_format),
+            Self::Draw {
+                vertices,
+                instances,
+            } => encoder.draw(vertices.clone(), instances.clone()),
+            Self::DrawIndexed {
+                indices,
+                base_vertex,
+                instances,
+            } => encoder.draw_indexed(indices.clone(), *base_vertex, instances.clone()),
+            Self::DrawIndirect { buffer, offset } => encoder.draw_indirect(buffer, *offset),
+            Self::DrawIndexedIndirect { buffer, offset } => {
+                encoder.draw_indexed_indirect(buffer, *offset)
+            }
+            Self::SetViewport { .. }
+            | Self::SetScissorRect { .. }
+            | Self::ExecuteBundles { .. } => return false,
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Builder for a [RenderBundle][crate::wgpu::RenderBundle] object.
+pub struct RenderBundleBuilder {
+    pub id: RenderBundleId,
+    pub device: DeviceHandle,
+    pub label: String,
+    pub color_formats: Vec<crate::wgpu::TextureFormat>,
+    pub depth_stencil_format: Option<crate::wgpu::TextureFormat>,
+    pub sample_count: u32,
+    pub commands: Vec<RenderCommandBuilder>,
+}
+impl RenderBundleBuilder {
+    pub fn new(
+        resource_manager: &ResourceManager,
+        id: RenderBundleId,
+        descriptor: &RenderBundleDescriptor,
+    ) -> Result<Self, ResourceBuilderError> {
+        let device = match resource_manager.device_handle_ref(&descriptor.device) {
+            Some(device) => device.clone(),
+            None => {
+                log::error!(target: "EntityManager","Failed to gather RenderBundle resources: parent Device of {} not found",id);
+                return Err(ResourceBuilderError::MissingDependencies);
+            }
+        };
+        if let Some(command) = descriptor
+            .commands
+            .iter()
+            .find(|command| !command.is_bundle_command())
+        {
+            log::error!(target: "EntityManager","Failed to gather RenderBundle resources: {} records {:?}, that render bundles do not support",id,command);
+            return Err(ResourceBuilderError::InvalidBundleCommand);
+        }
+        if !resource_manager.validate_indexed_draws(&descriptor.commands) {
+            log::error!(target: "EntityManager","Failed to gather RenderBundle resources: invalid indexed draws in {}",id);
+            return Err(ResourceBuilderError::InvalidIndexedDraw);
+        }
+        if let Some((pipeline, index, bind_group)) =
+            resource_manager.incompatible_render_bind_group(&descriptor.commands)
+        {
+            log::error!(target: "EntityManager","Failed to gather RenderBundle resources: {} at index {} is not compatible with the layout of {} in {}",bind_group,index,pipeline,id);
+            return Err(ResourceBuilderError::IncompatibleBindGroup(bind_group));
+        }
+
+        let mut commands = Vec::new();
+        for command in &descriptor.commands {
+            commands.push(RenderCommandBuilder::new(resource_manager, command)?);
+        }
+        Ok(Self {
+            id,
+            device,
+            label: descriptor.label.clone(),
+            color_formats: descriptor.color_formats.clone(),
+            depth_stencil_format: descriptor.depth_stencil_format,
+            sample_count: descriptor.sample_count,
+            commands,
+        })
+    }
+    pub fn build(&self, log_level: log::LevelFilter) -> RenderBundleHandle {
+        let descriptor = crate::wgpu::RenderBundleEncoderDescriptor {
+            label: Some(self.label.as_str()),
+            color_formats: self.color_formats.as_slice(),
+            depth_stencil_format: self.depth_stencil_format,
+            sample_count: self.sample_count,
+        };
+        let mut encoder = self.device.1.create_render_bundle_encoder(&descriptor);
+        for command in &self.commands {
+            command.build_bundle(&mut encoder);
+        }
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
+        Arc::new(encoder.finish(&crate::wgpu::RenderBundleDescriptor {
+            label: Some(self.label.as_str()),
+        }))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1816,8 +2514,9 @@ impl ColorViewBuilder {
             },
             ColorView::Swapchain(ref id) => match resource_manager.swapchain_handle_ref(id) {
                 Some(swapchain) => {
-                    if swapchain.current_frame().is_none() {
-                        return Err(ResourceBuilderError::MissingDependencies);
+                    if !swapchain.acquire_deferred_frame() {
+                        log::debug!(target: "EntityManager","Failed to gather Command::RenderPass resources: Swapchain {} has no frame acquired",id);
+                        return Err(ResourceBuilderError::SwapchainFrameNotAcquired(*id));
                     }
                     Ok(Self::Swapchain(swapchain.clone()))
                 }
@@ -1843,6 +2542,12 @@ impl RenderPassColorAttachmentBuilder {
         descriptor: &RenderPassColorAttachment,
     ) -> Result<Self, ResourceBuilderError> {
         let view = ColorViewBuilder::new(resource_manager, &descriptor.view)?;
+        if descriptor.stores_resolved_source() {
+            log::warn!(target: "EntityManager","Color attachment {:?} is resolved but also stored: consider a store operation of false to save bandwidth",descriptor.view);
+            if resource_manager.is_strict() {
+                return Err(ResourceBuilderError::StrictModeWarning);
+            }
+        }
 
         let resolve_target = match descriptor.resolve_target {
             Some(ref texture_view) => {
@@ -1930,6 +2635,10 @@ pub enum CommandBuilder {
     BufferToTexture(BufferToTextureCopyBuilder),
     TextureToTexture(TextureToTextureCopyBuilder),
     TextureToBuffer(TextureToBufferCopyBuilder),
+    WriteTimestamp {
+        query_set: QuerySetHandle,
+        index: u32,
+    },
     ComputePass {
         commands: Vec<ComputeCommandBuilder>,
     },
@@ -1970,7 +2679,77 @@ impl CommandBuilder {
                     Err(err) => Err(err),
                 }
             }
+            Command::CopyToCubemapFace {
+                src,
+                dst_cubemap,
+                face,
+            } => {
+                let src_descriptor = match resource_manager.texture_descriptor_ref(src) {
+                    Some(descriptor) => descriptor,
+                    None => {
+                        log::error!(target: "EntityManager","Failed to gather Command::CopyToCubemapFace resources: Texture source {} not found",src);
+                        return Err(ResourceBuilderError::MissingDependencies);
+                    }
+                };
+                let dst_descriptor = match resource_manager.texture_descriptor_ref(dst_cubemap) {
+                    Some(descriptor) => descriptor,
+                    None => {
+                        log::error!(target: "EntityManager","Failed to gather Command::CopyToCubemapFace resources: Texture destination {} not found",dst_cubemap);
+                        return Err(ResourceBuilderError::MissingDependencies);
+                    }
+                };
+                if !dst_descriptor.is_cube_compatible() || *face >= 6 {
+                    log::error!(target: "EntityManager","Failed to gather Command::CopyToCubemapFace resources: Texture {} is not a cubemap or face {} is out of range",dst_cubemap,face);
+                    return Err(ResourceBuilderError::InvalidCubemapCopy);
+                }
+                let copy_size = crate::wgpu::Extent3d {
+                    width: dst_descriptor.size.width,
+                    height: dst_descriptor.size.height,
+                    depth_or_array_layers: 1,
+                };
+                if src_descriptor.size.width != copy_size.width
+                    || src_descriptor.size.height != copy_size.height
+                {
+                    log::error!(target: "EntityManager","Failed to gather Command::CopyToCubemapFace resources: Texture {} does not match the face size of {}",src,dst_cubemap);
+                    return Err(ResourceBuilderError::InvalidCubemapCopy);
+                }
+
+                let descriptor = TextureToTextureCopy {
+                    src_texture: *src,
+                    src_mip_level: 0,
+                    src_origin: crate::wgpu::Origin3d::ZERO,
+                    dst_texture: *dst_cubemap,
+                    dst_mip_level: 0,
+                    dst_origin: crate::wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: *face,
+                    },
+                    copy_size,
+                };
+                match TextureToTextureCopyBuilder::new(resource_manager, &descriptor) {
+                    Ok(builder) => Ok(Self::TextureToTexture(builder)),
+                    Err(err) => Err(err),
+                }
+            }
+            Command::WriteTimestamp { query_set, index } => {
+                let query_set = match resource_manager.query_set_handle_ref(query_set) {
+                    Some(query_set) => query_set.clone(),
+                    None => {
+                        log::error!(target: "EntityManager","Failed to gather Command::WriteTimestamp resources: QuerySet {} not found",query_set);
+                        return Err(ResourceBuilderError::MissingDependencies);
+                    }
+                };
+                let index = *index;
+                Ok(Self::WriteTimestamp { query_set, index })
+            }
             Command::ComputePass(commands) => {
+                if let Some((pipeline, index, bind_group)) =
+                    resource_manager.incompatible_compute_bind_group(commands)
+                {
+                    log::error!(target: "EntityManager","Failed to gather Command::ComputePass resources: {} at index {} is not compatible with the layout of {}",bind_group,index,pipeline);
+                    return Err(ResourceBuilderError::IncompatibleBindGroup(bind_group));
+                }
                 let mut command_builders = Vec::new();
                 for command in commands {
                     match ComputeCommandBuilder::new(resource_manager, command) {
@@ -2013,6 +2792,46 @@ impl CommandBuilder {
                     color_attachment_builders.push(builder);
                 }
 
+                for command in commands {
+                    match command {
+                        RenderCommand::SetPipeline { pipeline } => {
+                            let targets = resource_manager
+                                .render_pipeline_descriptor_ref(pipeline)
+                                .and_then(|descriptor| descriptor.fragment.as_ref())
+                                .map(|fragment| fragment.targets.len())
+                                .unwrap_or(0);
+                            if targets != color_attachments.len() {
+                                log::error!(target: "EntityManager","Failed to gather Command::RenderPass resources: `{}` has {} color attachments, but {} has {} targets",label,color_attachments.len(),pipeline,targets);
+                                return Err(ResourceBuilderError::ColorAttachmentsMismatch);
+                            }
+                        }
+                        RenderCommand::ExecuteBundles { bundles } => {
+                            for bundle in bundles {
+                                let formats = resource_manager
+                                    .render_bundle_descriptor_ref(bundle)
+                                    .map(|descriptor| descriptor.color_formats.len())
+                                    .unwrap_or(0);
+                                if formats != color_attachments.len() {
+                                    log::error!(target: "EntityManager","Failed to gather Command::RenderPass resources: `{}` has {} color attachments, but {} has {} color formats",label,color_attachments.len(),bundle,formats);
+                                    return Err(ResourceBuilderError::ColorAttachmentsMismatch);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                if !resource_manager.validate_indexed_draws(commands) {
+                    log::error!(target: "EntityManager","Failed to gather Command::RenderPass resources: invalid indexed draws in `{}`",label);
+                    return Err(ResourceBuilderError::InvalidIndexedDraw);
+                }
+                if let Some((pipeline, index, bind_group)) =
+                    resource_manager.incompatible_render_bind_group(commands)
+                {
+                    log::error!(target: "EntityManager","Failed to gather Command::RenderPass resources: {} at index {} is not compatible with the layout of {} in `{}`",bind_group,index,pipeline,label);
+                    return Err(ResourceBuilderError::IncompatibleBindGroup(bind_group));
+                }
+
                 let mut command_builders = Vec::new();
                 for command in commands {
                     match RenderCommandBuilder::new(resource_manager, command) {
@@ -2036,6 +2855,10 @@ impl CommandBuilder {
             Self::BufferToTexture(command_builder) => command_builder.build(encoder),
             Self::TextureToTexture(command_builder) => command_builder.build(encoder),
             Self::TextureToBuffer(command_builder) => command_builder.build(encoder),
+            Self::WriteTimestamp { query_set, index } => {
+                encoder.write_timestamp(query_set, *index);
+                true
+            }
             Self::ComputePass { commands } => {
                 let mut compute_pass =
                     encoder.begin_compute_pass(&crate::wgpu::ComputePassDescriptor { label: None });
@@ -2140,6 +2963,26 @@ impl CommandBufferBuilder {
                 return Err(ResourceBuilderError::MissingDependencies);
             }
         };
+        let foreign_resources = resource_manager.foreign_resources(descriptor);
+        if !foreign_resources.is_empty() {
+            log::error!(target: "EntityManager","Failed to gather CommandBuffer resources: {} is on Device {}, but it references resources of other devices: {:?}",id,descriptor.device,foreign_resources);
+            return Err(ResourceBuilderError::DeviceMismatch(foreign_resources));
+        }
+        let commands_exceeded = !descriptor.check_max_commands();
+        let wrong_order = !resource_manager
+            .check_compute_render_order(descriptor)
+            .is_empty();
+        if resource_manager.is_strict() && (commands_exceeded || wrong_order) {
+            log::error!(target: "EntityManager","Failed to gather CommandBuffer resources: {} has validation warnings in strict mode",id);
+            return Err(ResourceBuilderError::StrictModeWarning);
+        }
+
+        let missing_usages = resource_manager.missing_usages(descriptor);
+        if !missing_usages.is_empty() {
+            log::error!(target: "EntityManager","Failed to gather CommandBuffer resources: {} uses resources without the required usages: {:?}",id,missing_usages);
+            return Err(ResourceBuilderError::MissingUsages(missing_usages));
+        }
+
         let mut commands = Vec::new();
         for command in &descriptor.commands {
             let command_builder = match CommandBuilder::new(resource_manager, command) {
@@ -2156,7 +2999,7 @@ impl CommandBufferBuilder {
             commands,
         })
     }
-    pub fn build(&self) -> CommandBufferHandle {
+    pub fn build(&self, log_level: log::LevelFilter) -> CommandBufferHandle {
         let descriptor = crate::wgpu::CommandEncoderDescriptor {
             label: Some(self.label.as_str()),
         };
@@ -2165,7 +3008,7 @@ impl CommandBufferBuilder {
         for command in &self.commands {
             command.build(&mut encoder);
         }
-        log::info!(target: "EntityManager","Building {}",self.id);
+        resource_log!(log_level, log::Level::Info, target: "EntityManager", "Building {}", self.id);
         Arc::new(encoder.finish())
     }
 }
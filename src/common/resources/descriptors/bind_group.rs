@@ -30,8 +30,8 @@ impl HaveDescriptor for BufferBinding {
     fn state_type(&self) -> StateType {
         StateType::Statefull
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
 
@@ -109,7 +109,7 @@ impl HaveDescriptor for BindGroupDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
@@ -13,6 +13,20 @@ pub struct BindGroupLayoutDescriptor {
     pub device: DeviceId,
     pub entries: Vec<crate::wgpu::BindGroupLayoutEntry>,
 }
+impl BindGroupLayoutDescriptor {
+    /**
+    Check if the layouts have the same entries, regardless of their order, so that the bind groups
+    created for one layout can be used where the other one is expected. Labels are not compared.
+    */
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        let sorted_entries = |descriptor: &Self| {
+            let mut entries = descriptor.entries.clone();
+            entries.sort_by_key(|entry| entry.binding);
+            entries
+        };
+        self.device == other.device && sorted_entries(self) == sorted_entries(other)
+    }
+}
 impl HaveDependencies for BindGroupLayoutDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         vec![*self.device.id_ref()]
@@ -32,7 +46,80 @@ impl HaveDescriptor for BindGroupLayoutDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
+    }
+}
+
+/**
+Constructors for the most common [BindGroupLayoutEntry][crate::wgpu::BindGroupLayoutEntry] kinds.
+The entry is a wgpu type, so the constructors are provided by this trait instead of an inherent implementation.
+*/
+pub trait BindGroupLayoutEntryExt {
+    /// Single filterable 2D float texture.
+    fn texture(binding: u32, visibility: crate::wgpu::ShaderStage) -> Self;
+    /**
+    Array of `count` filterable 2D float textures, as used for bindless texturing.
+    A `count` of 0 produces a single texture binding instead of an array, like [texture][BindGroupLayoutEntryExt::texture].
+    The array requires [SAMPLED_TEXTURE_BINDING_ARRAY][crate::wgpu::Features::SAMPLED_TEXTURE_BINDING_ARRAY].
+    */
+    fn texture_array(binding: u32, count: u32, visibility: crate::wgpu::ShaderStage) -> Self;
+    /// Filtering, non comparison sampler visible from the fragment stage.
+    fn sampler(binding: u32) -> Self;
+    /// Uniform buffer without dynamic offset.
+    fn uniform_buffer(binding: u32, visibility: crate::wgpu::ShaderStage) -> Self;
+    /// Storage buffer without dynamic offset.
+    fn storage_buffer(binding: u32, visibility: crate::wgpu::ShaderStage, read_only: bool) -> Self;
+}
+impl BindGroupLayoutEntryExt for crate::wgpu::BindGroupLayoutEntry {
+    fn texture(binding: u32, visibility: crate::wgpu::ShaderStage) -> Self {
+        Self::texture_array(binding, 0, visibility)
+    }
+    fn texture_array(binding: u32, count: u32, visibility: crate::wgpu::ShaderStage) -> Self {
+        Self {
+            binding,
+            visibility,
+            ty: crate::wgpu::BindingType::Texture {
+                sample_type: crate::wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: crate::wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: std::num::NonZeroU32::new(count),
+        }
+    }
+    fn sampler(binding: u32) -> Self {
+        Self {
+            binding,
+            visibility: crate::wgpu::ShaderStage::FRAGMENT,
+            ty: crate::wgpu::BindingType::Sampler {
+                comparison: false,
+                filtering: true,
+            },
+            count: None,
+        }
+    }
+    fn uniform_buffer(binding: u32, visibility: crate::wgpu::ShaderStage) -> Self {
+        Self {
+            binding,
+            visibility,
+            ty: crate::wgpu::BindingType::Buffer {
+                ty: crate::wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    }
+    fn storage_buffer(binding: u32, visibility: crate::wgpu::ShaderStage, read_only: bool) -> Self {
+        Self {
+            binding,
+            visibility,
+            ty: crate::wgpu::BindingType::Buffer {
+                ty: crate::wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
     }
 }
@@ -14,6 +14,33 @@ pub struct BufferDescriptor {
     pub size: crate::wgpu::BufferAddress,
     pub usage: crate::wgpu::BufferUsage,
 }
+impl BufferDescriptor {
+    /**
+    Check that the buffer can be used as argument of an indirect command reading `args_size` bytes from `offset`:
+    the buffer must have the `INDIRECT` usage, and the offset must be 4-aligned with the arguments within the buffer bounds.
+    */
+    pub fn validate_indirect(
+        &self,
+        offset: crate::wgpu::BufferAddress,
+        args_size: crate::wgpu::BufferAddress,
+    ) -> bool {
+        if !self.usage.contains(crate::wgpu::BufferUsage::INDIRECT) {
+            log::error!(target: "EntityManager","Buffer `{}`: indirect arguments require the INDIRECT usage, found {:?}",self.label,self.usage);
+            return false;
+        }
+        if offset % 4 != 0 {
+            log::error!(target: "EntityManager","Buffer `{}`: indirect offset {} is not a multiple of 4",self.label,offset);
+            return false;
+        }
+        match offset.checked_add(args_size) {
+            Some(end) if end <= self.size => true,
+            _ => {
+                log::error!(target: "EntityManager","Buffer `{}`: indirect arguments of {} bytes at {} are out of the buffer size {}",self.label,args_size,offset,self.size);
+                false
+            }
+        }
+    }
+}
 impl HaveDependencies for BufferDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         vec![*self.device.id_ref()]
@@ -33,7 +60,7 @@ impl HaveDescriptor for BufferDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Statefull
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
@@ -1,9 +1,13 @@
 //! CommandBuffer related structures and enumerations.
 
-use crate::common::resources::descriptors::{HaveDependencies, HaveDescriptor, StateType};
+use crate::common::resources::descriptors::{
+    HaveDependencies, HaveDescriptor, RenderBundleDescriptor, StateType,
+};
+use crate::engine::resource_manager::ResourceManager;
 use crate::entity_manager::EntityId;
 use crate::resources::{
-    BindGroupId, BufferId, DeviceId, RenderPipelineId, SwapchainId, TextureId, TextureViewId,
+    BindGroupId, BufferId, ComputePipelineId, DeviceId, QuerySetId, RenderBundleId,
+    RenderPipelineId, SwapchainId, TextureId, TextureViewId,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +18,8 @@ pub struct CommandBufferDescriptor {
     pub label: String,
     pub device: DeviceId,
     pub commands: Vec<Command>,
+    /// Soft limit on the number of commands. Exceeding it only logs a warning, to spot unbounded growth.
+    pub max_commands: Option<usize>,
 }
 impl CommandBufferDescriptor {
     pub fn swapchains(&self) -> Vec<(SwapchainId, Option<TextureViewId>)> {
@@ -22,7 +28,440 @@ impl CommandBufferDescriptor {
             .filter_map(|command| command.swapchain())
             .collect()
     }
+    /**
+    Color views written by the render passes, in order of first use and without duplicates.
+    Resolve targets are included, since the passes write on them too.
+    */
+    pub fn render_targets(&self) -> Vec<ColorView> {
+        let mut render_targets = Vec::new();
+        for command in &self.commands {
+            if let Command::RenderPass {
+                color_attachments, ..
+            } = command
+            {
+                for attachment in color_attachments {
+                    let views = std::iter::once(attachment.view.clone()).chain(
+                        attachment
+                            .resolve_target
+                            .map(|resolve_target| ColorView::TextureView(resolve_target)),
+                    );
+                    for view in views {
+                        if !render_targets.contains(&view) {
+                            render_targets.push(view);
+                        }
+                    }
+                }
+            }
+        }
+        render_targets
+    }
+    /// Number of commands, including the ones recorded inside the passes.
+    pub fn command_count(&self) -> usize {
+        self.commands
+            .iter()
+            .map(|command| command.command_count())
+            .sum()
+    }
+    /**
+    Set the instance range of the [Draw][RenderCommand::Draw] or [DrawIndexed][RenderCommand::DrawIndexed] command
+    at index `draw` of the render pass at index `pass`, like when the number of instances follows the length
+    of a [BufferManager][crate::BufferManager]. The other commands are left untouched.
+    Returns false if there is no such draw.
+    */
+    pub fn patch_draw_instances(
+        &mut self,
+        pass: usize,
+        draw: usize,
+        instances: std::ops::Range<u32>,
+    ) -> bool {
+        let commands = match self.commands.get_mut(pass) {
+            Some(Command::RenderPass { commands, .. }) => commands,
+            _ => return false,
+        };
+        match commands.get_mut(draw) {
+            Some(RenderCommand::Draw {
+                instances: draw_instances,
+                ..
+            })
+            | Some(RenderCommand::DrawIndexed {
+                instances: draw_instances,
+                ..
+            }) => {
+                *draw_instances = instances;
+                true
+            }
+            _ => false,
+        }
+    }
+    /**
+    Wrap the commands between two timestamps, written in the queries 0 and 1 of `query_set`, to time the whole
+    command buffer on the GPU. The query set needs at least 2 [timestamp][crate::QuerySetDescriptor::timestamps] queries,
+    and the elapsed time is read with [read_elapsed_time][crate::ResourceManager::read_elapsed_time].
+    */
+    pub fn with_timing(mut self, query_set: QuerySetId) -> Self {
+        self.commands.insert(
+            0,
+            Command::WriteTimestamp {
+                query_set,
+                index: 0,
+            },
+        );
+        self.commands.push(Command::WriteTimestamp {
+            query_set,
+            index: 1,
+        });
+        self
+    }
+    /// Check the command count against [max_commands][CommandBufferDescriptor::max_commands], logging a warning if exceeded.
+    pub fn check_max_commands(&self) -> bool {
+        match self.max_commands {
+            Some(max_commands) if self.command_count() > max_commands => {
+                log::warn!(target: "EntityManager","CommandBuffer `{}` has {} commands, exceeding the limit of {}",self.label,self.command_count(),max_commands);
+                false
+            }
+            _ => true,
+        }
+    }
+    /**
+    Run the structural checks on the command buffer against the resources of the [ResourceManager][ResourceManager],
+    reporting every problem found instead of failing at the first one like the build does.
+    Useful in tests to catch command buffer mistakes without running on the GPU.
+    */
+    pub fn validate(&self, resource_manager: &ResourceManager) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        let mut dependencies = self.dependencies();
+        dependencies.sort();
+        dependencies.dedup();
+        for dependency in &dependencies {
+            if resource_manager.entity_descriptor_ref(dependency).is_none() {
+                errors.push(ValidationError::MissingResource(*dependency));
+                continue;
+            }
+            let bind_group = BindGroupId::new(*dependency);
+            if let Some(descriptor) = resource_manager.bind_group_descriptor_ref(&bind_group) {
+                let missing: Vec<_> = resource_manager
+                    .bind_group_layout_descriptor_ref(&descriptor.layout)
+                    .map(|layout| layout.entries.as_slice())
+                    .unwrap_or(&[])
+                    .iter()
+                    .map(|entry| entry.binding)
+                    .filter(|binding| {
+                        !descriptor
+                            .entries
+                            .iter()
+                            .any(|entry| entry.binding == *binding)
+                    })
+                    .collect();
+                if !missing.is_empty() {
+                    errors.push(ValidationError::IncompleteBindGroup {
+                        bind_group,
+                        missing,
+                    });
+                }
+            }
+        }
+
+        errors.extend(
+            resource_manager
+                .foreign_resources(self)
+                .into_iter()
+                .map(|(resource, device)| ValidationError::DeviceMismatch { resource, device }),
+        );
+        errors.extend(
+            resource_manager
+                .missing_usages(self)
+                .into_iter()
+                .map(ValidationError::MissingUsage),
+        );
+
+        for command in &self.commands {
+            if let Command::RenderPass {
+                label,
+                color_attachments,
+                commands,
+                ..
+            } = command
+            {
+                validate_render_pass(
+                    resource_manager,
+                    label,
+                    color_attachments,
+                    commands,
+                    &mut errors,
+                );
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
+
+/// Check the pipelines and the draws of a render pass, see [CommandBufferDescriptor::validate][CommandBufferDescriptor::validate].
+fn validate_render_pass(
+    resource_manager: &ResourceManager,
+    pass: &str,
+    color_attachments: &[RenderPassColorAttachment],
+    commands: &[RenderCommand],
+    errors: &mut Vec<ValidationError>,
+) {
+    let attachment_formats: Vec<_> = color_attachments
+        .iter()
+        .map(|attachment| match attachment.view {
+            ColorView::TextureView(ref id) => resource_manager
+                .texture_view_descriptor_ref(id)
+                .map(|descriptor| descriptor.format),
+            ColorView::Swapchain(ref id) => resource_manager
+                .swapchain_descriptor_ref(id)
+                .map(|descriptor| descriptor.format),
+        })
+        .collect();
+    // Viewports and scissor rectangles must fit in every attachment, so in the smallest one.
+    let attachment_size = color_attachments
+        .iter()
+        .filter_map(|attachment| match attachment.view {
+            ColorView::TextureView(ref id) => {
+                let view = resource_manager.texture_view_descriptor_ref(id)?;
+                let texture = resource_manager.texture_descriptor_ref(&view.texture)?;
+                Some((
+                    (texture.size.width >> view.base_mip_level).max(1),
+                    (texture.size.height >> view.base_mip_level).max(1),
+                ))
+            }
+            ColorView::Swapchain(ref id) => resource_manager
+                .swapchain_descriptor_ref(id)
+                .map(|descriptor| (descriptor.width, descriptor.height)),
+        })
+        .reduce(|(width, height), (other_width, other_height)| {
+            (width.min(other_width), height.min(other_height))
+        });
+
+    let mut pipeline = None;
+    let mut vertex_buffers = std::collections::HashMap::new();
+    for command in commands {
+        match command {
+            RenderCommand::SetPipeline { pipeline: id } => {
+                pipeline = resource_manager
+                    .render_pipeline_descriptor_ref(id)
+                    .map(|descriptor| (*id, descriptor));
+                let (id, descriptor) = match pipeline {
+                    Some(pipeline) => pipeline,
+                    None => continue,
+                };
+                let targets = descriptor
+                    .fragment
+                    .as_ref()
+                    .map(|fragment| fragment.targets.as_slice())
+                    .unwrap_or(&[]);
+                if targets.len() != color_attachments.len() {
+                    errors.push(ValidationError::ColorAttachmentsMismatch {
+                        pass: pass.to_string(),
+                        pipeline: id,
+                        attachments: color_attachments.len(),
+                        targets: targets.len(),
+                    });
+                    continue;
+                }
+                for (attachment, (target, format)) in
+                    targets.iter().zip(attachment_formats.iter()).enumerate()
+                {
+                    match format {
+                        Some(format) if *format != target.format => {
+                            errors.push(ValidationError::AttachmentFormatMismatch {
+                                pass: pass.to_string(),
+                                pipeline: id,
+                                attachment,
+                                attachment_format: *format,
+                                target_format: target.format,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            RenderCommand::SetVertexBuffer {
+                slot,
+                buffer,
+                slice,
+            } => {
+                if let Some(descriptor) = resource_manager.buffer_descriptor_ref(buffer) {
+                    let range = slice.to_range(descriptor.size);
+                    vertex_buffers.insert(*slot, range.end.saturating_sub(range.start));
+                }
+            }
+            RenderCommand::Draw {
+                vertices,
+                instances,
+            } => {
+                let layouts = match pipeline {
+                    Some((_id, descriptor)) => &descriptor.vertex.buffers,
+                    None => continue,
+                };
+                for (slot, layout) in layouts.iter().enumerate() {
+                    if layout.array_stride == 0 {
+                        continue;
+                    }
+                    let slot = slot as u32;
+                    let required = match layout.step_mode {
+                        crate::wgpu::InputStepMode::Vertex => vertices.end,
+                        crate::wgpu::InputStepMode::Instance => instances.end,
+                    };
+                    let capacity =
+                        vertex_buffers.get(&slot).copied().unwrap_or(0) / layout.array_stride;
+                    if required as u64 > capacity {
+                        errors.push(ValidationError::DrawOutOfBounds {
+                            pass: pass.to_string(),
+                            slot,
+                            required,
+                            capacity,
+                        });
+                    }
+                }
+            }
+            RenderCommand::SetViewport {
+                x,
+                y,
+                width,
+                height,
+                min_depth,
+                max_depth,
+            } => {
+                let fits = match attachment_size {
+                    Some((attachment_width, attachment_height)) => {
+                        *x >= 0.0
+                            && *y >= 0.0
+                            && x + width <= attachment_width as f32
+                            && y + height <= attachment_height as f32
+                    }
+                    None => true,
+                };
+                let depth_valid = 0.0 <= *min_depth && min_depth <= max_depth && *max_depth <= 1.0;
+                if !fits || *width <= 0.0 || *height <= 0.0 || !depth_valid {
+                    errors.push(ValidationError::ViewportOutOfBounds {
+                        pass: pass.to_string(),
+                        viewport: [*x, *y, *width, *height, *min_depth, *max_depth],
+                        attachment_size,
+                    });
+                }
+            }
+            RenderCommand::SetScissorRect {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                if let Some((attachment_width, attachment_height)) = attachment_size {
+                    let fits = x
+                        .checked_add(*width)
+                        .map(|end| end <= attachment_width)
+                        .unwrap_or(false)
+                        && y.checked_add(*height)
+                            .map(|end| end <= attachment_height)
+                            .unwrap_or(false);
+                    if !fits {
+                        errors.push(ValidationError::ScissorOutOfBounds {
+                            pass: pass.to_string(),
+                            rect: [*x, *y, *width, *height],
+                            attachment_size: (attachment_width, attachment_height),
+                        });
+                    }
+                }
+            }
+            // The bundles reset the pass state once executed.
+            RenderCommand::ExecuteBundles { .. } => {
+                pipeline = None;
+                vertex_buffers.clear();
+            }
+            _ => {}
+        }
+    }
+
+    if !resource_manager.validate_indexed_draws(commands) {
+        errors.push(ValidationError::InvalidIndexedDraw {
+            pass: pass.to_string(),
+        });
+    }
+    if let Some((pipeline, index, bind_group)) =
+        resource_manager.incompatible_render_bind_group(commands)
+    {
+        errors.push(ValidationError::IncompatibleBindGroup {
+            pass: pass.to_string(),
+            pipeline,
+            index,
+            bind_group,
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Problem found by [CommandBufferDescriptor::validate][CommandBufferDescriptor::validate].
+pub enum ValidationError {
+    /// A resource referenced by the commands does not exist.
+    MissingResource(EntityId),
+    /// A resource has not been created with a usage required by the commands.
+    MissingUsage(RequiredUsage),
+    /// A resource belongs to another device than the command buffer one.
+    DeviceMismatch {
+        resource: EntityId,
+        device: DeviceId,
+    },
+    /// Some bindings of the layout are not provided by the bind group.
+    IncompleteBindGroup {
+        bind_group: BindGroupId,
+        missing: Vec<u32>,
+    },
+    /// The pipeline set in the render pass does not have a target for every color attachment.
+    ColorAttachmentsMismatch {
+        pass: String,
+        pipeline: RenderPipelineId,
+        attachments: usize,
+        targets: usize,
+    },
+    /// A color attachment has a different format than the corresponding pipeline target.
+    AttachmentFormatMismatch {
+        pass: String,
+        pipeline: RenderPipelineId,
+        attachment: usize,
+        attachment_format: crate::wgpu::TextureFormat,
+        target_format: crate::wgpu::TextureFormat,
+    },
+    /// A draw reads more vertices (or instances) than the buffer bound to the slot holds, counting whole strides.
+    DrawOutOfBounds {
+        pass: String,
+        slot: u32,
+        required: u32,
+        capacity: u64,
+    },
+    /// An indexed draw has no index buffer bound or reads beyond it.
+    InvalidIndexedDraw { pass: String },
+    /**
+    A viewport, as `[x, y, width, height, min_depth, max_depth]`, is empty, has depths outside `0.0..=1.0`
+    or exceeds the size of the attachments, if known.
+    */
+    ViewportOutOfBounds {
+        pass: String,
+        viewport: [f32; 6],
+        attachment_size: Option<(u32, u32)>,
+    },
+    /// A scissor rectangle, as `[x, y, width, height]`, exceeds the size of the attachments.
+    ScissorOutOfBounds {
+        pass: String,
+        rect: [u32; 4],
+        attachment_size: (u32, u32),
+    },
+    /// A draw uses a bind group at an index where the bound pipeline expects an incompatible layout.
+    IncompatibleBindGroup {
+        pass: String,
+        pipeline: RenderPipelineId,
+        index: u32,
+        bind_group: BindGroupId,
+    },
+}
+
 impl HaveDependencies for CommandBufferDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         std::iter::once(*self.device.id_ref())
@@ -49,11 +488,18 @@ impl HaveDescriptor for CommandBufferDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Usage flags that the commands of a [CommandBufferDescriptor][CommandBufferDescriptor] require on a resource.
+pub enum RequiredUsage {
+    Buffer(BufferId, crate::wgpu::BufferUsage),
+    Texture(TextureId, crate::wgpu::TextureUsage),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 /// Command to be written on [CommandBufferDescriptor][CommandBufferDescriptor] descriptor.
 pub enum Command {
@@ -61,6 +507,21 @@ pub enum Command {
     BufferToTexture(BufferToTextureCopy),
     TextureToTexture(TextureToTextureCopy),
     TextureToBuffer(TextureToBufferCopy),
+    /**
+    Copy the first mip level of the 2D texture `src` into the `face` layer (0..6) of the first mip level of `dst_cubemap`.
+    The destination must be cube compatible (see [TextureDescriptor::is_cube_compatible][crate::TextureDescriptor::is_cube_compatible])
+    and the source must have the same size of a face.
+    */
+    CopyToCubemapFace {
+        src: TextureId,
+        dst_cubemap: TextureId,
+        face: u32,
+    },
+    /// Write a timestamp in the query `index` of a query set, see [with_timing][CommandBufferDescriptor::with_timing].
+    WriteTimestamp {
+        query_set: QuerySetId,
+        index: u32,
+    },
     ComputePass(Vec<ComputeCommand>),
     RenderPass {
         label: String,
@@ -70,6 +531,14 @@ pub enum Command {
     },
 }
 impl Command {
+    /// Number of commands, including the ones recorded inside the pass.
+    pub fn command_count(&self) -> usize {
+        match self {
+            Self::ComputePass(commands) => 1 + commands.len(),
+            Self::RenderPass { commands, .. } => 1 + commands.len(),
+            _ => 1,
+        }
+    }
     pub fn swapchain(&self) -> Option<(SwapchainId, Option<TextureViewId>)> {
         if let Command::RenderPass {
             label: _,
@@ -95,6 +564,10 @@ impl HaveDependencies for Command {
             Self::BufferToTexture(descriptor) => descriptor.dependencies(),
             Self::TextureToTexture(descriptor) => descriptor.dependencies(),
             Self::TextureToBuffer(descriptor) => descriptor.dependencies(),
+            Self::CopyToCubemapFace {
+                src, dst_cubemap, ..
+            } => vec![*src.id_ref(), *dst_cubemap.id_ref()],
+            Self::WriteTimestamp { query_set, .. } => vec![*query_set.id_ref()],
             Self::ComputePass(descriptors) => descriptors
                 .iter()
                 .map(|descriptor| descriptor.dependencies())
@@ -160,9 +633,43 @@ pub struct RenderPassColorAttachment {
     pub ops: crate::wgpu::Operations<crate::wgpu::Color>,
 }
 impl RenderPassColorAttachment {
+    /// Attachment cleared to `color` at the start of the pass and stored at its end.
+    pub fn clear(view: ColorView, color: crate::wgpu::Color) -> Self {
+        Self {
+            view,
+            resolve_target: None,
+            ops: crate::wgpu::Operations {
+                load: crate::wgpu::LoadOp::Clear(color),
+                store: true,
+            },
+        }
+    }
+    /**
+    Multisampled attachment cleared to `color` and resolved into `resolve_target`.
+    The multisampled contents are not stored, since only the resolved ones are consumed:
+    this saves bandwidth, especially on tile-based GPUs.
+    */
+    pub fn resolve(
+        view: ColorView,
+        resolve_target: TextureViewId,
+        color: crate::wgpu::Color,
+    ) -> Self {
+        Self {
+            view,
+            resolve_target: Some(resolve_target),
+            ops: crate::wgpu::Operations {
+                load: crate::wgpu::LoadOp::Clear(color),
+                store: false,
+            },
+        }
+    }
     pub fn swapchain(&self) -> Option<SwapchainId> {
         self.view.swapchain()
     }
+    /// Returns true if the attachment is resolved but its multisampled contents are stored anyway.
+    pub fn stores_resolved_source(&self) -> bool {
+        self.resolve_target.is_some() && self.ops.store
+    }
 }
 impl HaveDependencies for RenderPassColorAttachment {
     fn dependencies(&self) -> Vec<EntityId> {
@@ -176,11 +683,45 @@ impl HaveDependencies for RenderPassColorAttachment {
 
 #[derive(Debug, Clone, PartialEq)]
 /// Builder for commands to be written in a [ComputePass][crate::wgpu::ComputePass] object.
-/// Never used nor implemented.
-pub enum ComputeCommand {}
+pub enum ComputeCommand {
+    SetPipeline {
+        pipeline: ComputePipelineId,
+    },
+    /// The stages must be [ShaderStage::COMPUTE][crate::wgpu::ShaderStage::COMPUTE].
+    SetPushConstants {
+        stages: crate::wgpu::ShaderStage,
+        offset: u32,
+        data: Vec<u8>,
+    },
+    SetBindGroup {
+        index: u32,
+        bind_group: BindGroupId,
+        offsets: Vec<crate::wgpu::DynamicOffset>,
+    },
+    /// Dispatch `x * y * z` workgroups.
+    Dispatch {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    /**
+    Dispatch the workgroups counted by three `u32` read from the buffer at `offset`, that needs the `INDIRECT` usage.
+    The buffer can be filled by a previous pass, or by a write of the same frame, since writes are flushed before the command buffers.
+    */
+    DispatchIndirect {
+        buffer: BufferId,
+        offset: crate::wgpu::BufferAddress,
+    },
+}
 impl HaveDependencies for ComputeCommand {
     fn dependencies(&self) -> Vec<EntityId> {
-        Vec::new()
+        match self {
+            Self::SetPipeline { pipeline } => vec![pipeline.id_ref().clone()],
+            Self::SetPushConstants { .. } => Vec::new(),
+            Self::SetBindGroup { bind_group, .. } => vec![bind_group.id_ref().clone()],
+            Self::Dispatch { .. } => Vec::new(),
+            Self::DispatchIndirect { buffer, .. } => vec![buffer.id_ref().clone()],
+        }
     }
 }
 
@@ -216,6 +757,26 @@ impl<T> std::ops::RangeBounds<T> for Slice<T> {
         }
     }
 }
+impl Slice<crate::wgpu::BufferAddress> {
+    /// Resolve the slice on a buffer of `size` bytes, with the unbounded ends clamped to the buffer.
+    pub fn to_range(
+        &self,
+        size: crate::wgpu::BufferAddress,
+    ) -> std::ops::Range<crate::wgpu::BufferAddress> {
+        use std::ops::{Bound, RangeBounds};
+        let start = match self.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match self.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => size,
+        };
+        start..end
+    }
+}
 impl<T> From<std::ops::Range<T>> for Slice<T> {
     fn from(range: std::ops::Range<T>) -> Self {
         Self::Range(range)
@@ -282,6 +843,54 @@ pub enum RenderCommand {
         base_vertex: i32,
         instances: std::ops::Range<u32>,
     },
+    /**
+    Draw with the four `u32` arguments of [util::DrawIndirect][crate::wgpu::util::DrawIndirect] read from the buffer at `offset`,
+    that needs the `INDIRECT` usage.
+    */
+    DrawIndirect {
+        buffer: BufferId,
+        offset: crate::wgpu::BufferAddress,
+    },
+    /**
+    Draw with the five arguments of [util::DrawIndexedIndirect][crate::wgpu::util::DrawIndexedIndirect] read from the buffer
+    at `offset`, that needs the `INDIRECT` usage. Like [DrawIndexed][RenderCommand::DrawIndexed], it needs an index buffer bound.
+    */
+    DrawIndexedIndirect {
+        buffer: BufferId,
+        offset: crate::wgpu::BufferAddress,
+    },
+    /// Map the normalized device coordinates to a region of the attachments, in pixels.
+    SetViewport {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        min_depth: f32,
+        max_depth: f32,
+    },
+    /// Discard the fragments outside a region of the attachments, in pixels.
+    SetScissorRect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /**
+    Execute the commands recorded in the [render bundles][RenderBundleDescriptor], in order.
+    The pipeline, bind groups and buffers bound before are unset afterwards, so they must be set again before drawing.
+    */
+    ExecuteBundles {
+        bundles: Vec<RenderBundleId>,
+    },
+}
+impl RenderCommand {
+    /// Returns true if the command can be recorded in a [render bundle][RenderBundleDescriptor].
+    pub fn is_bundle_command(&self) -> bool {
+        !matches!(
+            self,
+            Self::SetViewport { .. } | Self::SetScissorRect { .. } | Self::ExecuteBundles { .. }
+        )
+    }
 }
 impl HaveDependencies for RenderCommand {
     fn dependencies(&self) -> Vec<EntityId> {
@@ -293,6 +902,14 @@ impl HaveDependencies for RenderCommand {
             Self::SetIndexBuffer { buffer, .. } => vec![buffer.id_ref().clone()],
             Self::Draw { .. } => Vec::new(),
             Self::DrawIndexed { .. } => Vec::new(),
+            Self::DrawIndirect { buffer, .. } => vec![buffer.id_ref().clone()],
+            Self::DrawIndexedIndirect { buffer, .. } => vec![buffer.id_ref().clone()],
+            Self::SetViewport { .. } => Vec::new(),
+            Self::SetScissorRect { .. } => Vec::new(),
+            Self::ExecuteBundles { bundles } => bundles
+                .iter()
+                .map(|bundle| bundle.id_ref().clone())
+                .collect(),
         }
     }
 }
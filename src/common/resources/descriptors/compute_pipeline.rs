@@ -37,7 +37,7 @@ impl HaveDescriptor for ComputePipelineDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
@@ -16,6 +16,90 @@ pub struct DeviceDescriptor {
     pub features: crate::wgpu::Features,
     pub limits: crate::wgpu::Limits,
 }
+#[derive(Debug, Clone, PartialEq)]
+/**
+Capabilities lost by a device after being rebuilt, like when the adapter configuration changed.
+The limits are listed as (field, previous, current).
+*/
+pub struct DeviceRegression {
+    pub features: crate::wgpu::Features,
+    pub limits: Vec<(&'static str, u32, u32)>,
+}
+impl DeviceRegression {
+    /// Compare the capabilities of a device before and after a rebuild. Returns `None` if nothing has been lost.
+    pub fn between(
+        previous: (crate::wgpu::Features, &crate::wgpu::Limits),
+        current: (crate::wgpu::Features, &crate::wgpu::Limits),
+    ) -> Option<Self> {
+        let features = previous.0 - current.0;
+        let limits = crate::unmet_limits(previous.1, current.1);
+        if features.is_empty() && limits.is_empty() {
+            None
+        } else {
+            Some(Self { features, limits })
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+Alignment requirements of copies, mappings and bindings on a device, gathered in a single place
+instead of hand-applying the wgpu constants.
+*/
+pub struct DeviceAlignments {
+    /// Alignment of the size and offset of buffer copies and writes.
+    pub copy_buffer: crate::wgpu::BufferAddress,
+    /// Alignment of the bytes per row of buffer-texture copies.
+    pub copy_bytes_per_row: u32,
+    /// Alignment of the offset of buffer mappings.
+    pub map: crate::wgpu::BufferAddress,
+    /// Alignment of the offset of uniform and storage buffer bindings.
+    pub buffer_binding: crate::wgpu::BufferAddress,
+    /// Alignment of the vertex buffer strides.
+    pub vertex_stride: crate::wgpu::BufferAddress,
+    /// Alignment of the push constant ranges and writes.
+    pub push_constant: u32,
+    /// Push constant ranges must also end within this size.
+    pub max_push_constant_size: u32,
+}
+impl DeviceAlignments {
+    pub fn new(limits: &crate::wgpu::Limits) -> Self {
+        Self {
+            copy_buffer: crate::wgpu::COPY_BUFFER_ALIGNMENT,
+            copy_bytes_per_row: crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT,
+            map: crate::wgpu::MAP_ALIGNMENT,
+            buffer_binding: crate::wgpu::BIND_BUFFER_ALIGNMENT,
+            vertex_stride: crate::wgpu::VERTEX_STRIDE_ALIGNMENT,
+            push_constant: crate::wgpu::PUSH_CONSTANT_ALIGNMENT,
+            max_push_constant_size: limits.max_push_constant_size,
+        }
+    }
+    /// Round `value` up to a multiple of `alignment`, which must be greater than 0.
+    pub fn align_to(value: u64, alignment: u64) -> u64 {
+        (value + alignment - 1) / alignment * alignment
+    }
+    /// Round a buffer copy size up to [copy_buffer][DeviceAlignments::copy_buffer].
+    pub fn align_copy_size(&self, size: crate::wgpu::BufferAddress) -> crate::wgpu::BufferAddress {
+        Self::align_to(size, self.copy_buffer)
+    }
+    /// Round an unpadded row size up to [copy_bytes_per_row][DeviceAlignments::copy_bytes_per_row].
+    pub fn padded_bytes_per_row(&self, bytes_per_row: u32) -> u32 {
+        Self::align_to(bytes_per_row as u64, self.copy_bytes_per_row as u64) as u32
+    }
+    /// Round a buffer binding offset up to [buffer_binding][DeviceAlignments::buffer_binding].
+    pub fn align_binding_offset(
+        &self,
+        offset: crate::wgpu::BufferAddress,
+    ) -> crate::wgpu::BufferAddress {
+        Self::align_to(offset, self.buffer_binding)
+    }
+}
+impl Default for DeviceAlignments {
+    /// Alignments of a device with the default limits.
+    fn default() -> Self {
+        Self::new(&crate::wgpu::Limits::default())
+    }
+}
+
 impl HaveDependencies for DeviceDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         vec![self.instance.id_ref().clone()]
@@ -35,7 +119,7 @@ impl HaveDescriptor for DeviceDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
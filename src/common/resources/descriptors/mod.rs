@@ -4,11 +4,12 @@ use crate::common::*;
 use crate::engine::resource_manager::ResourceManager;
 
 pub use crate::wgpu::{
-    AddressMode, BindGroupLayoutEntry, CompareFunction, ComputePass,
-    DrmFormatImageProperties, DrmModifier, Extent3d, Features, FilterMode, Limits, PlaneLayout,
-    QuerySetDescriptor, RenderPass, Sampler, SamplerBorderColor, ShaderStage, SwapChainDescriptor,
+    AddressMode, BindGroupLayoutEntry, CompareFunction, ComputePass, Extent3d, Features,
+    FilterMode, Limits, RenderPass, Sampler, SamplerBorderColor, ShaderStage, SwapChainDescriptor,
     TextureAspect, TextureDimension, TextureFormat, TextureUsage, TextureViewDimension,
 };
+#[cfg(feature = "wgpu_custom")]
+pub use crate::wgpu::{DrmFormatImageProperties, DrmModifier, PlaneLayout};
 
 pub mod instance;
 pub use instance::*;
@@ -34,6 +35,9 @@ pub use sampler::*;
 mod shader_module;
 pub use shader_module::*;
 
+pub mod query_set;
+pub use query_set::*;
+
 pub mod bind_group_layout;
 pub use bind_group_layout::*;
 
@@ -49,6 +53,9 @@ pub use render_pipeline::*;
 pub mod compute_pipeline;
 pub use compute_pipeline::*;
 
+pub mod render_bundle;
+pub use render_bundle::*;
+
 pub mod command_buffer;
 pub use command_buffer::*;
 
@@ -118,6 +125,7 @@ pub enum ResourceDescriptor {
     TextureView(TextureViewDescriptor),
     Sampler(SamplerDescriptor),
     ShaderModule(ShaderModuleDescriptor),
+    QuerySet(QuerySetDescriptor),
 
     BindGroupLayout(BindGroupLayoutDescriptor),
     BindGroup(BindGroupDescriptor),
@@ -125,8 +133,35 @@ pub enum ResourceDescriptor {
     PipelineLayout(PipelineLayoutDescriptor),
     RenderPipeline(RenderPipelineDescriptor),
     ComputePipeline(ComputePipelineDescriptor),
+    RenderBundle(RenderBundleDescriptor),
     CommandBuffer(CommandBufferDescriptor),
 }
+impl ResourceDescriptor {
+    /// Returns the device the resource is created on. Instances and devices have none.
+    pub fn device(&self) -> Option<DeviceId> {
+        match self {
+            Self::Instance(_) => None,
+            Self::Device(_) => None,
+            Self::Swapchain(descriptor) => Some(descriptor.device),
+
+            Self::Buffer(descriptor) => Some(descriptor.device),
+            Self::Texture(descriptor) => Some(descriptor.device),
+            Self::TextureView(descriptor) => Some(descriptor.device),
+            Self::Sampler(descriptor) => Some(descriptor.device),
+            Self::ShaderModule(descriptor) => Some(descriptor.device),
+            Self::QuerySet(descriptor) => Some(descriptor.device),
+
+            Self::BindGroupLayout(descriptor) => Some(descriptor.device),
+            Self::BindGroup(descriptor) => Some(descriptor.device),
+
+            Self::PipelineLayout(descriptor) => Some(descriptor.device),
+            Self::RenderPipeline(descriptor) => Some(descriptor.device),
+            Self::ComputePipeline(descriptor) => Some(descriptor.device),
+            Self::RenderBundle(descriptor) => Some(descriptor.device),
+            Self::CommandBuffer(descriptor) => Some(descriptor.device),
+        }
+    }
+}
 impl HaveDependencies for ResourceDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         match self {
@@ -139,6 +174,7 @@ impl HaveDependencies for ResourceDescriptor {
             Self::TextureView(descriptor) => descriptor.dependencies(),
             Self::Sampler(descriptor) => descriptor.dependencies(),
             Self::ShaderModule(descriptor) => descriptor.dependencies(),
+            Self::QuerySet(descriptor) => descriptor.dependencies(),
 
             Self::BindGroupLayout(descriptor) => descriptor.dependencies(),
             Self::BindGroup(descriptor) => descriptor.dependencies(),
@@ -146,6 +182,7 @@ impl HaveDependencies for ResourceDescriptor {
             Self::PipelineLayout(descriptor) => descriptor.dependencies(),
             Self::RenderPipeline(descriptor) => descriptor.dependencies(),
             Self::ComputePipeline(descriptor) => descriptor.dependencies(),
+            Self::RenderBundle(descriptor) => descriptor.dependencies(),
             Self::CommandBuffer(descriptor) => descriptor.dependencies(),
         }
     }
@@ -172,6 +209,7 @@ impl HaveDescriptor for ResourceDescriptor {
             Self::TextureView(descriptor) => descriptor.state_type(),
             Self::Sampler(descriptor) => descriptor.state_type(),
             Self::ShaderModule(descriptor) => descriptor.state_type(),
+            Self::QuerySet(descriptor) => descriptor.state_type(),
 
             Self::BindGroupLayout(descriptor) => descriptor.state_type(),
             Self::BindGroup(descriptor) => descriptor.state_type(),
@@ -179,11 +217,12 @@ impl HaveDescriptor for ResourceDescriptor {
             Self::PipelineLayout(descriptor) => descriptor.state_type(),
             Self::RenderPipeline(descriptor) => descriptor.state_type(),
             Self::ComputePipeline(descriptor) => descriptor.state_type(),
+            Self::RenderBundle(descriptor) => descriptor.state_type(),
             Self::CommandBuffer(descriptor) => descriptor.state_type(),
         }
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
 impl From<InstanceDescriptor> for ResourceDescriptor {
@@ -221,6 +260,11 @@ impl From<ShaderModuleDescriptor> for ResourceDescriptor {
         Self::ShaderModule(descriptor)
     }
 }
+impl From<QuerySetDescriptor> for ResourceDescriptor {
+    fn from(descriptor: QuerySetDescriptor) -> Self {
+        Self::QuerySet(descriptor)
+    }
+}
 impl From<SamplerDescriptor> for ResourceDescriptor {
     fn from(descriptor: SamplerDescriptor) -> Self {
         Self::Sampler(descriptor)
@@ -251,6 +295,11 @@ impl From<ComputePipelineDescriptor> for ResourceDescriptor {
         Self::ComputePipeline(descriptor)
     }
 }
+impl From<RenderBundleDescriptor> for ResourceDescriptor {
+    fn from(descriptor: RenderBundleDescriptor) -> Self {
+        Self::RenderBundle(descriptor)
+    }
+}
 impl From<CommandBufferDescriptor> for ResourceDescriptor {
     fn from(descriptor: CommandBufferDescriptor) -> Self {
         Self::CommandBuffer(descriptor)
@@ -258,7 +307,11 @@ impl From<CommandBufferDescriptor> for ResourceDescriptor {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-/// Resource write command.
+/**
+Resource write command.
+The writes of a frame are flushed on the queue of their device before the command buffers of the same frame are submitted,
+so the command buffers always see the written data.
+*/
 pub enum ResourceWrite {
     Buffer(BufferWrite),
     Texture(TextureWrite),
@@ -280,6 +333,51 @@ impl ResourceWrite {
             }
         }
     }
+    /**
+    Merge the buffer writes that overlap or are adjacent on the same buffer, so that they can be recorded
+    with a single call. Overlapping data is taken from the latest write, like if the writes were recorded
+    in order. Texture writes are left unchanged, after the buffer ones.
+    */
+    pub fn merge(writes: Vec<Self>) -> Vec<Self> {
+        let mut buffer_writes: Vec<BufferWrite> = Vec::new();
+        let mut texture_writes = Vec::new();
+        for write in writes {
+            let mut write = match write {
+                Self::Buffer(write) => write,
+                Self::Texture(write) => {
+                    texture_writes.push(Self::Texture(write));
+                    continue;
+                }
+            };
+            // Merging can make the write touch writes that were previously disjoint, so repeat until stable.
+            while let Some(index) = buffer_writes.iter().position(|merged| {
+                merged.buffer == write.buffer
+                    && merged.offset <= write.offset + write.data.len() as u64
+                    && write.offset <= merged.offset + merged.data.len() as u64
+            }) {
+                let merged = buffer_writes.swap_remove(index);
+                let offset = merged.offset.min(write.offset);
+                let end = (merged.offset + merged.data.len() as u64)
+                    .max(write.offset + write.data.len() as u64);
+                let mut data = vec![0; (end - offset) as usize];
+                let merged_start = (merged.offset - offset) as usize;
+                data[merged_start..merged_start + merged.data.len()].copy_from_slice(&merged.data);
+                let write_start = (write.offset - offset) as usize;
+                data[write_start..write_start + write.data.len()].copy_from_slice(&write.data);
+                write = BufferWrite {
+                    buffer: write.buffer,
+                    offset,
+                    data,
+                };
+            }
+            buffer_writes.push(write);
+        }
+        buffer_writes
+            .into_iter()
+            .map(Self::Buffer)
+            .chain(texture_writes)
+            .collect()
+    }
     pub fn record(&self, resources: &ResourceManager, queue: &crate::wgpu::Queue) {
         match self {
             Self::Buffer(write) => {
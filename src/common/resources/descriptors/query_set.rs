@@ -0,0 +1,86 @@
+//! QuerySet related structures and enumerations.
+
+use crate::common::resources::descriptors::{HaveDependencies, HaveDescriptor, StateType};
+use crate::entity_manager::EntityId;
+use crate::resources::DeviceId;
+
+#[derive(Debug, Clone)]
+/**
+Descriptor of [QuerySetHandle][crate::common::resources::handles::QuerySetHandle]
+*/
+pub struct QuerySetDescriptor {
+    pub label: String,
+    pub device: DeviceId,
+    pub ty: crate::wgpu::QueryType,
+    pub count: u32,
+}
+impl QuerySetDescriptor {
+    /// Query set of `count` timestamps, that requires [TIMESTAMP_QUERY][crate::wgpu::Features::TIMESTAMP_QUERY].
+    pub fn timestamps(label: String, device: DeviceId, count: u32) -> Self {
+        Self {
+            label,
+            device,
+            ty: crate::wgpu::QueryType::Timestamp,
+            count,
+        }
+    }
+    /// Device features required by the query type.
+    pub fn required_features(&self) -> crate::wgpu::Features {
+        if let crate::wgpu::QueryType::Timestamp = self.ty {
+            crate::wgpu::Features::TIMESTAMP_QUERY
+        } else if let crate::wgpu::QueryType::PipelineStatistics(_) = self.ty {
+            crate::wgpu::Features::PIPELINE_STATISTICS_QUERY
+        } else {
+            crate::wgpu::Features::empty()
+        }
+    }
+    /// Size of the resolved result of a single query, as a `u64` for every value it holds.
+    pub fn result_size(&self) -> crate::wgpu::BufferAddress {
+        let values = if let crate::wgpu::QueryType::PipelineStatistics(types) = self.ty {
+            types.bits().count_ones()
+        } else {
+            1
+        };
+        values as crate::wgpu::BufferAddress
+            * std::mem::size_of::<u64>() as crate::wgpu::BufferAddress
+    }
+}
+// QueryType does not implement PartialEq.
+impl PartialEq for QuerySetDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        let same_type = match (self.ty, other.ty) {
+            (
+                crate::wgpu::QueryType::PipelineStatistics(types),
+                crate::wgpu::QueryType::PipelineStatistics(other_types),
+            ) => types == other_types,
+            (ty, other_ty) => std::mem::discriminant(&ty) == std::mem::discriminant(&other_ty),
+        };
+        same_type
+            && self.label == other.label
+            && self.device == other.device
+            && self.count == other.count
+    }
+}
+impl HaveDependencies for QuerySetDescriptor {
+    fn dependencies(&self) -> Vec<EntityId> {
+        vec![*self.device.id_ref()]
+    }
+}
+impl HaveDescriptor for QuerySetDescriptor {
+    type D = Self;
+    fn descriptor(&self) -> Self::D {
+        self.clone()
+    }
+    fn descriptor_ref(&self) -> &Self::D {
+        self
+    }
+    fn descriptor_mut(&mut self) -> &mut Self::D {
+        self
+    }
+    fn state_type(&self) -> StateType {
+        StateType::Statefull
+    }
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
+    }
+}
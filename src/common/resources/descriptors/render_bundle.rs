@@ -0,0 +1,53 @@
+//! RenderBundle related structures and enumerations.
+
+use crate::common::resources::descriptors::{
+    HaveDependencies, HaveDescriptor, RenderCommand, StateType,
+};
+use crate::entity_manager::EntityId;
+use crate::resources::DeviceId;
+
+#[derive(Debug, Clone, PartialEq)]
+/**
+Descriptor of [RenderBundleHandle][crate::common::resources::handles::RenderBundleHandle]:
+render commands recorded once and executed by render passes with [ExecuteBundles][RenderCommand::ExecuteBundles].
+The formats and the sample count must match the attachments of the passes executing it.
+Only the commands supported by [is_bundle_command][RenderCommand::is_bundle_command] can be recorded.
+*/
+pub struct RenderBundleDescriptor {
+    pub label: String,
+    pub device: DeviceId,
+    pub color_formats: Vec<crate::wgpu::TextureFormat>,
+    pub depth_stencil_format: Option<crate::wgpu::TextureFormat>,
+    pub sample_count: u32,
+    pub commands: Vec<RenderCommand>,
+}
+impl HaveDependencies for RenderBundleDescriptor {
+    fn dependencies(&self) -> Vec<EntityId> {
+        std::iter::once(*self.device.id_ref())
+            .chain(
+                self.commands
+                    .iter()
+                    .map(|command| command.dependencies())
+                    .flatten(),
+            )
+            .collect()
+    }
+}
+impl HaveDescriptor for RenderBundleDescriptor {
+    type D = Self;
+    fn descriptor(&self) -> Self::D {
+        self.clone()
+    }
+    fn descriptor_ref(&self) -> &Self::D {
+        self
+    }
+    fn descriptor_mut(&mut self) -> &mut Self::D {
+        self
+    }
+    fn state_type(&self) -> StateType {
+        StateType::Stateless
+    }
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
+    }
+}
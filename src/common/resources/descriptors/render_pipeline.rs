@@ -90,6 +90,61 @@ pub struct RenderPipelineDescriptor {
     pub multisample: crate::wgpu::MultisampleState,
     pub fragment: Option<FragmentState>,
 }
+impl RenderPipelineDescriptor {
+    /**
+    Device features required by the primitive state: non fill polygon modes (like wireframe) require
+    [NON_FILL_POLYGON_MODE][crate::wgpu::Features::NON_FILL_POLYGON_MODE], conservative rasterization requires
+    [CONSERVATIVE_RASTERIZATION][crate::wgpu::Features::CONSERVATIVE_RASTERIZATION] and depth clamping requires
+    [DEPTH_CLAMPING][crate::wgpu::Features::DEPTH_CLAMPING].
+    */
+    pub fn required_features(&self) -> crate::wgpu::Features {
+        let mut features = crate::wgpu::Features::empty();
+        if self.primitive.polygon_mode != crate::wgpu::PolygonMode::Fill {
+            features |= crate::wgpu::Features::NON_FILL_POLYGON_MODE;
+        }
+        if self.primitive.conservative {
+            features |= crate::wgpu::Features::CONSERVATIVE_RASTERIZATION;
+        }
+        if self.primitive.clamp_depth {
+            features |= crate::wgpu::Features::DEPTH_CLAMPING;
+        }
+        features
+    }
+
+    /**
+    Infer the vertex buffer layout from the inputs of the vertex entry point of a shader source.
+    See [reflect_vertex_layout][crate::utils::reflection::reflect_vertex_layout].
+    */
+    pub fn infer_vertex_layout(
+        source: &crate::ShaderSource,
+        entry_point: &str,
+        step_mode: crate::wgpu::InputStepMode,
+    ) -> Result<VertexBufferLayout, crate::utils::reflection::ReflectionError> {
+        crate::utils::reflection::reflect_vertex_layout(source, entry_point, step_mode)
+    }
+
+    /**
+    Returns a variant of the descriptor using the passed depth bias.
+    The depth bias is part of the pipeline state (there is no command to change it while recording),
+    so each bias value produces a different pipeline, while identical variants are shared by the stateless deduplication.
+    If the descriptor has no depth stencil state, it is returned unchanged.
+    */
+    pub fn with_depth_bias(mut self, constant: i32, slope_scale: f32, clamp: f32) -> Self {
+        match self.depth_stencil.as_mut() {
+            Some(depth_stencil) => {
+                depth_stencil.bias = crate::wgpu::DepthBiasState {
+                    constant,
+                    slope_scale,
+                    clamp,
+                };
+            }
+            None => {
+                log::warn!(target: "EntityManager","RenderPipeline `{}` has no depth stencil state, depth bias ignored",self.label);
+            }
+        }
+        self
+    }
+}
 impl HaveDependencies for RenderPipelineDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         std::iter::once(*self.device.id_ref())
@@ -118,7 +173,7 @@ impl HaveDescriptor for RenderPipelineDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
@@ -23,6 +23,51 @@ pub struct SamplerDescriptor {
     pub anisotropy_clamp: Option<std::num::NonZeroU8>,
     pub border_color: Option<crate::wgpu::SamplerBorderColor>,
 }
+impl SamplerDescriptor {
+    /**
+    Returns the anisotropy clamp limited by the passed cap. A `None` cap leaves it unchanged.
+    */
+    pub fn capped_anisotropy(
+        &self,
+        max_anisotropy: Option<std::num::NonZeroU8>,
+    ) -> Option<std::num::NonZeroU8> {
+        match (self.anisotropy_clamp, max_anisotropy) {
+            (Some(anisotropy), Some(max_anisotropy)) => Some(anisotropy.min(max_anisotropy)),
+            (anisotropy, _) => anisotropy,
+        }
+    }
+}
+impl Default for SamplerDescriptor {
+    /**
+    Default sampler state:
+    - `label`: empty;
+    - `device`: an invalid placeholder, it must always be overridden, otherwise adding the sampler fails for missing dependencies;
+    - `address_mode_u`, `address_mode_v`, `address_mode_w`: [Repeat][crate::wgpu::AddressMode::Repeat];
+    - `mag_filter`, `min_filter`, `mipmap_filter`: [Nearest][crate::wgpu::FilterMode::Nearest];
+    - `lod_min_clamp`: 0.0;
+    - `lod_max_clamp`: 32.0;
+    - `compare`: none;
+    - `anisotropy_clamp`: none;
+    - `border_color`: none.
+    */
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            device: DeviceId::new(EntityId::new(usize::MAX)),
+            address_mode_u: crate::wgpu::AddressMode::Repeat,
+            address_mode_v: crate::wgpu::AddressMode::Repeat,
+            address_mode_w: crate::wgpu::AddressMode::Repeat,
+            mag_filter: crate::wgpu::FilterMode::Nearest,
+            min_filter: crate::wgpu::FilterMode::Nearest,
+            mipmap_filter: crate::wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            compare: None,
+            anisotropy_clamp: None,
+            border_color: None,
+        }
+    }
+}
 impl HaveDependencies for SamplerDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         vec![*self.device.id_ref()]
@@ -42,7 +87,7 @@ impl HaveDescriptor for SamplerDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
@@ -2,7 +2,288 @@
 
 use crate::common::resources::descriptors::{HaveDependencies, HaveDescriptor, StateType};
 use crate::entity_manager::EntityId;
-use crate::resources::DeviceId;
+use crate::resources::{DeviceId, SwapchainId};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+How the swapchain images are composed with the other windows.
+The wgpu version in use always presents opaque images, so any other mode is rejected:
+see [SUPPORTED_COMPOSITE_ALPHA_MODES][SUPPORTED_COMPOSITE_ALPHA_MODES].
+*/
+pub enum CompositeAlphaMode {
+    Opaque,
+    PreMultiplied,
+    PostMultiplied,
+    Inherit,
+}
+impl CompositeAlphaMode {
+    /// Check if the swapchains can be presented with this mode.
+    pub fn is_supported(&self) -> bool {
+        SUPPORTED_COMPOSITE_ALPHA_MODES.contains(self)
+    }
+}
+impl Default for CompositeAlphaMode {
+    fn default() -> Self {
+        Self::Opaque
+    }
+}
+
+/// Composite alpha modes the swapchains can be presented with.
+pub const SUPPORTED_COMPOSITE_ALPHA_MODES: &[CompositeAlphaMode] = &[CompositeAlphaMode::Opaque];
+
+/// Common swapchain formats, checked besides the preferred one of the surface.
+const SWAPCHAIN_FORMAT_CANDIDATES: &[crate::wgpu::TextureFormat] = &[
+    crate::wgpu::TextureFormat::Bgra8UnormSrgb,
+    crate::wgpu::TextureFormat::Bgra8Unorm,
+    crate::wgpu::TextureFormat::Rgba8UnormSrgb,
+    crate::wgpu::TextureFormat::Rgba8Unorm,
+    crate::wgpu::TextureFormat::Rgb10a2Unorm,
+    crate::wgpu::TextureFormat::Rgba16Float,
+];
+
+/**
+Select the swapchain format among the formats supported by the surface.
+If the requested format (like the HDR `Rgba16Float`) is not supported, the first supported one is returned
+and the fallback is logged. Returns `None` if there are no supported formats.
+*/
+pub fn select_swapchain_format(
+    requested: crate::wgpu::TextureFormat,
+    supported: &[crate::wgpu::TextureFormat],
+) -> Option<crate::wgpu::TextureFormat> {
+    if supported.contains(&requested) {
+        Some(requested)
+    } else {
+        let fallback = supported.first().copied();
+        if let Some(fallback) = fallback {
+            log::warn!(target: "EntityManager","Swapchain format {:?} not supported by the surface, falling back to {:?}",requested,fallback);
+        }
+        fallback
+    }
+}
+
+/**
+Select the present mode for the backend: `Fifo` with vsync, otherwise the mode without vsync
+in [supported_present_modes][supported_present_modes] (`Mailbox` or `Immediate`).
+Backends supporting only `Fifo` fall back to it with a warning.
+*/
+pub fn select_present_mode(vsync: bool, backend: crate::wgpu::Backend) -> crate::wgpu::PresentMode {
+    if vsync {
+        return crate::wgpu::PresentMode::Fifo;
+    }
+    match supported_present_modes(backend)
+        .into_iter()
+        .find(|mode| *mode != crate::wgpu::PresentMode::Fifo)
+    {
+        Some(mode) => mode,
+        None => {
+            log::warn!(target: "EntityManager","Disabling vsync not supported by the {:?} backend, falling back to Fifo",backend);
+            crate::wgpu::PresentMode::Fifo
+        }
+    }
+}
+
+/**
+Get the present modes supported on a backend, `Fifo` first since it is always supported.
+The wgpu version in use cannot query them from the surface, so they are deduced from the backend.
+If a surface still lacks the selected mode, wgpu itself falls back to `Fifo` when creating the swapchain,
+logging a warning, so the swapchain keeps working with vsync.
+*/
+pub fn supported_present_modes(backend: crate::wgpu::Backend) -> Vec<crate::wgpu::PresentMode> {
+    match backend {
+        crate::wgpu::Backend::Vulkan | crate::wgpu::Backend::Dx12 => vec![
+            crate::wgpu::PresentMode::Fifo,
+            crate::wgpu::PresentMode::Mailbox,
+        ],
+        crate::wgpu::Backend::Metal | crate::wgpu::Backend::Dx11 => vec![
+            crate::wgpu::PresentMode::Fifo,
+            crate::wgpu::PresentMode::Immediate,
+        ],
+        _ => vec![crate::wgpu::PresentMode::Fifo],
+    }
+}
+
+/**
+Select the requested present mode if supported, otherwise fall back to `Fifo`, that every surface supports, with a warning.
+*/
+pub fn select_supported_present_mode(
+    requested: crate::wgpu::PresentMode,
+    supported: &[crate::wgpu::PresentMode],
+) -> crate::wgpu::PresentMode {
+    if supported.contains(&requested) {
+        requested
+    } else {
+        log::warn!(target: "EntityManager","Present mode {:?} not supported, falling back to Fifo",requested);
+        crate::wgpu::PresentMode::Fifo
+    }
+}
+
+/// Maximum frame latency of the swapchains when not specified, balancing latency and smoothness.
+pub const DEFAULT_FRAME_LATENCY: u32 = 2;
+
+/**
+Get the maximum frame latencies that can be requested on a backend, from the lowest latency to the smoothest presentation.
+They follow the swapchain image counts of the backend: Metal keeps at most 3 drawables, so 2 frames can be queued,
+while the backends without control over the swapchain images have a latency of 1.
+*/
+pub fn supported_frame_latencies(backend: crate::wgpu::Backend) -> std::ops::RangeInclusive<u32> {
+    match backend {
+        crate::wgpu::Backend::Vulkan | crate::wgpu::Backend::Dx12 | crate::wgpu::Backend::Dx11 => {
+            1..=3
+        }
+        crate::wgpu::Backend::Metal => 1..=2,
+        _ => 1..=1,
+    }
+}
+
+/**
+Clamp the desired maximum frame latency to the [ones supported][supported_frame_latencies] by the backend, logging the fallback.
+The wgpu version in use chooses the swapchain image count by itself, so the selected latency is only stored
+in the [SwapchainDescriptor][SwapchainDescriptor], for the tasks pacing their frames.
+*/
+pub fn select_frame_latency(desired: u32, backend: crate::wgpu::Backend) -> u32 {
+    let supported = supported_frame_latencies(backend);
+    let latency = desired.clamp(*supported.start(), *supported.end());
+    if latency != desired {
+        log::warn!(target: "EntityManager","Maximum frame latency {} not supported by the {:?} backend, falling back to {}",desired,backend,latency);
+    }
+    latency
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/**
+Options of the swapchain created for a surface by [create_surface][crate::WGpuEngine::create_surface].
+*/
+pub struct SurfaceOptions {
+    /**
+    Background of the surface: it is used for the frames presented before anything has been rendered on it,
+    and for the clear at the start of every frame.
+    */
+    pub clear_color: crate::wgpu::Color,
+    /**
+    Desired maximum frame latency, like 1 for latency sensitive applications or 3 for smoother ones,
+    see [select_frame_latency][select_frame_latency].
+    */
+    pub frame_latency: u32,
+    /**
+    Present mode of the swapchain, `Mailbox` if `None`. Modes not supported by the backend fall back to `Fifo` (with vsync),
+    see [select_supported_present_mode][select_supported_present_mode].
+    */
+    pub present_mode: Option<crate::wgpu::PresentMode>,
+}
+impl Default for SurfaceOptions {
+    fn default() -> Self {
+        Self {
+            clear_color: crate::wgpu::Color::BLACK,
+            frame_latency: DEFAULT_FRAME_LATENCY,
+            present_mode: None,
+        }
+    }
+}
+
+/**
+Coalesce the resizes queued during a frame, as `(external_id, width, height)`, keeping only the last size of
+every surface: a continuous window resize queues many of them, while the swapchain needs to be rebuilt once.
+The surfaces keep the order of their last resize.
+*/
+pub fn coalesce_resizes(resizes: Vec<(usize, u32, u32)>) -> Vec<(usize, u32, u32)> {
+    let mut coalesced: Vec<(usize, u32, u32)> = Vec::with_capacity(resizes.len());
+    for resize in resizes {
+        coalesced.retain(|(external_id, _width, _height)| *external_id != resize.0);
+        coalesced.push(resize);
+    }
+    coalesced
+}
+
+#[derive(Debug, Clone, Default)]
+/**
+Policy deciding whether the frame of a swapchain that nothing rendered on gets acquired and presented anyway.
+Without a maximum every frame is presented. With a maximum, once a frame has been presented untouched,
+the next frames are not acquired unless something renders on them, until `max_skipped` consecutive frames have been skipped:
+the compositor still receives a frame at least every `max_skipped + 1` frames.
+Those forced presents are cleared to the clear color like any untouched frame, since the swapchain keeps no copy of the rendered contents.
+*/
+pub struct PresentThrottle {
+    max_skipped: Option<u32>,
+    skipped: HashMap<SwapchainId, u32>,
+}
+impl PresentThrottle {
+    pub fn new(max_skipped: Option<u32>) -> Self {
+        Self {
+            max_skipped,
+            skipped: HashMap::new(),
+        }
+    }
+    /// Maximum number of consecutive presents skipped, if any.
+    pub fn max_skipped(&self) -> Option<u32> {
+        self.max_skipped
+    }
+    /// Change the maximum number of consecutive presents skipped, resetting the counts.
+    pub fn set_max_skipped(&mut self, max_skipped: Option<u32>) {
+        self.max_skipped = max_skipped;
+        self.skipped.clear();
+    }
+    /**
+    Check, before acquiring the next frame of the swapchain, if the acquire can be skipped until something renders on it.
+    Only the swapchains whose last frame was presented untouched are skipped, and never more than `max_skipped` times in a row.
+    */
+    pub fn skip_acquire(&mut self, swapchain: SwapchainId) -> bool {
+        let max_skipped = match self.max_skipped {
+            Some(max_skipped) => max_skipped,
+            None => return false,
+        };
+        match self.skipped.get_mut(&swapchain) {
+            Some(skipped) if *skipped < max_skipped => {
+                *skipped += 1;
+                true
+            }
+            Some(skipped) => {
+                *skipped = 0;
+                false
+            }
+            None => false,
+        }
+    }
+    /// Record a frame of the swapchain presented without anything rendered on it.
+    pub fn untouched(&mut self, swapchain: SwapchainId) {
+        if self.max_skipped.is_some() {
+            self.skipped.entry(swapchain).or_insert(0);
+        }
+    }
+    /// Record a frame of the swapchain presented with new contents, or its removal.
+    pub fn presented(&mut self, swapchain: &SwapchainId) {
+        self.skipped.remove(swapchain);
+    }
+}
+
+/**
+Get the formats supported by a surface, starting with the preferred one.
+wgpu cannot list the formats of a surface, so the other common swapchain formats are included
+if the adapter can render to them. Returns an empty list if the adapter cannot present on the surface.
+*/
+pub fn supported_swapchain_formats(
+    adapter: &crate::wgpu::Adapter,
+    surface: &crate::wgpu::Surface,
+) -> Vec<crate::wgpu::TextureFormat> {
+    let preferred = match adapter.get_swap_chain_preferred_format(surface) {
+        Some(preferred) => preferred,
+        None => return Vec::new(),
+    };
+    std::iter::once(preferred)
+        .chain(
+            SWAPCHAIN_FORMAT_CANDIDATES
+                .iter()
+                .copied()
+                .filter(|format| *format != preferred)
+                .filter(|format| {
+                    adapter
+                        .get_texture_format_features(*format)
+                        .allowed_usages
+                        .contains(crate::wgpu::TextureUsage::RENDER_ATTACHMENT)
+                }),
+        )
+        .collect()
+}
 
 #[derive(Debug, Clone)]
 /**
@@ -17,6 +298,11 @@ pub struct SwapchainDescriptor {
     pub width: u32,
     pub height: u32,
     pub present_mode: crate::wgpu::PresentMode,
+    pub composite_alpha_mode: CompositeAlphaMode,
+    /// Background of the surface, presented until something is rendered on it.
+    pub clear_color: crate::wgpu::Color,
+    /// Number of frames that can be queued before presenting blocks, see [select_frame_latency][select_frame_latency].
+    pub desired_maximum_frame_latency: u32,
 }
 impl HaveDependencies for SwapchainDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
@@ -37,8 +323,8 @@ impl HaveDescriptor for SwapchainDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
 
@@ -68,6 +354,15 @@ impl PartialEq for SwapchainDescriptor {
         if self.present_mode != other.present_mode {
             return false;
         }
+        if self.composite_alpha_mode != other.composite_alpha_mode {
+            return false;
+        }
+        if self.clear_color != other.clear_color {
+            return false;
+        }
+        if self.desired_maximum_frame_latency != other.desired_maximum_frame_latency {
+            return false;
+        }
         true
     }
 }
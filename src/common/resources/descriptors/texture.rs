@@ -5,12 +5,43 @@ use crate::entity_manager::EntityId;
 use crate::resources::DeviceId;
 
 #[derive(Debug, Clone, PartialEq)]
-/// Possible sources of a texture.
-pub enum TextureSource {
+/**
+DRM properties (modifier and plane layouts) of a dmabuf.
+The type is the same with or without the `wgpu_custom` feature, but it can only be created
+from the wgpu ones, so without the feature a [DmaBuf][TextureSource::DmaBuf] can only use `None`.
+*/
+pub struct DmaBufProperties {
     #[cfg(feature = "wgpu_custom")]
+    properties: crate::wgpu::DrmFormatImageProperties,
+    #[cfg(not(feature = "wgpu_custom"))]
+    _private: (),
+}
+#[cfg(feature = "wgpu_custom")]
+impl DmaBufProperties {
+    pub fn properties(&self) -> &crate::wgpu::DrmFormatImageProperties {
+        &self.properties
+    }
+}
+#[cfg(feature = "wgpu_custom")]
+impl From<crate::wgpu::DrmFormatImageProperties> for DmaBufProperties {
+    fn from(properties: crate::wgpu::DrmFormatImageProperties) -> Self {
+        Self { properties }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/**
+Possible sources of a texture.
+The external memory sources ([DmaBuf][TextureSource::DmaBuf] and [OpaqueFd][TextureSource::OpaqueFd])
+require the `wgpu_custom` feature, that is the custom wgpu fork on Linux with the Vulkan backend:
+otherwise the texture fails to build with [ExternalMemoryUnsupported][crate::ResourceBuilderError::ExternalMemoryUnsupported].
+Their file descriptors are unix `RawFd`s, so this enum (and the crate) does not build on Windows,
+where `std::os::unix` does not exist, even if external memory is never used.
+*/
+pub enum TextureSource {
     DmaBuf {
         fd: std::os::unix::io::RawFd,
-        drm_properties: Option<crate::wgpu::DrmFormatImageProperties>,
+        drm_properties: Option<DmaBufProperties>,
         offset: u64,
     },
     OpaqueFd {
@@ -36,6 +67,70 @@ pub struct TextureDescriptor {
     pub mip_level_count: u32,
     pub sample_count: u32,
 }
+impl TextureDescriptor {
+    /// Returns true if the texture can be viewed as a cubemap: a square 2D texture with 6 layers.
+    pub fn is_cube_compatible(&self) -> bool {
+        self.dimension == crate::wgpu::TextureDimension::D2
+            && self.size.depth_or_array_layers == 6
+            && self.size.width == self.size.height
+    }
+    /**
+    Check that a multisampled texture is usable: it must be a render attachment only
+    (it cannot be copied, sampled or used as storage), with a 2D uncompressed format that can be rendered to.
+    `allowed_usages` are the usages supported by the format on the adapter.
+    Textures with a single sample are always valid.
+    */
+    pub fn validate_sample_count(&self, allowed_usages: crate::wgpu::TextureUsage) -> bool {
+        if self.sample_count <= 1 {
+            return true;
+        }
+        if !self.sample_count.is_power_of_two() {
+            log::error!(target: "EntityManager","Texture `{}`: sample count {} is not a power of two",self.label,self.sample_count);
+            return false;
+        }
+        if self.usage != crate::wgpu::TextureUsage::RENDER_ATTACHMENT {
+            log::error!(target: "EntityManager","Texture `{}`: a multisampled texture can only have the RENDER_ATTACHMENT usage, found {:?}",self.label,self.usage);
+            return false;
+        }
+        if self.dimension != crate::wgpu::TextureDimension::D2 || self.mip_level_count != 1 {
+            log::error!(target: "EntityManager","Texture `{}`: a multisampled texture must be 2D with a single mip level",self.label);
+            return false;
+        }
+        if self.format.describe().block_dimensions != (1, 1)
+            || !allowed_usages.contains(crate::wgpu::TextureUsage::RENDER_ATTACHMENT)
+        {
+            log::error!(target: "EntityManager","Texture `{}`: format {:?} does not support multisampling",self.label,self.format);
+            return false;
+        }
+        true
+    }
+
+    /**
+    Estimate the memory used by the texture, summing the size of every mip level of every layer and sample.
+    Driver padding and alignment are ignored.
+    */
+    pub fn estimated_size(&self) -> u64 {
+        let info = self.format.describe();
+        let block_width = info.block_dimensions.0 as u32;
+        let block_height = info.block_dimensions.1 as u32;
+        let size: u64 = (0..self.mip_level_count)
+            .map(|level| {
+                let width = (self.size.width >> level).max(1);
+                let height = (self.size.height >> level).max(1);
+                let layers = match self.dimension {
+                    crate::wgpu::TextureDimension::D3 => {
+                        (self.size.depth_or_array_layers >> level).max(1)
+                    }
+                    _ => self.size.depth_or_array_layers,
+                };
+                let blocks = ((width + block_width - 1) / block_width) as u64
+                    * ((height + block_height - 1) / block_height) as u64;
+                blocks * info.block_size as u64 * layers as u64
+            })
+            .sum();
+        size * self.sample_count as u64
+    }
+}
 impl HaveDependencies for TextureDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         vec![*self.device.id_ref()]
@@ -55,7 +150,24 @@ impl HaveDescriptor for TextureDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Statefull
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
+    }
+}
+
+/**
+Select the highest sample count among the `supported` ones not exceeding `requested`, logging a warning
+if it is lower than the requested one. A single sample is always supported.
+*/
+pub fn select_sample_count(requested: u32, supported: &[u32]) -> u32 {
+    let selected = supported
+        .iter()
+        .copied()
+        .filter(|count| *count <= requested)
+        .max()
+        .unwrap_or(1);
+    if selected < requested {
+        log::warn!(target: "EntityManager","Sample count {} not supported, falling back to {}",requested,selected);
     }
+    selected
 }
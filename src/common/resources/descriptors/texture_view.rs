@@ -3,6 +3,7 @@
 use crate::common::resources::descriptors::{HaveDependencies, HaveDescriptor, StateType};
 use crate::entity_manager::EntityId;
 use crate::resources::{DeviceId, TextureId};
+use crate::TextureDescriptor;
 
 #[derive(Debug, Clone, PartialEq)]
 /**
@@ -20,6 +21,71 @@ pub struct TextureViewDescriptor {
     pub base_array_layer: u32,
     pub array_layer_count: Option<std::num::NonZeroU32>,
 }
+impl TextureViewDescriptor {
+    /**
+    2D view of a single layer of an array texture. Views of different layers of the same texture
+    can be bound together in a [BindingResource::TextureViewArray][crate::BindingResource::TextureViewArray],
+    using a single texture instead of one per element.
+    */
+    pub fn layer(
+        label: String,
+        device: DeviceId,
+        texture: TextureId,
+        format: crate::wgpu::TextureFormat,
+        layer: u32,
+    ) -> Self {
+        Self {
+            label,
+            device,
+            texture,
+            format,
+            dimension: crate::wgpu::TextureViewDimension::D2,
+            aspect: crate::wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: std::num::NonZeroU32::new(1),
+            base_array_layer: layer,
+            array_layer_count: std::num::NonZeroU32::new(1),
+        }
+    }
+    /// Single layer 2D view of a cubemap face, usable as color target.
+    pub fn cubemap_face(
+        label: String,
+        device: DeviceId,
+        texture: TextureId,
+        format: crate::wgpu::TextureFormat,
+        face: u32,
+    ) -> Self {
+        Self::layer(label, device, texture, format, face)
+    }
+
+    /// Check that the mip levels and the array layers of the view are within the ones of the texture.
+    pub fn is_within(&self, texture: &TextureDescriptor) -> bool {
+        let layer_count = match texture.dimension {
+            crate::wgpu::TextureDimension::D3 => 1,
+            _ => texture.size.depth_or_array_layers,
+        };
+        // A range overflowing u32 is out of the texture as well.
+        let mip_level_end = self.base_mip_level.checked_add(
+            self.mip_level_count
+                .map(|count| count.get())
+                .unwrap_or(texture.mip_level_count.saturating_sub(self.base_mip_level)),
+        );
+        let array_layer_end = self.base_array_layer.checked_add(
+            self.array_layer_count
+                .map(|count| count.get())
+                .unwrap_or(layer_count.saturating_sub(self.base_array_layer)),
+        );
+        match (mip_level_end, array_layer_end) {
+            (Some(mip_level_end), Some(array_layer_end)) => {
+                self.base_mip_level < texture.mip_level_count
+                    && mip_level_end <= texture.mip_level_count
+                    && self.base_array_layer < layer_count
+                    && array_layer_end <= layer_count
+            }
+            _ => false,
+        }
+    }
+}
 impl HaveDependencies for TextureViewDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
         std::iter::once(*self.device.id_ref())
@@ -41,7 +107,7 @@ impl HaveDescriptor for TextureViewDescriptor {
     fn state_type(&self) -> StateType {
         StateType::Stateless
     }
-    fn needs_update(&self, _other: &Self::D) -> bool {
-        true
+    fn needs_update(&self, other: &Self::D) -> bool {
+        self != other
     }
 }
@@ -1,6 +1,7 @@
 //! Handles for the resources.
 
 use std::convert::TryInto;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Handle for an [Instance][crate::wgpu::Instance].
@@ -24,6 +25,8 @@ pub type TextureViewHandle = Arc<crate::wgpu::TextureView>;
 pub type SamplerHandle = Arc<crate::wgpu::Sampler>;
 /// Handle for a [ShaderModule][crate::wgpu::ShaderModule].
 pub type ShaderModuleHandle = Arc<crate::wgpu::ShaderModule>;
+/// Handle for a [QuerySet][crate::wgpu::QuerySet].
+pub type QuerySetHandle = Arc<crate::wgpu::QuerySet>;
 
 /// Handle for a [BindGroupLayout][crate::wgpu::BindGroupLayout].
 pub type BindGroupLayoutHandle = Arc<crate::wgpu::BindGroupLayout>;
@@ -36,6 +39,8 @@ pub type PipelineLayoutHandle = Arc<crate::wgpu::PipelineLayout>;
 pub type RenderPipelineHandle = Arc<crate::wgpu::RenderPipeline>;
 /// Handle for a [ComputePipeline][crate::wgpu::ComputePipeline].
 pub type ComputePipelineHandle = Arc<crate::wgpu::ComputePipeline>;
+/// Handle for a [RenderBundle][crate::wgpu::RenderBundle].
+pub type RenderBundleHandle = Arc<crate::wgpu::RenderBundle>;
 /// Handle for a [CommandBuffer][crate::wgpu::CommandBuffer].
 pub type CommandBufferHandle = Arc<crate::wgpu::CommandBuffer>;
 
@@ -53,6 +58,7 @@ pub enum ResourceHandle {
     TextureView(TextureViewHandle),
     Sampler(SamplerHandle),
     ShaderModule(ShaderModuleHandle),
+    QuerySet(QuerySetHandle),
 
     BindGroupLayout(BindGroupLayoutHandle),
     BindGroup(BindGroupHandle),
@@ -60,6 +66,7 @@ pub enum ResourceHandle {
     PipelineLayout(PipelineLayoutHandle),
     RenderPipeline(RenderPipelineHandle),
     ComputePipeline(ComputePipelineHandle),
+    RenderBundle(RenderBundleHandle),
     CommandBuffer(CommandBufferHandle),
 }
 impl From<InstanceHandle> for ResourceHandle {
@@ -158,6 +165,22 @@ impl From<Arc<crate::wgpu::ShaderModule>> for ResourceHandle {
     }
 }
 
+impl TryInto<Arc<crate::wgpu::QuerySet>> for ResourceHandle {
+    type Error = Self;
+    fn try_into(self) -> Result<Arc<crate::wgpu::QuerySet>, Self::Error> {
+        if let ResourceHandle::QuerySet(handle) = self {
+            Ok(handle)
+        } else {
+            Err(self)
+        }
+    }
+}
+impl From<Arc<crate::wgpu::QuerySet>> for ResourceHandle {
+    fn from(resource: Arc<crate::wgpu::QuerySet>) -> Self {
+        Self::QuerySet(resource)
+    }
+}
+
 impl TryInto<Arc<crate::wgpu::BindGroup>> for ResourceHandle {
     type Error = Self;
     fn try_into(self) -> Result<Arc<crate::wgpu::BindGroup>, Self::Error> {
@@ -237,6 +260,22 @@ impl From<Arc<crate::wgpu::ComputePipeline>> for ResourceHandle {
         Self::ComputePipeline(resource)
     }
 }
+
+impl TryInto<Arc<crate::wgpu::RenderBundle>> for ResourceHandle {
+    type Error = Self;
+    fn try_into(self) -> Result<Arc<crate::wgpu::RenderBundle>, Self::Error> {
+        if let ResourceHandle::RenderBundle(handle) = self {
+            Ok(handle)
+        } else {
+            Err(self)
+        }
+    }
+}
+impl From<Arc<crate::wgpu::RenderBundle>> for ResourceHandle {
+    fn from(resource: Arc<crate::wgpu::RenderBundle>) -> Self {
+        Self::RenderBundle(resource)
+    }
+}
 impl From<Arc<crate::wgpu::CommandBuffer>> for ResourceHandle {
     fn from(resource: Arc<crate::wgpu::CommandBuffer>) -> Self {
         Self::CommandBuffer(resource)
@@ -246,10 +285,20 @@ impl From<Arc<crate::wgpu::CommandBuffer>> for ResourceHandle {
 #[derive(Debug, Clone)]
 /// Swapchain for the engine.
 pub struct Swapchain {
+    device: DeviceHandle,
     swapchain_descriptor: crate::wgpu::SwapChainDescriptor,
     swapchain: Arc<crate::wgpu::SwapChain>,
 
     current_frame: Arc<Mutex<Option<crate::wgpu::SwapChainFrame>>>,
+    // Whether the swapchain has been rendered to at least once: until then, its frames have undefined contents.
+    rendered: Arc<AtomicBool>,
+    // Number of frames cleared to the clear color by the swapchain itself.
+    cleared_frames: Arc<AtomicUsize>,
+    // Number of frames presented, whoever presented them.
+    presented_frames: Arc<AtomicUsize>,
+    // Whether the acquire of the next frame waits for something to render on the swapchain.
+    deferred: Arc<AtomicBool>,
+    clear_color: crate::wgpu::Color,
 }
 
 impl Swapchain {
@@ -260,14 +309,17 @@ impl Swapchain {
             crate::wgpu::Queue,
         )>,
         surface: Arc<crate::wgpu::Surface>,
+        format: crate::wgpu::TextureFormat,
         width: u32,
         height: u32,
+        present_mode: crate::wgpu::PresentMode,
+        clear_color: crate::wgpu::Color,
     ) -> Option<Self> {
         // Create swapchain
         let swapchain_descriptor = crate::wgpu::SwapChainDescriptor {
             usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
-            format: device.0.get_swap_chain_preferred_format(&surface).unwrap(),
-            present_mode: crate::wgpu::PresentMode::Mailbox,
+            format,
+            present_mode,
             width,
             height,
         };
@@ -279,9 +331,15 @@ impl Swapchain {
         };
 
         Some(Self {
+            device: device.clone(),
             swapchain_descriptor,
             swapchain,
             current_frame,
+            rendered: Arc::new(AtomicBool::new(false)),
+            cleared_frames: Arc::new(AtomicUsize::new(0)),
+            presented_frames: Arc::new(AtomicUsize::new(0)),
+            deferred: Arc::new(AtomicBool::new(false)),
+            clear_color,
         })
     }
 
@@ -296,9 +354,108 @@ impl Swapchain {
         }
     }
 
+    /**
+    Acquire the next frame only when something renders on the swapchain,
+    through [acquire_deferred_frame][Swapchain::acquire_deferred_frame].
+    */
+    pub fn defer_frame(&self) {
+        self.deferred.store(true, Ordering::Relaxed);
+    }
+    /// Acquire the frame deferred by [defer_frame][Swapchain::defer_frame], if any. Returns true if a frame is acquired.
+    pub fn acquire_deferred_frame(&self) -> bool {
+        if self.deferred.swap(false, Ordering::Relaxed) {
+            self.prepare_frame();
+        }
+        self.current_frame().is_some()
+    }
+    /// Stop waiting for something to render on the swapchain. Returns true if the deferred frame was never acquired.
+    pub fn cancel_deferred_frame(&self) -> bool {
+        self.deferred.swap(false, Ordering::Relaxed)
+    }
+
+    /**
+    Present the current frame. If the swapchain has never been rendered to,
+    the frame is cleared to the [clear color][Swapchain::clear_color] first, instead of presenting undefined contents.
+    */
     pub fn present(&self) {
         let mut current_frame = self.current_frame.lock().unwrap();
-        current_frame.take();
+        if let Some(frame) = current_frame.as_ref() {
+            if !self.is_rendered() {
+                log::info!(target: "Engine","Clearing never rendered swapchain frame before presenting");
+                self.clear(&frame.output.view);
+            }
+        }
+        if current_frame.take().is_some() {
+            self.presented_frames.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /**
+    Clear the current frame to the [clear color][Swapchain::clear_color] and present it,
+    for the frames that nothing has rendered on.
+    */
+    pub fn present_cleared(&self) {
+        let mut current_frame = self.current_frame.lock().unwrap();
+        if let Some(frame) = current_frame.as_ref() {
+            self.clear(&frame.output.view);
+        }
+        if current_frame.take().is_some() {
+            self.presented_frames.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Mark the swapchain as rendered to, so that its frames are presented as they are.
+    pub fn mark_rendered(&self) {
+        self.rendered.store(true, Ordering::Relaxed);
+    }
+    /// Background color of the swapchain.
+    pub fn clear_color(&self) -> crate::wgpu::Color {
+        self.clear_color
+    }
+    /**
+    Operations of the passes clearing the frames to the [clear color][Swapchain::clear_color],
+    both the ones presenting never rendered frames and the one at the start of every frame using the swapchain.
+    The cleared contents are stored, so the passes loading the frame afterwards start from the clear color.
+    */
+    pub fn clear_operations(&self) -> crate::wgpu::Operations<crate::wgpu::Color> {
+        crate::wgpu::Operations {
+            load: crate::wgpu::LoadOp::Clear(self.clear_color),
+            store: true,
+        }
+    }
+    /// Check if the swapchain has been rendered to at least once.
+    pub fn is_rendered(&self) -> bool {
+        self.rendered.load(Ordering::Relaxed)
+    }
+    /// Number of frames cleared to the [clear color][Swapchain::clear_color] before being presented, since its creation.
+    pub fn cleared_frames(&self) -> usize {
+        self.cleared_frames.load(Ordering::Relaxed)
+    }
+    /// Number of frames presented since its creation.
+    pub fn presented_frames(&self) -> usize {
+        self.presented_frames.load(Ordering::Relaxed)
+    }
+
+    fn clear(&self, view: &crate::wgpu::TextureView) {
+        let mut encoder =
+            self.device
+                .1
+                .create_command_encoder(&crate::wgpu::CommandEncoderDescriptor {
+                    label: Some("Swapchain first frame clear"),
+                });
+        {
+            let _render_pass = encoder.begin_render_pass(&crate::wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[crate::wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: self.clear_operations(),
+                }],
+                depth_stencil_attachment: None,
+            });
+        }
+        self.device.2.submit(std::iter::once(encoder.finish()));
+        self.cleared_frames.fetch_add(1, Ordering::Relaxed);
     }
 
     pub fn current_frame(&self) -> MutexGuard<Option<crate::wgpu::SwapChainFrame>> {
@@ -111,6 +111,9 @@ impl std::fmt::Display for Resource {
             ResourceDescriptor::ShaderModule(descriptor) => {
                 write!(f, "ShaderModule `{}`", descriptor.label)
             }
+            ResourceDescriptor::QuerySet(descriptor) => {
+                write!(f, "QuerySet `{}`", descriptor.label)
+            }
 
             ResourceDescriptor::BindGroupLayout(descriptor) => {
                 write!(f, "BindGroupLayout `{}`", descriptor.label)
@@ -128,6 +131,9 @@ impl std::fmt::Display for Resource {
             ResourceDescriptor::ComputePipeline(descriptor) => {
                 write!(f, "ComputePipeline `{}`", descriptor.label)
             }
+            ResourceDescriptor::RenderBundle(descriptor) => {
+                write!(f, "RenderBundle `{}`", descriptor.label)
+            }
             ResourceDescriptor::CommandBuffer(descriptor) => {
                 write!(f, "CommandBuffer `{}`", descriptor.label)
             }
@@ -285,11 +291,13 @@ make_resource_ids!(
     TextureView,
     Sampler,
     ShaderModule,
+    QuerySet,
     BindGroupLayout,
     BindGroup,
     PipelineLayout,
     RenderPipeline,
     ComputePipeline,
+    RenderBundle,
     CommandBuffer
 );
 
@@ -303,11 +311,13 @@ pub enum ResourceType {
     TextureView,
     Sampler,
     ShaderModule,
+    QuerySet,
     BindGroupLayout,
     BindGroup,
     PipelineLayout,
     RenderPipeline,
     ComputePipeline,
+    RenderBundle,
     CommandBuffer,
 }
 
@@ -6,15 +6,18 @@ pub struct TaskDescriptor {
     pub name: String,
     pub broken: bool,
     pub dependencies: Vec<TaskId>,
+    /// Devices the task is restricted to. If empty, the task can use every device.
+    pub devices: Vec<DeviceId>,
 }
 
 impl TaskDescriptor {
-    pub(crate) fn new(name: String, dependencies: Vec<TaskId>) -> Self {
+    pub(crate) fn new(name: String, dependencies: Vec<TaskId>, devices: Vec<DeviceId>) -> Self {
         let broken = false;
         Self {
             name,
             broken,
             dependencies,
+            devices,
         }
     }
     pub(crate) fn name(&self) -> &str {
@@ -26,6 +29,10 @@ impl TaskDescriptor {
     pub(crate) fn broken(&self) -> bool {
         self.broken
     }
+    /// Returns true if the task is allowed to use the device.
+    pub(crate) fn uses_device(&self, device: &DeviceId) -> bool {
+        self.devices.is_empty() || self.devices.contains(device)
+    }
 }
 impl HaveDependencies for TaskDescriptor {
     fn dependencies(&self) -> Vec<EntityId> {
@@ -31,6 +31,28 @@ impl<'a> Batch<'a> {
         &mut self.resource_manager
     }
 
+    /**
+    Get the command buffers added to the batch, of every device, sorted by id.
+    */
+    pub fn command_buffers(&self) -> Vec<CommandBufferId> {
+        let mut command_buffers: Vec<_> = self
+            .batches
+            .values()
+            .flat_map(|batch| batch.command_buffers_to_dispatch.iter().copied())
+            .collect();
+        command_buffers.sort_by_key(|id| id.id());
+        command_buffers
+    }
+
+    /**
+    Check if the batch has pending resource write operations.
+    */
+    pub fn has_resource_writes(&self) -> bool {
+        self.batches
+            .values()
+            .any(|batch| !batch.resource_writes.is_empty())
+    }
+
     /**
     Add a pending resource write operation.
     */
@@ -102,6 +124,16 @@ impl<'a> Batch<'a> {
             batch.submit(&mut self.resource_manager, &device_id)
         }
     }
+
+    /**
+    Submit only the resource writes of the batch, leaving the command buffers and the swapchains untouched.
+    */
+    pub fn submit_writes(self) {
+        log::info!(target: "Engine","Submitting batch writes");
+        for (device_id, batch) in self.batches {
+            batch.submit_writes(&self.resource_manager, &device_id)
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -147,6 +179,26 @@ impl DeviceBatch {
             .append(&mut command_buffers);
     }
 
+    /**
+    Submit only the resource writes.
+    */
+    pub fn submit_writes(self, resource_manager: &ResourceManager, device_id: &DeviceId) {
+        let device = match resource_manager.device_handle_ref(device_id) {
+            Some(device) => device.clone(),
+            None => {
+                log::error!(target: "Engine","Failed to dispatch Batch writes: Device {} does not exists, skipping",device_id);
+                return;
+            }
+        };
+
+        let queue = &device.2;
+        ResourceWrite::merge(self.resource_writes)
+            .into_iter()
+            .for_each(|resource_write| resource_write.record(resource_manager, queue));
+        // Writes are flushed by the next submission.
+        queue.submit(std::iter::empty());
+    }
+
     /**
     Submit the batch.
     */
@@ -160,7 +212,9 @@ impl DeviceBatch {
         };
 
         let queue = &device.2;
-        self.resource_writes
+        // Writes must be recorded before the submission, so that the command buffers of this batch
+        // (including the ones of tasks dispatched before the writing task) read the written data.
+        ResourceWrite::merge(self.resource_writes)
             .into_iter()
             .for_each(|resource_write| resource_write.record(&resource_manager, queue));
 
@@ -169,13 +223,14 @@ impl DeviceBatch {
                 Some(swapchain) => {
                     log::info!(target: "Engine","Preparing clear command buffer for {} ",swapchain_id);
                     let current_frame = swapchain.current_frame();
+                    if current_frame.is_none() {
+                        log::warn!(target: "Engine","{} has no frame (suspended?), skipping clear",swapchain_id);
+                        return;
+                    }
                     let color_attachments = vec![crate::wgpu::RenderPassColorAttachment {
                         view: &current_frame.as_ref().unwrap().output.view,
                         resolve_target: None,
-                        ops: crate::wgpu::Operations {
-                            load: crate::wgpu::LoadOp::Clear(crate::wgpu::Color::BLACK),
-                            store: false,
-                        },
+                        ops: swapchain.clear_operations(),
                     }];
 
                     let depth_stencil = depth_stencil_id.as_ref().map(|id|{
@@ -231,6 +286,11 @@ impl DeviceBatch {
         queue.submit(command_buffers);
         for (swapchain_id, _) in &self.swapchains_to_clear {
             if let Some(swapchain) = resource_manager.swapchain_handle_ref(swapchain_id) {
+                swapchain.mark_rendered();
+                // The claimed swapchains are presented by their task, through UpdateContext::present_swapchain.
+                if resource_manager.swapchain_owner(swapchain_id).is_some() {
+                    continue;
+                }
                 swapchain.present();
                 //swapchain.prepare_frame();
             }
@@ -10,11 +10,7 @@ enum PendingCommand {
         surface: Arc<crate::wgpu::Surface>,
         width: u32,
         height: u32,
-    },
-    ResizeSwapchain {
-        external_id: usize,
-        width: u32,
-        height: u32,
+        options: SurfaceOptions,
     },
     DestroySwapchain {
         external_id: usize,
@@ -24,11 +20,26 @@ enum PendingCommand {
 pub struct EngineTask {
     tokio: tokio::runtime::Handle,
     id: TaskId,
-    instance: InstanceId,
+    requirements: Requirements,
+    /// First limit missed by the adapters, if none of them satisfied the minimum limits.
+    unmet_limits: Option<(&'static str, u32, u32)>,
+    instances: Vec<InstanceId>,
     devices: Vec<DeviceId>,
     swapchains: HashMap<usize, SwapchainId>,
+    destroyed_swapchains: Vec<SwapchainId>,
+    requested_surfaces: HashSet<usize>,
+    surfaces_ready_sent: bool,
 
     pending_commands: Vec<PendingCommand>,
+    pending_resizes: Vec<(usize, u32, u32)>,
+    pending_vsync: Vec<(usize, bool)>,
+    resized_swapchains: Vec<SwapchainId>,
+    suspended_swapchains: HashSet<SwapchainId>,
+    pending_events: Vec<ResourceEvent>,
+    swapchain_format: Option<crate::wgpu::TextureFormat>,
+    composite_alpha_mode: CompositeAlphaMode,
+    present_throttle: PresentThrottle,
+    paused: bool,
 }
 
 impl EngineTask {
@@ -39,9 +50,90 @@ impl EngineTask {
         requirements: impl Into<Requirements>,
         update_context: &mut UpdateContext,
     ) -> Self {
-        let (features, limits) = requirements.into().into();
+        let requirements = requirements.into();
+
+        let backend = requirements.backends();
+        let (instance, mut devices, mut unmet_limits) =
+            match Self::init_instance(&tokio, backend, &requirements, update_context) {
+                Ok(instance_and_devices) => instance_and_devices,
+                Err(()) => {
+                    //return Err(WGpuEngineError::InitializationFailed);
+                    panic!()
+                }
+            };
+        let mut instances = vec![instance];
+
+        if devices.is_empty()
+            && requirements.allow_software_fallback()
+            && !backend.contains(crate::wgpu::BackendBit::GL)
+        {
+            let fallback_backend = crate::wgpu::BackendBit::GL;
+            log::warn!(target: "EngineTask","No {:?} adapter found, falling back on the {:?} backend: software adapters are much slower",backend,fallback_backend);
+            if let Ok((fallback_instance, fallback_devices, fallback_unmet_limits)) =
+                Self::init_instance(&tokio, fallback_backend, &requirements, update_context)
+            {
+                instances.push(fallback_instance);
+                if !fallback_devices.is_empty() {
+                    devices = fallback_devices;
+                    unmet_limits = fallback_unmet_limits;
+                }
+            }
+        }
+
+        let swapchains = HashMap::new();
+        let destroyed_swapchains = Vec::new();
+        let requested_surfaces = HashSet::new();
+        let surfaces_ready_sent = false;
+        let pending_commands = Vec::new();
+        let pending_resizes = Vec::new();
+        let pending_vsync = Vec::new();
+        let resized_swapchains = Vec::new();
+        let suspended_swapchains = HashSet::new();
+        let pending_events = Vec::new();
+        let swapchain_format = None;
+        let composite_alpha_mode = CompositeAlphaMode::default();
+        let present_throttle = PresentThrottle::default();
+        let paused = false;
 
-        let backend = crate::wgpu::BackendBit::VULKAN;
+        Self {
+            tokio,
+            id,
+            requirements,
+            unmet_limits,
+            instances,
+            devices,
+            swapchains,
+            destroyed_swapchains,
+            requested_surfaces,
+            surfaces_ready_sent,
+            pending_commands,
+            pending_resizes,
+            pending_vsync,
+            resized_swapchains,
+            suspended_swapchains,
+            pending_events,
+            swapchain_format,
+            composite_alpha_mode,
+            present_throttle,
+            paused,
+        }
+    }
+
+    /**
+    Create an instance of the passed backends, and a device for each one of their adapters.
+    Every device records the single backend of its adapter, so that it can be found again when rebuilt.
+    Adapters not satisfying the minimum limits are skipped: if all of them are skipped,
+    the first unmet limit is returned along with the instance.
+    Of the remaining adapters only the ones matching the requested power preference and name are used,
+    or the first one if none matches.
+    */
+    fn init_instance(
+        tokio: &tokio::runtime::Handle,
+        backend: crate::wgpu::BackendBit,
+        requirements: &Requirements,
+        update_context: &mut UpdateContext,
+    ) -> Result<(InstanceId, Vec<DeviceId>, Option<(&'static str, u32, u32)>), ()> {
+        let (features, limits) = requirements.clone().into();
         let instance_descriptor = InstanceDescriptor {
             label: String::from("Engine"),
             backend,
@@ -55,13 +147,34 @@ impl EngineTask {
             Ok(instance) => instance,
             Err(err) => {
                 log::error!(target: "Engine","Failed to initialize Instance: {:#?}",err);
-                //return Err(WGpuEngineError::InitializationFailed);
-                panic!()
+                return Err(());
             }
         };
 
-        let devices: Vec<_> = instance_handle
+        let mut unmet_limits = None;
+        let mut adapters: Vec<_> = instance_handle
             .enumerate_adapters(backend)
+            .filter(|adapter| match requirements.check_minimum_limits(&adapter.limits()) {
+                Ok(()) => true,
+                Err((field, required, available)) => {
+                    log::error!(target: "Engine","Skipping adapter {}: limit {} is {}, {} required",adapter.get_info().name,field,available,required);
+                    unmet_limits.get_or_insert((field, required, available));
+                    false
+                }
+            })
+            .collect();
+        if adapters
+            .iter()
+            .any(|adapter| requirements.matches_adapter(&adapter.get_info()))
+        {
+            adapters.retain(|adapter| requirements.matches_adapter(&adapter.get_info()));
+        } else if let Some(adapter) = adapters.first() {
+            log::warn!(target: "EngineTask","No adapter matches the power preference {:?} and name {:?}, falling back on {}",requirements.power_preference(),requirements.adapter_name(),adapter.get_info().name);
+            adapters.truncate(1);
+        }
+
+        let devices: Vec<_> = adapters
+            .into_iter()
             .map(|adapter| {
                 let features = adapter.features() & features;
                 let limits = adapter.limits().min(limits.clone());
@@ -71,8 +184,8 @@ impl EngineTask {
                 let descriptor = DeviceDescriptor {
                     label: adapter_info.name,
                     instance,
-                    backend,
-                    pci_id: adapter_info.vendor,
+                    backend: crate::wgpu::BackendBit::from(adapter_info.backend),
+                    pci_id: adapter_info.device,
                     features,
                     limits: limits.clone(),
                 };
@@ -82,9 +195,9 @@ impl EngineTask {
                     features,
                     limits,
                 };
-                let (device, queue) = tokio
-                    .block_on(adapter.request_device(&device_descriptor, None))
-                    .unwrap();
+                let (device, queue) =
+                    crate::utils::block_on(tokio, adapter.request_device(&device_descriptor, None))
+                        .unwrap();
                 (descriptor, Arc::new((adapter, device, queue)))
             })
             .filter_map(|(device_descriptor, device_handle)| {
@@ -101,21 +214,37 @@ impl EngineTask {
             })
             .collect();
 
-        let swapchains = HashMap::new();
-        let pending_commands = Vec::new();
-
-        Self {
-            tokio,
-            id,
-            instance,
-            devices,
-            swapchains,
-            pending_commands,
+        if !devices.is_empty() {
+            unmet_limits = None;
         }
+        Ok((instance, devices, unmet_limits))
+    }
+
+    /**
+    Add a new instance of the passed backend, creating a device for each one of its adapters.
+    */
+    pub fn add_instance(
+        &mut self,
+        backend: crate::wgpu::BackendBit,
+        update_context: &mut UpdateContext,
+    ) -> Result<InstanceId, ()> {
+        let (instance, mut devices, _unmet_limits) =
+            Self::init_instance(&self.tokio, backend, &self.requirements, update_context)?;
+        self.instances.push(instance);
+        self.devices.append(&mut devices);
+        Ok(instance)
     }
 
+    /// The first instance, created with the engine.
     pub fn instance(&self) -> &InstanceId {
-        &self.instance
+        &self.instances[0]
+    }
+    pub fn instances(&self) -> &Vec<InstanceId> {
+        &self.instances
+    }
+    /// First limit missed by the adapters of the first instance, if none of them satisfied the minimum limits.
+    pub fn unmet_limits(&self) -> Option<(&'static str, u32, u32)> {
+        self.unmet_limits
     }
     pub fn devices(&self) -> &Vec<DeviceId> {
         &self.devices
@@ -123,6 +252,55 @@ impl EngineTask {
     pub fn swapchains(&self) -> impl Iterator<Item = &SwapchainId> {
         self.swapchains.values()
     }
+    /// Swapchain of the surface with the passed external id, if created.
+    pub fn swapchain(&self, external_id: usize) -> Option<SwapchainId> {
+        self.swapchains.get(&external_id).copied()
+    }
+
+    /// Check if a swapchain is suspended because its surface has a zero size (like a minimized window).
+    pub fn is_suspended(&self, id: &SwapchainId) -> bool {
+        self.suspended_swapchains.contains(id)
+    }
+
+    /// Check if every requested surface has a swapchain, using `is_built` to check if it has been built.
+    pub fn surfaces_ready(&self, is_built: impl Fn(&SwapchainId) -> bool) -> bool {
+        !self.requested_surfaces.is_empty()
+            && self.requested_surfaces.iter().all(|external_id| {
+                self.swapchains
+                    .get(external_id)
+                    .map(|id| is_built(id))
+                    .unwrap_or(false)
+            })
+    }
+
+    /**
+    Set the output of the swapchains created from now on. A `None` format uses the surface preferred one,
+    while an unsupported format (like an HDR one on a SDR display) falls back to it.
+    */
+    pub fn set_swapchain_output(
+        &mut self,
+        format: Option<crate::wgpu::TextureFormat>,
+        composite_alpha_mode: CompositeAlphaMode,
+    ) {
+        self.swapchain_format = format;
+        self.composite_alpha_mode = composite_alpha_mode;
+    }
+
+    /**
+    Stop or resume acquiring the swapchain frames. While paused no frame is prepared,
+    so nothing gets presented until resumed.
+    */
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /**
+    Skip up to `max_skipped` consecutive presents of the unclaimed swapchains that nothing rendered on,
+    acquiring their frames only if something renders on them. `None` presents every frame. See [PresentThrottle][PresentThrottle].
+    */
+    pub fn set_max_skipped_presents(&mut self, max_skipped: Option<u32>) {
+        self.present_throttle.set_max_skipped(max_skipped);
+    }
 
     pub fn create_swapchain(
         &mut self,
@@ -131,25 +309,193 @@ impl EngineTask {
         surface: Arc<crate::wgpu::Surface>,
         width: u32,
         height: u32,
+        options: SurfaceOptions,
     ) {
+        self.requested_surfaces.insert(external_id);
         self.pending_commands.push(PendingCommand::CreateSwapchain {
             external_id,
             label,
             surface,
             width,
             height,
+            options,
         });
     }
 
+    /**
+    Queue a swapchain resize. Resizes are applied by [end_frame][EngineTask::end_frame],
+    once the current frame has been submitted and presented. Only the last resize of a surface
+    in a frame is applied, see [coalesce_resizes][crate::coalesce_resizes].
+    */
     pub fn resize_swapchain(&mut self, external_id: usize, width: u32, height: u32) {
-        self.pending_commands.push(PendingCommand::ResizeSwapchain {
-            external_id,
-            width,
-            height,
-        });
+        self.pending_resizes.push((external_id, width, height));
+    }
+
+    /**
+    Queue a switch of the swapchain present mode, between `Fifo` with vsync and a mode without it
+    (see [select_present_mode][crate::select_present_mode]). Like the resizes, it is applied by [end_frame][EngineTask::end_frame].
+    */
+    pub fn set_vsync(&mut self, external_id: usize, vsync: bool) {
+        self.pending_vsync.push((external_id, vsync));
+    }
+
+    /**
+    Frame barrier, to be called after the frame has been submitted and presented.
+    It applies the queued resizes and present mode switches, so that no frame acquired by the old swapchain is still in use,
+    while the SwapchainUpdated events are emitted on the next dispatch.
+    A zero width or height suspends the swapchain instead of reconfiguring it,
+    until a non-zero size arrives.
+    The swapchains not [claimed][UpdateContext::claim_swapchain] by any task and not rendered
+    during the frame are presented cleared, while the claimed ones are left to their task.
+    On a `reused` frame nothing has been submitted, so the acquired frames are kept for the next one
+    and the swapchains keep showing the last presented frame.
+    */
+    pub fn end_frame(&mut self, update_context: &mut UpdateContext, reused: bool) {
+        if !self.paused {
+            let unclaimed_swapchains = self.unclaimed_swapchains(update_context);
+            for id in self.swapchains.values() {
+                let handle = match update_context.swapchain_handle_ref(id) {
+                    Some(handle) => handle,
+                    None => continue,
+                };
+                if handle.current_frame().is_none() {
+                    if handle.cancel_deferred_frame() {
+                        log::info!(target: "EngineTask","Skipped the present of untouched {}",id);
+                    } else {
+                        // The frames rendered on during this dispatch have already been presented.
+                        self.present_throttle.presented(id);
+                    }
+                    continue;
+                }
+                if reused || !unclaimed_swapchains.contains(id) {
+                    continue;
+                }
+                log::info!(target: "EngineTask","Presenting unclaimed {}",id);
+                handle.present_cleared();
+                self.present_throttle.untouched(*id);
+            }
+        }
+
+        let queued_resizes = self.pending_resizes.len();
+        let pending_resizes = coalesce_resizes(self.pending_resizes.drain(..).collect());
+        if pending_resizes.len() < queued_resizes {
+            log::info!(target: "EngineTask","Coalesced {} resizes into {}",queued_resizes,pending_resizes.len());
+        }
+        for (external_id, width, height) in pending_resizes {
+            let id = match self.swapchains.get_mut(&external_id) {
+                Some(id) => id,
+                None => {
+                    log::error!(target: "EngineTask","Failed to resize surface {}: it does not exists",external_id);
+                    continue;
+                }
+            };
+            let mut descriptor = match update_context.swapchain_descriptor_ref(id).cloned() {
+                Some(descriptor) => descriptor,
+                None => {
+                    log::error!(target: "EngineTask","Failed to resize {}: it does not exists",id);
+                    continue;
+                }
+            };
+
+            if width == 0 || height == 0 {
+                if self.suspended_swapchains.insert(*id) {
+                    update_context
+                        .swapchain_handle_ref(id)
+                        .map(|handle| handle.present());
+                    self.pending_events
+                        .push(ResourceEvent::SwapchainSuspended(*id));
+                    log::info!(target: "EngineTask","{} suspended",id);
+                }
+                continue;
+            }
+            if self.suspended_swapchains.remove(id) {
+                self.pending_events
+                    .push(ResourceEvent::SwapchainResumed(*id));
+                log::info!(target: "EngineTask","{} resumed",id);
+            }
+            if descriptor.width == width && descriptor.height == height {
+                continue;
+            }
+
+            log::info!(target: "EngineTask","Resizing swapchain");
+            descriptor.width = width;
+            descriptor.height = height;
+
+            if update_context.update_swapchain_descriptor(id, descriptor) {
+                // Drop the frame acquired but never presented, it would belong to the old swapchain.
+                update_context
+                    .swapchain_handle_ref(id)
+                    .map(|handle| handle.present());
+                if !self.resized_swapchains.contains(id) {
+                    self.resized_swapchains.push(*id);
+                }
+                log::info!(target: "EngineTask","{} resized",id);
+                for texture in update_context.resize_linked_textures(id, width, height) {
+                    log::info!(target: "EngineTask","{} resized along with {}",texture,id);
+                }
+            } else {
+                log::error!(target: "EngineTask","Failed to resize {}",id);
+            }
+        }
+
+        for (external_id, vsync) in self.pending_vsync.drain(..) {
+            let id = match self.swapchains.get_mut(&external_id) {
+                Some(id) => id,
+                None => {
+                    log::error!(target: "EngineTask","Failed to set the vsync of surface {}: it does not exists",external_id);
+                    continue;
+                }
+            };
+            let mut descriptor = match update_context.swapchain_descriptor_ref(id).cloned() {
+                Some(descriptor) => descriptor,
+                None => {
+                    log::error!(target: "EngineTask","Failed to set the vsync of {}: it does not exists",id);
+                    continue;
+                }
+            };
+            let backend = match update_context.device_handle_ref(&descriptor.device) {
+                Some(device) => device.0.get_info().backend,
+                None => {
+                    log::error!(target: "EngineTask","Failed to set the vsync of {}: {} does not exists",id,descriptor.device);
+                    continue;
+                }
+            };
+            let present_mode = select_present_mode(vsync, backend);
+            if descriptor.present_mode == present_mode {
+                continue;
+            }
+            descriptor.present_mode = present_mode;
+
+            if update_context.update_swapchain_descriptor(id, descriptor) {
+                // Drop the frame acquired but never presented, it would belong to the old swapchain.
+                update_context
+                    .swapchain_handle_ref(id)
+                    .map(|handle| handle.present());
+                if !self.resized_swapchains.contains(id) {
+                    self.resized_swapchains.push(*id);
+                }
+                log::info!(target: "EngineTask","{} switched to {:?}",id,present_mode);
+            } else {
+                log::error!(target: "EngineTask","Failed to switch {} to {:?}",id,present_mode);
+            }
+        }
+    }
+
+    /// Swapchains not suspended and not claimed by any task, sorted by id.
+    pub fn unclaimed_swapchains(&self, update_context: &UpdateContext) -> Vec<SwapchainId> {
+        let mut swapchains: Vec<_> = self
+            .swapchains
+            .values()
+            .filter(|id| !self.suspended_swapchains.contains(id))
+            .filter(|id| update_context.swapchain_owner(id).is_none())
+            .copied()
+            .collect();
+        swapchains.sort_by_key(|id| id.id());
+        swapchains
     }
 
     pub fn remove_swapchain(&mut self, external_id: usize) {
+        self.requested_surfaces.remove(&external_id);
         self.pending_commands
             .push(PendingCommand::DestroySwapchain { external_id });
     }
@@ -161,6 +507,34 @@ impl TaskTrait for EngineTask {
     }
 
     fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        // Swapchains destroyed during the previous dispatch are removed only now,
+        // so that every task received the SwapchainDestroyed event and dropped its references first.
+        for id in self.destroyed_swapchains.drain(..) {
+            update_context.drop_swapchain_claim(&id);
+            match update_context.remove_swapchain(&id) {
+                Ok(()) => log::info!(target: "EngineTask","{} removed",id),
+                Err(()) => log::error!(target: "EngineTask","Failed to remove {}",id),
+            }
+        }
+
+        // Swapchains are built at the end of a dispatch, so the ones created on the previous dispatch are ready now.
+        if !self.surfaces_ready_sent
+            && self.surfaces_ready(|id| update_context.swapchain_handle_ref(id).is_some())
+        {
+            self.surfaces_ready_sent = true;
+            update_context.push_event(ResourceEvent::AllSurfacesReady);
+            log::info!(target: "EngineTask","All surfaces ready");
+        }
+
+        // Resized swapchains are rebuilt on this dispatch, so their frame is acquired by the new handle.
+        let resized_swapchains: Vec<_> = self.resized_swapchains.drain(..).collect();
+        for id in &resized_swapchains {
+            update_context.push_event(ResourceEvent::SwapchainUpdated(*id));
+        }
+        for event in self.pending_events.drain(..) {
+            update_context.push_event(event);
+        }
+
         let events: Vec<_> = self.pending_commands.drain(..).collect();
 
         let prepared_swapchains: HashSet<_> = events
@@ -172,31 +546,54 @@ impl TaskTrait for EngineTask {
                     surface,
                     width,
                     height,
+                    options,
                 } => {
                     let device = match self.devices.get(0) {
                         Some(device) => *device,
                         None => return None,
                     };
 
-                    let format = update_context
-                        .device_handle_ref(&device)
-                        .unwrap()
-                        .0
-                        .get_swap_chain_preferred_format(&surface)
-                        .expect("Incompatible device");
+                    let adapter = &update_context.device_handle_ref(&device).unwrap().0;
+                    let supported_formats = supported_swapchain_formats(adapter, &surface);
+                    let format = match self.swapchain_format {
+                        Some(format) => select_swapchain_format(format, &supported_formats),
+                        None => supported_formats.first().copied(),
+                    };
+                    let format = match format {
+                        Some(format) => format,
+                        None => {
+                            log::error!(target: "EngineTask","Failed to create surface {}: {} does not support any format",external_id,device);
+                            return None;
+                        }
+                    };
+                    log::info!(target: "EngineTask","Using swapchain format {:?}",format);
 
                     let usage = crate::wgpu::TextureUsage::RENDER_ATTACHMENT;
-                    let present_mode = crate::wgpu::PresentMode::Mailbox;
+                    // Without vsync unless requested, so the frames are not capped to the refresh rate.
+                    let requested = options
+                        .present_mode
+                        .unwrap_or(crate::wgpu::PresentMode::Mailbox);
+                    let backend = adapter.get_info().backend;
+                    let supported_modes = supported_present_modes(backend);
+                    let present_mode = select_supported_present_mode(requested, &supported_modes);
+                    log::info!(target: "EngineTask","Using present mode {:?}",present_mode);
+                    let frame_latency = select_frame_latency(options.frame_latency, backend);
+                    log::info!(target: "EngineTask","Using maximum frame latency {}",frame_latency);
 
+                    // A zero sized swapchain cannot be configured, so it gets created suspended.
+                    let suspended = width == 0 || height == 0;
                     let descriptor = SwapchainDescriptor {
                         label,
                         device,
                         surface,
                         format,
-                        width,
-                        height,
+                        width: width.max(1),
+                        height: height.max(1),
                         usage,
                         present_mode,
+                        composite_alpha_mode: self.composite_alpha_mode,
+                        clear_color: options.clear_color,
+                        desired_maximum_frame_latency: frame_latency,
                     };
 
                     match update_context.add_swapchain_descriptor(descriptor) {
@@ -206,69 +603,58 @@ impl TaskTrait for EngineTask {
                             update_context.push_event(ResourceEvent::SwapchainCreated {
                                 external_id,
                                 swapchain: id,
+                                format,
                             });
                             log::info!(target: "EngineTask","{} created",id);
+                            if suspended {
+                                self.suspended_swapchains.insert(id);
+                                update_context.push_event(ResourceEvent::SwapchainSuspended(id));
+                                log::info!(target: "EngineTask","{} suspended",id);
+                            }
                             Some(id)
                         }
                         Err(()) => None,
                     }
                 }
-                PendingCommand::ResizeSwapchain {
-                    external_id,
-                    width,
-                    height,
-                } => {
-                    if let Some(id) = self.swapchains.get_mut(&external_id) {
-                        update_context
-                            .swapchain_descriptor_ref(id)
-                            .cloned()
-                            .map(|mut descriptor| {
-                                log::info!(target: "EngineTask","Resizing swapchain");
-                                descriptor.width = width;
-                                descriptor.height = height;
-
-                                let result =
-                                    update_context.update_swapchain_descriptor(id, descriptor);
-                                if result {
-                                    //swapchain_to_prepare.remove(&id);
-                                    update_context
-                                        .swapchain_handle_ref(id)
-                                        .map(|handle| handle.present());
-                                    update_context.push_event(ResourceEvent::SwapchainUpdated(*id));
-                                    log::info!(target: "EngineTask","{} resized",id);
-                                    Some(*id)
-                                } else {
-                                    log::error!("Surface {} does not exists", id);
-                                    None
-                                }
-                            })
-                            .flatten()
-                    } else {
-                        None
-                    }
-                }
                 PendingCommand::DestroySwapchain { external_id } => {
                     self.swapchains.remove(&external_id).map(|id| {
                         //swapchain_to_prepare.remove(&id);
-                        update_context.remove_swapchain(&id).unwrap();
+                        self.suspended_swapchains.remove(&id);
+                        self.destroyed_swapchains.push(id);
+                        self.present_throttle.presented(&id);
                         update_context.push_event(ResourceEvent::SwapchainDestroyed(id));
                         log::info!(target: "EngineTask","{} destroyed",id);
                         id
                     })
                 }
             })
+            .chain(resized_swapchains)
             .collect();
 
         let current_swapchains: HashSet<SwapchainId> = self.swapchains.values().cloned().collect();
 
-        current_swapchains
-            .difference(&prepared_swapchains)
-            .for_each(|id| {
-                update_context.swapchain_handle_ref(&id).map(|handle| {
-                    log::info!(target: "EngineTask","Preparing frame for {}",id);
-                    handle.prepare_frame()
-                });
-            });
+        if self.paused {
+            return;
+        }
+        for id in current_swapchains.difference(&prepared_swapchains) {
+            if self.suspended_swapchains.contains(id) {
+                continue;
+            }
+            let handle = match update_context.swapchain_handle_ref(id) {
+                Some(handle) => handle,
+                None => continue,
+            };
+            // The frame of an idle unclaimed swapchain is acquired only if something renders on it.
+            if update_context.swapchain_owner(id).is_none()
+                && self.present_throttle.skip_acquire(*id)
+            {
+                log::info!(target: "EngineTask","Deferring the frame of untouched {}",id);
+                handle.defer_frame();
+            } else {
+                log::info!(target: "EngineTask","Preparing frame for {}",id);
+                handle.prepare_frame();
+            }
+        }
     }
     fn command_buffers(&self) -> Vec<CommandBufferId> {
         Vec::new()
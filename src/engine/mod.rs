@@ -9,41 +9,79 @@ pub mod task_manager;
 pub use task_manager::TaskManager;
 
 pub mod resource_manager;
-pub use resource_manager::ResourceManager;
+pub use resource_manager::{BatchItem, ResourceManager};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 /// Possible engine errors.
 pub enum WGpuEngineError {
     InitializationFailed,
+    SubmissionFailed,
+    /// No adapter satisfies the minimum limits: the first unmet limit, as (field, required, available).
+    LimitsNotMet(&'static str, u32, u32),
+    /// In [strict mode][WGpuEngine::set_strict], the resources that failed to build, in build order.
+    BuildFailed(Vec<EntityId>),
+    /// The surface with the passed external id cannot be presented by the engine device.
+    SurfaceCreationFailed(usize),
+    /// The composite alpha mode is not supported, see [SUPPORTED_COMPOSITE_ALPHA_MODES][crate::SUPPORTED_COMPOSITE_ALPHA_MODES].
+    UnsupportedCompositeAlphaMode(crate::CompositeAlphaMode),
 }
 
 /**
 The main entry point of the engine.
 */
 pub struct WGpuEngine {
-    runtime: tokio::runtime::Runtime,
+    // Owned runtime, if the engine was not created from an external one.
+    _runtime: Option<tokio::runtime::Runtime>,
+    tokio: tokio::runtime::Handle,
     task_manager: TaskManager,
     resource_manager: ResourceManager,
     engine_task: TaskId,
 
     tasks: Vec<Box<dyn TaskTrait + Sync + Send>>,
+    paused: bool,
+    update_while_paused: bool,
+    reuse_unchanged_frames: bool,
+    // Command buffers submitted by the last dispatch, sorted by id.
+    last_command_buffers: Vec<CommandBufferId>,
+    reused_frames: usize,
 }
 
 impl WGpuEngine {
     pub fn new(requirements: impl Into<Requirements>) -> Result<Self, WGpuEngineError> {
-        let requirements = requirements.into();
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(_) => return Err(WGpuEngineError::InitializationFailed),
+        };
+        let tokio = runtime.handle().clone();
+        Self::init(requirements, Some(runtime), tokio)
+    }
 
-        let runtime = tokio::runtime::Runtime::new().unwrap();
+    /**
+    Create the engine on an existing tokio runtime, instead of owning one.
+    The runtime must be multi threaded, since the engine blocks on it while it is possibly running.
+    */
+    pub fn with_runtime_handle(
+        requirements: impl Into<Requirements>,
+        tokio: tokio::runtime::Handle,
+    ) -> Result<Self, WGpuEngineError> {
+        Self::init(requirements, None, tokio)
+    }
+
+    fn init(
+        requirements: impl Into<Requirements>,
+        runtime: Option<tokio::runtime::Runtime>,
+        tokio: tokio::runtime::Handle,
+    ) -> Result<Self, WGpuEngineError> {
+        let requirements = requirements.into();
 
         let mut task_manager = TaskManager::new();
-        let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+        let mut resource_manager = ResourceManager::new(tokio.clone());
 
         let engine_task = task_processing::create_task(
             &mut task_manager,
             &mut resource_manager,
-            runtime.handle(),
-            String::from("EngineTask"),
-            Vec::new(),
+            &tokio,
+            TaskDescriptor::new(String::from("EngineTask"), Vec::new(), Vec::new()),
             requirements.clone(),
             |id, tokio, update_context| {
                 engine_task::EngineTask::new(
@@ -56,16 +94,212 @@ impl WGpuEngine {
         )
         .expect("Failed to initialize engine task");
 
+        let unmet_limits = task_manager
+            .task_handle_cast_ref(&engine_task, |engine_task: &engine_task::EngineTask| {
+                engine_task.unmet_limits()
+            })
+            .unwrap();
+        if let Some((field, required, available)) = unmet_limits {
+            return Err(WGpuEngineError::LimitsNotMet(field, required, available));
+        }
+
         let tasks = Vec::new();
         Ok(Self {
-            runtime,
+            _runtime: runtime,
+            tokio,
             task_manager,
             resource_manager,
             engine_task,
             tasks,
+            paused: false,
+            update_while_paused: true,
+            reuse_unchanged_frames: false,
+            last_command_buffers: Vec::new(),
+            reused_frames: 0,
         })
     }
 
+    /**
+    Add a new instance of the passed backend, creating a device for each one of its adapters.
+    It is meant for diagnostic purposes, like comparing backends or isolating a faulty one:
+    every instance loads its own backend driver and every device keeps its own queue and memory pools,
+    while resources are never shared among devices.
+    */
+    pub fn add_instance(
+        &mut self,
+        backend: crate::wgpu::BackendBit,
+    ) -> Result<InstanceId, WGpuEngineError> {
+        use crate::engine::engine_task::EngineTask;
+        let engine_task = self.engine_task;
+        let resource_manager = &mut self.resource_manager;
+        let mut events = Vec::new();
+        self.task_manager
+            .task_handle_cast_mut(&engine_task, |task: &mut EngineTask| {
+                let mut update_context =
+                    UpdateContext::new(engine_task, resource_manager, &mut events);
+                task.add_instance(backend, &mut update_context)
+            })
+            .unwrap()
+            .map_err(|_| WGpuEngineError::InitializationFailed)
+    }
+
+    /**
+    Pause or resume the rendering, like when the application goes in background.
+    While paused, [dispatch_tasks][WGpuEngine::dispatch_tasks] neither submits the command buffers
+    nor acquires and presents the swapchain frames, but the resources are kept:
+    resuming does not rebuild anything. The tasks are still updated, unless disabled with
+    [set_update_while_paused][WGpuEngine::set_update_while_paused].
+    */
+    pub fn set_paused(&mut self, paused: bool) {
+        use crate::engine::engine_task::EngineTask;
+        self.paused = paused;
+        self.task_manager
+            .task_handle_cast_mut(&self.engine_task, |engine_task: &mut EngineTask| {
+                engine_task.set_paused(paused)
+            })
+            .unwrap();
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    /**
+    Skip up to `max_skipped` consecutive presents of the swapchains that no task rendered on and that are not claimed,
+    instead of acquiring and presenting a cleared frame every dispatch. `None` (the default) presents every frame.
+    See [PresentThrottle][crate::PresentThrottle].
+    */
+    pub fn set_max_skipped_presents(&mut self, max_skipped: Option<u32>) {
+        use crate::engine::engine_task::EngineTask;
+        self.task_manager
+            .task_handle_cast_mut(&self.engine_task, |engine_task: &mut EngineTask| {
+                engine_task.set_max_skipped_presents(max_skipped)
+            })
+            .unwrap();
+    }
+    /**
+    Choose if the tasks keep being updated while paused, so that the resources stay current (the default),
+    or if [dispatch_tasks][WGpuEngine::dispatch_tasks] does nothing at all.
+    */
+    pub fn set_update_while_paused(&mut self, update_while_paused: bool) {
+        self.update_while_paused = update_while_paused;
+    }
+
+    /**
+    Reuse the last frame when nothing changed since it: no resource is damaged, there is nothing to write
+    and the tasks submit the same command buffers, without presenting any claimed swapchain.
+    Such a frame neither commits nor encodes and submits anything, and the swapchains keep showing the last presented frame.
+    It is disabled by default, since command buffers accumulating results on the GPU (like a simulation step)
+    do not produce the same contents when submitted again.
+    */
+    pub fn set_reuse_unchanged_frames(&mut self, reuse_unchanged_frames: bool) {
+        self.reuse_unchanged_frames = reuse_unchanged_frames;
+    }
+    /// Number of frames reused so far, see [set_reuse_unchanged_frames][WGpuEngine::set_reuse_unchanged_frames].
+    pub fn reused_frames(&self) -> usize {
+        self.reused_frames
+    }
+
+    /**
+    Cap the anisotropy of every sampler. A value of 0 removes the cap.
+    See [ResourceManager::set_max_anisotropy][ResourceManager::set_max_anisotropy].
+    */
+    pub fn set_max_anisotropy(&mut self, max_anisotropy: u8) {
+        self.resource_manager.set_max_anisotropy(max_anisotropy);
+    }
+
+    /**
+    Enable or disable the strict mode, meant for development and testing.
+    See [ResourceManager::set_strict][ResourceManager::set_strict].
+    */
+    pub fn set_strict(&mut self, strict: bool) {
+        self.resource_manager.set_strict(strict);
+    }
+
+    /**
+    Enable or disable the sharing of resources with identical descriptors.
+    See [ResourceManager::set_deduplication][ResourceManager::set_deduplication].
+    */
+    pub fn set_deduplication(&mut self, deduplication: bool) {
+        self.resource_manager.set_deduplication(deduplication);
+    }
+
+    /**
+    Set the most verbose level of the logs emitted for every resource build, to silence them without disabling all the logging.
+    See [ResourceManager::set_resource_log_level][ResourceManager::set_resource_log_level].
+    */
+    pub fn set_resource_log_level(&mut self, log_level: log::LevelFilter) {
+        self.resource_manager.set_resource_log_level(log_level);
+    }
+
+    /**
+    Number of resource commits performed so far, for profiling.
+    See [ResourceManager::commit_count][ResourceManager::commit_count].
+    */
+    pub fn commit_count(&self) -> usize {
+        self.resource_manager.commit_count()
+    }
+
+    /**
+    Remove every task together with its resources, for a soft reset like a level editor reloading a project.
+    The engine instances, devices and swapchains are kept, so the GPU is not reinitialized and the surfaces stay valid.
+    */
+    pub fn reset_scene(&mut self) {
+        let engine_task = self.engine_task;
+        for task in self.task_manager.tasks() {
+            if task == engine_task {
+                continue;
+            }
+            let released = self.resource_manager.release_task_resources(&task);
+            log::info!(target: "Engine","Reset scene: removing {} with {} resources",task,released);
+            let _ = self.task_manager.remove_task(&task);
+        }
+        self.tasks.clear();
+    }
+
+    /**
+    Remove every resource carrying the tag. Returns the number of released resources.
+    See [ResourceManager::free_tagged][ResourceManager::free_tagged].
+    */
+    pub fn free_tagged(&mut self, tag: &str) -> usize {
+        self.resource_manager.free_tagged(tag)
+    }
+
+    /**
+    Sample counts supported by a render target of the passed format on the device.
+    See [ResourceManager::supported_sample_counts][ResourceManager::supported_sample_counts].
+    */
+    pub fn supported_sample_counts(
+        &self,
+        device: DeviceId,
+        format: crate::wgpu::TextureFormat,
+    ) -> Vec<u32> {
+        self.resource_manager
+            .supported_sample_counts(device, format)
+    }
+
+    /**
+    List the instances of the engine.
+    */
+    pub fn instances(&self) -> Vec<InstanceId> {
+        use crate::engine::engine_task::EngineTask;
+        self.task_manager
+            .task_handle_cast_ref(&self.engine_task, |engine_task: &EngineTask| {
+                engine_task.instances().clone()
+            })
+            .unwrap()
+    }
+
+    /**
+    List the devices of the engine, one for each adapter of its instances.
+    */
+    pub fn devices(&self) -> Vec<DeviceId> {
+        use crate::engine::engine_task::EngineTask;
+        self.task_manager
+            .task_handle_cast_ref(&self.engine_task, |engine_task: &EngineTask| {
+                engine_task.devices().clone()
+            })
+            .unwrap()
+    }
+
     #[cfg(feature = "pal")]
     /**
     Retrieve the WGpuContext to allow the integration with PAL.
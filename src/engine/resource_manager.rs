@@ -5,9 +5,9 @@ use crate::entity_manager::DMGEntityManager;
 
 use petgraph::visit::Topo;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 macro_rules! make_resource_functions {
     ($name: ident) => {
@@ -79,6 +79,37 @@ macro_rules! make_resource_functions {
     };
 }
 
+/**
+Item of a batch added by [add_batch][ResourceManager::add_batch].
+Items can reference the resources added before them in the same batch,
+by building their descriptor from the ids of the previous items.
+*/
+pub enum BatchItem {
+    /// Descriptor whose dependencies already exist.
+    Descriptor(ResourceDescriptor),
+    /// Descriptor built from the ids of the previous items of the batch, in the passed order.
+    WithIds(Box<dyn FnOnce(&[ResourceId]) -> ResourceDescriptor + Send>),
+}
+impl BatchItem {
+    /// Item whose descriptor is built from the ids of the previous items of the batch.
+    pub fn with_ids<T: Into<ResourceDescriptor>>(
+        callback: impl FnOnce(&[ResourceId]) -> T + Send + 'static,
+    ) -> Self {
+        Self::WithIds(Box::new(|ids| callback(ids).into()))
+    }
+    fn descriptor(self, ids: &[ResourceId]) -> ResourceDescriptor {
+        match self {
+            Self::Descriptor(descriptor) => descriptor,
+            Self::WithIds(callback) => callback(ids),
+        }
+    }
+}
+impl<T: Into<ResourceDescriptor>> From<T> for BatchItem {
+    fn from(descriptor: T) -> Self {
+        Self::Descriptor(descriptor.into())
+    }
+}
+
 #[derive(Debug)]
 /**
 The resource manager is a specialized version of the DMGEntityManager and a major subsystem of WGpuEngine.
@@ -97,6 +128,7 @@ pub struct ResourceManager {
     texture_views: HashSet<TextureViewId>,
     samplers: HashSet<SamplerId>,
     shader_modules: HashSet<ShaderModuleId>,
+    query_sets: HashSet<QuerySetId>,
 
     bind_group_layouts: HashSet<BindGroupLayoutId>,
     bind_groups: HashSet<BindGroupId>,
@@ -104,7 +136,33 @@ pub struct ResourceManager {
     pipeline_layouts: HashSet<PipelineLayoutId>,
     render_pipelines: HashSet<RenderPipelineId>,
     compute_pipelines: HashSet<ComputePipelineId>,
+    render_bundles: HashSet<RenderBundleId>,
     command_buffers: HashSet<CommandBufferId>,
+
+    depth_targets: HashMap<(DeviceId, u32, u32, crate::wgpu::TextureFormat), TextureViewId>,
+    detached_depth_targets: HashMap<(TaskId, TextureViewId), TextureViewId>,
+    size_links: HashMap<TextureId, (TaskId, SwapchainId)>,
+    swapchain_claims: HashMap<SwapchainId, TaskId>,
+    max_anisotropy: Option<std::num::NonZeroU8>,
+    deduplication: bool,
+    strict: bool,
+    failed_builds: Vec<EntityId>,
+    build_errors: Vec<(EntityId, ResourceBuilderError)>,
+    commit_count: usize,
+    frame_number: u64,
+    transients: Vec<(TaskId, ResourceId)>,
+    tags: HashMap<String, Vec<(TaskId, ResourceId)>>,
+    task_devices: HashMap<TaskId, Vec<DeviceId>>,
+    ready_waiters: Mutex<HashMap<EntityId, Vec<tokio::sync::oneshot::Sender<()>>>>,
+    /// Resources rebuilt for a usage upgrade, with the descriptor of the handle to copy the contents from.
+    usage_upgrades: HashMap<EntityId, ResourceDescriptor>,
+    /// Command buffers damaged only because a submission consumed their handle.
+    resubmitted: HashSet<EntityId>,
+    /// Whether a task presented a claimed swapchain since the last check, see [take_task_presents][ResourceManager::take_task_presents].
+    task_presents: bool,
+    device_regressions: HashMap<EntityId, DeviceRegression>,
+    deferred_writes: Vec<ResourceWrite>,
+    log_level: log::LevelFilter,
 }
 impl ResourceManager {
     pub fn new(tokio: tokio::runtime::Handle) -> Self {
@@ -119,6 +177,7 @@ impl ResourceManager {
         let texture_views = HashSet::new();
         let samplers = HashSet::new();
         let shader_modules = HashSet::new();
+        let query_sets = HashSet::new();
 
         let bind_group_layouts = HashSet::new();
         let bind_groups = HashSet::new();
@@ -126,8 +185,31 @@ impl ResourceManager {
         let pipeline_layouts = HashSet::new();
         let render_pipelines = HashSet::new();
         let compute_pipelines = HashSet::new();
+        let render_bundles = HashSet::new();
         let command_buffers = HashSet::new();
 
+        let depth_targets = HashMap::new();
+        let detached_depth_targets = HashMap::new();
+        let size_links = HashMap::new();
+        let swapchain_claims = HashMap::new();
+        let max_anisotropy = None;
+        let deduplication = true;
+        let strict = false;
+        let failed_builds = Vec::new();
+        let build_errors = Vec::new();
+        let commit_count = 0;
+        let frame_number = 0;
+        let transients = Vec::new();
+        let tags = HashMap::new();
+        let task_devices = HashMap::new();
+        let ready_waiters = Mutex::new(HashMap::new());
+        let usage_upgrades = HashMap::new();
+        let resubmitted = HashSet::new();
+        let task_presents = false;
+        let device_regressions = HashMap::new();
+        let deferred_writes = Vec::new();
+        let log_level = log::LevelFilter::Trace;
+
         Self {
             inner,
             tokio,
@@ -140,6 +222,7 @@ impl ResourceManager {
             texture_views,
             samplers,
             shader_modules,
+            query_sets,
 
             bind_group_layouts,
             bind_groups,
@@ -147,10 +230,161 @@ impl ResourceManager {
             pipeline_layouts,
             render_pipelines,
             compute_pipelines,
+            render_bundles,
             command_buffers,
+
+            depth_targets,
+            detached_depth_targets,
+            size_links,
+            swapchain_claims,
+            max_anisotropy,
+            deduplication,
+            strict,
+            failed_builds,
+            build_errors,
+            commit_count,
+            frame_number,
+            transients,
+            tags,
+            task_devices,
+            ready_waiters,
+            usage_upgrades,
+            resubmitted,
+            task_presents,
+            device_regressions,
+            deferred_writes,
+            log_level,
+        }
+    }
+
+    /**
+    Cap the anisotropy of every sampler, regardless of the value requested by its descriptor.
+    A value of 0 removes the cap. Samplers using anisotropic filtering are rebuilt on the next commit.
+    */
+    pub fn set_max_anisotropy(&mut self, max_anisotropy: u8) {
+        self.max_anisotropy = std::num::NonZeroU8::new(max_anisotropy);
+        let samplers: Vec<_> = self
+            .samplers
+            .iter()
+            .filter(|id| {
+                self.sampler_descriptor_ref(id)
+                    .map(|descriptor| descriptor.anisotropy_clamp.is_some())
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        for sampler in samplers {
+            self.touch_resource(sampler);
         }
     }
 
+    /**
+    Get the anisotropy cap set by [set_max_anisotropy][ResourceManager::set_max_anisotropy].
+    */
+    pub fn max_anisotropy(&self) -> Option<std::num::NonZeroU8> {
+        self.max_anisotropy
+    }
+
+    /**
+    Set the most verbose level of the logs emitted for every resource build and commit, like "Updating X" and "X updated",
+    that flood the logs when resources are rebuilt every frame. Defaults to [Trace][log::LevelFilter::Trace],
+    leaving the filtering to the logger. Build failures are always logged.
+    */
+    pub fn set_resource_log_level(&mut self, log_level: log::LevelFilter) {
+        self.log_level = log_level;
+    }
+    pub fn resource_log_level(&self) -> log::LevelFilter {
+        self.log_level
+    }
+    /// Check if the resource build logs of the passed level are emitted, see [set_resource_log_level][ResourceManager::set_resource_log_level].
+    pub fn resource_log_enabled(&self, level: log::Level) -> bool {
+        resource_log_enabled(self.log_level, level)
+    }
+
+    /**
+    Enable or disable the sharing of resources with identical descriptors (enabled by default).
+    When disabled, every added or updated resource gets its own handle, even if an identical one already exists:
+    this makes the resources of different tasks independent, at the cost of duplicating
+    their GPU memory (and the build time of pipelines and shaders).
+    Resources already shared are not split.
+    */
+    pub fn set_deduplication(&mut self, deduplication: bool) {
+        self.deduplication = deduplication;
+    }
+
+    /**
+    Check if the resources with identical descriptors are shared.
+    See [set_deduplication][ResourceManager::set_deduplication].
+    */
+    pub fn deduplication(&self) -> bool {
+        self.deduplication
+    }
+
+    /**
+    Enable or disable the strict mode (disabled by default). In strict mode the validation warnings of the
+    command buffers (like exceeding [max_commands][CommandBufferDescriptor::max_commands]) fail their build,
+    and [WGpuEngine::try_dispatch_tasks][crate::WGpuEngine::try_dispatch_tasks] returns an error as soon as
+    a resource fails to build, instead of only logging the failure.
+    */
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /**
+    Check if the strict mode is enabled. See [set_strict][ResourceManager::set_strict].
+    */
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /**
+    Resources that failed to build during the last commit, in build order.
+    Resources waiting for a swapchain frame are not included.
+    */
+    pub fn failed_builds(&self) -> &[EntityId] {
+        &self.failed_builds
+    }
+
+    /**
+    Errors of the resources that failed to build during the last commit, in build order.
+    Unlike [failed_builds][ResourceManager::failed_builds], resources removed before building are not included.
+    */
+    pub fn build_errors(&self) -> &[(EntityId, ResourceBuilderError)] {
+        &self.build_errors
+    }
+
+    /**
+    Capabilities lost by a device on its last rebuild, if any. A device rebuilt without regressions
+    clears the previous one.
+    */
+    pub fn device_regression(&self, id: &DeviceId) -> Option<&DeviceRegression> {
+        self.device_regressions.get(id.id_ref())
+    }
+
+    /**
+    Keep the writes queued outside of a dispatch, like by the tasks while being created,
+    until the next dispatch submits them before the writes of its tasks.
+    */
+    pub(crate) fn defer_writes(&mut self, mut writes: Vec<ResourceWrite>) {
+        self.deferred_writes.append(&mut writes);
+    }
+    pub(crate) fn take_deferred_writes(&mut self) -> Vec<ResourceWrite> {
+        std::mem::take(&mut self.deferred_writes)
+    }
+
+    /// Number of commits performed so far. Frames without damaged resources skip the commit.
+    pub fn commit_count(&self) -> usize {
+        self.commit_count
+    }
+
+    /// Number of the current frame, starting from 0 and incremented at the end of every dispatch.
+    pub fn frame_number(&self) -> u64 {
+        self.frame_number
+    }
+    pub(crate) fn advance_frame(&mut self) {
+        self.frame_number += 1;
+    }
+
     /**
     Get the parent device that have created the passed entity id.
     */
@@ -168,6 +402,106 @@ impl ResourceManager {
         }
     }
 
+    /**
+    Get the raw adapter, device and queue of the passed device id.
+    This is an escape hatch for the functionalities not wrapped by the engine:
+    objects created directly from them are not tracked by the dependency graph,
+    so their lifetime and rebuilds are up to the caller.
+    */
+    pub fn raw_device(
+        &self,
+        id: DeviceId,
+    ) -> Option<(
+        &crate::wgpu::Adapter,
+        &crate::wgpu::Device,
+        &crate::wgpu::Queue,
+    )> {
+        self.device_handle_ref(&id)
+            .map(|device| (&device.0, &device.1, &device.2))
+    }
+
+    /**
+    Get the handle of the tokio runtime used by the manager.
+    */
+    pub fn runtime_handle(&self) -> &tokio::runtime::Handle {
+        &self.tokio
+    }
+
+    /**
+    List the resources created on the passed device.
+    */
+    pub fn resources_on_device(&self, device: DeviceId) -> Vec<EntityId> {
+        self.inner
+            .entities()
+            .filter(|id| {
+                self.inner
+                    .entity_descriptor_ref(id)
+                    .and_then(|descriptor| descriptor.device())
+                    == Some(device)
+            })
+            .collect()
+    }
+
+    /**
+    Estimate the memory used by the buffers and the textures on the passed device.
+    It ignores driver padding and imported textures, so it is meant to spot leaks and trends rather than exact usage.
+    */
+    pub fn estimated_memory(&self, device: DeviceId) -> u64 {
+        self.resources_on_device(device)
+            .iter()
+            .map(|id| match self.inner.entity_descriptor_ref(id) {
+                Some(ResourceDescriptor::Buffer(descriptor)) => descriptor.size,
+                Some(ResourceDescriptor::Texture(descriptor))
+                    if descriptor.source == TextureSource::Local =>
+                {
+                    descriptor.estimated_size()
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /**
+    Restrict the devices the task can create resources on. An empty list removes the restriction.
+    */
+    pub(crate) fn set_task_devices(&mut self, task: TaskId, devices: Vec<DeviceId>) {
+        if devices.is_empty() {
+            self.task_devices.remove(&task);
+        } else {
+            self.task_devices.insert(task, devices);
+        }
+    }
+
+    /**
+    Get the alignment requirements of the passed device, or `None` if the device does not exist.
+    */
+    pub fn device_alignments(&self, device: DeviceId) -> Option<DeviceAlignments> {
+        self.device_descriptor_ref(&device)
+            .map(|descriptor| DeviceAlignments::new(&descriptor.limits))
+    }
+
+    /**
+    Get the entities the passed entity directly depends on, sorted by id.
+    Unlike the graphviz log of the commits, it allows to walk the dependency graph.
+    */
+    pub fn dependencies_of(&self, id: impl AsRef<EntityId>) -> Vec<EntityId> {
+        let mut dependencies = self.inner.entity_parents(id.as_ref());
+        dependencies.sort();
+        dependencies.dedup();
+        dependencies
+    }
+
+    /**
+    Get the entities directly depending on the passed entity, sorted by id.
+    They are the ones rebuilt when the entity changes, and that break if it is removed.
+    */
+    pub fn dependents_of(&self, id: impl AsRef<EntityId>) -> Vec<EntityId> {
+        let mut dependents = self.inner.entity_children(id.as_ref());
+        dependents.sort();
+        dependents.dedup();
+        dependents
+    }
+
     /**
     Get the parent device id that have created the passed entity id.
     */
@@ -201,7 +535,7 @@ impl ResourceManager {
         id: Option<&ResourceId>,
         descriptor: &ResourceDescriptor,
     ) -> Option<ResourceId> {
-        if descriptor.state_type() == StateType::Statefull {
+        if !self.deduplication || descriptor.state_type() == StateType::Statefull {
             return None;
         }
 
@@ -365,6 +699,19 @@ impl ResourceManager {
                 })
                 .cloned()
                 .map(|current_id| current_id.into()),
+            ResourceDescriptor::QuerySet(descriptor) => self
+                .query_sets
+                .iter()
+                .find(|current_id| {
+                    if let Some(id) = id {
+                        if &ResourceId::from(**current_id) == id {
+                            return false;
+                        }
+                    }
+                    self.query_set_descriptor_ref(current_id).unwrap() == descriptor
+                })
+                .cloned()
+                .map(|current_id| current_id.into()),
             ResourceDescriptor::ComputePipeline(descriptor) => self
                 .compute_pipelines
                 .iter()
@@ -378,6 +725,19 @@ impl ResourceManager {
                 })
                 .cloned()
                 .map(|current_id| current_id.into()),
+            ResourceDescriptor::RenderBundle(descriptor) => self
+                .render_bundles
+                .iter()
+                .find(|current_id| {
+                    if let Some(id) = id {
+                        if &ResourceId::from(**current_id) == id {
+                            return false;
+                        }
+                    }
+                    self.render_bundle_descriptor_ref(current_id).unwrap() == descriptor
+                })
+                .cloned()
+                .map(|current_id| current_id.into()),
             ResourceDescriptor::CommandBuffer(descriptor) => self
                 .command_buffers
                 .iter()
@@ -403,10 +763,33 @@ impl ResourceManager {
         descriptor: impl Into<ResourceDescriptor>,
         handle: impl Into<Option<ResourceHandle>>,
     ) -> Result<ResourceId, ()> {
-        let descriptor = descriptor.into();
-        let handle = handle.into();
+        self.try_add_resource(task, descriptor.into(), handle.into())
+            .map_err(|_error| ())
+    }
+
+    /// Add a new resource to the manager, returning why it cannot be added on failure.
+    fn try_add_resource(
+        &mut self,
+        task: TaskId,
+        descriptor: ResourceDescriptor,
+        handle: Option<ResourceHandle>,
+    ) -> Result<ResourceId, ResourceBuilderError> {
         let damaged = handle.is_none();
 
+        if let Some(devices) = self.task_devices.get(&task) {
+            let device = descriptor
+                .dependencies()
+                .into_iter()
+                .map(DeviceId::new)
+                .find(|device| self.device_descriptor_ref(device).is_some());
+            if let Some(device) = device {
+                if !devices.contains(&device) {
+                    log::error!(target: "EntityManager","Failed to add resource for {}: Device {} is not one of the task devices",task,device);
+                    return Err(ResourceBuilderError::NotTaskDevice(device));
+                }
+            }
+        }
+
         if descriptor.state_type() == StateType::Stateless {
             if let Some(id) = self.search_compatible(None, &descriptor) {
                 self.inner.add_entity_owner(&id.into(), task);
@@ -423,7 +806,7 @@ impl ResourceManager {
                 let id = self.add_inner(&descriptor, id);
                 Ok(id)
             }
-            Err(_err) => Err(()),
+            Err(()) => Err(ResourceBuilderError::MissingDependencies),
         }
     }
 
@@ -439,7 +822,171 @@ impl ResourceManager {
     }
 
     /**
-    Update the descriptor of a resource.
+    Release every resource owned by the task, dependents first, and forget the task state (like its swapchain claims).
+    Resources shared with other tasks are kept for them. Returns the number of released resources.
+    */
+    pub fn release_task_resources(&mut self, task: &TaskId) -> usize {
+        let mut owned = Vec::new();
+        let mut visitor = Topo::new(self.graph());
+        while let Some(nx) = visitor.next(self.graph()) {
+            let id: EntityId = nx.into();
+            let is_owner = self
+                .inner
+                .entity_owners(&id)
+                .map(|owners| owners.contains(task))
+                .unwrap_or(false);
+            if is_owner {
+                if let Some(descriptor) = self.inner.entity_descriptor_ref(&id) {
+                    owned.push(Self::typed_id(descriptor, id));
+                }
+            }
+        }
+
+        let released = owned
+            .into_iter()
+            .rev()
+            .filter(|id| self.remove_resource(task, id).is_ok())
+            .count();
+        self.task_devices.remove(task);
+        self.swapchain_claims
+            .retain(|_swapchain, owner| owner != task);
+        self.size_links
+            .retain(|_texture, (owner, _swapchain)| owner != task);
+        self.transients.retain(|(owner, _id)| owner != task);
+        released
+    }
+
+    /**
+    Check if a resource is owned by more than one task, like a stateless resource
+    deduplicated among tasks using identical descriptors.
+    */
+    pub fn is_shared(&self, id: impl Into<ResourceId>) -> bool {
+        let id: EntityId = id.into().into();
+        self.inner
+            .entity_owners(&id)
+            .map(|owners| owners.len() > 1)
+            .unwrap_or(false)
+    }
+
+    /**
+    Add a resource that lives for the current frame only:
+    it is removed by [free_transients][ResourceManager::free_transients] once the frame has been submitted.
+    */
+    pub fn add_transient(
+        &mut self,
+        task: TaskId,
+        descriptor: impl Into<ResourceDescriptor>,
+    ) -> Result<ResourceId, ()> {
+        let id = self.add_resource(task, descriptor, None)?;
+        self.transients.push((task, id));
+        Ok(id)
+    }
+
+    /**
+    Remove the transient resources. Called after the frame submission, the GPU side destruction
+    is deferred by wgpu until the submitted work using them has completed.
+    */
+    pub(crate) fn free_transients(&mut self) {
+        let transients: Vec<_> = self.transients.drain(..).collect();
+        for (task, id) in transients {
+            if self.remove_resource(&task, &id).is_err() {
+                log::warn!(target: "EntityManager","Transient resource already removed");
+            }
+        }
+    }
+
+    /**
+    Add a resource marked with a user defined tag, so that it can be freed together with the other resources
+    of the same group (like the assets of a level) by [free_tagged][ResourceManager::free_tagged].
+    */
+    pub fn add_tagged(
+        &mut self,
+        task: TaskId,
+        descriptor: impl Into<ResourceDescriptor>,
+        tag: &str,
+    ) -> Result<ResourceId, ()> {
+        let id = self.add_resource(task, descriptor, None)?;
+        self.tag_resource(task, id, tag);
+        Ok(id)
+    }
+
+    /**
+    Add multiple resources at once, all or nothing: if any of them fails, the ones already added are removed.
+    The items are added in the passed order, and [BatchItem::WithIds][BatchItem::WithIds] items get the ids of the previous ones,
+    so they can reference the resources of the same batch (like a pipeline using the layout added before it).
+    The ids are returned in the passed order.
+    On failure the index of the failing item is returned, with the error that prevented adding it.
+    */
+    pub fn add_batch(
+        &mut self,
+        task: TaskId,
+        items: Vec<BatchItem>,
+    ) -> Result<Vec<ResourceId>, (usize, ResourceBuilderError)> {
+        let mut ids = Vec::new();
+        for (index, item) in items.into_iter().enumerate() {
+            let descriptor = item.descriptor(&ids);
+            match self.try_add_resource(task, descriptor, None) {
+                Ok(id) => ids.push(id),
+                Err(error) => {
+                    log::error!(target: "EntityManager","Failed to add batch item {} for {}: {}, rolling back",index,task,error);
+
+                    for id in ids.iter().rev() {
+                        let _ = self.remove_resource(&task, id);
+                    }
+                    return Err((index, error));
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /**
+    Mark the ownership of a resource by a task with a user defined tag. Returns false if the task does not own the resource.
+    */
+    pub fn tag_resource(&mut self, task: TaskId, id: impl Into<ResourceId>, tag: &str) -> bool {
+        let id = id.into();
+        let owned = self
+            .inner
+            .entity_owners(&id.into())
+            .map(|owners| owners.contains(&task))
+            .unwrap_or(false);
+        if !owned {
+            return false;
+        }
+        let tagged = self.tags.entry(tag.to_string()).or_insert_with(Vec::new);
+        if !tagged.contains(&(task, id)) {
+            tagged.push((task, id));
+        }
+        true
+    }
+
+    /**
+    Remove every resource carrying the tag, releasing the ownership of the tasks that tagged it:
+    resources shared with other owners are kept alive for them.
+    Resources are released in reverse order, so that dependent resources go before their dependencies.
+    Returns the number of released resources.
+    */
+    pub fn free_tagged(&mut self, tag: &str) -> usize {
+        let tagged = match self.tags.remove(tag) {
+            Some(tagged) => tagged,
+            None => return 0,
+        };
+        tagged
+            .into_iter()
+            .rev()
+            .filter(|(task, id)| self.remove_resource(task, id).is_ok())
+            .count()
+    }
+
+    /**
+    Update the descriptor of a resource. Resources whose descriptor does not change are not rebuilt.
+    Stateless resources are shared among the tasks using identical descriptors, so updating them migrates the ownership
+    instead of changing the resource under the other owners:
+    if a resource compatible with the new descriptor exists, the task becomes one of its owners,
+    otherwise if the resource is [shared][ResourceManager::is_shared] a new one is created for the task.
+    In both cases the task releases the previous resource and `id` is updated, while the other owners keep the previous one.
+    Built buffers and textures whose descriptor only changes in usage keep their contents if they have the `COPY_SRC` usage:
+    they get `COPY_DST` too, and the previous contents are copied in the rebuilt resource.
     */
     pub fn update_resource_descriptor<'a>(
         &mut self,
@@ -451,29 +998,298 @@ impl ResourceManager {
         let descriptor = descriptor.into();
 
         if descriptor.state_type() == StateType::Stateless {
-            if let Some(compatible_id) = self.search_compatible(Some(&(&id).into()), &descriptor) {
-                self.inner.remove_entity_owner(&id.clone().into(), task);
+            let current_id: ResourceId = (&id).into();
+            // Nothing to migrate, and the other owners would not see any change anyway.
+            if self.resource_descriptor(&current_id) == Some(&descriptor) {
+                return true;
+            }
+            if let Some(compatible_id) = self.search_compatible(Some(&current_id), &descriptor) {
                 self.inner
                     .add_entity_owner(&compatible_id.clone().into(), task.clone());
+                let _ = self.remove_resource(task, &current_id);
                 *id = compatible_id.into();
                 return true;
             }
+            if self.is_shared(current_id) {
+                let resource = Resource::new(vec![*task], descriptor.clone(), None);
+                return match self.inner.add_entity(resource) {
+                    Ok(new_id) => {
+                        self.inner.damage_entity(new_id);
+                        let new_id = self.add_inner(&descriptor, new_id);
+                        let _ = self.remove_resource(task, &current_id);
+                        *id = new_id.into();
+                        true
+                    }
+                    Err(_err) => false,
+                };
+            }
         }
+        let entity: EntityId = ResourceId::from(&id).into();
+        let descriptor = match self.usage_upgrade(&entity, &descriptor) {
+            Some(upgraded) => {
+                // Submitting the upgraded usage again without COPY_DST leaves the resource as it is.
+                if let Some(previous) = self.inner.entity_descriptor_ref(&entity) {
+                    if *previous != upgraded {
+                        // Upgraded again before being rebuilt: the contents still come from the built handle.
+                        self.usage_upgrades
+                            .entry(entity)
+                            .or_insert_with(|| previous.clone());
+                    }
+                }
+                upgraded
+            }
+            None => {
+                // Any other change rebuilds the resource without its previous contents.
+                self.usage_upgrades.remove(&entity);
+                descriptor
+            }
+        };
+        let resubmitted = &mut self.resubmitted;
         self.inner
             .update_entity_descriptor(&id.into(), |entity_descriptor| {
+                // A changed command buffer is not just encoded again.
+                if *entity_descriptor != descriptor {
+                    resubmitted.remove(&entity);
+                }
                 *entity_descriptor = descriptor;
             })
             .is_some()
     }
 
+    /**
+    Set the instance range of a single draw of a command buffer, see [CommandBufferDescriptor::patch_draw_instances][CommandBufferDescriptor::patch_draw_instances].
+    Unlike [update_command_buffer_descriptor][ResourceManager::update_command_buffer_descriptor], the descriptor is patched
+    in place, without comparing it with the other command buffers: the id does not change and the command buffer
+    is recorded again on the next commit. A command buffer shared with other tasks goes through the usual update instead,
+    so that `id` gets a copy and the other tasks keep the original draw.
+    Returns false if the task does not own the command buffer, or it has no such draw.
+    */
+    pub fn patch_draw_instances(
+        &mut self,
+        task: &TaskId,
+        id: &mut CommandBufferId,
+        pass: usize,
+        draw: usize,
+        instances: std::ops::Range<u32>,
+    ) -> bool {
+        let owned = self
+            .inner
+            .entity_owners(id.id_ref())
+            .map(|owners| owners.contains(task))
+            .unwrap_or(false);
+        if !owned {
+            log::error!(target: "EntityManager","Failed to patch {}: not owned by {}",id,task);
+            return false;
+        }
+        if self.is_shared(*id) {
+            let mut descriptor = match self.command_buffer_descriptor_ref(id) {
+                Some(descriptor) => descriptor.clone(),
+                None => return false,
+            };
+            return descriptor.patch_draw_instances(pass, draw, instances)
+                && self.update_command_buffer_descriptor(task, id, descriptor);
+        }
+        let patched = self
+            .inner
+            .update_entity_descriptor(id.id_ref(), |descriptor| match descriptor {
+                ResourceDescriptor::CommandBuffer(descriptor) => {
+                    descriptor.patch_draw_instances(pass, draw, instances)
+                }
+                _ => false,
+            })
+            .unwrap_or(false);
+        if patched {
+            self.resubmitted.remove(id.id_ref());
+        } else {
+            log::error!(target: "EntityManager","Failed to patch {}: no draw at command {} of pass {}",id,draw,pass);
+        }
+        patched
+    }
+
+    /**
+    Check if a descriptor update of a built buffer or texture only changes its usage. Since the usage of a wgpu resource
+    cannot change, the resource still gets rebuilt, but its contents are copied from the previous one by
+    [update_resource_handle][ResourceManager::update_resource_handle].
+    Returns the new descriptor with `COPY_DST` added for the copy, or `None` if the contents cannot be preserved:
+    the previous resource needs `COPY_SRC`, and textures must be local and not multisampled.
+    */
+    fn usage_upgrade(
+        &self,
+        id: &EntityId,
+        descriptor: &ResourceDescriptor,
+    ) -> Option<ResourceDescriptor> {
+        if !matches!(self.inner.entity_handle_ref(id), Some(Some(_))) {
+            return None;
+        }
+        match (self.inner.entity_descriptor_ref(id), descriptor) {
+            (Some(ResourceDescriptor::Buffer(previous)), ResourceDescriptor::Buffer(current)) => {
+                let mut unchanged = previous.clone();
+                unchanged.usage = current.usage;
+                if unchanged != *current || previous.usage == current.usage {
+                    return None;
+                }
+                // Mappable buffers for writing only allow COPY_SRC.
+                if !previous.usage.contains(crate::wgpu::BufferUsage::COPY_SRC)
+                    || current.usage.contains(crate::wgpu::BufferUsage::MAP_WRITE)
+                {
+                    log::warn!(target: "EntityManager","Usage of {} changed from {:?} to {:?}: the contents cannot be copied and get lost",id,previous.usage,current.usage);
+                    return None;
+                }
+                let mut current = current.clone();
+                current.usage |= crate::wgpu::BufferUsage::COPY_DST;
+                Some(current.into())
+            }
+            (Some(ResourceDescriptor::Texture(previous)), ResourceDescriptor::Texture(current)) => {
+                let mut unchanged = previous.clone();
+                unchanged.usage = current.usage;
+                if unchanged != *current || previous.usage == current.usage {
+                    return None;
+                }
+                if !previous.usage.contains(crate::wgpu::TextureUsage::COPY_SRC)
+                    || previous.source != TextureSource::Local
+                    || previous.sample_count > 1
+                {
+                    log::warn!(target: "EntityManager","Usage of {} changed from {:?} to {:?}: the contents cannot be copied and get lost",id,previous.usage,current.usage);
+                    return None;
+                }
+                let mut current = current.clone();
+                current.usage |= crate::wgpu::TextureUsage::COPY_DST;
+                Some(current.into())
+            }
+            _ => None,
+        }
+    }
+
+    /**
+    Copy the contents of the previous handle of an upgraded resource in the rebuilt one, see [usage_upgrade][ResourceManager::usage_upgrade].
+    `previous_descriptor` is the descriptor of the previous handle: only the extent both resources have in common is copied.
+    */
+    fn copy_previous_contents(
+        &self,
+        id: &EntityId,
+        previous_descriptor: &ResourceDescriptor,
+        current: &ResourceHandle,
+    ) {
+        let (previous, descriptor) = match (
+            self.inner.entity_handle_ref(id),
+            self.inner.entity_descriptor_ref(id),
+        ) {
+            (Some(Some(previous)), Some(descriptor)) => (previous, descriptor),
+            _ => return,
+        };
+        let device = match descriptor
+            .device()
+            .and_then(|device| self.device_handle_ref(&device))
+        {
+            Some(device) => device,
+            None => {
+                log::error!(target: "EntityManager","Failed to copy the contents of {}: parent Device not found",id);
+                return;
+            }
+        };
+
+        let mut encoder = device
+            .1
+            .create_command_encoder(&crate::wgpu::CommandEncoderDescriptor {
+                label: Some("Usage upgrade encoder"),
+            });
+        match (previous, current, previous_descriptor, descriptor) {
+            (
+                ResourceHandle::Buffer(previous),
+                ResourceHandle::Buffer(current),
+                ResourceDescriptor::Buffer(previous_descriptor),
+                ResourceDescriptor::Buffer(descriptor),
+            ) => {
+                let alignment = crate::wgpu::COPY_BUFFER_ALIGNMENT;
+                let size = previous_descriptor.size.min(descriptor.size) / alignment * alignment;
+                encoder.copy_buffer_to_buffer(previous, 0, current, 0, size);
+            }
+            (
+                ResourceHandle::Texture(previous),
+                ResourceHandle::Texture(current),
+                ResourceDescriptor::Texture(previous_descriptor),
+                ResourceDescriptor::Texture(descriptor),
+            ) => {
+                let common_size = crate::wgpu::Extent3d {
+                    width: previous_descriptor.size.width.min(descriptor.size.width),
+                    height: previous_descriptor.size.height.min(descriptor.size.height),
+                    depth_or_array_layers: previous_descriptor
+                        .size
+                        .depth_or_array_layers
+                        .min(descriptor.size.depth_or_array_layers),
+                };
+                let mip_level_count = previous_descriptor
+                    .mip_level_count
+                    .min(descriptor.mip_level_count);
+                // Copies of compressed formats cover whole blocks, even where the mip level is smaller than a block.
+                let (block_width, block_height) = descriptor.format.describe().block_dimensions;
+                let round_up = |extent: u32, block: u8| {
+                    let block = block as u32;
+                    (extent + block - 1) / block * block
+                };
+                for mip_level in 0..mip_level_count {
+                    let depth_or_array_layers =
+                        if descriptor.dimension == crate::wgpu::TextureDimension::D3 {
+                            (common_size.depth_or_array_layers >> mip_level).max(1)
+                        } else {
+                            common_size.depth_or_array_layers
+                        };
+                    let size = crate::wgpu::Extent3d {
+                        width: round_up((common_size.width >> mip_level).max(1), block_width),
+                        height: round_up((common_size.height >> mip_level).max(1), block_height),
+                        depth_or_array_layers,
+                    };
+                    encoder.copy_texture_to_texture(
+                        crate::wgpu::ImageCopyTexture {
+                            texture: previous,
+                            mip_level,
+                            origin: crate::wgpu::Origin3d::ZERO,
+                        },
+                        crate::wgpu::ImageCopyTexture {
+                            texture: current,
+                            mip_level,
+                            origin: crate::wgpu::Origin3d::ZERO,
+                        },
+                        size,
+                    );
+                }
+            }
+            _ => return,
+        }
+        device.2.submit(Some(encoder.finish()));
+        resource_log!(self.log_level, log::Level::Info, target: "EntityManager", "Contents of {} copied in the upgraded resource",id);
+    }
+
     /**
     Update the handle of a resource.
+    Resources rebuilt for a [usage upgrade][ResourceManager::usage_upgrade] get the contents of the previous handle.
     */
     pub(crate) fn update_resource_handle(
         &mut self,
         id: &EntityId,
         resource: ResourceHandle,
     ) -> bool {
+        if let Some(previous_descriptor) = self.usage_upgrades.remove(id) {
+            self.copy_previous_contents(id, &previous_descriptor, &resource);
+        }
+        // A rebuilt device could come from an adapter that changed configuration, so check that nothing has been lost.
+        if let (Some(Some(ResourceHandle::Device(previous))), ResourceHandle::Device(current)) =
+            (self.inner.entity_handle_ref(id), &resource)
+        {
+            let regression = DeviceRegression::between(
+                (previous.1.features(), &previous.1.limits()),
+                (current.1.features(), &current.1.limits()),
+            );
+            match regression {
+                Some(regression) => {
+                    log::error!(target: "EntityManager","{} rebuilt with less capabilities, dependent resources could fail: lost features {:?}, lost limits {:?}",id,regression.features,regression.limits);
+                    self.device_regressions.insert(*id, regression);
+                }
+                None => {
+                    self.device_regressions.remove(id);
+                }
+            }
+        }
         self.inner.update_entity_handle(id, Some(resource))
     }
 
@@ -482,10 +1298,17 @@ impl ResourceManager {
     */
     pub fn remove_resource(&mut self, task: &TaskId, id: &ResourceId) -> Result<(), ()> {
         let owners_count = self.inner.remove_entity_owner(&id.clone().into(), task);
+        if owners_count.is_some() {
+            self.tags.values_mut().for_each(|tagged| {
+                tagged.retain(|(tagged_task, tagged_id)| tagged_task != task || tagged_id != id)
+            });
+        }
 
         match owners_count {
             Some(0) => self.inner.remove_entity(&id.clone().into()).map(|v| {
                 self.remove_inner(id);
+                self.usage_upgrades.remove(&id.clone().into());
+                self.resubmitted.remove(&id.clone().into());
                 v
             }),
             Some(_) => Ok(()),
@@ -493,6 +1316,1101 @@ impl ResourceManager {
         }
     }
 
+    /**
+    Damage a resource without changing its descriptor, so that it (and every resource depending on it)
+    gets rebuilt on the next commit. Return false if the resource does not exist.
+    */
+    pub fn touch_resource(&mut self, id: impl AsRef<EntityId>) -> bool {
+        let id = *id.as_ref();
+        if self.inner.entity_descriptor_ref(&id).is_some() {
+            self.resubmitted.remove(&id);
+            self.inner.damage_entity(id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Check if a resource has been built and it is not waiting to be rebuilt.
+    pub fn is_ready(&self, id: impl AsRef<EntityId>) -> bool {
+        let id = id.as_ref();
+        matches!(self.inner.entity_handle_ref(id), Some(Some(_))) && !self.is_damaged(id)
+    }
+
+    /**
+    Check if a bind group and all the resources it binds are [ready][ResourceManager::is_ready], so that recording a
+    [SetBindGroup][RenderCommand::SetBindGroup] with it does not make the command buffer fail with `MissingDependencies`.
+    Resources created asynchronously, like imported surfaces, can be skipped until they are ready.
+    */
+    pub fn bind_group_ready(&self, id: &BindGroupId) -> bool {
+        self.is_ready(id)
+            && self
+                .dependencies_of(id)
+                .iter()
+                .all(|dependency| self.is_ready(dependency))
+    }
+
+    /**
+    Get a future resolving once the resource is [ready][ResourceManager::is_ready], that is after the commit building it.
+    It does not borrow the manager, so it can be awaited from a task spawned on the runtime while the engine keeps dispatching.
+    The future also resolves if the resource is removed before being built.
+    */
+    pub fn when_ready(
+        &self,
+        id: impl AsRef<EntityId>,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let receiver = if self.is_ready(&id) {
+            None
+        } else {
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            self.ready_waiters
+                .lock()
+                .unwrap()
+                .entry(*id.as_ref())
+                .or_insert_with(Vec::new)
+                .push(sender);
+            Some(receiver)
+        };
+        async move {
+            if let Some(receiver) = receiver {
+                let _ = receiver.await;
+            }
+        }
+    }
+
+    // Wake the futures waiting for resources that are now ready or have been removed.
+    fn notify_ready(&mut self) {
+        let mut waiters = std::mem::take(self.ready_waiters.get_mut().unwrap());
+        waiters.retain(|id, senders| {
+            let removed = self.inner.entity_descriptor_ref(id).is_none();
+            if removed || self.is_ready(id) {
+                senders.drain(..).for_each(|sender| {
+                    let _ = sender.send(());
+                });
+                false
+            } else {
+                true
+            }
+        });
+        self.ready_waiters.get_mut().unwrap().extend(waiters);
+    }
+
+    /**
+    Get the entities that will be rebuilt on the next commit, sorted by id.
+    Entities that failed to build on the previous commit are still damaged, so they get retried.
+    */
+    pub fn damaged_entities(&self) -> Vec<EntityId> {
+        let mut damaged: Vec<_> = self.inner.damaged_entities().collect();
+        damaged.sort();
+        damaged
+    }
+    /// Check if any entity is damaged, that is if the next commit has something to rebuild.
+    pub fn has_damage(&self) -> bool {
+        self.inner.damaged_entities().next().is_some()
+    }
+    /**
+    Check if any entity is damaged by a change since the last submission. Unlike [has_damage][ResourceManager::has_damage],
+    it ignores the command buffers damaged only because the submission consumed them: they would be encoded again as they were.
+    */
+    pub fn has_changes(&self) -> bool {
+        self.inner
+            .damaged_entities()
+            .any(|id| !self.resubmitted.contains(&id))
+    }
+
+    /**
+    Infer the usages that the commands of a command buffer require on the buffers and textures they reference:
+    copies require `COPY_SRC`/`COPY_DST`, attachments `RENDER_ATTACHMENT`, vertex and index buffers `VERTEX`/`INDEX`,
+    indirect arguments `INDIRECT` (for dispatches and draws), and bind groups the usage matching the binding type of their layout.
+    Every resource appears once, in order of first use.
+    */
+    pub fn required_usages(&self, descriptor: &CommandBufferDescriptor) -> Vec<RequiredUsage> {
+        let mut required = Vec::new();
+        for command in &descriptor.commands {
+            match command {
+                Command::BufferToBuffer(copy) => {
+                    require_buffer(
+                        &mut required,
+                        copy.src_buffer,
+                        crate::wgpu::BufferUsage::COPY_SRC,
+                    );
+                    require_buffer(
+                        &mut required,
+                        copy.dst_buffer,
+                        crate::wgpu::BufferUsage::COPY_DST,
+                    );
+                }
+                Command::BufferToTexture(copy) => {
+                    require_buffer(
+                        &mut required,
+                        copy.src_buffer,
+                        crate::wgpu::BufferUsage::COPY_SRC,
+                    );
+                    require_texture(
+                        &mut required,
+                        copy.dst_texture,
+                        crate::wgpu::TextureUsage::COPY_DST,
+                    );
+                }
+                Command::TextureToTexture(copy) => {
+                    require_texture(
+                        &mut required,
+                        copy.src_texture,
+                        crate::wgpu::TextureUsage::COPY_SRC,
+                    );
+                    require_texture(
+                        &mut required,
+                        copy.dst_texture,
+                        crate::wgpu::TextureUsage::COPY_DST,
+                    );
+                }
+                Command::TextureToBuffer(copy) => {
+                    require_texture(
+                        &mut required,
+                        copy.src_texture,
+                        crate::wgpu::TextureUsage::COPY_SRC,
+                    );
+                    require_buffer(
+                        &mut required,
+                        copy.dst_buffer,
+                        crate::wgpu::BufferUsage::COPY_DST,
+                    );
+                }
+                Command::CopyToCubemapFace {
+                    src, dst_cubemap, ..
+                } => {
+                    require_texture(&mut required, *src, crate::wgpu::TextureUsage::COPY_SRC);
+                    require_texture(
+                        &mut required,
+                        *dst_cubemap,
+                        crate::wgpu::TextureUsage::COPY_DST,
+                    );
+                }
+                Command::WriteTimestamp { .. } => {}
+                Command::ComputePass(commands) => {
+                    for command in commands {
+                        match command {
+                            ComputeCommand::SetBindGroup { bind_group, .. } => {
+                                self.bind_group_usages(&mut required, bind_group)
+                            }
+                            ComputeCommand::DispatchIndirect { buffer, .. } => require_buffer(
+                                &mut required,
+                                *buffer,
+                                crate::wgpu::BufferUsage::INDIRECT,
+                            ),
+                            _ => {}
+                        }
+                    }
+                }
+                Command::RenderPass {
+                    depth_stencil,
+                    color_attachments,
+                    commands,
+                    ..
+                } => {
+                    let views = depth_stencil
+                        .iter()
+                        .chain(color_attachments.iter().flat_map(|attachment| {
+                            let view = match &attachment.view {
+                                ColorView::TextureView(view) => Some(view),
+                                ColorView::Swapchain(_swapchain) => None,
+                            };
+                            view.into_iter().chain(attachment.resolve_target.iter())
+                        }));
+                    for view in views {
+                        if let Some(view) = self.texture_view_descriptor_ref(view) {
+                            require_texture(
+                                &mut required,
+                                view.texture,
+                                crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+                            );
+                        }
+                    }
+
+                    self.render_command_usages(&mut required, commands);
+                }
+            }
+        }
+        required
+    }
+
+    /// Usages required by the commands of a render pass, including the ones recorded in the executed render bundles.
+    fn render_command_usages(&self, required: &mut Vec<RequiredUsage>, commands: &[RenderCommand]) {
+        for command in commands {
+            match command {
+                RenderCommand::SetVertexBuffer { buffer, .. } => {
+                    require_buffer(required, *buffer, crate::wgpu::BufferUsage::VERTEX)
+                }
+                RenderCommand::SetIndexBuffer { buffer, .. } => {
+                    require_buffer(required, *buffer, crate::wgpu::BufferUsage::INDEX)
+                }
+                RenderCommand::SetBindGroup { bind_group, .. } => {
+                    self.bind_group_usages(required, bind_group)
+                }
+                RenderCommand::DrawIndirect { buffer, .. }
+                | RenderCommand::DrawIndexedIndirect { buffer, .. } => {
+                    require_buffer(required, *buffer, crate::wgpu::BufferUsage::INDIRECT)
+                }
+                RenderCommand::ExecuteBundles { bundles } => {
+                    for bundle in bundles {
+                        if let Some(bundle) = self.render_bundle_descriptor_ref(bundle) {
+                            self.render_command_usages(required, &bundle.commands);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn bind_group_usages(&self, required: &mut Vec<RequiredUsage>, bind_group: &BindGroupId) {
+        let bind_group = match self.bind_group_descriptor_ref(bind_group) {
+            Some(bind_group) => bind_group,
+            None => return,
+        };
+        let layout = match self.bind_group_layout_descriptor_ref(&bind_group.layout) {
+            Some(layout) => layout,
+            None => return,
+        };
+        for entry in &bind_group.entries {
+            let ty = match layout
+                .entries
+                .iter()
+                .find(|layout_entry| layout_entry.binding == entry.binding)
+            {
+                Some(layout_entry) => layout_entry.ty,
+                None => continue,
+            };
+            let buffer_usage = match ty {
+                crate::wgpu::BindingType::Buffer {
+                    ty: crate::wgpu::BufferBindingType::Uniform,
+                    ..
+                } => crate::wgpu::BufferUsage::UNIFORM,
+                crate::wgpu::BindingType::Buffer { .. } => crate::wgpu::BufferUsage::STORAGE,
+                _ => crate::wgpu::BufferUsage::empty(),
+            };
+            let texture_usage = match ty {
+                crate::wgpu::BindingType::Texture { .. } => crate::wgpu::TextureUsage::SAMPLED,
+                crate::wgpu::BindingType::StorageTexture { .. } => {
+                    crate::wgpu::TextureUsage::STORAGE
+                }
+                _ => crate::wgpu::TextureUsage::empty(),
+            };
+
+            match &entry.resource {
+                BindingResource::Buffer(binding) => {
+                    require_buffer(required, binding.buffer, buffer_usage)
+                }
+                BindingResource::BufferArray(bindings) => bindings
+                    .iter()
+                    .for_each(|binding| require_buffer(required, binding.buffer, buffer_usage)),
+                BindingResource::TextureView(view) => {
+                    if let Some(view) = self.texture_view_descriptor_ref(view) {
+                        require_texture(required, view.texture, texture_usage);
+                    }
+                }
+                BindingResource::TextureViewArray(views) => {
+                    for view in views {
+                        if let Some(view) = self.texture_view_descriptor_ref(view) {
+                            require_texture(required, view.texture, texture_usage);
+                        }
+                    }
+                }
+                BindingResource::Sampler(_sampler) => {}
+            }
+        }
+    }
+
+    /**
+    Get the resources referenced by the commands of a command buffer that belong to a device other than the
+    command buffer one (like a swapchain of a window handled by another device), with their device.
+    A command buffer can only be submitted on its own device, so they must be empty.
+    */
+    pub fn foreign_resources(
+        &self,
+        descriptor: &CommandBufferDescriptor,
+    ) -> Vec<(EntityId, DeviceId)> {
+        let mut dependencies = descriptor.dependencies();
+        dependencies.sort();
+        dependencies.dedup();
+        dependencies
+            .into_iter()
+            .filter_map(|id| {
+                self.entity_descriptor_ref(&id)
+                    .and_then(|resource| resource.device())
+                    .filter(|device| *device != descriptor.device)
+                    .map(|device| (id, device))
+            })
+            .collect()
+    }
+
+    /**
+    Get the usages required by the commands of a command buffer (see [required_usages][ResourceManager::required_usages])
+    that the referenced resources have not been created with. Only the missing flags are reported.
+    */
+    pub fn missing_usages(&self, descriptor: &CommandBufferDescriptor) -> Vec<RequiredUsage> {
+        self.required_usages(descriptor)
+            .into_iter()
+            .filter_map(|required| match required {
+                RequiredUsage::Buffer(id, usage) => {
+                    let missing = usage - self.buffer_descriptor_ref(&id)?.usage;
+                    Some(RequiredUsage::Buffer(id, missing)).filter(|_| !missing.is_empty())
+                }
+                RequiredUsage::Texture(id, usage) => {
+                    let missing = usage - self.texture_descriptor_ref(&id)?.usage;
+                    Some(RequiredUsage::Texture(id, missing)).filter(|_| !missing.is_empty())
+                }
+            })
+            .collect()
+    }
+
+    /**
+    Add the [missing usages][ResourceManager::missing_usages] to the descriptors of the referenced resources,
+    so that they get rebuilt with them. Rebuilding a resource loses its contents, so this is meant to be used
+    before the resources are first written. The descriptors are updated on behalf of the task
+    through [update_resource_descriptor][ResourceManager::update_resource_descriptor].
+    Returns the added usages, with the ids of the updated resources.
+    */
+    pub fn augment_usages(
+        &mut self,
+        task: &TaskId,
+        descriptor: &CommandBufferDescriptor,
+    ) -> Vec<RequiredUsage> {
+        let missing = self.missing_usages(descriptor);
+        missing
+            .into_iter()
+            .filter_map(|required| match required {
+                RequiredUsage::Buffer(mut id, usage) => {
+                    let mut descriptor = self.buffer_descriptor_ref(&id)?.clone();
+                    descriptor.usage |= usage;
+                    if self.update_buffer_descriptor(task, &mut id, descriptor) {
+                        Some(RequiredUsage::Buffer(id, usage))
+                    } else {
+                        log::error!(target: "EntityManager","Failed to add {:?} to {}",usage,id);
+                        None
+                    }
+                }
+                RequiredUsage::Texture(mut id, usage) => {
+                    let mut descriptor = self.texture_descriptor_ref(&id)?.clone();
+                    descriptor.usage |= usage;
+                    if self.update_texture_descriptor(task, &mut id, descriptor) {
+                        Some(RequiredUsage::Texture(id, usage))
+                    } else {
+                        log::error!(target: "EntityManager","Failed to add {:?} to {}",usage,id);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /**
+    Check the order of the passes of a command buffer sharing buffers between compute and graphics:
+    a compute pass writing a storage buffer must precede the render passes reading it,
+    otherwise they read the data of the previous frame. wgpu synchronizes the passes in recording order,
+    so this only catches the passes recorded in the wrong order.
+    Returns the buffers written by a compute pass after being read by a render pass.
+    */
+    pub fn check_compute_render_order(
+        &self,
+        descriptor: &CommandBufferDescriptor,
+    ) -> Vec<BufferId> {
+        let mut read = Vec::new();
+        let mut misordered = Vec::new();
+        for command in &descriptor.commands {
+            match command {
+                Command::RenderPass { commands, .. } => self.render_pass_reads(&mut read, commands),
+                Command::ComputePass(commands) => {
+                    let written = commands
+                        .iter()
+                        .filter_map(|command| match command {
+                            ComputeCommand::SetBindGroup { bind_group, .. } => Some(bind_group),
+                            _ => None,
+                        })
+                        .flat_map(|bind_group| self.bind_group_buffers(bind_group, true));
+                    for buffer in written {
+                        if read.contains(&buffer) && !misordered.contains(&buffer) {
+                            log::warn!(target: "EntityManager","CommandBuffer `{}`: {} is written by a compute pass after being read by a render pass",descriptor.label,buffer);
+                            misordered.push(buffer);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        misordered
+    }
+
+    // Buffers read by the commands of a render pass, including the ones recorded in the executed render bundles.
+    fn render_pass_reads(&self, read: &mut Vec<BufferId>, commands: &[RenderCommand]) {
+        for command in commands {
+            match command {
+                RenderCommand::SetVertexBuffer { buffer, .. }
+                | RenderCommand::SetIndexBuffer { buffer, .. } => read.push(*buffer),
+                RenderCommand::SetBindGroup { bind_group, .. } => {
+                    read.append(&mut self.bind_group_buffers(bind_group, false))
+                }
+                RenderCommand::ExecuteBundles { bundles } => {
+                    for bundle in bundles {
+                        if let Some(bundle) = self.render_bundle_descriptor_ref(bundle) {
+                            self.render_pass_reads(read, &bundle.commands);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Buffers bound by a bind group, possibly only the ones bound as writable storage.
+    fn bind_group_buffers(&self, bind_group: &BindGroupId, writable_only: bool) -> Vec<BufferId> {
+        let bind_group = match self.bind_group_descriptor_ref(bind_group) {
+            Some(bind_group) => bind_group,
+            None => return Vec::new(),
+        };
+        let layout = self.bind_group_layout_descriptor_ref(&bind_group.layout);
+        bind_group
+            .entries
+            .iter()
+            .filter(|entry| {
+                !writable_only
+                    || layout
+                        .and_then(|layout| {
+                            layout
+                                .entries
+                                .iter()
+                                .find(|layout_entry| layout_entry.binding == entry.binding)
+                        })
+                        .map(|layout_entry| {
+                            matches!(
+                                layout_entry.ty,
+                                crate::wgpu::BindingType::Buffer {
+                                    ty: crate::wgpu::BufferBindingType::Storage {
+                                        read_only: false
+                                    },
+                                    ..
+                                }
+                            )
+                        })
+                        .unwrap_or(false)
+            })
+            .flat_map(|entry| match &entry.resource {
+                BindingResource::Buffer(binding) => vec![binding.buffer],
+                BindingResource::BufferArray(bindings) => {
+                    bindings.iter().map(|binding| binding.buffer).collect()
+                }
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    /**
+    Check if the bind group can be used at `index` with the pipeline layout, that is if its layout
+    is [compatible][BindGroupLayoutDescriptor::is_compatible_with] with the one the pipeline layout expects at that index.
+    Resources that do not exist are not checked.
+    */
+    pub fn is_bind_group_compatible(
+        &self,
+        pipeline_layout: &PipelineLayoutId,
+        index: u32,
+        bind_group: &BindGroupId,
+    ) -> bool {
+        let pipeline_layout = match self.pipeline_layout_descriptor_ref(pipeline_layout) {
+            Some(pipeline_layout) => pipeline_layout,
+            None => return true,
+        };
+        let layout = match self.bind_group_descriptor_ref(bind_group) {
+            Some(descriptor) => descriptor.layout,
+            None => return true,
+        };
+        let expected = match pipeline_layout.bind_group_layouts.get(index as usize) {
+            Some(expected) => *expected,
+            None => return false,
+        };
+        if expected == layout {
+            return true;
+        }
+        match (
+            self.bind_group_layout_descriptor_ref(&expected),
+            self.bind_group_layout_descriptor_ref(&layout),
+        ) {
+            (Some(expected), Some(layout)) => expected.is_compatible_with(layout),
+            _ => true,
+        }
+    }
+
+    /**
+    Find the first bind group used by a draw of the render pass with a pipeline whose layout expects an incompatible one.
+    The bind groups are checked at every draw against the pipeline bound at that time, since they can be set before the pipeline
+    and stay bound when it changes. Only the indices used by the pipeline layout are checked,
+    and pipelines with an automatic layout are not checked.
+    */
+    pub fn incompatible_render_bind_group(
+        &self,
+        commands: &[RenderCommand],
+    ) -> Option<(RenderPipelineId, u32, BindGroupId)> {
+        let mut pipeline = None;
+        let mut bind_groups = BTreeMap::new();
+        for command in commands {
+            match command {
+                RenderCommand::SetPipeline { pipeline: id } => pipeline = Some(*id),
+                RenderCommand::SetBindGroup {
+                    index, bind_group, ..
+                } => {
+                    bind_groups.insert(*index, *bind_group);
+                }
+                RenderCommand::Draw { .. }
+                | RenderCommand::DrawIndexed { .. }
+                | RenderCommand::DrawIndirect { .. }
+                | RenderCommand::DrawIndexedIndirect { .. } => {
+                    let pipeline = match pipeline {
+                        Some(pipeline) => pipeline,
+                        None => continue,
+                    };
+                    let layout = self
+                        .render_pipeline_descriptor_ref(&pipeline)
+                        .and_then(|descriptor| descriptor.layout);
+                    if let Some((index, bind_group)) =
+                        self.incompatible_bind_group(layout, &bind_groups)
+                    {
+                        return Some((pipeline, index, bind_group));
+                    }
+                }
+                // Executing render bundles unbinds the pipeline and the bind groups.
+                RenderCommand::ExecuteBundles { .. } => {
+                    pipeline = None;
+                    bind_groups.clear();
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /**
+    Find the first bind group used by a dispatch of the compute pass with a pipeline whose layout expects an incompatible one,
+    like [incompatible_render_bind_group][ResourceManager::incompatible_render_bind_group] does for the draws.
+    */
+    pub fn incompatible_compute_bind_group(
+        &self,
+        commands: &[ComputeCommand],
+    ) -> Option<(ComputePipelineId, u32, BindGroupId)> {
+        let mut pipeline = None;
+        let mut bind_groups = BTreeMap::new();
+        for command in commands {
+            match command {
+                ComputeCommand::SetPipeline { pipeline: id } => pipeline = Some(*id),
+                ComputeCommand::SetBindGroup {
+                    index, bind_group, ..
+                } => {
+                    bind_groups.insert(*index, *bind_group);
+                }
+                ComputeCommand::Dispatch { .. } | ComputeCommand::DispatchIndirect { .. } => {
+                    let pipeline = match pipeline {
+                        Some(pipeline) => pipeline,
+                        None => continue,
+                    };
+                    let layout = self
+                        .compute_pipeline_descriptor_ref(&pipeline)
+                        .and_then(|descriptor| descriptor.layout);
+                    if let Some((index, bind_group)) =
+                        self.incompatible_bind_group(layout, &bind_groups)
+                    {
+                        return Some((pipeline, index, bind_group));
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    // First bound bind group, among the indices used by the pipeline layout, that is not compatible with it.
+    fn incompatible_bind_group(
+        &self,
+        pipeline_layout: Option<PipelineLayoutId>,
+        bind_groups: &BTreeMap<u32, BindGroupId>,
+    ) -> Option<(u32, BindGroupId)> {
+        let pipeline_layout = pipeline_layout?;
+        let used = self
+            .pipeline_layout_descriptor_ref(&pipeline_layout)?
+            .bind_group_layouts
+            .len() as u32;
+        bind_groups
+            .range(..used)
+            .find(|(index, bind_group)| {
+                !self.is_bind_group_compatible(&pipeline_layout, **index, bind_group)
+            })
+            .map(|(index, bind_group)| (*index, *bind_group))
+    }
+
+    /**
+    Validate the indexed draws of a render pass, tracking the index buffer bound by
+    [SetIndexBuffer][RenderCommand::SetIndexBuffer]: every [DrawIndexed][RenderCommand::DrawIndexed] must follow one,
+    and its indices must fit the bound slice with the format it has been bound with.
+    A [DrawIndexedIndirect][RenderCommand::DrawIndexedIndirect] only needs an index buffer bound, since its indices are read on the GPU.
+    Meshes with different index formats can be mixed in a pass by binding the index buffer again with the new format.
+    */
+    pub fn validate_indexed_draws(&self, commands: &[RenderCommand]) -> bool {
+        let mut bound = None;
+        for command in commands {
+            match command {
+                RenderCommand::SetIndexBuffer {
+                    index_format,
+                    buffer,
+                    slice,
+                } => {
+                    let size = match self.buffer_descriptor_ref(buffer) {
+                        Some(descriptor) => descriptor.size,
+                        None => {
+                            log::error!(target: "EntityManager","Index buffer {:?} not found",buffer);
+                            return false;
+                        }
+                    };
+                    let range = slice.to_range(size);
+                    let index_size = match index_format {
+                        crate::wgpu::IndexFormat::Uint16 => 2,
+                        crate::wgpu::IndexFormat::Uint32 => 4,
+                    };
+                    let capacity = range.end.saturating_sub(range.start) / index_size;
+                    bound = Some((*index_format, capacity));
+                }
+                RenderCommand::DrawIndexed { indices, .. } => match bound {
+                    Some((_index_format, capacity)) if indices.end as u64 <= capacity => {}
+                    Some((index_format, capacity)) => {
+                        log::error!(target: "EntityManager","DrawIndexed of indices {:?} out of the bound index buffer, holding {} {:?} indices",indices,capacity,index_format);
+                        return false;
+                    }
+                    None => {
+                        log::error!(target: "EntityManager","DrawIndexed of indices {:?} without an index buffer bound",indices);
+                        return false;
+                    }
+                },
+                RenderCommand::DrawIndexedIndirect { buffer, .. } if bound.is_none() => {
+                    log::error!(target: "EntityManager","DrawIndexedIndirect from {} without an index buffer bound",buffer);
+                    return false;
+                }
+                // Executing render bundles unbinds the index buffer.
+                RenderCommand::ExecuteBundles { .. } => bound = None,
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /**
+    Link the size of a texture to the one of a swapchain: whenever the swapchain gets resized,
+    the width and height of the texture descriptor are updated to match, so that the texture
+    (and the views depending on it) is rebuilt. If the swapchain already exists, the texture is resized immediately.
+    A depth target shared with other tasks is not resized for all of them: the task is moved on a target
+    of the new size instead, retrieved with [take_detached_depth_target][ResourceManager::take_detached_depth_target].
+    Returns false if the texture does not exist.
+    */
+    pub fn link_to_swapchain_size(
+        &mut self,
+        task: TaskId,
+        texture: TextureId,
+        swapchain: SwapchainId,
+    ) -> bool {
+        if self.texture_descriptor_ref(&texture).is_none() {
+            log::error!(target: "EntityManager","Failed to link {} to the size of {}: the texture does not exists",texture,swapchain);
+            return false;
+        }
+        self.size_links.insert(texture, (task, swapchain));
+        if let Some((width, height)) = self
+            .swapchain_descriptor_ref(&swapchain)
+            .map(|descriptor| (descriptor.width, descriptor.height))
+        {
+            self.resize_texture(&task, texture, width, height);
+        }
+        true
+    }
+
+    /**
+    Remove the link set by [link_to_swapchain_size][ResourceManager::link_to_swapchain_size].
+    Returns false if the texture was not linked.
+    */
+    pub fn unlink_swapchain_size(&mut self, texture: &TextureId) -> bool {
+        self.size_links.remove(texture).is_some()
+    }
+
+    /**
+    Resize the textures linked to the swapchain, returning the ones that changed size.
+    The links of removed textures are dropped.
+    */
+    pub(crate) fn resize_linked_textures(
+        &mut self,
+        swapchain: &SwapchainId,
+        width: u32,
+        height: u32,
+    ) -> Vec<TextureId> {
+        let removed: Vec<_> = self
+            .size_links
+            .keys()
+            .filter(|texture| self.texture_descriptor_ref(texture).is_none())
+            .copied()
+            .collect();
+        for texture in removed {
+            self.size_links.remove(&texture);
+        }
+
+        let linked: Vec<_> = self
+            .size_links
+            .iter()
+            .filter(|(_texture, (_task, linked_swapchain))| linked_swapchain == swapchain)
+            .map(|(texture, (task, _swapchain))| (*texture, *task))
+            .collect();
+        linked
+            .into_iter()
+            .filter_map(|(texture, task)| self.resize_texture(&task, texture, width, height))
+            .collect()
+    }
+
+    /**
+    Get the depth target the task was moved on when resizing the shared depth target `view`,
+    see [link_to_swapchain_size][ResourceManager::link_to_swapchain_size].
+    Returns None if the task was not moved since the last call.
+    */
+    pub fn take_detached_depth_target(
+        &mut self,
+        task: &TaskId,
+        view: &TextureViewId,
+    ) -> Option<TextureViewId> {
+        self.detached_depth_targets.remove(&(*task, *view))
+    }
+
+    /**
+    Make the task responsible for presenting the swapchain, through [present_swapchain][ResourceManager::present_swapchain]:
+    the engine presents only the swapchains not claimed by any task, clearing the frames nobody rendered on.
+    Returns false if the swapchain is already claimed by another task.
+    */
+    pub fn claim_swapchain(&mut self, task: TaskId, swapchain: SwapchainId) -> bool {
+        match self.swapchain_claims.get(&swapchain) {
+            Some(owner) if *owner != task => {
+                log::error!(target: "EntityManager","Failed to claim {}: already claimed by {}",swapchain,owner);
+                false
+            }
+            _ => {
+                self.swapchain_claims.insert(swapchain, task);
+                true
+            }
+        }
+    }
+
+    /**
+    Remove the claim set by [claim_swapchain][ResourceManager::claim_swapchain].
+    Returns false if the swapchain is not claimed by the task.
+    */
+    pub fn release_swapchain(&mut self, task: &TaskId, swapchain: &SwapchainId) -> bool {
+        if self.swapchain_claims.get(swapchain) != Some(task) {
+            return false;
+        }
+        self.swapchain_claims.remove(swapchain);
+        true
+    }
+
+    /**
+    Present the current frame of a swapchain claimed by the task.
+    Returns an error if the swapchain is not claimed by the task or has no frame to present.
+    */
+    pub fn present_swapchain(&mut self, task: &TaskId, swapchain: &SwapchainId) -> Result<(), ()> {
+        if self.swapchain_claims.get(swapchain) != Some(task) {
+            log::error!(target: "EntityManager","Failed to present {}: it is not claimed by {}",swapchain,task);
+            return Err(());
+        }
+        let handle = match self.swapchain_handle_ref(swapchain) {
+            Some(handle) => handle,
+            None => {
+                log::error!(target: "EntityManager","Failed to present {}: it does not exists",swapchain);
+                return Err(());
+            }
+        };
+        if handle.current_frame().is_none() {
+            log::error!(target: "EntityManager","Failed to present {}: it has no frame",swapchain);
+            return Err(());
+        }
+        handle.present();
+        self.task_presents = true;
+        Ok(())
+    }
+
+    /// Check if a task presented a claimed swapchain since the last call, so that its next frame needs to be rendered.
+    pub(crate) fn take_task_presents(&mut self) -> bool {
+        std::mem::take(&mut self.task_presents)
+    }
+
+    /// Task responsible for presenting the swapchain, if any.
+    pub fn swapchain_owner(&self, swapchain: &SwapchainId) -> Option<TaskId> {
+        self.swapchain_claims.get(swapchain).copied()
+    }
+
+    /// Drop the claim of a removed swapchain, whatever task owns it.
+    pub(crate) fn drop_swapchain_claim(&mut self, swapchain: &SwapchainId) {
+        self.swapchain_claims.remove(swapchain);
+    }
+
+    /**
+    Update the width and height of a texture descriptor, returning the resized texture,
+    or None if the size did not change. A depth target shared with other tasks is left as it is
+    and the task is moved on a depth target of the new size.
+    */
+    fn resize_texture(
+        &mut self,
+        task: &TaskId,
+        texture: TextureId,
+        width: u32,
+        height: u32,
+    ) -> Option<TextureId> {
+        let mut descriptor = self.texture_descriptor_ref(&texture).cloned()?;
+        if descriptor.size.width == width && descriptor.size.height == height {
+            return None;
+        }
+        descriptor.size.width = width;
+        descriptor.size.height = height;
+        let format = descriptor.format;
+        let device = descriptor.device;
+
+        let depth_target = self
+            .depth_targets
+            .iter()
+            .find(|(_key, view)| {
+                self.texture_view_descriptor_ref(view)
+                    .map(|view| view.texture == texture)
+                    .unwrap_or(false)
+            })
+            .map(|(key, view)| (*key, *view));
+
+        if let Some((_key, view)) = depth_target {
+            if self.is_shared(texture) {
+                return self.detach_depth_target(task, view, texture, width, height);
+            }
+        }
+
+        let mut id = texture;
+        if !self.update_texture_descriptor(task, &mut id, descriptor) {
+            log::error!(target: "EntityManager","Failed to resize {}",texture);
+            return None;
+        }
+
+        // Keep the depth target reachable by its new size.
+        if let Some((key, view)) = depth_target {
+            self.depth_targets.remove(&key);
+            self.depth_targets
+                .entry((device, width, height, format))
+                .or_insert(view);
+        }
+        Some(id)
+    }
+
+    /// Move the task from the shared depth target `view` to one of the new size, leaving the other owners on the old one.
+    fn detach_depth_target(
+        &mut self,
+        task: &TaskId,
+        view: TextureViewId,
+        texture: TextureId,
+        width: u32,
+        height: u32,
+    ) -> Option<TextureId> {
+        let (device, format) = self
+            .texture_descriptor_ref(&texture)
+            .map(|descriptor| (descriptor.device, descriptor.format))?;
+        let detached_view = match self.create_depth_target(*task, device, width, height, format) {
+            Ok(detached_view) => detached_view,
+            Err(()) => {
+                log::error!(target: "EntityManager","Failed to resize {}: cannot detach {} from the shared depth target",texture,task);
+                return None;
+            }
+        };
+        let detached_texture = self
+            .texture_view_descriptor_ref(&detached_view)
+            .map(|descriptor| descriptor.texture)?;
+        let _ = self.release_depth_target(task, &view);
+
+        if let Some(link) = self.size_links.remove(&texture) {
+            self.size_links.insert(detached_texture, link);
+        }
+        self.detached_depth_targets
+            .insert((*task, view), detached_view);
+        Some(detached_texture)
+    }
+
+    /**
+    Get a depth target of the passed size and format. If another task already requested an identical one,
+    the same target is shared and the task is added to its owners.
+    Sharing is only meaningful among tasks using a compatible [DepthStencilState][crate::DepthStencilState],
+    since every pass writing on the target sees the depth values of the others.
+    The target is freed when every owner released it with [release_depth_target][ResourceManager::release_depth_target].
+    */
+    pub fn create_depth_target(
+        &mut self,
+        task: TaskId,
+        device: DeviceId,
+        width: u32,
+        height: u32,
+        format: crate::wgpu::TextureFormat,
+    ) -> Result<TextureViewId, ()> {
+        let key = (device, width, height, format);
+        if let Some(view) = self.depth_targets.get(&key).copied() {
+            if let Some(texture) = self
+                .texture_view_descriptor_ref(&view)
+                .map(|view| view.texture)
+            {
+                self.inner.add_entity_owner(texture.id_ref(), task);
+                self.inner.add_entity_owner(view.id_ref(), task);
+                return Ok(view);
+            }
+        }
+
+        let label = format!("Depth target {}x{} {:?}", width, height, format);
+        let texture = self.add_texture(
+            task,
+            TextureDescriptor {
+                label: label.clone(),
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+                size: crate::wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            },
+            None,
+        )?;
+        let view = self.add_texture_view(
+            task,
+            TextureViewDescriptor {
+                label,
+                device,
+                texture,
+                format,
+                dimension: crate::wgpu::TextureViewDimension::D2,
+                aspect: crate::wgpu::TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: None,
+                base_array_layer: 0,
+                array_layer_count: None,
+            },
+            None,
+        );
+        match view {
+            Ok(view) => {
+                self.depth_targets.insert(key, view);
+                Ok(view)
+            }
+            Err(()) => {
+                let _ = self.remove_texture(&task, &texture);
+                Err(())
+            }
+        }
+    }
+
+    /**
+    Sample counts supported by a render target of the passed format on the device. The adapter cannot report
+    the supported counts with this wgpu version, so the counts guaranteed by the WebGPU specification are returned:
+    1 and 4 for renderable uncompressed formats, only 1 otherwise. An unknown device supports no sample count.
+    */
+    pub fn supported_sample_counts(
+        &self,
+        device: DeviceId,
+        format: crate::wgpu::TextureFormat,
+    ) -> Vec<u32> {
+        let device = match self.device_handle_ref(&device) {
+            Some(device) => device,
+            None => return Vec::new(),
+        };
+        let renderable = device
+            .0
+            .get_texture_format_features(format)
+            .allowed_usages
+            .contains(crate::wgpu::TextureUsage::RENDER_ATTACHMENT);
+        if renderable && format.describe().block_dimensions == (1, 1) {
+            vec![1, 4]
+        } else {
+            vec![1]
+        }
+    }
+
+    /**
+    Create a multisampled render target, using the highest supported sample count not exceeding `sample_count`.
+    Returns the view of the target and the selected sample count.
+    */
+    pub fn create_msaa_target(
+        &mut self,
+        task: TaskId,
+        device: DeviceId,
+        width: u32,
+        height: u32,
+        format: crate::wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Result<(TextureViewId, u32), ()> {
+        let sample_count =
+            select_sample_count(sample_count, &self.supported_sample_counts(device, format));
+
+        let label = format!(
+            "MSAA target {}x{} {:?} x{}",
+            width, height, format, sample_count
+        );
+        let texture = self.add_texture(
+            task,
+            TextureDescriptor {
+                label: label.clone(),
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+                size: crate::wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count,
+            },
+            None,
+        )?;
+        let view = self.add_texture_view(
+            task,
+            TextureViewDescriptor {
+                label,
+                device,
+                texture,
+                format,
+                dimension: crate::wgpu::TextureViewDimension::D2,
+                aspect: crate::wgpu::TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: None,
+                base_array_layer: 0,
+                array_layer_count: None,
+            },
+            None,
+        );
+        match view {
+            Ok(view) => Ok((view, sample_count)),
+            Err(()) => {
+                let _ = self.remove_texture(&task, &texture);
+                Err(())
+            }
+        }
+    }
+
+    /**
+    Release a depth target obtained from [create_depth_target][ResourceManager::create_depth_target].
+    */
+    pub fn release_depth_target(&mut self, task: &TaskId, view: &TextureViewId) -> Result<(), ()> {
+        let texture = match self.texture_view_descriptor_ref(view) {
+            Some(descriptor) => descriptor.texture,
+            None => return Err(()),
+        };
+        self.remove_texture_view(task, view)?;
+        self.remove_texture(task, &texture)
+    }
+
     /**
     Get the descriptor of the resource corrisponding to the resource id.
     */
@@ -509,7 +2427,10 @@ impl ResourceManager {
     ) -> Option<crate::wgpu::CommandBuffer> {
         match self.inner.take_entity_handle(id.id_ref()) {
             Some(ResourceHandle::CommandBuffer(handle)) => match Arc::try_unwrap(handle) {
-                Ok(unwrapped_command_buffer) => Some(unwrapped_command_buffer),
+                Ok(unwrapped_command_buffer) => {
+                    self.resubmitted.insert(*id.id_ref());
+                    Some(unwrapped_command_buffer)
+                }
                 Err(arc_command_buffer) => {
                     self.update_resource_handle(
                         id.id_ref(),
@@ -522,6 +2443,310 @@ impl ResourceManager {
         }
     }
 
+    /**
+    Validate and map a range of a buffer, waiting for the mapping to be resolved.
+    */
+    fn map_buffer(
+        &self,
+        id: &BufferId,
+        offset: crate::wgpu::BufferAddress,
+        size: crate::wgpu::BufferAddress,
+        mode: crate::wgpu::MapMode,
+    ) -> Result<BufferHandle, ()> {
+        let descriptor = match self.buffer_descriptor_ref(id) {
+            Some(descriptor) => descriptor,
+            None => {
+                log::error!(target: "EntityManager","Failed to map {}: buffer not found",id);
+                return Err(());
+            }
+        };
+
+        let required_usage = match mode {
+            crate::wgpu::MapMode::Read => crate::wgpu::BufferUsage::MAP_READ,
+            crate::wgpu::MapMode::Write => crate::wgpu::BufferUsage::MAP_WRITE,
+        };
+        if !descriptor.usage.contains(required_usage) {
+            log::error!(target: "EntityManager","Failed to map {}: {:?} usage is required",id,required_usage);
+            return Err(());
+        }
+        let end = match offset.checked_add(size) {
+            Some(end) if end <= descriptor.size => end,
+            _ => {
+                log::error!(target: "EntityManager","Failed to map {}: range of {} bytes at offset {} exceeds the buffer size {}",id,size,offset,descriptor.size);
+                return Err(());
+            }
+        };
+        if offset % crate::wgpu::MAP_ALIGNMENT != 0
+            || size % crate::wgpu::COPY_BUFFER_ALIGNMENT != 0
+        {
+            log::error!(target: "EntityManager","Failed to map {}: offset must be a multiple of {} and size a multiple of {}",id,crate::wgpu::MAP_ALIGNMENT,crate::wgpu::COPY_BUFFER_ALIGNMENT);
+            return Err(());
+        }
+
+        let buffer = match self.buffer_handle_ref(id) {
+            Some(buffer) => buffer.clone(),
+            None => {
+                log::error!(target: "EntityManager","Failed to map {}: buffer not built yet",id);
+                return Err(());
+            }
+        };
+        let device = match self.device_handle_ref(&descriptor.device) {
+            Some(device) => device,
+            None => {
+                log::error!(target: "EntityManager","Failed to map {}: parent Device {} not found",id,descriptor.device);
+                return Err(());
+            }
+        };
+
+        let future = buffer.slice(offset..end).map_async(mode);
+        device.1.poll(crate::wgpu::Maintain::Wait);
+        match crate::utils::block_on(&self.tokio, future) {
+            Ok(()) => Ok(buffer),
+            Err(err) => {
+                log::error!(target: "EntityManager","Failed to map {}: {:?}",id,err);
+                Err(())
+            }
+        }
+    }
+
+    /**
+    Record a batch of writes on the queues of their devices, [merging][ResourceWrite::merge] the adjacent
+    buffer writes to reduce the number of calls. Like the queue writes, they are flushed by the next submission.
+    Returns the number of write calls recorded. Writes on missing resources are skipped.
+    */
+    pub fn write_batch(&self, writes: Vec<ResourceWrite>) -> usize {
+        let mut device_writes: HashMap<DeviceId, Vec<ResourceWrite>> = HashMap::new();
+        for write in writes {
+            let device = match &write {
+                ResourceWrite::Buffer(buffer_write) => self
+                    .buffer_descriptor_ref(&buffer_write.buffer)
+                    .map(|descriptor| descriptor.device),
+                ResourceWrite::Texture(texture_write) => self
+                    .texture_descriptor_ref(&texture_write.texture)
+                    .map(|descriptor| descriptor.device),
+            };
+            match device {
+                Some(device) => device_writes.entry(device).or_default().push(write),
+                None => {
+                    log::error!(target: "EntityManager","Failed to record {:?}: resource not found",write);
+                }
+            }
+        }
+
+        let mut calls = 0;
+        for (device, writes) in device_writes {
+            let queue = match self.device_handle_ref(&device) {
+                Some(device) => &device.2,
+                None => {
+                    log::error!(target: "EntityManager","Failed to record writes: Device {} not built yet",device);
+                    continue;
+                }
+            };
+            for write in ResourceWrite::merge(writes) {
+                write.record(self, queue);
+                calls += 1;
+            }
+        }
+        calls
+    }
+
+    /**
+    Synchronously write data in a buffer by mapping it. The buffer requires the `MAP_WRITE` usage.
+    The write is applied immediately, not on the next dispatch.
+    */
+    pub fn write_buffer_mapped(
+        &mut self,
+        id: &BufferId,
+        offset: crate::wgpu::BufferAddress,
+        data: &[u8],
+    ) -> Result<(), ()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let size = data.len() as crate::wgpu::BufferAddress;
+        let buffer = self.map_buffer(id, offset, size, crate::wgpu::MapMode::Write)?;
+        buffer
+            .slice(offset..offset + size)
+            .get_mapped_range_mut()
+            .copy_from_slice(data);
+        buffer.unmap();
+        Ok(())
+    }
+
+    /**
+    Synchronously read data from a buffer by mapping it. The buffer requires the `MAP_READ` usage.
+    */
+    pub fn read_buffer(
+        &self,
+        id: &BufferId,
+        offset: crate::wgpu::BufferAddress,
+        size: crate::wgpu::BufferAddress,
+    ) -> Result<Vec<u8>, ()> {
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+        let buffer = self.map_buffer(id, offset, size, crate::wgpu::MapMode::Read)?;
+        let data = buffer
+            .slice(offset..offset + size)
+            .get_mapped_range()
+            .to_vec();
+        buffer.unmap();
+        Ok(data)
+    }
+
+    /**
+    Synchronously read the first `count` elements of a buffer as a typed slice, like
+    [read_buffer][ResourceManager::read_buffer]. The mapped range is rounded up to
+    [COPY_BUFFER_ALIGNMENT][crate::wgpu::COPY_BUFFER_ALIGNMENT], so it must fit the buffer too.
+    */
+    pub fn read_buffer_typed<T: bytemuck::Pod>(
+        &self,
+        id: &BufferId,
+        count: usize,
+    ) -> Result<Vec<T>, ()> {
+        let element_size = std::mem::size_of::<T>();
+        if element_size == 0 {
+            log::error!(target: "EntityManager","Failed to read {}: zero sized elements",id);
+            return Err(());
+        }
+        let size = match count.checked_mul(element_size) {
+            Some(size) => size as crate::wgpu::BufferAddress,
+            None => {
+                log::error!(target: "EntityManager","Failed to read {}: {} elements of {} bytes overflow the address space",id,count,element_size);
+                return Err(());
+            }
+        };
+        let (buffer_size, alignments) = match self.buffer_descriptor_ref(id) {
+            Some(descriptor) => (
+                descriptor.size,
+                self.device_alignments(descriptor.device)
+                    .unwrap_or_default(),
+            ),
+            None => {
+                log::error!(target: "EntityManager","Failed to read {}: buffer not found",id);
+                return Err(());
+            }
+        };
+        if size > buffer_size {
+            log::error!(target: "EntityManager","Failed to read {}: {} elements of {} bytes exceed the buffer size {}",id,count,element_size,buffer_size);
+            return Err(());
+        }
+
+        let mapped_size = alignments.align_copy_size(size);
+        let data = self.read_buffer(id, 0, mapped_size)?;
+        Ok(data[..size as usize]
+            .chunks_exact(element_size)
+            .map(bytemuck::pod_read_unaligned)
+            .collect())
+    }
+
+    /**
+    Synchronously resolve and read the results of the `queries` of a query set, one `u64` for every value of a query.
+    The results are resolved in temporary buffers on the queue of the parent device, so they reflect the command
+    buffers submitted by the previous dispatches.
+    */
+    pub fn read_query_results(
+        &self,
+        id: &QuerySetId,
+        queries: std::ops::Range<u32>,
+    ) -> Result<Vec<u64>, ()> {
+        let descriptor = match self.query_set_descriptor_ref(id) {
+            Some(descriptor) => descriptor,
+            None => {
+                log::error!(target: "EntityManager","Failed to read the results of {}: query set not found",id);
+                return Err(());
+            }
+        };
+        if queries.start >= queries.end || queries.end > descriptor.count {
+            log::error!(target: "EntityManager","Failed to read the results of {}: queries {:?} out of the {} queries of the set",id,queries,descriptor.count);
+            return Err(());
+        }
+        let query_set = match self.query_set_handle_ref(id) {
+            Some(query_set) => query_set,
+            None => {
+                log::error!(target: "EntityManager","Failed to read the results of {}: query set not built yet",id);
+                return Err(());
+            }
+        };
+        let device = match self.device_handle_ref(&descriptor.device) {
+            Some(device) => device,
+            None => {
+                log::error!(target: "EntityManager","Failed to read the results of {}: parent Device {} not found",id,descriptor.device);
+                return Err(());
+            }
+        };
+
+        let size =
+            descriptor.result_size() * (queries.end - queries.start) as crate::wgpu::BufferAddress;
+        // Queries are resolved like a copy, then the results are copied in a staging buffer to be mapped.
+        let resolve = device.1.create_buffer(&crate::wgpu::BufferDescriptor {
+            label: Some("Query resolve buffer"),
+            size,
+            usage: crate::wgpu::BufferUsage::COPY_DST | crate::wgpu::BufferUsage::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.1.create_buffer(&crate::wgpu::BufferDescriptor {
+            label: Some("Query staging buffer"),
+            size,
+            usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device
+            .1
+            .create_command_encoder(&crate::wgpu::CommandEncoderDescriptor {
+                label: Some("Query resolve encoder"),
+            });
+        encoder.resolve_query_set(query_set, queries, &resolve, 0);
+        encoder.copy_buffer_to_buffer(&resolve, 0, &staging, 0, size);
+        device.2.submit(Some(encoder.finish()));
+
+        let future = staging.slice(..).map_async(crate::wgpu::MapMode::Read);
+        device.1.poll(crate::wgpu::Maintain::Wait);
+        if let Err(err) = crate::utils::block_on(&self.tokio, future) {
+            log::error!(target: "EntityManager","Failed to read the results of {}: {:?}",id,err);
+            return Err(());
+        }
+        let results = staging
+            .slice(..)
+            .get_mapped_range()
+            .chunks_exact(std::mem::size_of::<u64>())
+            .map(bytemuck::pod_read_unaligned)
+            .collect();
+        staging.unmap();
+        Ok(results)
+    }
+
+    /**
+    Synchronously read the GPU time elapsed between the timestamps written in the queries 0 and 1 of a query set,
+    like the ones of a command buffer [with timing][CommandBufferDescriptor::with_timing].
+    */
+    pub fn read_elapsed_time(&self, id: &QuerySetId) -> Result<std::time::Duration, ()> {
+        let descriptor = match self.query_set_descriptor_ref(id) {
+            Some(descriptor) => descriptor,
+            None => {
+                log::error!(target: "EntityManager","Failed to read the elapsed time of {}: query set not found",id);
+                return Err(());
+            }
+        };
+        if !matches!(descriptor.ty, crate::wgpu::QueryType::Timestamp) {
+            log::error!(target: "EntityManager","Failed to read the elapsed time of {}: not a timestamp query set",id);
+            return Err(());
+        }
+        let period = match self.device_handle_ref(&descriptor.device) {
+            Some(device) => device.2.get_timestamp_period(),
+            None => {
+                log::error!(target: "EntityManager","Failed to read the elapsed time of {}: parent Device {} not found",id,descriptor.device);
+                return Err(());
+            }
+        };
+
+        let timestamps = self.read_query_results(id, 0..2)?;
+        let ticks = timestamps[1].saturating_sub(timestamps[0]);
+        Ok(std::time::Duration::from_nanos(
+            (ticks as f64 * period as f64) as u64,
+        ))
+    }
+
     fn add_inner(&mut self, descriptor: &ResourceDescriptor, id: EntityId) -> ResourceId {
         match descriptor {
             ResourceDescriptor::Instance(_) => {
@@ -565,6 +2790,11 @@ impl ResourceManager {
                 self.shader_modules.insert(id);
                 id.into()
             }
+            ResourceDescriptor::QuerySet(_) => {
+                let id = QuerySetId::new(id);
+                self.query_sets.insert(id);
+                id.into()
+            }
 
             ResourceDescriptor::BindGroupLayout(_) => {
                 let id = BindGroupLayoutId::new(id);
@@ -592,6 +2822,11 @@ impl ResourceManager {
                 self.compute_pipelines.insert(id);
                 id.into()
             }
+            ResourceDescriptor::RenderBundle(_) => {
+                let id = RenderBundleId::new(id);
+                self.render_bundles.insert(id);
+                id.into()
+            }
             ResourceDescriptor::CommandBuffer(_) => {
                 let id = CommandBufferId::new(id);
                 self.command_buffers.insert(id);
@@ -600,6 +2835,48 @@ impl ResourceManager {
         }
     }
 
+    fn typed_id(descriptor: &ResourceDescriptor, id: EntityId) -> ResourceId {
+        match descriptor {
+            ResourceDescriptor::Instance(_) => InstanceId::new(id).into(),
+            ResourceDescriptor::Device(_) => DeviceId::new(id).into(),
+            ResourceDescriptor::Swapchain(_) => SwapchainId::new(id).into(),
+            ResourceDescriptor::Buffer(_) => BufferId::new(id).into(),
+            ResourceDescriptor::Texture(_) => TextureId::new(id).into(),
+            ResourceDescriptor::TextureView(_) => TextureViewId::new(id).into(),
+            ResourceDescriptor::Sampler(_) => SamplerId::new(id).into(),
+            ResourceDescriptor::ShaderModule(_) => ShaderModuleId::new(id).into(),
+            ResourceDescriptor::QuerySet(_) => QuerySetId::new(id).into(),
+            ResourceDescriptor::BindGroupLayout(_) => BindGroupLayoutId::new(id).into(),
+            ResourceDescriptor::BindGroup(_) => BindGroupId::new(id).into(),
+            ResourceDescriptor::PipelineLayout(_) => PipelineLayoutId::new(id).into(),
+            ResourceDescriptor::RenderPipeline(_) => RenderPipelineId::new(id).into(),
+            ResourceDescriptor::ComputePipeline(_) => ComputePipelineId::new(id).into(),
+            ResourceDescriptor::RenderBundle(_) => RenderBundleId::new(id).into(),
+            ResourceDescriptor::CommandBuffer(_) => CommandBufferId::new(id).into(),
+        }
+    }
+
+    /**
+    Find the resources without owners that are still part of the graph. Releasing the last owner removes the resource,
+    so any orphan is a leak caused by an ownership bug. Checked after every commit in debug builds.
+    */
+    pub fn find_orphans(&self) -> Vec<ResourceId> {
+        self.inner
+            .entities()
+            .filter(|id| {
+                self.inner
+                    .entity_owners(id)
+                    .map(|owners| owners.is_empty())
+                    .unwrap_or(false)
+            })
+            .filter_map(|id| {
+                self.inner
+                    .entity_descriptor_ref(&id)
+                    .map(|descriptor| Self::typed_id(descriptor, id))
+            })
+            .collect()
+    }
+
     fn remove_inner(&mut self, id: &ResourceId) {
         match id {
             ResourceId::Instance(id) => {
@@ -607,6 +2884,7 @@ impl ResourceManager {
             }
             ResourceId::Device(id) => {
                 self.devices.remove(&id);
+                self.device_regressions.remove(id.id_ref());
             }
             ResourceId::Swapchain(id) => {
                 self.swapchains.remove(&id);
@@ -620,6 +2898,8 @@ impl ResourceManager {
             }
             ResourceId::TextureView(id) => {
                 self.texture_views.remove(&id);
+                self.depth_targets.retain(|_, view| view != id);
+                self.detached_depth_targets.retain(|_, view| view != id);
             }
             ResourceId::Sampler(id) => {
                 self.samplers.remove(&id);
@@ -627,6 +2907,9 @@ impl ResourceManager {
             ResourceId::ShaderModule(id) => {
                 self.shader_modules.remove(&id);
             }
+            ResourceId::QuerySet(id) => {
+                self.query_sets.remove(&id);
+            }
 
             ResourceId::BindGroupLayout(id) => {
                 self.bind_group_layouts.remove(&id);
@@ -644,6 +2927,9 @@ impl ResourceManager {
             ResourceId::ComputePipeline(id) => {
                 self.compute_pipelines.remove(&id);
             }
+            ResourceId::RenderBundle(id) => {
+                self.render_bundles.remove(&id);
+            }
             ResourceId::CommandBuffer(id) => {
                 self.command_buffers.remove(&id);
             }
@@ -658,21 +2944,29 @@ impl ResourceManager {
     make_resource_functions!(TextureView);
     make_resource_functions!(Sampler);
     make_resource_functions!(ShaderModule);
+    make_resource_functions!(QuerySet);
     make_resource_functions!(BindGroupLayout);
     make_resource_functions!(BindGroup);
     make_resource_functions!(PipelineLayout);
     make_resource_functions!(RenderPipeline);
     make_resource_functions!(ComputePipeline);
+    make_resource_functions!(RenderBundle);
     make_resource_functions!(CommandBuffer);
 
     /**
     Commit the update of the pending resources.
     */
     pub(crate) fn commit_resources(&mut self) -> bool {
-        log::info!(target: "Engine","Committing resources updates");
-        self.print_graphviz();
+        if self.resource_log_enabled(log::Level::Info) {
+            log::info!(target: "Engine","Committing resources updates");
+            self.print_graphviz();
+        }
 
         let mut entity_path = Vec::new();
+        self.commit_count += 1;
+        self.resubmitted.clear();
+        self.failed_builds.clear();
+        self.build_errors.clear();
 
         let mut visitor = Topo::new(self.graph());
         while let Some(nx) = visitor.next(self.graph()) {
@@ -688,10 +2982,17 @@ impl ResourceManager {
         }
 
         #[cfg(multithreading)]
-        return self.commit_resources_mt(entity_path);
+        let committed = self.commit_resources_mt(entity_path);
 
         #[cfg(not(multithreading))]
-        return self.commit_resources_st(entity_path);
+        let committed = self.commit_resources_st(entity_path);
+
+        debug_assert!(
+            self.find_orphans().is_empty(),
+            "Resources without owners found after the commit"
+        );
+        self.notify_ready();
+        committed
     }
 
     #[cfg(multithreading)]
@@ -706,6 +3007,7 @@ impl ResourceManager {
         use tokio::sync::RwLock;
 
         let mut syncs = HashMap::new();
+        let log_level = self.log_level;
         tokio_scoped::scoped(&self.tokio.clone()).scope(|scope|{
             let resource_manager = Arc::new(RwLock::new(self));
 
@@ -730,37 +3032,34 @@ impl ResourceManager {
                         }
                     }
                     /*Execute task start*/
-                    log::info!(target: "EntityManager","Updating {}",entity);
+                    resource_log!(log_level, log::Level::Info, target: "EntityManager", "Updating {}",entity);
                     let builder = {
                         let resource_manager = resource_manager.read().await;
 
                         match resource_manager.entity_descriptor_ref(&entity) {
-                            Some(descriptor)=>{
-                                match ResourceBuilder::new(&resource_manager,entity,descriptor){
-                                    Ok(builder)=>Some(builder),
-                                    Err(_)=>None
-                                }
-                            },
-                            _=>None
+                            Some(descriptor)=>ResourceBuilder::new(&resource_manager,entity,descriptor).map_err(Some),
+                            _=>Err(None)
                         }
                     };
 
-                    if let Some(builder) = builder {
-                        let entity_handle = builder.build();
+                    match builder {
+                        Ok(builder)=>{
+                            let entity_handle = builder.build(log_level);
 
-                        {
-                            let mut resource_manager = resource_manager.write().await;
-                            resource_manager.update_resource_handle(&entity,entity_handle);
-                            log::info!(target: "EntityManager","{} updated",entity);
-                        }
+                            {
+                                let mut resource_manager = resource_manager.write().await;
+                                resource_manager.update_resource_handle(&entity,entity_handle);
+                                resource_log!(log_level, log::Level::Info, target: "EntityManager", "{} updated",entity);
+                            }
 
-                        /*Execute task stop*/
-                        sender.send(true).unwrap();
-                    }
-                    else{
-                        /*Execute task stop*/
-                        log::error!(target: "EntityManager","{} failed to update",entity);
-                        sender.send(false).unwrap();
+                            /*Execute task stop*/
+                            sender.send(true).unwrap();
+                        }
+                        Err(err)=>{
+                            /*Execute task stop*/
+                            resource_manager.write().await.record_build_failure(entity, err);
+                            sender.send(false).unwrap();
+                        }
                     }
                 });
             }
@@ -778,36 +3077,108 @@ impl ResourceManager {
         &mut self,
         entity_path: impl IntoIterator<Item = (EntityId, Vec<EntityId>)>,
     ) -> bool {
+        let mut success = true;
+        let log_level = self.log_level;
         for (entity, _dependencies) in entity_path {
             /*Execute task start*/
-            log::info!(target: "EntityManager","Updating {}",entity);
+            resource_log!(self.log_level, log::Level::Info, target: "EntityManager", "Updating {}",entity);
             let builder = {
                 match self.entity_descriptor_ref(&entity) {
-                    Some(descriptor) => match ResourceBuilder::new(&self, entity, descriptor) {
-                        Ok(builder) => Some(builder),
-                        Err(_) => None,
-                    },
-                    _ => None,
+                    Some(descriptor) => {
+                        ResourceBuilder::new(&self, entity, descriptor).map_err(Some)
+                    }
+                    _ => Err(None),
                 }
             };
 
-            if let Some(builder) = builder {
-                let entity_handle = builder.build();
+            // On failure the entity is left damaged, so it will be retried on the next commit.
+            match builder {
+                Ok(builder) => {
+                    let entity_handle = builder.build(log_level);
 
-                {
-                    self.update_resource_handle(&entity, entity_handle);
-                    log::info!(target: "EntityManager","{} updated",entity);
+                    {
+                        self.update_resource_handle(&entity, entity_handle);
+                        resource_log!(self.log_level, log::Level::Info, target: "EntityManager", "{} updated",entity);
+                    }
+
+                    /*Execute task stop*/
                 }
+                Err(err) => {
+                    /*Execute task stop*/
+                    self.record_build_failure(entity, err);
+                    success = false;
+                }
+            }
+        }
 
-                /*Execute task stop*/
-            } else {
-                /*Execute task stop*/
+        success
+    }
+
+    /**
+    Record an entity that failed to build, so that it is reported by [failed_builds][ResourceManager::failed_builds]
+    and [build_errors][ResourceManager::build_errors]. A swapchain without an acquired frame is transient,
+    so it is only logged at debug level and not reported: the entity is left damaged and retried on the next commit anyway.
+    Returns true if the failure has been reported.
+    */
+    pub(crate) fn record_build_failure(
+        &mut self,
+        entity: EntityId,
+        err: Option<ResourceBuilderError>,
+    ) -> bool {
+        match err {
+            Some(ResourceBuilderError::SwapchainFrameNotAcquired(swapchain)) => {
+                resource_log!(self.log_level, log::Level::Debug, target: "EntityManager", "{} not updated: waiting for a frame of {}",entity,swapchain);
+                false
+            }
+            err => {
                 log::error!(target: "EntityManager","{} failed to update",entity);
+                self.failed_builds.push(entity);
+                if let Some(err) = err {
+                    log::error!(target: "EntityManager","{} build error: {}",entity,err);
+                    self.build_errors.push((entity, err));
+                }
+                true
             }
         }
+    }
+}
 
-        true
+fn require_buffer(
+    required: &mut Vec<RequiredUsage>,
+    id: BufferId,
+    usage: crate::wgpu::BufferUsage,
+) {
+    if usage.is_empty() {
+        return;
+    }
+    for required in required.iter_mut() {
+        if let RequiredUsage::Buffer(required_id, required_usage) = required {
+            if *required_id == id {
+                *required_usage |= usage;
+                return;
+            }
+        }
+    }
+    required.push(RequiredUsage::Buffer(id, usage));
+}
+
+fn require_texture(
+    required: &mut Vec<RequiredUsage>,
+    id: TextureId,
+    usage: crate::wgpu::TextureUsage,
+) {
+    if usage.is_empty() {
+        return;
+    }
+    for required in required.iter_mut() {
+        if let RequiredUsage::Texture(required_id, required_usage) = required {
+            if *required_id == id {
+                *required_usage |= usage;
+                return;
+            }
+        }
     }
+    required.push(RequiredUsage::Texture(id, usage));
 }
 
 impl std::ops::Deref for ResourceManager {
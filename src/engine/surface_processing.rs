@@ -1,9 +1,14 @@
-use super::WGpuEngine;
+use super::{WGpuEngine, WGpuEngineError};
 
 use crate::engine::engine_task::EngineTask;
 use std::sync::Arc;
 
 impl WGpuEngine {
+    /**
+    Create a swapchain for the passed surface, with the clear color, frame latency and present mode of `options`.
+    It fails if the surface cannot be presented by the engine device, like when the surface
+    belongs to an incompatible display.
+    */
     pub fn create_surface(
         &mut self,
         external_id: usize,
@@ -11,13 +16,54 @@ impl WGpuEngine {
         surface: Arc<crate::wgpu::Surface>,
         width: u32,
         height: u32,
-    ) {
+        options: crate::SurfaceOptions,
+    ) -> Result<(), WGpuEngineError> {
+        let device = self
+            .task_manager
+            .task_handle_cast_ref(&self.engine_task, |engine_task: &EngineTask| {
+                engine_task.devices().first().copied()
+            })
+            .unwrap();
+        let presentable = device
+            .and_then(|device| self.resource_manager.device_handle_ref(&device))
+            .map(|device| !crate::supported_swapchain_formats(&device.0, &surface).is_empty())
+            .unwrap_or(false);
+        if !presentable {
+            log::error!(target: "Engine","Failed to create surface {}: no device can present on it",external_id);
+            return Err(WGpuEngineError::SurfaceCreationFailed(external_id));
+        }
+
         assert!(self
             .task_manager
             .task_handle_cast_mut(&self.engine_task, |engine_task: &mut EngineTask| {
-                engine_task.create_swapchain(external_id, label, surface, width, height);
+                engine_task.create_swapchain(external_id, label, surface, width, height, options);
             },)
             .is_some());
+        Ok(())
+    }
+
+    /**
+    Set the format and composite alpha mode of the surfaces created from now on.
+    A `None` format uses the surface preferred one, while an unsupported format
+    (like an HDR one on a SDR display) falls back to it.
+    It fails if the composite alpha mode is not supported, leaving the output unchanged.
+    */
+    pub fn set_surface_output(
+        &mut self,
+        format: Option<crate::wgpu::TextureFormat>,
+        composite_alpha_mode: crate::CompositeAlphaMode,
+    ) -> Result<(), WGpuEngineError> {
+        if !composite_alpha_mode.is_supported() {
+            log::error!(target: "Engine","Failed to set the surface output: {:?} composite alpha mode not supported",composite_alpha_mode);
+            return Err(WGpuEngineError::UnsupportedCompositeAlphaMode(
+                composite_alpha_mode,
+            ));
+        }
+        self.task_manager
+            .task_handle_cast_mut(&self.engine_task, |engine_task: &mut EngineTask| {
+                engine_task.set_swapchain_output(format, composite_alpha_mode);
+            })
+            .ok_or(WGpuEngineError::InitializationFailed)
     }
 
     pub fn resize_surface(&mut self, external_id: usize, width: u32, height: u32) {
@@ -29,6 +75,49 @@ impl WGpuEngine {
             .is_some())
     }
 
+    /**
+    Turn the vsync of a surface on or off, switching its present mode at the end of the current frame.
+    Modes not supported by the backend fall back to `Fifo` with a warning.
+    */
+    pub fn set_vsync(&mut self, external_id: usize, vsync: bool) {
+        assert!(self
+            .task_manager
+            .task_handle_cast_mut(&self.engine_task, |engine_task: &mut EngineTask| {
+                engine_task.set_vsync(external_id, vsync);
+            },)
+            .is_some())
+    }
+
+    /// Present mode of the swapchain of a surface, if created.
+    pub fn surface_present_mode(&self, external_id: usize) -> Option<crate::wgpu::PresentMode> {
+        let resource_manager = &self.resource_manager;
+        self.task_manager
+            .task_handle_cast_ref(&self.engine_task, |engine_task: &EngineTask| {
+                engine_task
+                    .swapchain(external_id)
+                    .and_then(|id| resource_manager.swapchain_descriptor_ref(&id))
+                    .map(|descriptor| descriptor.present_mode)
+            })
+            .flatten()
+    }
+
+    /**
+    Format chosen for the swapchain of a surface, if created. It is the same reported by the
+    [SwapchainCreated][crate::ResourceEvent::SwapchainCreated] event, and the one the pipelines
+    rendering on the surface must target.
+    */
+    pub fn swapchain_format(&self, external_id: usize) -> Option<crate::wgpu::TextureFormat> {
+        let resource_manager = &self.resource_manager;
+        self.task_manager
+            .task_handle_cast_ref(&self.engine_task, |engine_task: &EngineTask| {
+                engine_task
+                    .swapchain(external_id)
+                    .and_then(|id| resource_manager.swapchain_descriptor_ref(&id))
+                    .map(|descriptor| descriptor.format)
+            })
+            .flatten()
+    }
+
     pub fn destroy_surface(&mut self, external_id: usize) {
         assert!(self
             .task_manager
@@ -38,6 +127,18 @@ impl WGpuEngine {
             .is_some());
     }
 
+    /**
+    Check if every surface passed to [create_surface][WGpuEngine::create_surface] has a built swapchain.
+    */
+    pub fn surfaces_ready(&self) -> bool {
+        let resource_manager = &self.resource_manager;
+        self.task_manager
+            .task_handle_cast_ref(&self.engine_task, |engine_task: &EngineTask| {
+                engine_task.surfaces_ready(|id| resource_manager.swapchain_handle_ref(id).is_some())
+            })
+            .unwrap()
+    }
+
     pub fn surface_count(&self) -> usize {
         self.task_manager
             .task_handle_cast_ref(&self.engine_task, |engine_task: &EngineTask| {
@@ -2,6 +2,7 @@
 
 use crate::common::*;
 use crate::engine::batch::Batch;
+use crate::engine::resource_manager::ResourceManager;
 
 use crate::EntityManager;
 use crate::Task;
@@ -26,6 +27,20 @@ impl TaskManager {
         }
     }
 
+    /**
+    Remove a task from the manager. Its resources are not released.
+    */
+    pub(crate) fn remove_task(&mut self, id: &TaskId) -> Result<(), ()> {
+        self.0.remove_entity(id.id_ref())
+    }
+
+    /**
+    List the tasks of the manager.
+    */
+    pub(crate) fn tasks(&self) -> Vec<TaskId> {
+        self.0.entities().map(TaskId::new).collect()
+    }
+
     /**
     Update the handle of a task.
     */
@@ -115,21 +130,62 @@ impl TaskManager {
         let mut visitor = Topo::new(self.0.graph());
         while let Some(nx) = visitor.next(self.0.graph()) {
             let id: TaskId = TaskId::new(nx.into());
+            let descriptor = match self.task_descriptor_ref(&id) {
+                Some(descriptor) => descriptor.clone(),
+                None => continue,
+            };
             self.task_handle_mut(&id, |task| {
                 //task.update();
 
                 log::info!(target: "Engine","Updating task resources {}",id);
+                // Only the events of the swapchains on the devices of the task are routed to it.
+                let mut task_events: Vec<_> = {
+                    let resource_manager = batch.resource_manager_mut();
+                    events
+                        .iter()
+                        .filter(|event| is_routed(resource_manager, &descriptor, event))
+                        .copied()
+                        .collect()
+                };
+                let routed = task_events.len();
                 let mut update_context =
-                    UpdateContext::new(id, batch.resource_manager_mut(), &mut events);
+                    UpdateContext::new(id, batch.resource_manager_mut(), &mut task_events);
                 task.update_resources(&mut update_context);
 
                 let resource_writes = update_context.into_resource_writes();
                 batch.add_resource_writes(resource_writes);
+                events.extend(task_events.drain(routed..));
 
-                task.command_buffers().into_iter().for_each(|id| {
-                    batch.add_command_buffer(id);
+                task.command_buffers().into_iter().for_each(|command_buffer| {
+                    let device = batch
+                        .resource_manager_mut()
+                        .command_buffer_descriptor_ref(&command_buffer)
+                        .map(|command_buffer| command_buffer.device);
+                    match device {
+                        Some(device) if !descriptor.uses_device(&device) => {
+                            log::warn!(target: "Engine","Skipping {} of {}: Device {} is not one of the task devices",command_buffer,descriptor,device);
+                        }
+                        _ => {
+                            batch.add_command_buffer(command_buffer);
+                        }
+                    }
                 });
             });
         }
     }
 }
+
+/// Check if an event has to be routed to the task, that is if it does not refer to a swapchain on another device.
+fn is_routed(
+    resource_manager: &ResourceManager,
+    descriptor: &TaskDescriptor,
+    event: &ResourceEvent,
+) -> bool {
+    match event
+        .swapchain()
+        .and_then(|swapchain| resource_manager.swapchain_descriptor_ref(&swapchain))
+    {
+        Some(swapchain) => descriptor.uses_device(&swapchain.device),
+        None => true,
+    }
+}
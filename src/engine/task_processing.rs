@@ -1,8 +1,10 @@
 use crate::{
     common::*,
     engine::batch::Batch,
+    engine::engine_task::EngineTask,
     engine::resource_manager::ResourceManager,
     engine::task_manager::TaskManager,
+    engine::WGpuEngineError,
     entity_manager::UpdateContext,
     tasks::{TaskDescriptor, TaskTrait},
 };
@@ -19,13 +21,30 @@ impl super::WGpuEngine {
         name: String,
         features_and_limits: (crate::wgpu::Features, crate::wgpu::Limits),
         callback: C,
+    ) -> Option<TaskId> {
+        self.create_task_on_devices(name, features_and_limits, Vec::new(), callback)
+    }
+
+    /**
+    Create a task in the TaskManager, restricted to the passed devices.
+    The task can only create resources on those devices, receives only the events of their swapchains
+    and only its command buffers on those devices are submitted. An empty list allows every device.
+    */
+    pub fn create_task_on_devices<
+        T: 'static + TaskTrait,
+        C: Fn(TaskId, &tokio::runtime::Handle, &mut UpdateContext) -> T,
+    >(
+        &mut self,
+        name: String,
+        features_and_limits: (crate::wgpu::Features, crate::wgpu::Limits),
+        devices: Vec<DeviceId>,
+        callback: C,
     ) -> Option<TaskId> {
         create_task(
             &mut self.task_manager,
             &mut self.resource_manager,
-            self.runtime.handle(),
-            name,
-            vec![self.engine_task],
+            &self.tokio,
+            TaskDescriptor::new(name, vec![self.engine_task], devices),
             features_and_limits,
             callback,
         )
@@ -42,19 +61,138 @@ impl super::WGpuEngine {
         self.task_manager.task_handle_cast_mut(id, callback)
     }
 
+    /**
+    Build and submit a command buffer immediately, outside of the task dispatching, and wait for its completion.
+    Pending resource updates are committed first, so the resources used by the command buffer are up to date.
+    The command buffer is freed once completed.
+    */
+    pub fn submit_once(
+        &mut self,
+        descriptor: CommandBufferDescriptor,
+    ) -> Result<(), WGpuEngineError> {
+        let device_id = descriptor.device;
+        let id = self
+            .resource_manager
+            .add_command_buffer(self.engine_task, descriptor, None)
+            .map_err(|_| WGpuEngineError::SubmissionFailed)?;
+        self.resource_manager.commit_resources();
+        if self.resource_manager.is_strict() && !self.resource_manager.failed_builds().is_empty() {
+            let failed_builds = self.resource_manager.failed_builds().to_vec();
+            let _ = self
+                .resource_manager
+                .remove_command_buffer(&self.engine_task, &id);
+            return Err(WGpuEngineError::BuildFailed(failed_builds));
+        }
+
+        let command_buffer = self.resource_manager.take_command_buffer(&id);
+        let result = match (
+            command_buffer,
+            self.resource_manager.device_handle_ref(&device_id),
+        ) {
+            (Some(command_buffer), Some(device)) => {
+                device.2.submit(std::iter::once(command_buffer));
+                device.1.poll(crate::wgpu::Maintain::Wait);
+                Ok(())
+            }
+            _ => {
+                log::error!(target: "Engine","Failed to submit {}: it failed to build",id);
+                Err(WGpuEngineError::SubmissionFailed)
+            }
+        };
+
+        let _ = self
+            .resource_manager
+            .remove_command_buffer(&self.engine_task, &id);
+        result
+    }
+
     /**
     Dispatch all the tasks and elaborate all the pending operations.
+    While [paused][super::WGpuEngine::set_paused] only the resource writes are submitted.
+    In [strict mode][super::WGpuEngine::set_strict] it panics if a resource fails to build,
+    see [try_dispatch_tasks][super::WGpuEngine::try_dispatch_tasks].
     */
     pub fn dispatch_tasks(&mut self) {
+        if let Err(err) = self.try_dispatch_tasks() {
+            panic!(
+                "Strict mode: dispatch failed with {:?}, see the EntityManager log for the causes",
+                err
+            );
+        }
+    }
+
+    /**
+    Dispatch all the tasks like [dispatch_tasks][super::WGpuEngine::dispatch_tasks].
+    In [strict mode][super::WGpuEngine::set_strict], if any resource fails to build the dispatch stops
+    before submitting anything and the failed resources are returned. It never fails otherwise.
+    If no resource is damaged after updating the tasks, like for a static scene, the commit is skipped.
+    With [set_reuse_unchanged_frames][super::WGpuEngine::set_reuse_unchanged_frames], a frame submitting the same work
+    as the last one is skipped entirely, and the swapchains keep showing the last presented frame.
+    */
+    pub fn try_dispatch_tasks(&mut self) -> Result<(), WGpuEngineError> {
+        if self.paused && !self.update_while_paused {
+            log::info!(target: "Engine","Paused, skipping dispatch");
+            return Ok(());
+        }
         log::info!(target: "Engine","Dispatching tasks");
 
         let mut batch = Batch::new(&mut self.resource_manager);
+        let deferred_writes = batch.resource_manager_mut().take_deferred_writes();
+        batch.add_resource_writes(deferred_writes);
         self.task_manager.commit_tasks(&mut batch);
 
-        batch.resource_manager_mut().commit_resources();
-        batch.submit();
+        let command_buffers = batch.command_buffers();
+        let has_resource_writes = batch.has_resource_writes();
+        let resource_manager = batch.resource_manager_mut();
+        let task_presents = resource_manager.take_task_presents();
+        // The submitted command buffers are damaged only because they have been consumed, so encoding them again would change nothing.
+        let reused = self.reuse_unchanged_frames
+            && !self.paused
+            && !task_presents
+            && !resource_manager.has_changes()
+            && !has_resource_writes
+            && command_buffers == self.last_command_buffers;
+        let mut failed_builds = None;
+        if reused {
+            log::info!(target: "Engine","Nothing changed since the last frame, reusing it");
+            self.reused_frames += 1;
+        } else if resource_manager.has_damage() {
+            resource_manager.commit_resources();
+            if resource_manager.is_strict() && !resource_manager.failed_builds().is_empty() {
+                log::error!(target: "Engine","Strict mode: {} resources failed to build, dispatch aborted",resource_manager.failed_builds().len());
+                failed_builds = Some(resource_manager.failed_builds().to_vec());
+            }
+        } else {
+            log::info!(target: "Engine","No damaged resources, skipping commit");
+        }
+        // A reused frame has nothing to submit: the batch has no writes and its command buffers have already been consumed.
+        // An aborted dispatch still uploads the writes of the tasks and ends the frame, only the command buffers are dropped.
+        if !reused {
+            if self.paused || failed_builds.is_some() {
+                batch.submit_writes();
+            } else {
+                self.last_command_buffers = command_buffers;
+                batch.submit();
+            }
+        }
+        self.resource_manager.free_transients();
+
+        let engine_task = self.engine_task;
+        let resource_manager = &mut self.resource_manager;
+        let mut events = Vec::new();
+        self.task_manager
+            .task_handle_cast_mut(&engine_task, |task: &mut EngineTask| {
+                let mut update_context =
+                    UpdateContext::new(engine_task, resource_manager, &mut events);
+                task.end_frame(&mut update_context, reused);
+            });
+        self.resource_manager.advance_frame();
 
+        if let Some(failed_builds) = failed_builds {
+            return Err(WGpuEngineError::BuildFailed(failed_builds));
+        }
         log::info!(target: "Engine","Dispatch completed\n");
+        Ok(())
     }
 }
 
@@ -65,18 +203,20 @@ pub(crate) fn create_task<
     task_manager: &mut TaskManager,
     resource_manager: &mut ResourceManager,
     tokio: &tokio::runtime::Handle,
-    name: String,
-    dependencies: Vec<TaskId>,
+    descriptor: TaskDescriptor,
     _features_and_limits: impl Into<(crate::wgpu::Features, crate::wgpu::Limits)>,
     callback: C,
 ) -> Option<TaskId> {
-    let descriptor = TaskDescriptor::new(name, dependencies);
+    let devices = descriptor.devices.clone();
 
     match task_manager.add_task((descriptor, None)) {
         Ok(id) => {
+            resource_manager.set_task_devices(id, devices);
             let mut events = Vec::new();
             let mut update_context = UpdateContext::new(id, resource_manager, &mut events);
             let handle: TaskHandle = Box::new(callback(id, tokio, &mut update_context));
+            let resource_writes = update_context.into_resource_writes();
+            resource_manager.defer_writes(resource_writes);
 
             task_manager.update_task_handle(&id, handle);
             Some(id)
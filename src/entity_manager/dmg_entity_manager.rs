@@ -92,6 +92,9 @@ impl<D: HaveDescriptor + HaveDescriptor<D = D>, H, N: HaveDescriptorAndHandle<D
     pub(crate) fn is_damaged(&self, id: &EntityId) -> bool {
         self.1.contains(id)
     }
+    pub(crate) fn damaged_entities(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.1.iter().copied()
+    }
 
     #[inline]
     pub(crate) fn add_dependency(&mut self, entity1: &EntityId, entity2: &EntityId) {
@@ -99,7 +102,10 @@ impl<D: HaveDescriptor + HaveDescriptor<D = D>, H, N: HaveDescriptorAndHandle<D
     }
     #[inline]
     pub(crate) fn remove_entity(&mut self, id: &EntityId) -> Result<(), ()> {
-        self.0.remove_entity(id)
+        self.0.remove_entity(id).map(|v| {
+            self.1.remove(id);
+            v
+        })
     }
 
     #[inline]
@@ -126,7 +132,7 @@ impl<
 }
 
 impl<O: PartialEq, N: HaveDescriptorAndHandle + HaveOwners<O = O>> DMGEntityManager<N> {
-    pub fn entity_owners(&mut self, id: &EntityId) -> Option<Vec<O>> {
+    pub fn entity_owners(&self, id: &EntityId) -> Option<Vec<O>> {
         self.0.entity(id).map(|entity| entity.owners())
     }
     pub fn add_entity_owner(&mut self, id: &EntityId, new_owner: O) {
@@ -102,6 +102,13 @@ impl<N: HaveDependencies> EntityManager<N> {
             .map(|index| EntityId::new(index.index()))
             .collect()
     }
+    /// Get the children of an entity, that is the entities depending on it.
+    pub(crate) fn entity_children(&self, id: &EntityId) -> Vec<EntityId> {
+        self.graph()
+            .neighbors_directed((*id).into(), Direction::Outgoing)
+            .map(|index| EntityId::new(index.index()))
+            .collect()
+    }
     /// Add an entity to the graph.
     pub(crate) fn add_entity(
         &mut self,
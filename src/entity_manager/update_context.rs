@@ -1,7 +1,8 @@
 //! [UpdateContext][UpdateContext] related structures, enumerations and macros.
 
 use crate::common::*;
-use crate::engine::resource_manager::ResourceManager;
+use crate::engine::resource_manager::{BatchItem, ResourceManager};
+use std::convert::TryInto;
 
 macro_rules! make_update_context_functions {
     ($($name: ident),*) => {
@@ -86,13 +87,405 @@ impl<'a> UpdateContext<'a> {
         }
     }
 
+    /**
+    Number of the current frame, starting from 0 and incremented by every dispatch of the tasks.
+    It is the same for every task and every call within a dispatch, so it can drive animations
+    or temporal effects without each task keeping its own counter.
+    */
+    pub fn frame_number(&self) -> u64 {
+        self.resource_manager.frame_number()
+    }
+    /**
+    Index of the current frame in a ring of `ring_size` per-frame resources, like uniform buffers
+    that are written while the previous frames are still in flight.
+    */
+    pub fn ring_index(&self, ring_size: usize) -> usize {
+        (self.frame_number() % ring_size.max(1) as u64) as usize
+    }
+
+    /**
+    Errors of the resources of this task that failed to build during the last commit, in build order,
+    so that the task can react (like falling back on another resource).
+    */
+    pub fn last_build_errors(&self) -> Vec<(EntityId, ResourceBuilderError)> {
+        self.resource_manager
+            .build_errors()
+            .iter()
+            .filter(|(id, _err)| {
+                self.resource_manager
+                    .entity_owners(id)
+                    .map(|owners| owners.contains(&self.task))
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect()
+    }
+
     pub fn is_damaged(&self, id: &EntityId) -> bool {
         self.resource_manager.is_damaged(id)
     }
+    /**
+    Get the entities that will be rebuilt on the next commit, sorted by id.
+    Updating a descriptor damages only the resources that transitively depend on it.
+    */
+    pub fn damaged_entities(&self) -> Vec<EntityId> {
+        self.resource_manager.damaged_entities()
+    }
+
+    /**
+    Get the usage flags that the commands of a command buffer require, but the referenced resources lack.
+    See [ResourceManager::missing_usages][ResourceManager::missing_usages].
+    */
+    pub fn missing_usages(&self, descriptor: &CommandBufferDescriptor) -> Vec<RequiredUsage> {
+        self.resource_manager.missing_usages(descriptor)
+    }
+    /**
+    Add the usage flags that the commands of a command buffer require to the referenced resources.
+    See [ResourceManager::augment_usages][ResourceManager::augment_usages].
+    */
+    pub fn augment_usages(&mut self, descriptor: &CommandBufferDescriptor) -> Vec<RequiredUsage> {
+        self.resource_manager.augment_usages(&self.task, descriptor)
+    }
+
+    /**
+    Force the rebuild of a resource on the next commit, even if its descriptor did not change.
+    Useful when the underlying data changed externally (like an imported texture).
+    */
+    pub fn touch(&mut self, id: impl AsRef<EntityId>) -> bool {
+        self.resource_manager.touch_resource(id)
+    }
+
+    /**
+    Create a resource that is automatically freed once the current frame has been submitted,
+    like a scratch buffer for a single dispatch.
+    */
+    pub fn create_transient(
+        &mut self,
+        descriptor: impl Into<ResourceDescriptor>,
+    ) -> Result<ResourceId, ()> {
+        self.resource_manager.add_transient(self.task, descriptor)
+    }
+
+    /**
+    Create a resource marked with a user defined tag.
+    See [ResourceManager::add_tagged][ResourceManager::add_tagged].
+    */
+    pub fn add_tagged(
+        &mut self,
+        descriptor: impl Into<ResourceDescriptor>,
+        tag: &str,
+    ) -> Result<ResourceId, ()> {
+        self.resource_manager.add_tagged(self.task, descriptor, tag)
+    }
+
+    /**
+    Remove a resource of any kind owned by the task.
+    See [ResourceManager::remove_resource][ResourceManager::remove_resource].
+    */
+    pub fn remove_resource(&mut self, id: &ResourceId) -> Result<(), ()> {
+        self.resource_manager.remove_resource(&self.task, id)
+    }
+
+    /**
+    Create multiple resources at once, removing the already created ones if any fails.
+    See [ResourceManager::add_batch][ResourceManager::add_batch].
+    */
+    pub fn add_batch(
+        &mut self,
+        items: Vec<BatchItem>,
+    ) -> Result<Vec<ResourceId>, (usize, ResourceBuilderError)> {
+        self.resource_manager.add_batch(self.task, items)
+    }
+
+    /**
+    Mark a resource owned by the task with a user defined tag.
+    See [ResourceManager::tag_resource][ResourceManager::tag_resource].
+    */
+    pub fn tag_resource(&mut self, id: impl Into<ResourceId>, tag: &str) -> bool {
+        self.resource_manager.tag_resource(self.task, id, tag)
+    }
+
+    /**
+    Update the instance range of a single draw of a command buffer in place.
+    See [ResourceManager::patch_draw_instances][ResourceManager::patch_draw_instances].
+    */
+    pub fn patch_draw_instances(
+        &mut self,
+        id: &mut CommandBufferId,
+        pass: usize,
+        draw: usize,
+        instances: std::ops::Range<u32>,
+    ) -> bool {
+        self.resource_manager
+            .patch_draw_instances(&self.task, id, pass, draw, instances)
+    }
+
+    /**
+    Get a future resolving once the resource has been built.
+    See [ResourceManager::when_ready][ResourceManager::when_ready].
+    */
+    pub fn when_ready(
+        &self,
+        id: impl AsRef<EntityId>,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        self.resource_manager.when_ready(id)
+    }
+
+    /**
+    Check if a bind group and the resources it binds have been built.
+    See [ResourceManager::bind_group_ready][ResourceManager::bind_group_ready].
+    */
+    pub fn bind_group_ready(&self, id: BindGroupId) -> bool {
+        self.resource_manager.bind_group_ready(&id)
+    }
 
     pub fn entity_device_id(&self, id: impl AsRef<EntityId>) -> Option<DeviceId> {
         self.resource_manager.entity_device_id(id)
     }
+    /// See [ResourceManager::dependencies_of][ResourceManager::dependencies_of].
+    pub fn dependencies_of(&self, id: impl AsRef<EntityId>) -> Vec<EntityId> {
+        self.resource_manager.dependencies_of(id)
+    }
+    /// See [ResourceManager::dependents_of][ResourceManager::dependents_of].
+    pub fn dependents_of(&self, id: impl AsRef<EntityId>) -> Vec<EntityId> {
+        self.resource_manager.dependents_of(id)
+    }
+
+    /**
+    Get the alignment requirements of the passed device.
+    See [ResourceManager::device_alignments][ResourceManager::device_alignments].
+    */
+    pub fn device_alignments(&self, device: DeviceId) -> Option<DeviceAlignments> {
+        self.resource_manager.device_alignments(device)
+    }
+
+    /**
+    Get the raw adapter, device and queue of the passed device id.
+    Objects created from them are not tracked by the dependency graph.
+    ```no_run
+    # fn example(update_context: &wgpu_engine::UpdateContext, device: wgpu_engine::DeviceId) {
+    if let Some((_adapter, device, _queue)) = update_context.raw_device(device) {
+        let _query_set = device.create_query_set(&wgpu_engine::wgpu::QuerySetDescriptor {
+            label: Some("Timestamps"),
+            ty: wgpu_engine::wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+    }
+    # }
+    ```
+    */
+    pub fn raw_device(
+        &self,
+        id: DeviceId,
+    ) -> Option<(
+        &crate::wgpu::Adapter,
+        &crate::wgpu::Device,
+        &crate::wgpu::Queue,
+    )> {
+        self.resource_manager.raw_device(id)
+    }
+
+    /**
+    Record lists of render commands in parallel, running every closure on the tokio pool.
+    The lists are returned in the same order of the closures, so they can be appended
+    to the commands of a render pass. A closure that panics produces an empty list.
+    */
+    pub fn record_commands_parallel<F>(&self, closures: Vec<F>) -> Vec<Vec<RenderCommand>>
+    where
+        F: FnOnce() -> Vec<RenderCommand> + Send + 'static,
+    {
+        let tokio = self.resource_manager.runtime_handle();
+        let handles: Vec<_> = closures
+            .into_iter()
+            .map(|closure| tokio.spawn_blocking(closure))
+            .collect();
+        crate::utils::block_on(tokio, async move {
+            let mut commands = Vec::with_capacity(handles.len());
+            for handle in handles {
+                match handle.await {
+                    Ok(recorded) => commands.push(recorded),
+                    Err(err) => {
+                        log::error!(target: "EntityManager","Failed to record commands: {}",err);
+                        commands.push(Vec::new());
+                    }
+                }
+            }
+            commands
+        })
+    }
+
+    /**
+    Record render bundles in parallel, running every closure on the tokio pool like
+    [record_commands_parallel][UpdateContext::record_commands_parallel]. Every list of commands becomes a
+    [render bundle][RenderBundleDescriptor] for passes with a single `color_format` attachment and no depth,
+    to be executed with [ExecuteBundles][RenderCommand::ExecuteBundles].
+    The bundles are returned in the same order of the closures. If any of them cannot be created,
+    none is kept and the returned list is empty.
+    */
+    pub fn record_bundle_parallel<F>(
+        &mut self,
+        device: DeviceId,
+        color_format: crate::wgpu::TextureFormat,
+        closures: Vec<F>,
+    ) -> Vec<RenderBundleId>
+    where
+        F: FnOnce() -> Vec<RenderCommand> + Send + 'static,
+    {
+        let descriptors = self
+            .record_commands_parallel(closures)
+            .into_iter()
+            .enumerate()
+            .map(|(index, commands)| {
+                RenderBundleDescriptor {
+                    label: format!("Parallel render bundle {}", index),
+                    device,
+                    color_formats: vec![color_format],
+                    depth_stencil_format: None,
+                    sample_count: 1,
+                    commands,
+                }
+                .into()
+            })
+            .collect();
+        match self.add_batch(descriptors) {
+            Ok(ids) => ids
+                .into_iter()
+                .filter_map(|id| id.try_into().ok())
+                .collect(),
+            Err((index, err)) => {
+                log::error!(target: "EntityManager","Failed to add the recorded render bundle {}: {}",index,err);
+                Vec::new()
+            }
+        }
+    }
+
+    /**
+    Get a depth target of the passed size and format, shared with the other tasks requesting an identical one.
+    See [ResourceManager::create_depth_target][ResourceManager::create_depth_target].
+    */
+    pub fn create_depth_target(
+        &mut self,
+        device: DeviceId,
+        width: u32,
+        height: u32,
+        format: crate::wgpu::TextureFormat,
+    ) -> Result<TextureViewId, ()> {
+        self.resource_manager
+            .create_depth_target(self.task, device, width, height, format)
+    }
+
+    /**
+    Keep the size of a texture (like a depth target) in sync with the size of a swapchain.
+    See [ResourceManager::link_to_swapchain_size][ResourceManager::link_to_swapchain_size].
+    */
+    pub fn link_to_swapchain_size(&mut self, texture: TextureId, swapchain: SwapchainId) -> bool {
+        self.resource_manager
+            .link_to_swapchain_size(self.task, texture, swapchain)
+    }
+
+    /**
+    Remove the link set by [link_to_swapchain_size][UpdateContext::link_to_swapchain_size].
+    */
+    pub fn unlink_swapchain_size(&mut self, texture: &TextureId) -> bool {
+        self.resource_manager.unlink_swapchain_size(texture)
+    }
+
+    /**
+    Make this task responsible for presenting the swapchain.
+    See [ResourceManager::claim_swapchain][ResourceManager::claim_swapchain].
+    */
+    pub fn claim_swapchain(&mut self, swapchain: SwapchainId) -> bool {
+        self.resource_manager.claim_swapchain(self.task, swapchain)
+    }
+
+    /**
+    Remove the claim set by [claim_swapchain][UpdateContext::claim_swapchain].
+    */
+    pub fn release_swapchain(&mut self, swapchain: &SwapchainId) -> bool {
+        self.resource_manager
+            .release_swapchain(&self.task, swapchain)
+    }
+
+    /**
+    Present the current frame of a swapchain claimed by this task.
+    The batches rendering on a claimed swapchain leave its frame unpresented, so the task chooses when it reaches the screen,
+    for example skipping a dispatch to keep rendering on the same frame.
+    See [ResourceManager::present_swapchain][ResourceManager::present_swapchain].
+    */
+    pub fn present_swapchain(&mut self, swapchain: &SwapchainId) -> Result<(), ()> {
+        self.resource_manager
+            .present_swapchain(&self.task, swapchain)
+    }
+
+    /// Task responsible for presenting the swapchain, if any.
+    pub fn swapchain_owner(&self, swapchain: &SwapchainId) -> Option<TaskId> {
+        self.resource_manager.swapchain_owner(swapchain)
+    }
+
+    pub(crate) fn drop_swapchain_claim(&mut self, swapchain: &SwapchainId) {
+        self.resource_manager.drop_swapchain_claim(swapchain)
+    }
+
+    pub(crate) fn resize_linked_textures(
+        &mut self,
+        swapchain: &SwapchainId,
+        width: u32,
+        height: u32,
+    ) -> Vec<TextureId> {
+        self.resource_manager
+            .resize_linked_textures(swapchain, width, height)
+    }
+
+    /**
+    Get the depth target the task was moved on when resizing the shared depth target `view`.
+    See [ResourceManager::take_detached_depth_target][ResourceManager::take_detached_depth_target].
+    */
+    pub fn take_detached_depth_target(&mut self, view: &TextureViewId) -> Option<TextureViewId> {
+        self.resource_manager
+            .take_detached_depth_target(&self.task, view)
+    }
+
+    /**
+    Release a depth target obtained from [create_depth_target][UpdateContext::create_depth_target].
+    */
+    pub fn release_depth_target(&mut self, view: &TextureViewId) -> Result<(), ()> {
+        self.resource_manager.release_depth_target(&self.task, view)
+    }
+
+    /**
+    Sample counts supported by a render target of the passed format on the device.
+    See [ResourceManager::supported_sample_counts][ResourceManager::supported_sample_counts].
+    */
+    pub fn supported_sample_counts(
+        &self,
+        device: DeviceId,
+        format: crate::wgpu::TextureFormat,
+    ) -> Vec<u32> {
+        self.resource_manager
+            .supported_sample_counts(device, format)
+    }
+
+    /**
+    Create a multisampled render target, falling back to the highest supported sample count.
+    See [ResourceManager::create_msaa_target][ResourceManager::create_msaa_target].
+    */
+    pub fn create_msaa_target(
+        &mut self,
+        device: DeviceId,
+        width: u32,
+        height: u32,
+        format: crate::wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Result<(TextureViewId, u32), ()> {
+        self.resource_manager.create_msaa_target(
+            self.task,
+            device,
+            width,
+            height,
+            format,
+            sample_count,
+        )
+    }
 
     make_update_context_functions!(
         Instance,
@@ -103,14 +496,77 @@ impl<'a> UpdateContext<'a> {
         TextureView,
         Sampler,
         ShaderModule,
+        QuerySet,
         BindGroupLayout,
         BindGroup,
         PipelineLayout,
         RenderPipeline,
         ComputePipeline,
+        RenderBundle,
         CommandBuffer
     );
 
+    /**
+    Synchronously write data in a buffer by mapping it. The buffer requires the `MAP_WRITE` usage.
+    Unlike [write_resource][UpdateContext::write_resource], the write is applied immediately.
+    */
+    pub fn write_buffer_mapped(
+        &mut self,
+        id: &BufferId,
+        offset: crate::wgpu::BufferAddress,
+        data: &[u8],
+    ) -> Result<(), ()> {
+        self.resource_manager.write_buffer_mapped(id, offset, data)
+    }
+
+    /**
+    Synchronously read data from a buffer by mapping it. The buffer requires the `MAP_READ` usage.
+    */
+    pub fn read_buffer(
+        &self,
+        id: &BufferId,
+        offset: crate::wgpu::BufferAddress,
+        size: crate::wgpu::BufferAddress,
+    ) -> Result<Vec<u8>, ()> {
+        self.resource_manager.read_buffer(id, offset, size)
+    }
+
+    /**
+    Synchronously read the first `count` elements of a buffer as a typed slice.
+    See [ResourceManager::read_buffer_typed][ResourceManager::read_buffer_typed].
+    */
+    pub fn read_buffer_typed<T: bytemuck::Pod>(
+        &self,
+        id: &BufferId,
+        count: usize,
+    ) -> Result<Vec<T>, ()> {
+        self.resource_manager.read_buffer_typed(id, count)
+    }
+
+    /**
+    Synchronously resolve and read the results of some queries of a query set.
+    See [ResourceManager::read_query_results][ResourceManager::read_query_results].
+    */
+    pub fn read_query_results(
+        &self,
+        id: &QuerySetId,
+        queries: std::ops::Range<u32>,
+    ) -> Result<Vec<u64>, ()> {
+        self.resource_manager.read_query_results(id, queries)
+    }
+
+    /**
+    Synchronously read the GPU time elapsed between the first two timestamps of a query set.
+    See [ResourceManager::read_elapsed_time][ResourceManager::read_elapsed_time].
+    */
+    pub fn read_elapsed_time(&self, id: &QuerySetId) -> Result<std::time::Duration, ()> {
+        self.resource_manager.read_elapsed_time(id)
+    }
+
+    /**
+    Queue resource writes. They are applied before the command buffers of this dispatch are submitted,
+    regardless of the order the tasks are dispatched.
+    */
     pub fn write_resource(&mut self, writes: &mut Vec<ResourceWrite>) {
         self.resource_writes.append(writes);
     }
@@ -0,0 +1,110 @@
+use crate::entity_manager::UpdateContext;
+use crate::*;
+
+pub struct BindGroupReadyTask {
+    bind_group: BindGroupId,
+    states: Vec<bool>,
+}
+
+impl BindGroupReadyTask {
+    const TASK_NAME: &'static str = "BindGroupReadyTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let format = crate::wgpu::TextureFormat::Rgba8Unorm;
+
+        let texture = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " texture",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::SAMPLED,
+                size: crate::wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        let view = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                Self::TASK_NAME.to_string() + " view",
+                device,
+                texture,
+                format,
+                0,
+            ))
+            .unwrap();
+        let layout = update_context
+            .add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " bind group layout",
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry::texture(
+                    0,
+                    crate::wgpu::ShaderStage::FRAGMENT,
+                )],
+            })
+            .unwrap();
+        let bind_group = update_context
+            .add_bind_group_descriptor(BindGroupDescriptor {
+                label: Self::TASK_NAME.to_string() + " bind group",
+                device,
+                layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(view),
+                }],
+            })
+            .unwrap();
+
+        Self {
+            bind_group,
+            states: Vec::new(),
+        }
+    }
+}
+
+impl TaskTrait for BindGroupReadyTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        self.states
+            .push(update_context.bind_group_ready(self.bind_group));
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn bind_group_ready_after_commit() {
+    let mut wgpu_engine = WGpuEngine::new((
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    ))
+    .unwrap();
+
+    let task = wgpu_engine
+        .create_task(
+            BindGroupReadyTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| BindGroupReadyTask::new(update_context),
+        )
+        .unwrap();
+    wgpu_engine.dispatch_tasks();
+    wgpu_engine.dispatch_tasks();
+
+    let states = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut BindGroupReadyTask| task.states.clone())
+        .unwrap();
+    // The texture view is only built by the commit of the first dispatch.
+    assert_eq!(states, vec![false, true]);
+}
@@ -0,0 +1,195 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine, texture_readback};
+use crate::*;
+
+const SIZE: u32 = 4;
+const TEXEL: [u8; 4] = [255, 128, 0, 255];
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+fn texture(
+    update_context: &mut UpdateContext,
+    device: DeviceId,
+    label: &str,
+    format: crate::wgpu::TextureFormat,
+    usage: crate::wgpu::TextureUsage,
+) -> (TextureId, TextureViewId) {
+    let texture = update_context
+        .add_texture_descriptor(TextureDescriptor {
+            label: label.to_string(),
+            device,
+            source: TextureSource::Local,
+            usage,
+            size: crate::wgpu::Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: 1,
+            },
+            format,
+            dimension: crate::wgpu::TextureDimension::D2,
+            mip_level_count: 1,
+            sample_count: 1,
+        })
+        .unwrap();
+    let view = update_context
+        .add_texture_view_descriptor(TextureViewDescriptor::layer(
+            label.to_string() + " view",
+            device,
+            texture,
+            format,
+            0,
+        ))
+        .unwrap();
+    (texture, view)
+}
+
+/// Convert a half float to a float, ignoring infinities and NaNs.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+    if exponent == 0 {
+        sign * mantissa * 2f32.powi(-24)
+    } else {
+        sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15)
+    }
+}
+
+pub struct BlitTask {
+    device: DeviceId,
+    src: TextureId,
+    dst: TextureId,
+    blit: Blit,
+    readback: BufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl BlitTask {
+    const TASK_NAME: &'static str = "BlitTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let (src, src_view) = texture(
+            update_context,
+            device,
+            "BlitTask source",
+            crate::wgpu::TextureFormat::Rgba8Unorm,
+            crate::wgpu::TextureUsage::SAMPLED | crate::wgpu::TextureUsage::COPY_DST,
+        );
+        let (dst, dst_view) = texture(
+            update_context,
+            device,
+            "BlitTask destination",
+            crate::wgpu::TextureFormat::Rgba16Float,
+            crate::wgpu::TextureUsage::RENDER_ATTACHMENT | crate::wgpu::TextureUsage::COPY_SRC,
+        );
+        let sampler = update_context
+            .add_sampler_descriptor(SamplerDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                ..Default::default()
+            })
+            .unwrap();
+        let blit = blit(update_context, Self::TASK_NAME, src_view, dst_view, sampler).unwrap();
+
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        Self {
+            device,
+            src,
+            dst,
+            blit,
+            readback,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for BlitTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => {
+                let data: Vec<u8> = (0..SIZE * SIZE).flat_map(|_| TEXEL).collect();
+                update_context.write_resource(&mut vec![ResourceWrite::Texture(TextureWrite {
+                    texture: self.src,
+                    mip_level: 0,
+                    origin: crate::wgpu::Origin3d::ZERO,
+                    data,
+                    layout: crate::wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: std::num::NonZeroU32::new(SIZE * 4),
+                        rows_per_image: std::num::NonZeroU32::new(SIZE),
+                    },
+                    size: crate::wgpu::Extent3d {
+                        width: SIZE,
+                        height: SIZE,
+                        depth_or_array_layers: 1,
+                    },
+                })]);
+            }
+            1 => {
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE) as u64)
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn blit_between_formats() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        BlitTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        BlitTask::new,
+    );
+
+    let (device, dst, blit_command, readback) =
+        run_task(&mut wgpu_engine, &task, 1, |task: &mut BlitTask| {
+            (task.device, task.dst, task.blit.command(), task.readback)
+        });
+    wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("Blit"),
+            device,
+            commands: vec![
+                blit_command,
+                texture_readback(dst, readback, (SIZE, SIZE), BYTES_PER_ROW),
+            ],
+            max_commands: None,
+        })
+        .unwrap();
+
+    let result = run_task(&mut wgpu_engine, &task, 1, |task: &mut BlitTask| {
+        task.result.clone()
+    })
+    .unwrap();
+    // Every texel of the first row, as 4 half floats.
+    for texel in result[..(SIZE * 8) as usize].chunks(8) {
+        let values: Vec<_> = texel
+            .chunks(2)
+            .map(|bits| f16_to_f32(u16::from_le_bytes([bits[0], bits[1]])))
+            .collect();
+        for (value, expected) in values.iter().zip(TEXEL.iter()) {
+            assert!((value - *expected as f32 / 255.0).abs() < 0.01);
+        }
+    }
+}
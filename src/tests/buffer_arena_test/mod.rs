@@ -0,0 +1,64 @@
+use crate::*;
+
+fn arena() -> BufferArena {
+    BufferArena::new(BufferId::new(EntityId::new(0)), 64)
+}
+
+#[test]
+fn alloc_and_free_merge_blocks() {
+    let mut arena = arena();
+    let first = arena.alloc(10).unwrap();
+    let second = arena.alloc(16).unwrap();
+    let third = arena.alloc(16).unwrap();
+    // Sizes are rounded up to COPY_BUFFER_ALIGNMENT.
+    assert_eq!(arena.range(first), Some((0, 12)));
+    assert_eq!(arena.range(second), Some((12, 16)));
+    assert_eq!(arena.range(third), Some((28, 16)));
+    assert_eq!(arena.free_space(), 20);
+
+    assert!(arena.free(second));
+    assert!(!arena.free(second));
+    assert!(arena.free(first));
+    // The two freed allocations were adjacent, so they are merged in a single block.
+    assert_eq!(arena.largest_free_block(), 28);
+    assert_eq!(
+        arena.alloc(28).and_then(|handle| arena.range(handle)),
+        Some((0, 28))
+    );
+
+    assert!(arena.free(third));
+    assert_eq!(arena.alloc(100), None);
+}
+
+#[test]
+fn defragment_fragmented_arena() {
+    let mut arena = arena();
+    let handles: Vec<_> = (0..4).map(|_| arena.alloc(16).unwrap()).collect();
+    for (index, handle) in handles.iter().enumerate() {
+        assert!(arena.write(*handle, &[index as u8; 16]));
+    }
+    arena.take_writes();
+
+    assert!(arena.free(handles[0]));
+    assert!(arena.free(handles[2]));
+    // 32 bytes are free, but split in two blocks.
+    assert_eq!(arena.free_space(), 32);
+    assert_eq!(arena.alloc(32), None);
+
+    assert!(arena.defragment());
+    assert_eq!(arena.range(handles[1]), Some((0, 16)));
+    assert_eq!(arena.range(handles[3]), Some((16, 16)));
+    assert_eq!(arena.largest_free_block(), 32);
+    assert!(!arena.defragment());
+
+    // The moved allocations keep their contents and are uploaded in a single write.
+    let writes = arena.take_writes();
+    assert_eq!(writes.len(), 1);
+    assert_eq!(writes[0].offset, 0);
+    assert_eq!(&writes[0].data[..16], &[1; 16]);
+    assert_eq!(&writes[0].data[16..], &[3; 16]);
+
+    let handle = arena.alloc(32).unwrap();
+    assert_eq!(arena.slice(handle), Some(Slice::from(32..64)));
+    assert!(!arena.write(handle, &[0; 40]));
+}
@@ -0,0 +1,134 @@
+use crate::*;
+
+fn apply(buffer: &mut [u8], writes: &[BufferWrite]) {
+    for write in writes {
+        let offset = write.offset as usize;
+        buffer[offset..offset + write.data.len()].copy_from_slice(&write.data);
+    }
+}
+
+fn writes(buffer: BufferId, shadow: &mut [u8], slots: &[usize]) -> Vec<BufferWrite> {
+    slots
+        .iter()
+        .map(|slot| {
+            let offset = slot * 16;
+            let data = vec![*slot as u8 + 1; 16];
+            shadow[offset..offset + 16].copy_from_slice(&data);
+            BufferWrite {
+                buffer,
+                offset: offset as u64,
+                data,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn coalesced_writes_match_per_element_writes() {
+    let buffer = BufferId::new(EntityId::new(0));
+    let initial: Vec<u8> = (0..256).map(|value| value as u8).collect();
+
+    for (slots, expected_strategy) in [
+        (vec![0, 1, 3, 4, 5], WriteStrategy::SingleSpan),
+        (vec![0, 15], WriteStrategy::PerElement),
+    ] {
+        let mut shadow = initial.clone();
+        let writes = writes(buffer, &mut shadow, &slots);
+
+        let mut per_element = initial.clone();
+        apply(&mut per_element, &writes);
+
+        let (strategy, coalesced) = coalesce_writes(buffer, &shadow, writes);
+        assert_eq!(strategy, expected_strategy);
+        let mut result = initial.clone();
+        apply(&mut result, &coalesced);
+
+        assert_eq!(result, per_element);
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Light {
+    color: [f32; 3],
+    intensity: f32,
+}
+
+#[test]
+fn uniform_slots_are_aligned() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let instance = resource_manager
+        .add_instance(
+            task,
+            InstanceDescriptor {
+                label: String::from("BufferManagerTest instance"),
+                backend: crate::wgpu::BackendBit::empty(),
+            },
+            None,
+        )
+        .unwrap();
+    let device = resource_manager
+        .add_device(
+            task,
+            DeviceDescriptor {
+                label: String::from("BufferManagerTest device"),
+                instance,
+                backend: crate::wgpu::BackendBit::empty(),
+                pci_id: 0,
+                features: crate::wgpu::Features::empty(),
+                limits: crate::wgpu::Limits::default(),
+            },
+            None,
+        )
+        .unwrap();
+    let mut events = Vec::new();
+    let mut update_context = UpdateContext::new(task, &mut resource_manager, &mut events);
+    let alignment = update_context
+        .device_alignments(device)
+        .unwrap()
+        .buffer_binding as usize;
+
+    let mut lights: BufferManager<Light, ()> = BufferManager::new(
+        &mut update_context,
+        String::from("BufferManagerTest lights"),
+        device,
+        2,
+        crate::wgpu::BufferUsage::UNIFORM,
+    );
+    assert_eq!(lights.stride(), alignment);
+    for index in 0..3 {
+        lights.request(
+            index,
+            (),
+            Light {
+                color: [1.0; 3],
+                intensity: index as f32,
+            },
+        );
+    }
+    lights.update(&mut update_context);
+
+    for index in 0..3 {
+        let offset = lights.dynamic_offset(&index).unwrap() as usize;
+        assert_eq!(offset % alignment, 0);
+        assert_eq!(offset, lights.data_slot(&index).unwrap() * alignment);
+    }
+    assert_eq!(
+        update_context
+            .buffer_descriptor_ref(lights.id())
+            .unwrap()
+            .size as usize,
+        lights.capacity() * alignment
+    );
+
+    // Without the uniform usage, the elements stay tightly packed.
+    assert_eq!(
+        element_stride::<Light>(
+            crate::wgpu::BufferUsage::STORAGE,
+            &DeviceAlignments::default()
+        ),
+        std::mem::size_of::<Light>()
+    );
+}
@@ -0,0 +1,208 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+const DATA: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+
+pub struct MappingTask {
+    upload: BufferId,
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    copy_pending: bool,
+    result: Option<Vec<u8>>,
+    overflow_rejected: bool,
+}
+
+impl MappingTask {
+    const TASK_NAME: &'static str = "MappingTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let upload = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " upload buffer",
+                device,
+                size: DATA.len() as u64,
+                usage: crate::wgpu::BufferUsage::MAP_WRITE | crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: DATA.len() as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![Command::BufferToBuffer(BufferToBufferCopy {
+                    src_buffer: upload,
+                    src_offset: 0,
+                    dst_buffer: readback,
+                    dst_offset: 0,
+                    size: DATA.len() as u64,
+                })],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            upload,
+            readback,
+            command_buffer,
+            frame: 0,
+            copy_pending: false,
+            result: None,
+            overflow_rejected: false,
+        }
+    }
+}
+
+impl TaskTrait for MappingTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        self.copy_pending = false;
+        match self.frame {
+            1 => {
+                update_context
+                    .write_buffer_mapped(&self.upload, 0, &DATA)
+                    .unwrap();
+                self.copy_pending = true;
+            }
+            2 => {
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, DATA.len() as u64)
+                    .ok();
+                // The end of the range overflows, it must not wrap around into a valid range.
+                self.overflow_rejected = update_context
+                    .read_buffer(&self.readback, 8, u64::MAX - 3)
+                    .is_err();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        if self.copy_pending {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn mapped_write_read_round_trip() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        MappingTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        MappingTask::new,
+    );
+
+    let (result, overflow_rejected) =
+        run_task(&mut wgpu_engine, &task, 3, |task: &mut MappingTask| {
+            (task.result.clone(), task.overflow_rejected)
+        });
+    assert_eq!(result, Some(DATA.to_vec()));
+    assert!(overflow_rejected);
+}
+
+const VALUES: [f32; 5] = [0.5, 1.0, 1.5, 2.0, 2.5];
+
+pub struct TypedReadbackTask {
+    readback: BufferId,
+    frame: usize,
+    result: Option<Vec<f32>>,
+    out_of_range: Option<Result<Vec<f32>, ()>>,
+    overflowing: Option<Result<Vec<f32>, ()>>,
+}
+
+impl TypedReadbackTask {
+    const TASK_NAME: &'static str = "TypedReadbackTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: std::mem::size_of_val(&VALUES) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        Self {
+            readback,
+            frame: 0,
+            result: None,
+            out_of_range: None,
+            overflowing: None,
+        }
+    }
+}
+
+impl TaskTrait for TypedReadbackTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            1 => {
+                update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                    buffer: self.readback,
+                    offset: 0,
+                    data: bytemuck::cast_slice(&VALUES).to_vec(),
+                })]);
+            }
+            2 => {
+                self.result = update_context
+                    .read_buffer_typed(&self.readback, VALUES.len())
+                    .ok();
+                self.out_of_range =
+                    Some(update_context.read_buffer_typed(&self.readback, VALUES.len() + 1));
+                self.overflowing =
+                    Some(update_context.read_buffer_typed(&self.readback, usize::MAX));
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn typed_read_back() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        TypedReadbackTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        TypedReadbackTask::new,
+    );
+
+    let (result, out_of_range, overflowing) = run_task(
+        &mut wgpu_engine,
+        &task,
+        3,
+        |task: &mut TypedReadbackTask| {
+            (
+                task.result.clone(),
+                task.out_of_range.clone(),
+                task.overflowing.clone(),
+            )
+        },
+    );
+    assert_eq!(result, Some(VALUES.to_vec()));
+    assert_eq!(out_of_range, Some(Err(())));
+    assert_eq!(overflowing, Some(Err(())));
+}
@@ -0,0 +1,459 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+const INPUT: [u32; 4] = [1, 2, 3, 4];
+
+const SHADER: &str = r#"
+[[block]]
+struct Values {
+    data: [[stride(4)]] array<u32>;
+};
+
+[[group(0), binding(0)]]
+var<storage> values: [[access(read_write)]] Values;
+
+[[stage(compute), workgroup_size(1)]]
+fn main([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    values.data[global_id.x] = values.data[global_id.x] * 2u;
+}
+"#;
+
+const PUSH_CONSTANTS_SHADER: &str = r#"
+[[block]]
+struct Values {
+    data: [[stride(4)]] array<u32>;
+};
+[[block]]
+struct Parameters {
+    factor: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage> values: [[access(read_write)]] Values;
+var<push_constant> parameters: Parameters;
+
+[[stage(compute), workgroup_size(1)]]
+fn main([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    values.data[global_id.x] = values.data[global_id.x] * parameters.factor;
+}
+"#;
+
+pub struct ComputeTask {
+    storage: BufferId,
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+    typed_result: Option<Vec<u32>>,
+}
+
+impl ComputeTask {
+    const TASK_NAME: &'static str = "ComputeTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        Self::with_shader(update_context, SHADER, None)
+    }
+
+    /// Multiply the input by a `factor` passed with the push constants.
+    pub fn with_factor(update_context: &mut UpdateContext, factor: u32) -> Self {
+        Self::with_shader(update_context, PUSH_CONSTANTS_SHADER, Some(factor))
+    }
+
+    fn with_shader(update_context: &mut UpdateContext, shader: &str, factor: Option<u32>) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let size = std::mem::size_of_val(&INPUT) as u64;
+
+        let storage = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " storage buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::STORAGE
+                    | crate::wgpu::BufferUsage::COPY_DST
+                    | crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        let shader_module = update_context
+            .add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string() + " shader",
+                device,
+                source: ShaderSource::Wgsl(shader.to_string()),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })
+            .unwrap();
+        let bind_group_layout = update_context
+            .add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " bind group layout",
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry::storage_buffer(
+                    0,
+                    crate::wgpu::ShaderStage::COMPUTE,
+                    false,
+                )],
+            })
+            .unwrap();
+        let bind_group = update_context
+            .add_bind_group_descriptor(BindGroupDescriptor {
+                label: Self::TASK_NAME.to_string() + " bind group",
+                device,
+                layout: bind_group_layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: storage,
+                        offset: 0,
+                        size: None,
+                    }),
+                }],
+            })
+            .unwrap();
+        let pipeline_layout = update_context
+            .add_pipeline_layout_descriptor(PipelineLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " pipeline layout",
+                device,
+                bind_group_layouts: vec![bind_group_layout],
+                push_constant_ranges: factor
+                    .iter()
+                    .map(|_| crate::wgpu::PushConstantRange {
+                        stages: crate::wgpu::ShaderStage::COMPUTE,
+                        range: 0..align_push_constant_size(std::mem::size_of::<u32>()),
+                    })
+                    .collect(),
+            })
+            .unwrap();
+        let pipeline = update_context
+            .add_compute_pipeline_descriptor(ComputePipelineDescriptor {
+                label: Self::TASK_NAME.to_string() + " pipeline",
+                device,
+                layout: Some(pipeline_layout),
+                module: shader_module,
+                entry_point: String::from("main"),
+            })
+            .unwrap();
+
+        let mut compute_commands = vec![
+            ComputeCommand::SetPipeline { pipeline },
+            ComputeCommand::SetBindGroup {
+                index: 0,
+                bind_group,
+                offsets: Vec::new(),
+            },
+        ];
+        if let Some(factor) = factor {
+            compute_commands.push(ComputeCommand::SetPushConstants {
+                stages: crate::wgpu::ShaderStage::COMPUTE,
+                offset: 0,
+                data: bytemuck::bytes_of(&factor).to_vec(),
+            });
+        }
+        compute_commands.push(ComputeCommand::Dispatch {
+            x: INPUT.len() as u32,
+            y: 1,
+            z: 1,
+        });
+
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![
+                    Command::ComputePass(compute_commands),
+                    Command::BufferToBuffer(BufferToBufferCopy {
+                        src_buffer: storage,
+                        src_offset: 0,
+                        dst_buffer: readback,
+                        dst_offset: 0,
+                        size,
+                    }),
+                ],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            storage,
+            readback,
+            command_buffer,
+            frame: 0,
+            result: None,
+            typed_result: None,
+        }
+    }
+}
+
+impl TaskTrait for ComputeTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                buffer: self.storage,
+                offset: 0,
+                data: bytemuck::cast_slice(&INPUT).to_vec(),
+            })]),
+            1 => {
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, std::mem::size_of_val(&INPUT) as u64)
+                    .ok();
+                self.typed_result = update_context
+                    .read_buffer_typed(&self.readback, INPUT.len())
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        // Dispatched once, on the frame uploading the input.
+        if self.frame == 1 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn compute_pass_readback() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        ComputeTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        ComputeTask::new,
+    );
+
+    let (result, typed_result) = run_task(&mut wgpu_engine, &task, 2, |task: &mut ComputeTask| {
+        (task.result.clone(), task.typed_result.clone())
+    });
+    let expected: Vec<u32> = INPUT.iter().map(|value| value * 2).collect();
+    assert_eq!(result, Some(bytemuck::cast_slice(&expected).to_vec()));
+    // The typed readback of the compute results matches the bytes.
+    assert_eq!(typed_result, Some(expected));
+}
+
+#[test]
+fn compute_push_constants_readback() {
+    let features = crate::wgpu::Features::PUSH_CONSTANTS;
+    let limits = crate::wgpu::Limits {
+        max_push_constant_size: align_push_constant_size(std::mem::size_of::<u32>()),
+        ..crate::wgpu::Limits::default()
+    };
+    let (mut wgpu_engine, task) =
+        single_task_engine(ComputeTask::TASK_NAME, features, limits, |update_context| {
+            ComputeTask::with_factor(update_context, 3)
+        });
+
+    // The factor comes from the push constants, not from the shader.
+    let result = run_task(&mut wgpu_engine, &task, 2, |task: &mut ComputeTask| {
+        task.result.clone()
+    });
+    let expected: Vec<u32> = INPUT.iter().map(|value| value * 3).collect();
+    assert_eq!(result, Some(bytemuck::cast_slice(&expected).to_vec()));
+}
+
+const UNIFORM_VALUES: [u32; 4] = [3, 1, 4, 1];
+
+/// Copy the uniform created by [uniform_bind_group][crate::uniform_bind_group] into a storage buffer.
+const UNIFORM_COPY_SHADER: &str = r#"
+[[block]]
+struct Params {
+    values: vec4<u32>;
+};
+[[block]]
+struct Values {
+    data: [[stride(4)]] array<u32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> params: Params;
+[[group(1), binding(0)]]
+var<storage> values: [[access(read_write)]] Values;
+
+[[stage(compute), workgroup_size(1)]]
+fn main([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    values.data[global_id.x] = params.values[global_id.x];
+}
+"#;
+
+pub struct UniformCopyTask {
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u32>>,
+}
+
+impl UniformCopyTask {
+    const TASK_NAME: &'static str = "UniformCopyTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let size = std::mem::size_of_val(&UNIFORM_VALUES) as u64;
+
+        let (_uniform, uniform_layout, uniform_bind_group) = uniform_bind_group(
+            update_context,
+            device,
+            Self::TASK_NAME,
+            crate::wgpu::ShaderStage::COMPUTE,
+            &UNIFORM_VALUES,
+        )
+        .unwrap();
+        let storage = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " storage buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::STORAGE | crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        let shader_module = update_context
+            .add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string() + " shader",
+                device,
+                source: ShaderSource::Wgsl(UNIFORM_COPY_SHADER.to_string()),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })
+            .unwrap();
+        let storage_layout = update_context
+            .add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " storage bind group layout",
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry::storage_buffer(
+                    0,
+                    crate::wgpu::ShaderStage::COMPUTE,
+                    false,
+                )],
+            })
+            .unwrap();
+        let storage_bind_group = update_context
+            .add_bind_group_descriptor(BindGroupDescriptor {
+                label: Self::TASK_NAME.to_string() + " storage bind group",
+                device,
+                layout: storage_layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: storage,
+                        offset: 0,
+                        size: None,
+                    }),
+                }],
+            })
+            .unwrap();
+        let pipeline_layout = update_context
+            .add_pipeline_layout_descriptor(PipelineLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " pipeline layout",
+                device,
+                bind_group_layouts: vec![uniform_layout, storage_layout],
+                push_constant_ranges: Vec::new(),
+            })
+            .unwrap();
+        let pipeline = update_context
+            .add_compute_pipeline_descriptor(ComputePipelineDescriptor {
+                label: Self::TASK_NAME.to_string() + " pipeline",
+                device,
+                layout: Some(pipeline_layout),
+                module: shader_module,
+                entry_point: String::from("main"),
+            })
+            .unwrap();
+
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![
+                    Command::ComputePass(vec![
+                        ComputeCommand::SetPipeline { pipeline },
+                        ComputeCommand::SetBindGroup {
+                            index: 0,
+                            bind_group: uniform_bind_group,
+                            offsets: Vec::new(),
+                        },
+                        ComputeCommand::SetBindGroup {
+                            index: 1,
+                            bind_group: storage_bind_group,
+                            offsets: Vec::new(),
+                        },
+                        ComputeCommand::Dispatch {
+                            x: UNIFORM_VALUES.len() as u32,
+                            y: 1,
+                            z: 1,
+                        },
+                    ]),
+                    Command::BufferToBuffer(BufferToBufferCopy {
+                        src_buffer: storage,
+                        src_offset: 0,
+                        dst_buffer: readback,
+                        dst_offset: 0,
+                        size,
+                    }),
+                ],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            readback,
+            command_buffer,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for UniformCopyTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        if self.frame == 1 {
+            self.result = update_context
+                .read_buffer_typed(&self.readback, UNIFORM_VALUES.len())
+                .ok();
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        // The uniform data is uploaded on the creation of the task, before the first submission.
+        if self.frame == 1 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn uniform_bind_group_read_by_shader() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        UniformCopyTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        UniformCopyTask::new,
+    );
+
+    let result = run_task(&mut wgpu_engine, &task, 2, |task: &mut UniformCopyTask| {
+        task.result.clone()
+    });
+    assert_eq!(result, Some(UNIFORM_VALUES.to_vec()));
+}
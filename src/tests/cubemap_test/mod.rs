@@ -0,0 +1,233 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine, unpad_rows};
+use crate::*;
+
+const SIZE: u32 = 4;
+const FACES: u32 = 6;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+fn face_data(face: u32) -> Vec<u8> {
+    (0..SIZE * SIZE * 4)
+        .map(|value| (value + face * 64) as u8)
+        .collect()
+}
+
+fn texture_descriptor(label: String, device: DeviceId, layers: u32) -> TextureDescriptor {
+    TextureDescriptor {
+        label,
+        device,
+        source: TextureSource::Local,
+        usage: crate::wgpu::TextureUsage::COPY_SRC
+            | crate::wgpu::TextureUsage::COPY_DST
+            | crate::wgpu::TextureUsage::SAMPLED
+            | crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+        size: crate::wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: layers,
+        },
+        format: crate::wgpu::TextureFormat::Rgba8Unorm,
+        dimension: crate::wgpu::TextureDimension::D2,
+        mip_level_count: 1,
+        sample_count: 1,
+    }
+}
+
+pub struct CubemapTask {
+    device: DeviceId,
+    sources: Vec<TextureId>,
+    cubemap: TextureId,
+    face_views: Vec<TextureViewId>,
+    readback: BufferId,
+    frame: usize,
+    result: Option<Vec<Vec<u8>>>,
+}
+
+impl CubemapTask {
+    const TASK_NAME: &'static str = "CubemapTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let sources = (0..FACES)
+            .map(|face| {
+                update_context
+                    .add_texture_descriptor(texture_descriptor(
+                        format!("{} source {}", Self::TASK_NAME, face),
+                        device,
+                        1,
+                    ))
+                    .unwrap()
+            })
+            .collect();
+        let cubemap = update_context
+            .add_texture_descriptor(texture_descriptor(
+                Self::TASK_NAME.to_string() + " cubemap",
+                device,
+                FACES,
+            ))
+            .unwrap();
+        let face_views = (0..FACES)
+            .map(|face| {
+                update_context
+                    .add_texture_view_descriptor(TextureViewDescriptor::cubemap_face(
+                        format!("{} face {}", Self::TASK_NAME, face),
+                        device,
+                        cubemap,
+                        crate::wgpu::TextureFormat::Rgba8Unorm,
+                        face,
+                    ))
+                    .unwrap()
+            })
+            .collect();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE * FACES) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        Self {
+            device,
+            sources,
+            cubemap,
+            face_views,
+            readback,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for CubemapTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => {
+                let mut writes = self
+                    .sources
+                    .iter()
+                    .enumerate()
+                    .map(|(face, source)| {
+                        ResourceWrite::Texture(TextureWrite {
+                            texture: *source,
+                            mip_level: 0,
+                            origin: crate::wgpu::Origin3d::ZERO,
+                            data: face_data(face as u32),
+                            layout: crate::wgpu::ImageDataLayout {
+                                offset: 0,
+                                bytes_per_row: std::num::NonZeroU32::new(SIZE * 4),
+                                rows_per_image: std::num::NonZeroU32::new(SIZE),
+                            },
+                            size: crate::wgpu::Extent3d {
+                                width: SIZE,
+                                height: SIZE,
+                                depth_or_array_layers: 1,
+                            },
+                        })
+                    })
+                    .collect();
+                update_context.write_resource(&mut writes);
+            }
+            1 => {
+                let data = update_context
+                    .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE * FACES) as u64)
+                    .unwrap();
+                self.result = Some(
+                    data.chunks((BYTES_PER_ROW * SIZE) as usize)
+                        .map(|face| unpad_rows(face, (SIZE * 4) as usize, BYTES_PER_ROW as usize))
+                        .collect(),
+                );
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn copy_to_cubemap_faces() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        CubemapTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        CubemapTask::new,
+    );
+    let (device, sources, cubemap, face_views, readback) =
+        run_task(&mut wgpu_engine, &task, 1, |task: &mut CubemapTask| {
+            (
+                task.device,
+                task.sources.clone(),
+                task.cubemap,
+                task.face_views.clone(),
+                task.readback,
+            )
+        });
+    assert_eq!(face_views.len(), FACES as usize);
+
+    // A plain 2D texture is not a valid destination.
+    assert!(wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("Invalid cubemap copy"),
+            device,
+            commands: vec![Command::CopyToCubemapFace {
+                src: sources[0],
+                dst_cubemap: sources[1],
+                face: 0,
+            }],
+            max_commands: None,
+        })
+        .is_err());
+
+    let commands = sources
+        .iter()
+        .enumerate()
+        .map(|(face, source)| Command::CopyToCubemapFace {
+            src: *source,
+            dst_cubemap: cubemap,
+            face: face as u32,
+        })
+        .chain(std::iter::once(Command::TextureToBuffer(
+            TextureToBufferCopy {
+                src_texture: cubemap,
+                src_mip_level: 0,
+                src_origin: crate::wgpu::Origin3d::ZERO,
+                dst_buffer: readback,
+                dst_layout: crate::wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(BYTES_PER_ROW),
+                    rows_per_image: std::num::NonZeroU32::new(SIZE),
+                },
+                copy_size: crate::wgpu::Extent3d {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: FACES,
+                },
+            },
+        )))
+        .collect();
+    wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("Cubemap copy"),
+            device,
+            commands,
+            max_commands: None,
+        })
+        .unwrap();
+
+    let result = run_task(&mut wgpu_engine, &task, 1, |task: &mut CubemapTask| {
+        task.result.clone()
+    })
+    .unwrap();
+    for face in 0..FACES {
+        assert_eq!(result[face as usize], face_data(face));
+    }
+}
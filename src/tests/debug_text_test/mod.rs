@@ -0,0 +1,165 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine, texture_readback};
+use crate::*;
+
+const WIDTH: u32 = 32;
+const HEIGHT: u32 = 16;
+const SCALE: u32 = 2;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+pub struct DebugTextTestTask {
+    device: DeviceId,
+    target: TextureId,
+    text: DebugTextTask,
+    readback: BufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl DebugTextTestTask {
+    const TASK_NAME: &'static str = "DebugTextTestTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let format = crate::wgpu::TextureFormat::Rgba8Unorm;
+
+        let target = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " target",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT
+                    | crate::wgpu::TextureUsage::COPY_SRC,
+                size: crate::wgpu::Extent3d {
+                    width: WIDTH,
+                    height: HEIGHT,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        let target_view = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                Self::TASK_NAME.to_string() + " target view",
+                device,
+                target,
+                format,
+                0,
+            ))
+            .unwrap();
+
+        let text = DebugTextTask::new(
+            update_context,
+            device,
+            ColorView::TextureView(target_view),
+            format,
+            WIDTH,
+            HEIGHT,
+            SCALE,
+        )
+        .unwrap();
+
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * HEIGHT) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        Self {
+            device,
+            target,
+            text,
+            readback,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for DebugTextTestTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        self.text.update_resources(update_context);
+        if self.frame == 1 {
+            self.result = update_context
+                .read_buffer(&self.readback, 0, (BYTES_PER_ROW * HEIGHT) as u64)
+                .ok();
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        self.text.command_buffers()
+    }
+}
+
+#[test]
+fn debug_text_readback() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        DebugTextTestTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        DebugTextTestTask::new,
+    );
+    wgpu_engine.task_handle_cast_mut(&task, |task: &mut DebugTextTestTask| {
+        task.text.push_text(0.0, 0.0, "10")
+    });
+    let (device, target, readback) = run_task(
+        &mut wgpu_engine,
+        &task,
+        1,
+        |task: &mut DebugTextTestTask| (task.device, task.target, task.readback),
+    );
+    wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("DebugText readback"),
+            device,
+            commands: vec![texture_readback(
+                target,
+                readback,
+                (WIDTH, HEIGHT),
+                BYTES_PER_ROW,
+            )],
+            max_commands: None,
+        })
+        .unwrap();
+
+    let result = run_task(
+        &mut wgpu_engine,
+        &task,
+        1,
+        |task: &mut DebugTextTestTask| task.result.clone(),
+    )
+    .unwrap();
+    // Sample the center of every glyph texel, magnified by the scale.
+    let lit = |x: u32, y: u32| {
+        let offset = (y * SCALE * BYTES_PER_ROW + x * SCALE * 4) as usize;
+        result[offset..offset + 4] == [255, 255, 255, 255]
+    };
+
+    // `1` on the first glyph: rows 010 and 110.
+    assert!(!lit(0, 0));
+    assert!(lit(1, 0));
+    assert!(!lit(2, 0));
+    assert!(lit(0, 1));
+    assert!(lit(1, 1));
+    // Spacing column between the glyphs.
+    assert!(!lit(3, 0));
+    // `0` on the second glyph: rows 111 and 101.
+    assert!(lit(4, 0));
+    assert!(lit(5, 0));
+    assert!(lit(6, 0));
+    assert!(lit(4, 1));
+    assert!(!lit(5, 1));
+    assert!(lit(6, 1));
+    // Nothing below the glyphs.
+    assert!(!lit(0, 6));
+}
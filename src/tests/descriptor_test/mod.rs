@@ -0,0 +1,761 @@
+use crate::*;
+
+fn render_pipeline_descriptor() -> RenderPipelineDescriptor {
+    let device = DeviceId::new(EntityId::new(0));
+    let shader_module = ShaderModuleId::new(EntityId::new(1));
+    let depth_stencil = TextureViewId::new(EntityId::new(2));
+    RenderPipelineDescriptor {
+        label: String::from("DescriptorTest"),
+        device,
+        layout: None,
+        vertex: VertexState {
+            module: shader_module,
+            entry_point: String::from("vs_main"),
+            buffers: Vec::new(),
+        },
+        primitive: crate::wgpu::PrimitiveState::default(),
+        depth_stencil: Some(DepthStencilState {
+            id: depth_stencil,
+            depth_write_enabled: true,
+            depth_compare: crate::wgpu::CompareFunction::LessEqual,
+            stencil: crate::wgpu::StencilState::default(),
+            bias: crate::wgpu::DepthBiasState::default(),
+        }),
+        multisample: crate::wgpu::MultisampleState::default(),
+        fragment: None,
+    }
+}
+
+#[test]
+fn depth_bias_variants() {
+    let descriptor = render_pipeline_descriptor();
+    let decal = descriptor.clone().with_depth_bias(-2, -1.0, 0.0);
+    let shadow = descriptor.clone().with_depth_bias(4, 2.0, 0.5);
+
+    assert_eq!(decal.depth_stencil.as_ref().unwrap().bias.constant, -2);
+    assert_eq!(shadow.depth_stencil.as_ref().unwrap().bias.slope_scale, 2.0);
+    assert_ne!(decal, shadow);
+    assert_ne!(decal, descriptor);
+    assert_eq!(decal, descriptor.with_depth_bias(-2, -1.0, 0.0));
+}
+
+#[test]
+fn max_commands_exceeded() {
+    let device = DeviceId::new(EntityId::new(0));
+    let buffer = BufferId::new(EntityId::new(1));
+    let copy = Command::BufferToBuffer(BufferToBufferCopy {
+        src_buffer: buffer,
+        src_offset: 0,
+        dst_buffer: buffer,
+        dst_offset: 0,
+        size: 4,
+    });
+    let mut descriptor = CommandBufferDescriptor {
+        label: String::from("DescriptorTest"),
+        device,
+        commands: vec![copy; 4],
+        max_commands: Some(4),
+    };
+    assert!(descriptor.check_max_commands());
+
+    descriptor.max_commands = Some(3);
+    assert!(!descriptor.check_max_commands());
+
+    descriptor.max_commands = None;
+    assert!(descriptor.check_max_commands());
+}
+
+#[test]
+fn sampler_descriptor_default() {
+    let descriptor = SamplerDescriptor::default();
+    assert_eq!(descriptor.label, "");
+    assert_eq!(descriptor.address_mode_u, crate::wgpu::AddressMode::Repeat);
+    assert_eq!(descriptor.address_mode_v, crate::wgpu::AddressMode::Repeat);
+    assert_eq!(descriptor.address_mode_w, crate::wgpu::AddressMode::Repeat);
+    assert_eq!(descriptor.mag_filter, crate::wgpu::FilterMode::Nearest);
+    assert_eq!(descriptor.min_filter, crate::wgpu::FilterMode::Nearest);
+    assert_eq!(descriptor.mipmap_filter, crate::wgpu::FilterMode::Nearest);
+    assert_eq!(descriptor.lod_min_clamp, 0.0);
+    assert_eq!(descriptor.lod_max_clamp, 32.0);
+    assert_eq!(descriptor.compare, None);
+    assert_eq!(descriptor.anisotropy_clamp, None);
+    assert_eq!(descriptor.border_color, None);
+}
+
+#[test]
+fn hdr_swapchain_format_fallback() {
+    let hdr = crate::wgpu::TextureFormat::Rgba16Float;
+    let sdr = crate::wgpu::TextureFormat::Bgra8UnormSrgb;
+
+    assert_eq!(select_swapchain_format(hdr, &[sdr, hdr]), Some(hdr));
+    assert_eq!(select_swapchain_format(hdr, &[sdr]), Some(sdr));
+    assert_eq!(select_swapchain_format(hdr, &[]), None);
+    assert_eq!(CompositeAlphaMode::default(), CompositeAlphaMode::Opaque);
+}
+
+#[test]
+fn unsupported_composite_alpha_mode_rejected() {
+    assert!(CompositeAlphaMode::Opaque.is_supported());
+    assert!(!CompositeAlphaMode::PreMultiplied.is_supported());
+
+    let mut wgpu_engine = WGpuEngine::new((
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    ))
+    .unwrap();
+    assert!(matches!(
+        wgpu_engine.set_surface_output(None, CompositeAlphaMode::PostMultiplied),
+        Err(WGpuEngineError::UnsupportedCompositeAlphaMode(
+            CompositeAlphaMode::PostMultiplied
+        ))
+    ));
+    assert!(wgpu_engine
+        .set_surface_output(
+            Some(crate::wgpu::TextureFormat::Rgba16Float),
+            CompositeAlphaMode::Opaque
+        )
+        .is_ok());
+}
+
+#[test]
+fn multisampled_texture_validation() {
+    let mut descriptor = TextureDescriptor {
+        label: String::from("MSAA target"),
+        device: DeviceId::new(EntityId::new(0)),
+        source: TextureSource::Local,
+        usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+        size: crate::wgpu::Extent3d {
+            width: 800,
+            height: 600,
+            depth_or_array_layers: 1,
+        },
+        format: crate::wgpu::TextureFormat::Rgba8Unorm,
+        dimension: crate::wgpu::TextureDimension::D2,
+        mip_level_count: 1,
+        sample_count: 4,
+    };
+    let allowed_usages = crate::wgpu::TextureUsage::all();
+    assert!(descriptor.validate_sample_count(allowed_usages));
+    assert!(!descriptor.validate_sample_count(crate::wgpu::TextureUsage::SAMPLED));
+
+    descriptor.usage |= crate::wgpu::TextureUsage::SAMPLED;
+    assert!(!descriptor.validate_sample_count(allowed_usages));
+
+    descriptor.usage = crate::wgpu::TextureUsage::RENDER_ATTACHMENT;
+    descriptor.format = crate::wgpu::TextureFormat::Bc1RgbaUnorm;
+    assert!(!descriptor.validate_sample_count(allowed_usages));
+
+    descriptor.sample_count = 1;
+    descriptor.usage = crate::wgpu::TextureUsage::SAMPLED | crate::wgpu::TextureUsage::COPY_DST;
+    assert!(descriptor.validate_sample_count(allowed_usages));
+}
+
+#[test]
+fn indirect_buffer_validation() {
+    // Dispatch arguments are three u32.
+    let args_size = 12;
+    let mut descriptor = BufferDescriptor {
+        label: String::from("Indirect buffer"),
+        device: DeviceId::new(EntityId::new(0)),
+        size: 32,
+        usage: crate::wgpu::BufferUsage::INDIRECT | crate::wgpu::BufferUsage::STORAGE,
+    };
+    assert!(descriptor.validate_indirect(0, args_size));
+    assert!(descriptor.validate_indirect(20, args_size));
+    assert!(!descriptor.validate_indirect(2, args_size));
+    assert!(!descriptor.validate_indirect(24, args_size));
+    // An offset close to the address limit must not wrap around into the buffer.
+    assert!(!descriptor.validate_indirect(u64::MAX - 3, args_size));
+
+    descriptor.usage = crate::wgpu::BufferUsage::STORAGE;
+    assert!(!descriptor.validate_indirect(0, args_size));
+}
+
+#[test]
+fn push_constant_size_alignment() {
+    assert_eq!(align_push_constant_size(0), 0);
+    assert_eq!(align_push_constant_size(4), 4);
+    assert_eq!(align_push_constant_size(64), 64);
+    assert_eq!(align_push_constant_size(1), 4);
+    assert_eq!(align_push_constant_size(6), 8);
+    assert_eq!(align_push_constant_size(65), 68);
+}
+
+#[test]
+fn push_constant_layout_segments() {
+    let mut layout = PushConstantLayout::new();
+    let transform = layout
+        .add::<[[f32; 4]; 4]>(crate::wgpu::ShaderStage::VERTEX)
+        .unwrap();
+    let tint = layout
+        .add::<[f32; 3]>(crate::wgpu::ShaderStage::FRAGMENT)
+        .unwrap();
+    let time = layout
+        .add::<f32>(crate::wgpu::ShaderStage::FRAGMENT)
+        .unwrap();
+    assert_eq!((transform.offset, transform.size), (0, 64));
+    assert_eq!((tint.offset, tint.size), (64, 12));
+    assert_eq!((time.offset, time.size), (76, 4));
+    assert_eq!(layout.size(), 80);
+
+    let ranges = layout.ranges();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[0].stages, crate::wgpu::ShaderStage::VERTEX);
+    assert_eq!(ranges[0].range, 0..64);
+    assert_eq!(ranges[1].stages, crate::wgpu::ShaderStage::FRAGMENT);
+    assert_eq!(ranges[1].range, 64..80);
+
+    // A stage can only appear in a single range.
+    assert!(layout
+        .add::<f32>(crate::wgpu::ShaderStage::VERTEX_FRAGMENT)
+        .is_err());
+    assert!(layout.add::<f32>(crate::wgpu::ShaderStage::VERTEX).is_err());
+    assert_eq!(layout.size(), 80);
+
+    match tint.render_command(&[1.0f32, 0.5, 0.25]) {
+        RenderCommand::SetPushConstants {
+            stages,
+            offset,
+            data,
+        } => {
+            assert_eq!(stages, crate::wgpu::ShaderStage::FRAGMENT);
+            assert_eq!(offset, 64);
+            assert_eq!(data.len(), 12);
+        }
+        _ => panic!("Unexpected command"),
+    }
+}
+
+#[test]
+#[should_panic]
+fn push_constant_segment_refuses_bigger_values() {
+    let mut layout = PushConstantLayout::new();
+    let time = layout
+        .add::<f32>(crate::wgpu::ShaderStage::FRAGMENT)
+        .unwrap();
+    layout
+        .add::<f32>(crate::wgpu::ShaderStage::FRAGMENT)
+        .unwrap();
+    time.compute_command(&[1.0f32, 2.0]);
+}
+
+#[test]
+fn primitive_state_required_features() {
+    let mut descriptor = render_pipeline_descriptor();
+    assert!(descriptor.required_features().is_empty());
+
+    descriptor.primitive.polygon_mode = crate::wgpu::PolygonMode::Line;
+    descriptor.primitive.conservative = true;
+    assert_eq!(
+        descriptor.required_features(),
+        crate::wgpu::Features::NON_FILL_POLYGON_MODE
+            | crate::wgpu::Features::CONSERVATIVE_RASTERIZATION
+    );
+}
+
+#[test]
+fn patch_draw_instances() {
+    let pipeline = RenderPipelineId::new(EntityId::new(3));
+    let buffer = BufferId::new(EntityId::new(4));
+    let mut descriptor = CommandBufferDescriptor {
+        label: String::from("DescriptorTest"),
+        device: DeviceId::new(EntityId::new(0)),
+        commands: vec![
+            Command::BufferToBuffer(BufferToBufferCopy {
+                src_buffer: buffer,
+                src_offset: 0,
+                dst_buffer: buffer,
+                dst_offset: 256,
+                size: 256,
+            }),
+            Command::RenderPass {
+                label: String::from("DescriptorTest pass"),
+                depth_stencil: None,
+                color_attachments: Vec::new(),
+                commands: vec![
+                    RenderCommand::SetPipeline { pipeline },
+                    RenderCommand::SetVertexBuffer {
+                        slot: 0,
+                        buffer,
+                        slice: Slice::from(..),
+                    },
+                    RenderCommand::Draw {
+                        vertices: 0..6,
+                        instances: 0..3,
+                    },
+                    RenderCommand::Draw {
+                        vertices: 6..12,
+                        instances: 0..3,
+                    },
+                ],
+            },
+        ],
+        max_commands: None,
+    };
+    let original = descriptor.clone();
+
+    // Targets that are not a draw of a render pass are rejected and leave the descriptor unchanged.
+    assert!(!descriptor.patch_draw_instances(0, 0, 0..5));
+    assert!(!descriptor.patch_draw_instances(1, 0, 0..5));
+    assert!(!descriptor.patch_draw_instances(1, 4, 0..5));
+    assert!(!descriptor.patch_draw_instances(2, 2, 0..5));
+    assert_eq!(descriptor, original);
+
+    assert!(descriptor.patch_draw_instances(1, 2, 0..5));
+    assert_eq!(descriptor.commands[0], original.commands[0]);
+    match (&descriptor.commands[1], &original.commands[1]) {
+        (
+            Command::RenderPass { commands, .. },
+            Command::RenderPass {
+                commands: original_commands,
+                ..
+            },
+        ) => {
+            assert_eq!(commands[..2], original_commands[..2]);
+            assert_eq!(
+                commands[2],
+                RenderCommand::Draw {
+                    vertices: 0..6,
+                    instances: 0..5,
+                }
+            );
+            // The other draw of the pass keeps its instances.
+            assert_eq!(commands[3], original_commands[3]);
+        }
+        _ => panic!("Expected a render pass"),
+    }
+}
+
+#[test]
+fn render_targets_of_multiple_passes() {
+    let color_attachment = |view| RenderPassColorAttachment {
+        view,
+        resolve_target: None,
+        ops: crate::wgpu::Operations::default(),
+    };
+    let render_pass = |color_attachments| Command::RenderPass {
+        label: String::from("DescriptorTest pass"),
+        depth_stencil: None,
+        color_attachments,
+        commands: Vec::new(),
+    };
+    let gbuffer = ColorView::TextureView(TextureViewId::new(EntityId::new(1)));
+    let msaa = ColorView::TextureView(TextureViewId::new(EntityId::new(2)));
+    let resolved = TextureViewId::new(EntityId::new(3));
+    let swapchain = ColorView::Swapchain(SwapchainId::new(EntityId::new(4)));
+
+    let descriptor = CommandBufferDescriptor {
+        label: String::from("DescriptorTest"),
+        device: DeviceId::new(EntityId::new(0)),
+        commands: vec![
+            render_pass(vec![color_attachment(gbuffer.clone())]),
+            render_pass(vec![RenderPassColorAttachment {
+                resolve_target: Some(resolved),
+                ..color_attachment(msaa.clone())
+            }]),
+            render_pass(vec![
+                color_attachment(swapchain.clone()),
+                color_attachment(gbuffer.clone()),
+            ]),
+        ],
+        max_commands: None,
+    };
+    assert_eq!(
+        descriptor.render_targets(),
+        vec![gbuffer, msaa, ColorView::TextureView(resolved), swapchain]
+    );
+}
+
+#[test]
+fn device_regression_after_rebuild() {
+    let features =
+        crate::wgpu::Features::NON_FILL_POLYGON_MODE | crate::wgpu::Features::PUSH_CONSTANTS;
+    let limits = crate::wgpu::Limits {
+        max_push_constant_size: 128,
+        ..Default::default()
+    };
+    assert_eq!(
+        DeviceRegression::between((features, &limits), (features, &limits)),
+        None
+    );
+    // Gaining capabilities is not a regression.
+    assert_eq!(
+        DeviceRegression::between(
+            (
+                crate::wgpu::Features::empty(),
+                &crate::wgpu::Limits::default()
+            ),
+            (features, &limits)
+        ),
+        None
+    );
+
+    let reduced_limits = crate::wgpu::Limits {
+        max_push_constant_size: 64,
+        ..Default::default()
+    };
+    assert_eq!(
+        DeviceRegression::between(
+            (features, &limits),
+            (crate::wgpu::Features::PUSH_CONSTANTS, &reduced_limits)
+        ),
+        Some(DeviceRegression {
+            features: crate::wgpu::Features::NON_FILL_POLYGON_MODE,
+            limits: vec![("max_push_constant_size", 128, 64)],
+        })
+    );
+}
+
+#[test]
+fn bind_group_layout_entry_helpers() {
+    let stages = crate::wgpu::ShaderStage::VERTEX | crate::wgpu::ShaderStage::FRAGMENT;
+
+    assert_eq!(
+        BindGroupLayoutEntry::texture_array(0, 8, crate::wgpu::ShaderStage::FRAGMENT),
+        BindGroupLayoutEntry {
+            binding: 0,
+            visibility: crate::wgpu::ShaderStage::FRAGMENT,
+            ty: crate::wgpu::BindingType::Texture {
+                sample_type: crate::wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: crate::wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: std::num::NonZeroU32::new(8),
+        }
+    );
+    assert_eq!(
+        BindGroupLayoutEntry::texture_array(0, 0, crate::wgpu::ShaderStage::FRAGMENT).count,
+        None
+    );
+    assert_eq!(
+        BindGroupLayoutEntry::texture(0, crate::wgpu::ShaderStage::FRAGMENT),
+        BindGroupLayoutEntry::texture_array(0, 0, crate::wgpu::ShaderStage::FRAGMENT)
+    );
+    assert_eq!(
+        BindGroupLayoutEntry::sampler(1),
+        BindGroupLayoutEntry {
+            binding: 1,
+            visibility: crate::wgpu::ShaderStage::FRAGMENT,
+            ty: crate::wgpu::BindingType::Sampler {
+                comparison: false,
+                filtering: true,
+            },
+            count: None,
+        }
+    );
+    assert_eq!(
+        BindGroupLayoutEntry::uniform_buffer(2, stages),
+        BindGroupLayoutEntry {
+            binding: 2,
+            visibility: stages,
+            ty: crate::wgpu::BindingType::Buffer {
+                ty: crate::wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    );
+    assert_eq!(
+        BindGroupLayoutEntry::storage_buffer(3, crate::wgpu::ShaderStage::COMPUTE, true),
+        BindGroupLayoutEntry {
+            binding: 3,
+            visibility: crate::wgpu::ShaderStage::COMPUTE,
+            ty: crate::wgpu::BindingType::Buffer {
+                ty: crate::wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    );
+}
+
+#[test]
+fn resolved_attachment_not_stored() {
+    let msaa = ColorView::TextureView(TextureViewId::new(EntityId::new(1)));
+    let resolved = TextureViewId::new(EntityId::new(2));
+
+    let attachment =
+        RenderPassColorAttachment::resolve(msaa.clone(), resolved, crate::wgpu::Color::BLACK);
+    assert_eq!(attachment.resolve_target, Some(resolved));
+    assert!(!attachment.ops.store);
+    assert_eq!(
+        attachment.ops.load,
+        crate::wgpu::LoadOp::Clear(crate::wgpu::Color::BLACK)
+    );
+    assert!(!attachment.stores_resolved_source());
+
+    let stored = RenderPassColorAttachment {
+        ops: crate::wgpu::Operations {
+            store: true,
+            ..attachment.ops
+        },
+        ..attachment.clone()
+    };
+    assert!(stored.stores_resolved_source());
+    // Storing is expected when nothing resolves the attachment.
+    assert!(
+        !RenderPassColorAttachment::clear(msaa, crate::wgpu::Color::BLACK).stores_resolved_source()
+    );
+}
+
+#[test]
+fn merge_adjacent_buffer_writes() {
+    let buffer = BufferId::new(EntityId::new(0));
+    let other_buffer = BufferId::new(EntityId::new(1));
+    let texture = TextureId::new(EntityId::new(2));
+
+    let mut writes: Vec<_> = (0..100u32)
+        .map(|index| {
+            ResourceWrite::Buffer(BufferWrite {
+                buffer,
+                offset: index as u64 * 4,
+                data: index.to_le_bytes().to_vec(),
+            })
+        })
+        .collect();
+    // The latest write wins on the overlapping range.
+    writes.push(ResourceWrite::Buffer(BufferWrite {
+        buffer,
+        offset: 8,
+        data: 1000u32.to_le_bytes().to_vec(),
+    }));
+    writes.push(ResourceWrite::Buffer(BufferWrite {
+        buffer: other_buffer,
+        offset: 0,
+        data: vec![0; 4],
+    }));
+    let texture_write = TextureWrite {
+        texture,
+        mip_level: 0,
+        origin: crate::wgpu::Origin3d::ZERO,
+        data: vec![0; 4],
+        layout: crate::wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: None,
+            rows_per_image: None,
+        },
+        size: crate::wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    };
+    writes.push(ResourceWrite::Texture(texture_write.clone()));
+
+    let merged = ResourceWrite::merge(writes);
+    assert_eq!(merged.len(), 3);
+
+    let mut expected: Vec<u8> = (0..100u32).flat_map(|index| index.to_le_bytes()).collect();
+    expected[8..12].copy_from_slice(&1000u32.to_le_bytes());
+    assert!(merged.contains(&ResourceWrite::Buffer(BufferWrite {
+        buffer,
+        offset: 0,
+        data: expected,
+    })));
+    assert!(merged.contains(&ResourceWrite::Buffer(BufferWrite {
+        buffer: other_buffer,
+        offset: 0,
+        data: vec![0; 4],
+    })));
+    assert_eq!(merged.last(), Some(&ResourceWrite::Texture(texture_write)));
+}
+
+#[test]
+fn frame_latency_clamped_to_supported_range() {
+    use crate::wgpu::Backend;
+    assert_eq!(
+        SurfaceOptions::default().frame_latency,
+        DEFAULT_FRAME_LATENCY
+    );
+    assert_eq!(
+        select_frame_latency(DEFAULT_FRAME_LATENCY, Backend::Vulkan),
+        2
+    );
+    assert_eq!(select_frame_latency(1, Backend::Vulkan), 1);
+    assert_eq!(select_frame_latency(3, Backend::Vulkan), 3);
+    assert_eq!(select_frame_latency(0, Backend::Vulkan), 1);
+    assert_eq!(select_frame_latency(8, Backend::Vulkan), 3);
+    // The range depends on the backend.
+    assert_eq!(select_frame_latency(3, Backend::Metal), 2);
+    assert_eq!(select_frame_latency(3, Backend::Gl), 1);
+    for backend in [
+        Backend::Vulkan,
+        Backend::Metal,
+        Backend::Dx12,
+        Backend::Dx11,
+        Backend::Gl,
+    ] {
+        assert!(supported_frame_latencies(backend).contains(&1));
+    }
+}
+
+#[test]
+fn vsync_present_modes() {
+    use crate::wgpu::{Backend, PresentMode};
+    for backend in [Backend::Vulkan, Backend::Metal, Backend::Gl] {
+        assert_eq!(select_present_mode(true, backend), PresentMode::Fifo);
+    }
+    assert_eq!(
+        select_present_mode(false, Backend::Vulkan),
+        PresentMode::Mailbox
+    );
+    assert_eq!(
+        select_present_mode(false, Backend::Metal),
+        PresentMode::Immediate
+    );
+    // Only Fifo is available, so vsync stays on.
+    assert_eq!(select_present_mode(false, Backend::Gl), PresentMode::Fifo);
+    // The selected mode always comes from the supported ones.
+    for backend in [
+        Backend::Vulkan,
+        Backend::Metal,
+        Backend::Dx12,
+        Backend::Dx11,
+        Backend::Gl,
+    ] {
+        assert!(supported_present_modes(backend).contains(&select_present_mode(false, backend)));
+    }
+}
+
+#[test]
+fn unsupported_present_mode_falls_back_to_fifo() {
+    use crate::wgpu::{Backend, PresentMode};
+    let vulkan = supported_present_modes(Backend::Vulkan);
+    assert_eq!(
+        select_supported_present_mode(PresentMode::Mailbox, &vulkan),
+        PresentMode::Mailbox
+    );
+    assert_eq!(
+        select_supported_present_mode(PresentMode::Fifo, &vulkan),
+        PresentMode::Fifo
+    );
+    for backend in [Backend::Metal, Backend::Gl] {
+        assert_eq!(
+            select_supported_present_mode(PresentMode::Mailbox, &supported_present_modes(backend)),
+            PresentMode::Fifo
+        );
+    }
+}
+
+#[test]
+fn resizes_coalesced_per_surface() {
+    // Three resizes of the first surface in the same frame, while dragging its border.
+    let resizes = vec![(0, 800, 600), (1, 640, 480), (0, 810, 605), (0, 820, 610)];
+    // The first surface is rebuilt once, with its last size.
+    assert_eq!(
+        coalesce_resizes(resizes),
+        vec![(1, 640, 480), (0, 820, 610)]
+    );
+    assert!(coalesce_resizes(Vec::new()).is_empty());
+}
+
+#[test]
+fn untouched_swapchain_presents_skipped() {
+    let untouched = SwapchainId::new(EntityId::new(0));
+    let rendered = SwapchainId::new(EntityId::new(1));
+
+    // Every frame is acquired by default.
+    let mut throttle = PresentThrottle::default();
+    throttle.untouched(untouched);
+    assert!(!throttle.skip_acquire(untouched));
+
+    throttle.set_max_skipped(Some(2));
+    // Until a frame has been presented untouched, the swapchain is expected to be rendered on.
+    assert!(!throttle.skip_acquire(untouched));
+    throttle.untouched(untouched);
+    let skips: Vec<bool> = (0..6)
+        .map(|_| {
+            let skip = throttle.skip_acquire(untouched);
+            if !skip {
+                throttle.untouched(untouched);
+            }
+            skip
+        })
+        .collect();
+    // The compositor still gets a frame every 3 frames.
+    assert_eq!(skips, vec![true, true, false, true, true, false]);
+
+    // Rendering on a skipped frame resets the count.
+    throttle.untouched(rendered);
+    assert!(throttle.skip_acquire(rendered));
+    throttle.presented(&rendered);
+    assert!(!throttle.skip_acquire(rendered));
+}
+
+#[test]
+fn pillarbox_in_wide_swapchain() {
+    // 4:3 content in a 16:9 window.
+    let (viewport, borders) = letterbox_viewport((1920, 1080), 4.0 / 3.0);
+    assert_eq!(
+        viewport,
+        Viewport {
+            x: 240.0,
+            y: 0.0,
+            width: 1440.0,
+            height: 1080.0,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    );
+    assert_eq!(
+        borders,
+        [
+            ScissorRect {
+                x: 0,
+                y: 0,
+                width: 240,
+                height: 1080,
+            },
+            ScissorRect {
+                x: 1680,
+                y: 0,
+                width: 240,
+                height: 1080,
+            },
+        ]
+    );
+}
+
+#[test]
+fn letterbox_in_tall_swapchain() {
+    let (viewport, [top, bottom]) = letterbox_viewport((1000, 1000), 16.0 / 9.0);
+    assert_eq!((viewport.x, viewport.width), (0.0, 1000.0));
+    assert_eq!(viewport.height, 563.0);
+    assert_eq!(top.y, 0);
+    assert_eq!(top.height as f32, viewport.y);
+    assert_eq!(bottom.y as f32, viewport.y + viewport.height);
+    assert_eq!(top.height + viewport.height as u32 + bottom.height, 1000);
+    // Centered up to a pixel.
+    assert!((top.height as i32 - bottom.height as i32).abs() <= 1);
+}
+
+#[test]
+fn matching_aspect_has_no_borders() {
+    let (viewport, borders) = letterbox_viewport((1280, 720), 16.0 / 9.0);
+    assert_eq!((viewport.width, viewport.height), (1280.0, 720.0));
+    assert!(borders.iter().all(|border| border.is_empty()));
+}
+
+#[test]
+fn commands_clipped_to_content() {
+    let commands = letterbox_commands(
+        (1920, 1080),
+        4.0 / 3.0,
+        vec![RenderCommand::Draw {
+            vertices: 0..3,
+            instances: 0..1,
+        }],
+    );
+    assert_eq!(commands.len(), 3);
+    assert_eq!(
+        commands[1],
+        RenderCommand::SetScissorRect {
+            x: 240,
+            y: 0,
+            width: 1440,
+            height: 1080,
+        }
+    );
+}
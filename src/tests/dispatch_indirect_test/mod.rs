@@ -0,0 +1,292 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+/// Number of workgroups the first pass writes in the indirect arguments.
+const INVOCATIONS: u32 = 16;
+/// Size of the output, larger than the invocations to catch dispatches of the wrong size.
+const OUTPUT_LEN: usize = 32;
+
+/**
+`write_args` writes the dispatch dimensions, `fill` marks the element of every invocation.
+They use disjoint bindings of the group, so each pipeline layout only declares its own, and the arguments
+are not bound as a writable storage buffer while being read by the indirect dispatch.
+*/
+const SHADER: &str = r#"
+[[block]]
+struct Args {
+    x: u32;
+    y: u32;
+    z: u32;
+};
+[[block]]
+struct Output {
+    data: [[stride(4)]] array<u32>;
+};
+
+[[group(0), binding(0)]]
+var<storage> args: [[access(read_write)]] Args;
+[[group(0), binding(1)]]
+var<storage> output: [[access(read_write)]] Output;
+
+[[stage(compute), workgroup_size(1)]]
+fn write_args() {
+    args.x = 16u;
+    args.y = 1u;
+    args.z = 1u;
+}
+
+[[stage(compute), workgroup_size(1)]]
+fn fill([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    output.data[global_id.x] = 1u;
+}
+"#;
+
+/// Arguments written by the task, instead of computing them in a first pass.
+const WRITTEN_ARGS: [u32; 3] = [INVOCATIONS / 2, 1, 1];
+
+pub struct DispatchIndirectTask {
+    args: BufferId,
+    /// Arguments written with a [BufferWrite] on the frame of the dispatch, if any.
+    written_args: Option<[u32; 3]>,
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl DispatchIndirectTask {
+    const TASK_NAME: &'static str = "DispatchIndirectTask";
+
+    /// Pipeline running `entry_point` with a single bind group binding `buffer` at `binding`.
+    fn pipeline(
+        update_context: &mut UpdateContext,
+        device: DeviceId,
+        shader_module: ShaderModuleId,
+        entry_point: &str,
+        binding: u32,
+        buffer: BufferId,
+    ) -> (ComputePipelineId, BindGroupId) {
+        let label = format!("{} {}", Self::TASK_NAME, entry_point);
+        let bind_group_layout = update_context
+            .add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: label.clone() + " bind group layout",
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry::storage_buffer(
+                    binding,
+                    crate::wgpu::ShaderStage::COMPUTE,
+                    false,
+                )],
+            })
+            .unwrap();
+        let bind_group = update_context
+            .add_bind_group_descriptor(BindGroupDescriptor {
+                label: label.clone() + " bind group",
+                device,
+                layout: bind_group_layout,
+                entries: vec![BindGroupEntry {
+                    binding,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                }],
+            })
+            .unwrap();
+        let pipeline_layout = update_context
+            .add_pipeline_layout_descriptor(PipelineLayoutDescriptor {
+                label: label.clone() + " pipeline layout",
+                device,
+                bind_group_layouts: vec![bind_group_layout],
+                push_constant_ranges: Vec::new(),
+            })
+            .unwrap();
+        let pipeline = update_context
+            .add_compute_pipeline_descriptor(ComputePipelineDescriptor {
+                label: label + " pipeline",
+                device,
+                layout: Some(pipeline_layout),
+                module: shader_module,
+                entry_point: entry_point.to_string(),
+            })
+            .unwrap();
+        (pipeline, bind_group)
+    }
+
+    /// Task dispatching from `written_args` if any, or else from the arguments computed by `write_args`.
+    pub fn new(update_context: &mut UpdateContext, written_args: Option<[u32; 3]>) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let output_size = (OUTPUT_LEN * std::mem::size_of::<u32>()) as u64;
+
+        let args = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " indirect buffer",
+                device,
+                size: std::mem::size_of::<[u32; 3]>() as u64,
+                usage: crate::wgpu::BufferUsage::STORAGE
+                    | crate::wgpu::BufferUsage::INDIRECT
+                    | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let output = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " output buffer",
+                device,
+                size: output_size,
+                usage: crate::wgpu::BufferUsage::STORAGE | crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: output_size,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        let shader_module = update_context
+            .add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string() + " shader",
+                device,
+                source: ShaderSource::Wgsl(SHADER.to_string()),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })
+            .unwrap();
+        let (write_pipeline, args_bind_group) =
+            Self::pipeline(update_context, device, shader_module, "write_args", 0, args);
+        let (fill_pipeline, output_bind_group) =
+            Self::pipeline(update_context, device, shader_module, "fill", 1, output);
+
+        let mut commands = Vec::new();
+        if written_args.is_none() {
+            commands.push(Command::ComputePass(vec![
+                ComputeCommand::SetPipeline {
+                    pipeline: write_pipeline,
+                },
+                ComputeCommand::SetBindGroup {
+                    index: 0,
+                    bind_group: args_bind_group,
+                    offsets: Vec::new(),
+                },
+                ComputeCommand::Dispatch { x: 1, y: 1, z: 1 },
+            ]));
+        }
+        commands.extend(vec![
+            Command::ComputePass(vec![
+                ComputeCommand::SetPipeline {
+                    pipeline: fill_pipeline,
+                },
+                ComputeCommand::SetBindGroup {
+                    index: 0,
+                    bind_group: output_bind_group,
+                    offsets: Vec::new(),
+                },
+                ComputeCommand::DispatchIndirect {
+                    buffer: args,
+                    offset: 0,
+                },
+            ]),
+            Command::BufferToBuffer(BufferToBufferCopy {
+                src_buffer: output,
+                src_offset: 0,
+                dst_buffer: readback,
+                dst_offset: 0,
+                size: output_size,
+            }),
+        ]);
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands,
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            args,
+            written_args,
+            readback,
+            command_buffer,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for DispatchIndirectTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match (self.frame, self.written_args) {
+            (0, Some(args)) => {
+                update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                    buffer: self.args,
+                    offset: 0,
+                    data: bytemuck::cast_slice(&args).to_vec(),
+                })])
+            }
+            (1, _) => {
+                self.result = update_context
+                    .read_buffer(
+                        &self.readback,
+                        0,
+                        (OUTPUT_LEN * std::mem::size_of::<u32>()) as u64,
+                    )
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        if self.frame == 1 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Run a [DispatchIndirectTask] and return its output.
+fn dispatch_output(written_args: Option<[u32; 3]>) -> Vec<u32> {
+    let (mut wgpu_engine, task) = single_task_engine(
+        DispatchIndirectTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        |update_context| DispatchIndirectTask::new(update_context, written_args),
+    );
+    let result = run_task(
+        &mut wgpu_engine,
+        &task,
+        2,
+        |task: &mut DispatchIndirectTask| task.result.clone(),
+    )
+    .unwrap();
+    bytemuck::cast_slice(&result).to_vec()
+}
+
+/// Output marking the first `invocations` elements.
+fn expected_output(invocations: u32) -> Vec<u32> {
+    (0..OUTPUT_LEN)
+        .map(|index| (index < invocations as usize) as u32)
+        .collect()
+}
+
+#[test]
+fn dispatch_from_computed_arguments() {
+    // Exactly the invocations of the indirect arguments written by the first pass ran.
+    assert_eq!(dispatch_output(None), expected_output(INVOCATIONS));
+}
+
+#[test]
+fn dispatch_from_arguments_written_in_the_same_frame() {
+    // The write lands before the dispatch reading it, in the same frame.
+    assert_eq!(
+        dispatch_output(Some(WRITTEN_ARGS)),
+        expected_output(WRITTEN_ARGS[0])
+    );
+}
@@ -0,0 +1,192 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine, texture_readback};
+use crate::*;
+
+const SIZE: u32 = 4;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+const FORMAT: crate::wgpu::TextureFormat = crate::wgpu::TextureFormat::Rgba8Unorm;
+/// Vertex count, instance count, first vertex and first instance.
+const ARGS: [u32; 4] = [3, 1, 0, 0];
+
+pub struct DrawIndirectTask {
+    args: BufferId,
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl DrawIndirectTask {
+    const TASK_NAME: &'static str = "DrawIndirectTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let target = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " target",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT
+                    | crate::wgpu::TextureUsage::COPY_SRC,
+                size: crate::wgpu::Extent3d {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: 1,
+                },
+                format: FORMAT,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        let target_view = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                Self::TASK_NAME.to_string() + " target view",
+                device,
+                target,
+                FORMAT,
+                0,
+            ))
+            .unwrap();
+        let args = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " indirect arguments",
+                device,
+                size: std::mem::size_of_val(&ARGS) as u64,
+                usage: crate::wgpu::BufferUsage::INDIRECT | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        let shader_module = update_context
+            .add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                source: ShaderSource::Wgsl(
+                    include_str!("../triangle_test/shader.wgsl").to_string(),
+                ),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })
+            .unwrap();
+        let render_pipeline = update_context
+            .add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                layout: None,
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: Vec::new(),
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: String::from("fs_main"),
+                    targets: vec![crate::wgpu::ColorTargetState {
+                        format: FORMAT,
+                        blend: None,
+                        write_mask: crate::wgpu::ColorWrite::ALL,
+                    }],
+                }),
+            })
+            .unwrap();
+
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![
+                    Command::RenderPass {
+                        label: Self::TASK_NAME.to_string(),
+                        depth_stencil: None,
+                        color_attachments: vec![RenderPassColorAttachment::clear(
+                            ColorView::TextureView(target_view),
+                            crate::wgpu::Color::BLACK,
+                        )],
+                        commands: vec![
+                            RenderCommand::SetPipeline {
+                                pipeline: render_pipeline,
+                            },
+                            RenderCommand::DrawIndirect {
+                                buffer: args,
+                                offset: 0,
+                            },
+                        ],
+                    },
+                    texture_readback(target, readback, (SIZE, SIZE), BYTES_PER_ROW),
+                ],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            args,
+            readback,
+            command_buffer,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for DrawIndirectTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                buffer: self.args,
+                offset: 0,
+                data: bytemuck::cast_slice(&ARGS).to_vec(),
+            })]),
+            1 => {
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE) as u64)
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        // Drawn once, on the frame writing the arguments.
+        if self.frame == 1 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn triangle_from_indirect_arguments() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        DrawIndirectTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        DrawIndirectTask::new,
+    );
+
+    let data = run_task(&mut wgpu_engine, &task, 2, |task: &mut DrawIndirectTask| {
+        task.result.clone()
+    })
+    .unwrap();
+    let texel = |x: u32, y: u32| {
+        let offset = (y * BYTES_PER_ROW + x * 4) as usize;
+        data[offset..offset + 4].to_vec()
+    };
+    // The triangle covers the center of the target, but not its top left corner.
+    assert_eq!(texel(1, 2), vec![255, 0, 0, 255]);
+    assert_eq!(texel(0, 0), vec![0, 0, 0, 255]);
+}
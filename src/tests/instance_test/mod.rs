@@ -0,0 +1,563 @@
+use crate::*;
+
+#[test]
+fn multiple_instances() {
+    let mut wgpu_engine = WGpuEngine::new(Requirements::default()).unwrap();
+    assert_eq!(wgpu_engine.instances().len(), 1);
+
+    // Instances are created even when the backend has no adapter on the host,
+    // in that case they simply have no devices.
+    let gl_instance = wgpu_engine
+        .add_instance(crate::wgpu::BackendBit::GL)
+        .unwrap();
+
+    let instances = wgpu_engine.instances();
+    assert_eq!(instances.len(), 2);
+    assert_ne!(instances[0], gl_instance);
+    assert_eq!(instances[1], gl_instance);
+}
+
+#[test]
+fn external_runtime_handle() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut wgpu_engine =
+        WGpuEngine::with_runtime_handle(Requirements::default(), runtime.handle().clone()).unwrap();
+    assert_eq!(wgpu_engine.instances().len(), 1);
+    wgpu_engine.dispatch_tasks();
+
+    // The engine does not own the runtime, so it is still usable by the application.
+    drop(wgpu_engine);
+    assert_eq!(runtime.block_on(async { 42 }), 42);
+}
+
+struct RecordingTask {
+    recorded: Vec<usize>,
+}
+impl TaskTrait for RecordingTask {
+    fn name(&self) -> String {
+        String::from("RecordingTask")
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        let closures: Vec<_> = (0..4)
+            .map(|_index| {
+                || {
+                    vec![RenderCommand::Draw {
+                        vertices: 0..3,
+                        instances: 0..1,
+                    }]
+                }
+            })
+            .collect();
+        self.recorded = update_context
+            .record_commands_parallel(closures)
+            .iter()
+            .map(|commands| commands.len())
+            .collect();
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn engine_inside_async_runtime() {
+    // Blocking on the current runtime from one of its workers would panic without block_in_place.
+    let mut wgpu_engine =
+        WGpuEngine::with_runtime_handle(Requirements::default(), tokio::runtime::Handle::current())
+            .unwrap();
+    assert_eq!(wgpu_engine.instances().len(), 1);
+    let task = wgpu_engine
+        .create_task(
+            String::from("RecordingTask"),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, _update_context| RecordingTask {
+                recorded: Vec::new(),
+            },
+        )
+        .unwrap();
+    wgpu_engine.dispatch_tasks();
+
+    let recorded = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut RecordingTask| task.recorded.clone())
+        .unwrap();
+    assert_eq!(recorded, vec![1; 4]);
+    // The runtime keeps running async code afterwards.
+    assert_eq!(tokio::spawn(async { 42 }).await.unwrap(), 42);
+}
+
+#[test]
+fn minimum_limits_not_met() {
+    let requirements = Requirements::default().with_minimum_limits(crate::wgpu::Limits {
+        max_bind_groups: u32::MAX,
+        ..Default::default()
+    });
+    match WGpuEngine::new(requirements) {
+        Err(WGpuEngineError::LimitsNotMet(field, required, available)) => {
+            assert_eq!(field, "max_bind_groups");
+            assert_eq!(required, u32::MAX);
+            assert!(available < required);
+        }
+        _ => panic!("Expected the minimum limits not to be met"),
+    }
+}
+
+#[test]
+#[ignore = "needs a machine without Vulkan adapters, like the CI runners"]
+fn software_fallback() {
+    let hardware_devices = WGpuEngine::new(Requirements::default())
+        .unwrap()
+        .devices()
+        .len();
+    assert_eq!(hardware_devices, 0, "A Vulkan adapter is available");
+
+    let mut wgpu_engine =
+        WGpuEngine::new(Requirements::default().with_software_fallback(true)).unwrap();
+    let task = wgpu_engine
+        .create_task(
+            String::from("BackendTask"),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| BackendTask::new(update_context),
+        )
+        .unwrap();
+
+    let (instance_backends, device_backends) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut BackendTask| {
+            (task.instance_backends.clone(), task.device_backends.clone())
+        })
+        .unwrap();
+    assert_eq!(instance_backends.len(), 2);
+    assert!(instance_backends.contains(&crate::wgpu::BackendBit::VULKAN));
+    assert!(instance_backends.contains(&crate::wgpu::BackendBit::GL));
+    // Every device comes from the software adapter of the fallback backend.
+    assert!(!device_backends.is_empty());
+    for backend in device_backends {
+        assert_eq!(backend, crate::wgpu::BackendBit::GL);
+    }
+}
+
+struct IdleTask;
+impl TaskTrait for IdleTask {
+    fn name(&self) -> String {
+        String::from("IdleTask")
+    }
+    fn update_resources(&mut self, _update_context: &mut UpdateContext) {}
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn quick_run_fails_instead_of_hanging() {
+    let limits = crate::wgpu::Limits {
+        max_bind_groups: u32::MAX,
+        ..Default::default()
+    };
+    let result = quick_run(
+        1,
+        crate::wgpu::Features::empty(),
+        limits,
+        |_id, _tokio, _update_context| IdleTask,
+        |_task| {},
+    );
+    match result {
+        Err(WGpuEngineError::LimitsNotMet(field, _required, _available)) => {
+            assert_eq!(field, "max_bind_groups");
+        }
+        _ => panic!("Expected the quick run to fail on the unmet limits"),
+    }
+}
+
+#[test]
+fn no_swapchain_format_without_surface() {
+    let mut wgpu_engine = WGpuEngine::new(Requirements::default()).unwrap();
+    wgpu_engine.dispatch_tasks();
+
+    // Surfaces need a display, so only the missing surface path is covered here.
+    assert_eq!(wgpu_engine.swapchain_format(0), None);
+    assert_eq!(wgpu_engine.surface_present_mode(0), None);
+}
+
+#[test]
+fn unchanged_frame_skips_commit() {
+    let mut wgpu_engine = WGpuEngine::new(Requirements::default()).unwrap();
+    let features_and_limits = (
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    );
+    wgpu_engine
+        .create_task(
+            String::from("IdleTask"),
+            features_and_limits.clone(),
+            |_id, _tokio, _update_context| IdleTask,
+        )
+        .unwrap();
+
+    wgpu_engine.dispatch_tasks();
+    let commit_count = wgpu_engine.commit_count();
+    // Nothing changed since the last frame.
+    wgpu_engine.dispatch_tasks();
+    wgpu_engine.dispatch_tasks();
+    assert_eq!(wgpu_engine.commit_count(), commit_count);
+
+    // A new resource has to be built.
+    wgpu_engine
+        .create_task(
+            String::from("BufferTask"),
+            features_and_limits,
+            |_id, _tokio, update_context| {
+                let device = update_context.devices().next().unwrap();
+                update_context
+                    .add_buffer_descriptor(BufferDescriptor {
+                        label: String::from("BufferTask buffer"),
+                        device,
+                        size: 16,
+                        usage: crate::wgpu::BufferUsage::COPY_DST,
+                    })
+                    .unwrap();
+                IdleTask
+            },
+        )
+        .unwrap();
+    wgpu_engine.dispatch_tasks();
+    assert_eq!(wgpu_engine.commit_count(), commit_count + 1);
+}
+
+/// Task submitting a buffer copy on every dispatch, writing the source buffer when `write` is set.
+struct CopyTask {
+    source: BufferId,
+    command_buffer: CommandBufferId,
+    write: Option<Vec<u8>>,
+}
+impl CopyTask {
+    fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let source = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: String::from("CopyTask source"),
+                device,
+                size: 16,
+                usage: crate::wgpu::BufferUsage::COPY_SRC | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let destination = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: String::from("CopyTask destination"),
+                device,
+                size: 16,
+                usage: crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: String::from("CopyTask command buffer"),
+                device,
+                commands: vec![Command::BufferToBuffer(BufferToBufferCopy {
+                    src_buffer: source,
+                    src_offset: 0,
+                    dst_buffer: destination,
+                    dst_offset: 0,
+                    size: 16,
+                })],
+                max_commands: None,
+            })
+            .unwrap();
+        Self {
+            source,
+            command_buffer,
+            write: None,
+        }
+    }
+}
+impl TaskTrait for CopyTask {
+    fn name(&self) -> String {
+        String::from("CopyTask")
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        if let Some(data) = self.write.take() {
+            update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                buffer: self.source,
+                offset: 0,
+                data,
+            })]);
+        }
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        vec![self.command_buffer]
+    }
+}
+
+#[test]
+fn unchanged_submitting_frame_reused() {
+    let mut wgpu_engine = WGpuEngine::new(Requirements::default()).unwrap();
+    let task = wgpu_engine
+        .create_task(
+            String::from("CopyTask"),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| CopyTask::new(update_context),
+        )
+        .unwrap();
+
+    // Without reusing the frames, the consumed command buffer is encoded again on every frame.
+    wgpu_engine.dispatch_tasks();
+    let commit_count = wgpu_engine.commit_count();
+    wgpu_engine.dispatch_tasks();
+    assert_eq!(wgpu_engine.commit_count(), commit_count + 1);
+    assert_eq!(wgpu_engine.reused_frames(), 0);
+
+    // The same copy of the same contents is neither committed nor submitted again.
+    wgpu_engine.set_reuse_unchanged_frames(true);
+    let commit_count = wgpu_engine.commit_count();
+    wgpu_engine.dispatch_tasks();
+    wgpu_engine.dispatch_tasks();
+    assert_eq!(wgpu_engine.commit_count(), commit_count);
+    assert_eq!(wgpu_engine.reused_frames(), 2);
+
+    // New contents have to be copied, so the command buffer is encoded and submitted again.
+    wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut CopyTask| task.write = Some(vec![1; 16]))
+        .unwrap();
+    wgpu_engine.dispatch_tasks();
+    assert_eq!(wgpu_engine.commit_count(), commit_count + 1);
+    assert_eq!(wgpu_engine.reused_frames(), 2);
+    wgpu_engine.dispatch_tasks();
+    assert_eq!(wgpu_engine.reused_frames(), 3);
+}
+
+struct FrameTask {
+    frames: Vec<(u64, u64, usize)>,
+}
+impl TaskTrait for FrameTask {
+    fn name(&self) -> String {
+        String::from("FrameTask")
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        let first = update_context.frame_number();
+        let second = update_context.frame_number();
+        self.frames
+            .push((first, second, update_context.ring_index(2)));
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn frame_number_increments_across_dispatches() {
+    let mut wgpu_engine = WGpuEngine::new(Requirements::default()).unwrap();
+    let task = wgpu_engine
+        .create_task(
+            String::from("FrameTask"),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, _update_context| FrameTask { frames: Vec::new() },
+        )
+        .unwrap();
+
+    for _ in 0..3 {
+        wgpu_engine.dispatch_tasks();
+    }
+    let frames = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut FrameTask| task.frames.clone())
+        .unwrap();
+    assert_eq!(frames, vec![(0, 0, 0), (1, 1, 1), (2, 2, 0)]);
+}
+
+#[test]
+fn reset_scene_keeps_devices() {
+    let mut wgpu_engine = WGpuEngine::new(Requirements::default()).unwrap();
+    let features_and_limits = (
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    );
+    let devices = wgpu_engine.devices();
+
+    let task = wgpu_engine
+        .create_task(
+            String::from("SceneTask"),
+            features_and_limits.clone(),
+            |_id, _tokio, update_context| {
+                let device = update_context.devices().next().unwrap();
+                update_context
+                    .add_buffer_descriptor(BufferDescriptor {
+                        label: String::from("SceneTask buffer"),
+                        device,
+                        size: 16,
+                        usage: crate::wgpu::BufferUsage::COPY_DST,
+                    })
+                    .unwrap();
+                update_context
+                    .add_command_buffer_descriptor(CommandBufferDescriptor {
+                        label: String::from("SceneTask command buffer"),
+                        device,
+                        commands: Vec::new(),
+                        max_commands: None,
+                    })
+                    .unwrap();
+                IdleTask
+            },
+        )
+        .unwrap();
+    wgpu_engine.dispatch_tasks();
+
+    wgpu_engine.reset_scene();
+    assert!(wgpu_engine
+        .task_handle_cast_mut(&task, |_task: &mut IdleTask| ())
+        .is_none());
+    assert_eq!(wgpu_engine.devices(), devices);
+
+    // Only the resources of the engine are left.
+    let remaining = std::cell::Cell::new(None);
+    wgpu_engine
+        .create_task(
+            String::from("InspectTask"),
+            features_and_limits,
+            |_id, _tokio, update_context| {
+                remaining.set(Some((
+                    update_context.instances().count(),
+                    update_context.devices().count(),
+                    update_context.buffers().count(),
+                    update_context.command_buffers().count(),
+                )));
+                IdleTask
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        remaining.get(),
+        Some((wgpu_engine.instances().len(), devices.len(), 0, 0))
+    );
+    wgpu_engine.dispatch_tasks();
+}
+
+struct BackendTask {
+    instance_backends: Vec<crate::wgpu::BackendBit>,
+    device_backends: Vec<crate::wgpu::BackendBit>,
+}
+impl BackendTask {
+    fn new(update_context: &mut UpdateContext) -> Self {
+        let instance_backends = update_context
+            .instances()
+            .map(|instance| {
+                update_context
+                    .instance_descriptor_ref(&instance)
+                    .unwrap()
+                    .backend
+            })
+            .collect();
+        let device_backends = update_context
+            .devices()
+            .map(|device| {
+                update_context
+                    .device_descriptor_ref(&device)
+                    .unwrap()
+                    .backend
+            })
+            .collect();
+        Self {
+            instance_backends,
+            device_backends,
+        }
+    }
+}
+impl TaskTrait for BackendTask {
+    fn name(&self) -> String {
+        String::from("BackendTask")
+    }
+    fn update_resources(&mut self, _update_context: &mut UpdateContext) {}
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn requested_backends() {
+    let backends = crate::wgpu::BackendBit::PRIMARY;
+    let mut wgpu_engine = WGpuEngine::new(Requirements::default().with_backends(backends)).unwrap();
+    let task = wgpu_engine
+        .create_task(
+            String::from("BackendTask"),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| BackendTask::new(update_context),
+        )
+        .unwrap();
+
+    let (instance_backends, device_backends) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut BackendTask| {
+            (task.instance_backends.clone(), task.device_backends.clone())
+        })
+        .unwrap();
+    assert_eq!(instance_backends, vec![backends]);
+    // Every device is bound to the single backend of its adapter.
+    for backend in device_backends {
+        assert!(backends.contains(backend));
+        assert_eq!(backend.bits().count_ones(), 1);
+    }
+}
+
+#[test]
+fn adapter_selection() {
+    let adapter = |name: &str, device_type| crate::wgpu::AdapterInfo {
+        name: name.to_string(),
+        vendor: 0,
+        device: 0,
+        device_type,
+        backend: crate::wgpu::Backend::Vulkan,
+    };
+    let discrete = adapter("NVIDIA GeForce RTX", crate::wgpu::DeviceType::DiscreteGpu);
+    let integrated = adapter("Intel UHD Graphics", crate::wgpu::DeviceType::IntegratedGpu);
+
+    let requirements = Requirements::default();
+    assert!(requirements.matches_adapter(&discrete));
+    assert!(requirements.matches_adapter(&integrated));
+
+    let requirements = Requirements::default()
+        .with_power_preference(crate::wgpu::PowerPreference::HighPerformance);
+    assert!(requirements.matches_adapter(&discrete));
+    assert!(!requirements.matches_adapter(&integrated));
+
+    let requirements = Requirements::default().with_adapter_name("intel");
+    assert!(!requirements.matches_adapter(&discrete));
+    assert!(requirements.matches_adapter(&integrated));
+
+    let requirements = Requirements::default()
+        .with_power_preference(crate::wgpu::PowerPreference::LowPower)
+        .with_adapter_name("geforce");
+    assert!(!requirements.matches_adapter(&discrete));
+    assert!(!requirements.matches_adapter(&integrated));
+}
+
+#[test]
+fn unmatched_adapter_falls_back_on_first() {
+    let mut wgpu_engine =
+        WGpuEngine::new(Requirements::default().with_adapter_name("No adapter is named like this"))
+            .unwrap();
+    let task = wgpu_engine
+        .create_task(
+            String::from("BackendTask"),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| BackendTask::new(update_context),
+        )
+        .unwrap();
+
+    let device_backends = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut BackendTask| task.device_backends.clone())
+        .unwrap();
+    assert_eq!(device_backends.len(), 1);
+}
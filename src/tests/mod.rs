@@ -1,3 +1,186 @@
+mod bind_group_ready_test;
+mod blit_test;
+mod buffer_arena_test;
+mod buffer_manager_test;
+mod buffer_mapping_test;
+mod compute_test;
+mod cubemap_test;
+#[cfg(feature = "debug_text")]
+mod debug_text_test;
+mod descriptor_test;
+mod dispatch_indirect_test;
+mod draw_indirect_test;
+mod instance_test;
+mod mrt_clear_test;
+mod msaa_test;
+mod pause_test;
+mod ping_pong_targets_test;
+mod primitives_test;
+mod rectangle_test;
+mod reflection_test;
+mod render_bundle_test;
+mod resource_manager_test;
+mod shader_reload_test;
+mod strict_mode_test;
+mod submit_once_test;
+mod swapchain_test;
+mod texture_region_writer_test;
+mod texture_view_array_test;
+mod timing_test;
 mod triangle_test;
-//mod resource_manager_test;
-//mod rectangle_test;
+mod usage_upgrade_test;
+mod wireframe_test;
+mod write_ordering_test;
+
+use crate::entity_manager::UpdateContext;
+use crate::{BufferId, Command, TaskId, TaskTrait, TextureId, TextureToBufferCopy, WGpuEngine};
+use pal::definitions::*;
+
+/**
+Create an engine on a device with `features` and `limits`, running a single task named `name` created by `create`.
+The tests then drive it with [run_task].
+*/
+pub(crate) fn single_task_engine<T: 'static + TaskTrait>(
+    name: &str,
+    features: crate::wgpu::Features,
+    limits: crate::wgpu::Limits,
+    create: impl Fn(&mut UpdateContext) -> T,
+) -> (WGpuEngine, TaskId) {
+    let mut wgpu_engine = WGpuEngine::new((features, limits.clone())).unwrap();
+    let task = wgpu_engine
+        .create_task(
+            name.to_string(),
+            (features, limits),
+            |_id, _tokio, update_context| create(update_context),
+        )
+        .unwrap();
+    (wgpu_engine, task)
+}
+
+/// Dispatch the tasks `frames` times, then read the state of `task` with `read`.
+pub(crate) fn run_task<T: TaskTrait, R>(
+    wgpu_engine: &mut WGpuEngine,
+    task: &TaskId,
+    frames: usize,
+    read: impl FnOnce(&mut T) -> R,
+) -> R {
+    for _ in 0..frames {
+        wgpu_engine.dispatch_tasks();
+    }
+    wgpu_engine.task_handle_cast_mut(task, read).unwrap()
+}
+
+/**
+Copy the first mip level of the 2D `texture` of `size` texels into `buffer`, with rows padded to `bytes_per_row`.
+The rows can be stripped of the padding with [unpad_rows].
+*/
+pub(crate) fn texture_readback(
+    texture: TextureId,
+    buffer: BufferId,
+    size: (u32, u32),
+    bytes_per_row: u32,
+) -> Command {
+    Command::TextureToBuffer(TextureToBufferCopy {
+        src_texture: texture,
+        src_mip_level: 0,
+        src_origin: crate::wgpu::Origin3d::ZERO,
+        dst_buffer: buffer,
+        dst_layout: crate::wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: std::num::NonZeroU32::new(bytes_per_row),
+            rows_per_image: std::num::NonZeroU32::new(size.1),
+        },
+        copy_size: crate::wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 1,
+        },
+    })
+}
+
+/// Keep the first `row_size` bytes of every row of `bytes_per_row` bytes of a [texture_readback].
+pub(crate) fn unpad_rows(data: &[u8], row_size: usize, bytes_per_row: usize) -> Vec<u8> {
+    data.chunks(bytes_per_row)
+        .flat_map(|row| row[..row_size].to_vec())
+        .collect()
+}
+
+/**
+Open `surface_count` windows and create a swapchain cleared to `clear_color` on each of them,
+then dispatch the tasks until `frame_callback` returns false. The callback runs after every dispatch
+and receives the engine, the external ids of the surfaces and the number of dispatches done since the surfaces have been created.
+It needs a display, so the tests using it are ignored by default: run them with `cargo test -- --ignored`.
+*/
+pub(crate) fn run_on_surfaces(
+    wgpu_engine: &mut WGpuEngine,
+    surface_count: usize,
+    clear_color: crate::wgpu::Color,
+    mut frame_callback: impl FnMut(&mut WGpuEngine, &[usize], usize) -> bool,
+) {
+    let mut platform = pal::Platform::new(vec![Box::new(wgpu_engine.wgpu_context())]);
+    (0..surface_count).for_each(|_| {
+        platform.requests(vec![Request::Surface {
+            request: SurfaceRequest::Create(None),
+        }]);
+    });
+
+    use std::os::unix::io::AsRawFd;
+    let mut event_loop = calloop::EventLoop::try_new().unwrap();
+    let interest = calloop::Interest {
+        readable: true,
+        writable: false,
+    };
+    event_loop
+        .handle()
+        .insert_source(
+            calloop::generic::Generic::new(platform.as_raw_fd(), interest, calloop::Mode::Edge),
+            move |_event, _metadata, _data: &mut ()| Ok(calloop::PostAction::Continue),
+        )
+        .unwrap();
+
+    let mut surfaces: Vec<usize> = Vec::new();
+    let mut frame = 0;
+    loop {
+        let _ = event_loop.dispatch(Some(std::time::Duration::from_millis(1)), &mut ());
+        for event in platform.events() {
+            if let pal::Event::Surface { time: _, id, event } = event {
+                match &event {
+                    pal::SurfaceEvent::Added(surface_info) => {
+                        let surface = match &surface_info.surface {
+                            Surface::WGpu(surface) => surface.clone(),
+                            _ => panic!("The surface is not of WGpu type"),
+                        };
+                        wgpu_engine
+                            .create_surface(
+                                id.into(),
+                                String::from("TestSurface"),
+                                surface,
+                                surface_info.size.width,
+                                surface_info.size.height,
+                                crate::SurfaceOptions {
+                                    clear_color,
+                                    ..Default::default()
+                                },
+                            )
+                            .unwrap();
+                        surfaces.push(id.into());
+                    }
+                    pal::SurfaceEvent::Resized(size) => {
+                        wgpu_engine.resize_surface(id.into(), size.width, size.height);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // Wait for all the windows before dispatching, so the swapchains get created on the same dispatch.
+        if surfaces.len() < surface_count {
+            continue;
+        }
+
+        wgpu_engine.dispatch_tasks();
+        if !frame_callback(wgpu_engine, &surfaces, frame) {
+            break;
+        }
+        frame += 1;
+    }
+}
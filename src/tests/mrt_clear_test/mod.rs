@@ -0,0 +1,235 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+const SIZE: u32 = 4;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+const FORMAT: crate::wgpu::TextureFormat = crate::wgpu::TextureFormat::Rgba8Unorm;
+
+pub struct MrtClearTask {
+    device: DeviceId,
+    targets: Vec<(TextureId, TextureViewId)>,
+    render_pipeline: RenderPipelineId,
+    readback: BufferId,
+    frame: usize,
+    result: Option<Vec<Vec<u8>>>,
+}
+
+impl MrtClearTask {
+    const TASK_NAME: &'static str = "MrtClearTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let targets = (0..2)
+            .map(|index| {
+                let label = format!("{} target {}", Self::TASK_NAME, index);
+                let texture = update_context
+                    .add_texture_descriptor(TextureDescriptor {
+                        label: label.clone(),
+                        device,
+                        source: TextureSource::Local,
+                        usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT
+                            | crate::wgpu::TextureUsage::COPY_SRC,
+                        size: crate::wgpu::Extent3d {
+                            width: SIZE,
+                            height: SIZE,
+                            depth_or_array_layers: 1,
+                        },
+                        format: FORMAT,
+                        dimension: crate::wgpu::TextureDimension::D2,
+                        mip_level_count: 1,
+                        sample_count: 1,
+                    })
+                    .unwrap();
+                let view = update_context
+                    .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                        label + " view",
+                        device,
+                        texture,
+                        FORMAT,
+                        0,
+                    ))
+                    .unwrap();
+                (texture, view)
+            })
+            .collect();
+
+        let shader_module = update_context
+            .add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                source: ShaderSource::Wgsl(
+                    include_str!("../triangle_test/shader.wgsl").to_string(),
+                ),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })
+            .unwrap();
+        // Single target pipeline, used to check the attachment count validation.
+        let render_pipeline = update_context
+            .add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                layout: None,
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: Vec::new(),
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: String::from("fs_main"),
+                    targets: vec![crate::wgpu::ColorTargetState {
+                        format: FORMAT,
+                        blend: None,
+                        write_mask: crate::wgpu::ColorWrite::ALL,
+                    }],
+                }),
+            })
+            .unwrap();
+
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE * 2) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        Self {
+            device,
+            targets,
+            render_pipeline,
+            readback,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for MrtClearTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        if self.frame == 1 {
+            let data = update_context
+                .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE * 2) as u64)
+                .unwrap();
+            // First texel of every target.
+            self.result = Some(
+                data.chunks((BYTES_PER_ROW * SIZE) as usize)
+                    .map(|target| target[..4].to_vec())
+                    .collect(),
+            );
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn clear_targets_to_different_colors() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        MrtClearTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        MrtClearTask::new,
+    );
+    let (device, targets, render_pipeline, readback) =
+        run_task(&mut wgpu_engine, &task, 1, |task: &mut MrtClearTask| {
+            (
+                task.device,
+                task.targets.clone(),
+                task.render_pipeline,
+                task.readback,
+            )
+        });
+
+    let color_attachments = vec![
+        RenderPassColorAttachment::clear(
+            ColorView::TextureView(targets[0].1),
+            crate::wgpu::Color::BLACK,
+        ),
+        RenderPassColorAttachment::clear(
+            ColorView::TextureView(targets[1].1),
+            crate::wgpu::Color {
+                r: 0.0,
+                g: 0.0,
+                b: 1.0,
+                a: 1.0,
+            },
+        ),
+    ];
+
+    // The pipeline has a single target, while the pass has two attachments.
+    assert!(wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("Mismatching MRT pass"),
+            device,
+            commands: vec![Command::RenderPass {
+                label: String::from("Mismatching MRT pass"),
+                depth_stencil: None,
+                color_attachments: color_attachments.clone(),
+                commands: vec![
+                    RenderCommand::SetPipeline {
+                        pipeline: render_pipeline,
+                    },
+                    RenderCommand::Draw {
+                        vertices: 0..3,
+                        instances: 0..1,
+                    },
+                ],
+            }],
+            max_commands: None,
+        })
+        .is_err());
+
+    let copies = targets.iter().enumerate().map(|(index, (texture, _view))| {
+        Command::TextureToBuffer(TextureToBufferCopy {
+            src_texture: *texture,
+            src_mip_level: 0,
+            src_origin: crate::wgpu::Origin3d::ZERO,
+            dst_buffer: readback,
+            dst_layout: crate::wgpu::ImageDataLayout {
+                offset: (BYTES_PER_ROW * SIZE * index as u32) as u64,
+                bytes_per_row: std::num::NonZeroU32::new(BYTES_PER_ROW),
+                rows_per_image: std::num::NonZeroU32::new(SIZE),
+            },
+            copy_size: crate::wgpu::Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: 1,
+            },
+        })
+    });
+    let commands = std::iter::once(Command::RenderPass {
+        label: String::from("MRT clear pass"),
+        depth_stencil: None,
+        color_attachments,
+        commands: Vec::new(),
+    })
+    .chain(copies)
+    .collect();
+    wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("MRT clear"),
+            device,
+            commands,
+            max_commands: None,
+        })
+        .unwrap();
+
+    let result = run_task(&mut wgpu_engine, &task, 1, |task: &mut MrtClearTask| {
+        task.result.clone()
+    })
+    .unwrap();
+    assert_eq!(result, vec![vec![0, 0, 0, 255], vec![0, 0, 255, 255]]);
+}
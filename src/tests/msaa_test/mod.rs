@@ -0,0 +1,55 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+pub struct MsaaTask {
+    device: DeviceId,
+    target: (TextureViewId, u32),
+}
+
+impl MsaaTask {
+    const TASK_NAME: &'static str = "MsaaTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let target = update_context
+            .create_msaa_target(device, 64, 64, crate::wgpu::TextureFormat::Rgba8Unorm, 8)
+            .unwrap();
+        Self { device, target }
+    }
+}
+
+impl TaskTrait for MsaaTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, _update_context: &mut UpdateContext) {}
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn supported_sample_counts() {
+    assert_eq!(select_sample_count(8, &[1, 4]), 4);
+    assert_eq!(select_sample_count(2, &[1, 4]), 1);
+    assert_eq!(select_sample_count(4, &[1, 2, 4, 8]), 4);
+
+    let (mut wgpu_engine, task) = single_task_engine(
+        MsaaTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        MsaaTask::new,
+    );
+
+    let (device, (_view, sample_count)) =
+        run_task(&mut wgpu_engine, &task, 1, |task: &mut MsaaTask| {
+            (task.device, task.target)
+        });
+    let counts =
+        wgpu_engine.supported_sample_counts(device, crate::wgpu::TextureFormat::Rgba8Unorm);
+    assert!(counts.contains(&1));
+    assert!(counts.iter().all(|count| [1, 2, 4, 8].contains(count)));
+    assert!(counts.contains(&sample_count));
+    assert!(sample_count <= 8);
+}
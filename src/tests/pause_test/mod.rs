@@ -0,0 +1,108 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+pub struct PauseTask {
+    source: BufferId,
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: u32,
+    reads: Vec<u32>,
+}
+
+impl PauseTask {
+    const TASK_NAME: &'static str = "PauseTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let size = std::mem::size_of::<u32>() as u64;
+
+        let source = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " source buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::COPY_DST | crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![Command::BufferToBuffer(BufferToBufferCopy {
+                    src_buffer: source,
+                    src_offset: 0,
+                    dst_buffer: readback,
+                    dst_offset: 0,
+                    size,
+                })],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            source,
+            readback,
+            command_buffer,
+            frame: 0,
+            reads: Vec::new(),
+        }
+    }
+}
+
+impl TaskTrait for PauseTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        if self.frame > 0 {
+            let data = update_context
+                .read_buffer(&self.readback, 0, std::mem::size_of::<u32>() as u64)
+                .unwrap();
+            self.reads
+                .push(u32::from_ne_bytes([data[0], data[1], data[2], data[3]]));
+        }
+        self.frame += 1;
+        // Every frame copies its own number into the readback buffer.
+        update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+            buffer: self.source,
+            offset: 0,
+            data: bytemuck::bytes_of(&self.frame).to_vec(),
+        })]);
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        vec![self.command_buffer]
+    }
+}
+
+#[test]
+fn nothing_submitted_while_paused() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        PauseTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        PauseTask::new,
+    );
+
+    wgpu_engine.dispatch_tasks();
+    wgpu_engine.set_paused(true);
+    wgpu_engine.dispatch_tasks();
+    wgpu_engine.dispatch_tasks();
+    wgpu_engine.set_paused(false);
+
+    let (frame, reads) = run_task(&mut wgpu_engine, &task, 2, |task: &mut PauseTask| {
+        (task.frame, task.reads.clone())
+    });
+    // The task kept being updated while paused, but the copies of frames 2 and 3 were never submitted.
+    assert_eq!(frame, 5);
+    assert_eq!(reads, vec![1, 1, 1, 4]);
+}
@@ -0,0 +1,171 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine, texture_readback};
+use crate::*;
+
+const SIZE: u32 = 4;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+const SWAPS: usize = 5;
+
+fn data() -> Vec<u8> {
+    (0..SIZE * SIZE * 4).map(|value| value as u8).collect()
+}
+
+fn extent() -> crate::wgpu::Extent3d {
+    crate::wgpu::Extent3d {
+        width: SIZE,
+        height: SIZE,
+        depth_or_array_layers: 1,
+    }
+}
+
+pub struct PingPongTask {
+    device: DeviceId,
+    targets: PingPongTargets,
+    readback: BufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl PingPongTask {
+    const TASK_NAME: &'static str = "PingPongTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let targets = PingPongTargets::new(
+            update_context,
+            TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " target",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::COPY_SRC | crate::wgpu::TextureUsage::COPY_DST,
+                size: extent(),
+                format: crate::wgpu::TextureFormat::Rgba8Unorm,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            },
+        )
+        .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        Self {
+            device,
+            targets,
+            readback,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for PingPongTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => {
+                update_context.write_resource(&mut vec![ResourceWrite::Texture(TextureWrite {
+                    texture: self.targets.read().0,
+                    mip_level: 0,
+                    origin: crate::wgpu::Origin3d::ZERO,
+                    data: data(),
+                    layout: crate::wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: std::num::NonZeroU32::new(SIZE * 4),
+                        rows_per_image: std::num::NonZeroU32::new(SIZE),
+                    },
+                    size: extent(),
+                })]);
+            }
+            1 => {
+                let data = update_context
+                    .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE) as u64)
+                    .unwrap();
+                self.result = Some(
+                    data.chunks(BYTES_PER_ROW as usize)
+                        .map(|row| &row[..(SIZE * 4) as usize])
+                        .flatten()
+                        .copied()
+                        .collect(),
+                );
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn ping_pong_copies_migrate_data() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        PingPongTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        PingPongTask::new,
+    );
+    let (device, first) = run_task(&mut wgpu_engine, &task, 1, |task: &mut PingPongTask| {
+        (task.device, task.targets.read().0)
+    });
+    for _ in 0..SWAPS {
+        let (read, write) = wgpu_engine
+            .task_handle_cast_mut(&task, |task: &mut PingPongTask| {
+                (task.targets.read().0, task.targets.write().0)
+            })
+            .unwrap();
+        wgpu_engine
+            .submit_once(CommandBufferDescriptor {
+                label: String::from("Ping pong copy"),
+                device,
+                commands: vec![Command::TextureToTexture(TextureToTextureCopy {
+                    src_texture: read,
+                    src_mip_level: 0,
+                    src_origin: crate::wgpu::Origin3d::ZERO,
+                    dst_texture: write,
+                    dst_mip_level: 0,
+                    dst_origin: crate::wgpu::Origin3d::ZERO,
+                    copy_size: extent(),
+                })],
+                max_commands: None,
+            })
+            .unwrap();
+        wgpu_engine.task_handle_cast_mut(&task, |task: &mut PingPongTask| task.targets.swap());
+    }
+
+    let (read, readback) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut PingPongTask| {
+            (task.targets.read().0, task.readback)
+        })
+        .unwrap();
+    assert_ne!(read, first);
+    wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("Ping pong readback"),
+            device,
+            commands: vec![texture_readback(
+                read,
+                readback,
+                (SIZE, SIZE),
+                BYTES_PER_ROW,
+            )],
+            max_commands: None,
+        })
+        .unwrap();
+
+    let result = run_task(&mut wgpu_engine, &task, 1, |task: &mut PingPongTask| {
+        task.result.clone()
+    });
+    assert_eq!(result, Some(data()));
+}
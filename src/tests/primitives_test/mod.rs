@@ -0,0 +1,67 @@
+use crate::primitives::*;
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Normal of the triangle, assuming counter-clockwise front faces.
+fn normal(vertices: &[Vertex], triangle: &[u16]) -> [f32; 3] {
+    let a = vertices[triangle[0] as usize].position;
+    let b = vertices[triangle[1] as usize].position;
+    let c = vertices[triangle[2] as usize].position;
+    cross(sub(b, a), sub(c, a))
+}
+
+#[test]
+fn unit_quad_faces_forward() {
+    let (vertices, indices) = unit_quad();
+    assert_eq!(vertices.len(), 4);
+    assert_eq!(indices.len(), 6);
+    for triangle in indices.chunks(3) {
+        assert!(normal(&vertices, triangle)[2] > 0.0);
+    }
+}
+
+#[test]
+fn unit_cube_faces_outward() {
+    let (vertices, indices) = unit_cube();
+    assert_eq!(vertices.len(), 24);
+    assert_eq!(indices.len(), 36);
+    for triangle in indices.chunks(3) {
+        let centroid = triangle.iter().fold([0.0; 3], |sum, index| {
+            let position = vertices[*index as usize].position;
+            [
+                sum[0] + position[0],
+                sum[1] + position[1],
+                sum[2] + position[2],
+            ]
+        });
+        assert!(dot(normal(&vertices, triangle), centroid) > 0.0);
+    }
+    assert!(vertices
+        .iter()
+        .all(|vertex| vertex.position.iter().all(|value| value.abs() == 0.5)));
+}
+
+#[test]
+fn fullscreen_triangle_covers_clip_space() {
+    let vertices = fullscreen_triangle();
+    assert_eq!(vertices.len(), 3);
+    assert!(normal(&vertices, &[0, 1, 2])[2] > 0.0);
+    assert_eq!(
+        Vertex::layout().array_stride,
+        std::mem::size_of::<Vertex>() as u64
+    );
+}
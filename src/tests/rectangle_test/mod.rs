@@ -1,631 +1,613 @@
 use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine, texture_readback};
+use crate::BindGroupLayoutEntryExt;
 use crate::*;
 use bytemuck::{Pod, Zeroable};
-use inline_spirv::*;
-use std::num::NonZeroU32;
-use ultraviolet::{Mat4, Vec4};
-mod surface_manager;
-use std::path::PathBuf;
-use surface_manager::*;
-
-const VERTEX_SHADER_CODE: &[u32] = inline_spirv!(
-    r#"
-#version 450
-
-layout(location = 0) in vec3 position;
-layout(location = 1) in vec2 size;
-layout(location = 2) in uint index;
-
-layout(push_constant) uniform PushConstants {
-    mat4 projection_matrix;
-};
 
-layout(location = 0) out vec3 fragment_pos;
-layout(location = 1) flat out uint out_index;
+/**
+Every rectangle is drawn as a triangle strip over its corners, sampling its own image.
+The projection maps the pixels of the target to clip space, and the depth of the rectangles to `0.0..1.0`.
+*/
+const SHADER: &str = r#"
+[[block]]
+struct Projection {
+    matrix: mat4x4<f32>;
+};
+[[group(0), binding(0)]]
+var<uniform> projection: Projection;
 
-void main() {
-    switch (gl_VertexIndex) {
-        case 0:{
-            vec3 vertex = position;
+[[group(1), binding(0)]]
+var image: texture_2d<f32>;
+[[group(1), binding(1)]]
+var image_sampler: sampler;
 
-            vec4 projected_vertex = projection_matrix * vec4(vertex,1.0);
-            gl_Position = vec4(projected_vertex.xy,0.0,1.0);
-            fragment_pos = vec3(0.0,0.0,projected_vertex.z);
-            break;
-        }
-        case 1:{
-            vec3 vertex = vec3(position.x,position.y+size.y,position.z);
-
-            vec4 projected_vertex = projection_matrix * vec4(vertex,1.0);
-            gl_Position = vec4(projected_vertex.xy,0.0,1.0);
-            fragment_pos = vec3(0.0,1.0,projected_vertex.z);
-            break;
-        }
-        case 2:{
-            vec3 vertex = vec3(position.x+size.x,position.y,position.z);
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+};
 
-            vec4 projected_vertex = projection_matrix * vec4(vertex,1.0);
-            gl_Position = vec4(projected_vertex.xy,0.0,1.0);
-            fragment_pos = vec3(1.0,0.0,projected_vertex.z);
-            break;
-        }
-        case 3:{
-            vec3 vertex = vec3(position.x+size.x,position.y+size.y,position.z);
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] index: u32,
+    [[location(0)]] position: vec3<f32>,
+    [[location(1)]] size: vec2<f32>,
+) -> VertexOutput {
+    let corner = vec2<f32>(f32(index / 2u), f32(index % 2u));
+    var output: VertexOutput;
+    output.position = projection.matrix * vec4<f32>(position.xy + corner * size, position.z, 1.0);
+    output.uv = corner;
+    return output;
+}
 
-            vec4 projected_vertex = projection_matrix * vec4(vertex,1.0);
-            gl_Position = vec4(projected_vertex.xy,0.0,1.0);
-            fragment_pos = vec3(1.0,1.0,projected_vertex.z);
-            break;
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return textureSample(image, image_sampler, input.uv);
+}
+"#;
+
+/// Configuration of the [RectangleTask].
+#[derive(Debug, Clone)]
+pub struct RectangleTaskConfig {
+    /// Sampler used to sample every rectangle image.
+    pub sampler: SamplerDescriptor,
+    /// Maximum number of rectangles the task can draw, used to spread them on the depth range.
+    pub max_surfaces: u32,
+}
+impl RectangleTaskConfig {
+    /// Default configuration on the passed device: a linear sampler with 16x anisotropy and up to 1024 rectangles.
+    pub fn new(device: DeviceId) -> Self {
+        Self {
+            sampler: SamplerDescriptor {
+                label: String::from("RectangleTask sampler"),
+                device,
+                anisotropy_clamp: std::num::NonZeroU8::new(16),
+                min_filter: crate::wgpu::FilterMode::Linear,
+                mag_filter: crate::wgpu::FilterMode::Linear,
+                ..SamplerDescriptor::default()
+            },
+            max_surfaces: 1024,
         }
-        default:{
-            vec3 vertex = vec3(2.0,2.0,2.0);
+    }
 
-            gl_Position = vec4(vertex,1.0);
-            fragment_pos = vec3(0.0,0.0,0.0);
+    /// Check the configuration, that needs room for at least one rectangle.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_surfaces == 0 {
+            return Err(String::from("max_surfaces must be greater than 0"));
         }
+        Ok(())
     }
-
-    out_index = index;
 }
-"#,
-    vert
-);
-
-const FRAGMENT_SHADER_CODE: &[u32] = inline_spirv!(
-    r#"
-#version 450
-
-#extension GL_EXT_nonuniform_qualifier : require
-
-layout(location = 0) in vec3 fragment_position;
-layout(location = 1) nonuniformEXT flat in uint index;  // dynamically non-uniform
-layout(location = 0) out vec4 fragment_color;
-
-layout(set = 0, binding = 0) uniform texture2D textures[];
-layout(set = 0, binding = 1) uniform sampler samp;
-
-void main() {
-    vec4 color = vec4(texture(sampler2D(textures[index], samp), fragment_position.xy));
-    if(color.w == 0.0) {discard;}
-    else{fragment_color = color;}
 
-    gl_FragDepth = fragment_position.z;
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct Rectangle {
+    position: [f32; 3],
+    size: [f32; 2],
 }
-"#,
-    frag
-);
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
-pub struct PushConstants {
-    projection_matrix: Mat4,
+pub struct Projection {
+    matrix: [[f32; 4]; 4],
 }
-impl PushConstants {
-    pub fn new(target_surface_size: [u32; 2], max_surface_count: u32) -> Self {
-        let projection_matrix = Mat4::new(
-            Vec4::new(2.0 / target_surface_size[0] as f32, 0.0, 0.0, 0.0),
-            Vec4::new(0.0, -2.0 / target_surface_size[1] as f32, 0.0, 0.0),
-            Vec4::new(0.0, 0.0, 1.0 / max_surface_count as f32, 0.0),
-            Vec4::new(-1.0, 1.0, 0.0, 0.0),
-        );
-        Self { projection_matrix }
+impl Projection {
+    /// Map the pixels of the target to clip space, with the origin on the top left, and the depths `0..max_surfaces` to `0.0..1.0`.
+    pub fn new(target_size: [u32; 2], max_surfaces: u32) -> Self {
+        Self {
+            matrix: [
+                [2.0 / target_size[0] as f32, 0.0, 0.0, 0.0],
+                [0.0, -2.0 / target_size[1] as f32, 0.0, 0.0],
+                [0.0, 0.0, 1.0 / max_surfaces as f32, 0.0],
+                [-1.0, 1.0, 0.0, 1.0],
+            ],
+        }
     }
 }
 
-enum TaskEvent {
-    CreateSurface {
-        id: usize,
-        label: String,
-        source: SurfaceSource,
-        position: [u32; 3],
-        size: [u32; 2],
-    },
+/// Resources recorded in the command buffer of the [RectangleTask].
+struct RectanglePass {
+    device: DeviceId,
+    target: TextureViewId,
+    render_pipeline: RenderPipelineId,
+    projection_bind_group: BindGroupId,
+    vertex_buffer: BufferId,
+    /// Bind group of the image of every rectangle.
+    images: Vec<BindGroupId>,
 }
 
+/// Task drawing textured rectangles on a target, in the order they have been added.
 pub struct RectangleTask {
-    id: EntityId,
-    pending_events: Vec<TaskEvent>,
-
-    rectangle_manager: RectangleManager,
-
-    target_surface: EntityId,
-    target_surface_size: [u32; 2],
-
-    fragment_shader_id: EntityId,
-    vertex_shader_id: EntityId,
-    sampler_id: EntityId,
-
-    bind_group_layout_id: EntityId,
-    pipeline_layout_id: EntityId,
-    render_pipeline_id: EntityId,
-
-    bind_group_id: EntityId,
-    command_buffer_id: EntityId,
-
-    data_copy_command_buffer_id: EntityId,
-
-    command_buffers_to_execute: Vec<EntityId>,
+    max_surfaces: u32,
+    sampler: SamplerId,
+    image_layout: BindGroupLayoutId,
+    rectangles: Vec<Rectangle>,
+    pass: RectanglePass,
+    command_buffer: CommandBufferId,
 }
 
 impl RectangleTask {
     const TASK_NAME: &'static str = "RectangleTask";
 
+    /**
+    Task drawing on `target`, a view of a `target_size` texture, with the sampler and capacity of `config`.
+    Fails if the configuration is not valid.
+    */
     pub fn new(
-        id: EntityId,
         update_context: &mut UpdateContext,
-        target_surface: EntityId,
-        target_surface_size: [u32; 2],
-    ) -> Self {
-        let pending_events = Vec::new();
-        let task_name = Self::TASK_NAME.to_string();
-
-        let vertex_shader_descriptor = ShaderModuleDescriptor {
-            label: String::from("RectangleTask vertex shader"),
-            source: ShaderSource::SpirV(VERTEX_SHADER_CODE.to_vec()),
-            flags: crate::wgpu::ShaderFlags::empty(),
-        };
-        let vertex_shader_id = update_context
-            .add_resource_descriptor(vertex_shader_descriptor)
-            .unwrap();
-
-        let fragment_shader_descriptor = ShaderModuleDescriptor {
-            label: String::from("RectangleTask fragment shader"),
-            source: ShaderSource::SpirV(FRAGMENT_SHADER_CODE.to_vec()),
-            flags: crate::wgpu::ShaderFlags::empty(),
-        };
-        let fragment_shader_id = update_context
-            .add_resource_descriptor(fragment_shader_descriptor)
-            .unwrap();
-
-        let sampler_descriptor = SamplerDescriptor {
-            label: Self::TASK_NAME.to_string() + " index buffer",
-            anisotropy_clamp: Some(NonZeroU8::new(16).unwrap()),
-            min_filter: crate::wgpu::FilterMode::Linear,
-            mag_filter: crate::wgpu::FilterMode::Linear,
-            ..SamplerDescriptor::default()
-        };
-        let sampler_id = update_context.add_resource_descriptor(sampler_descriptor).unwrap();
-
-        let bind_group_layout = BindGroupLayoutDescriptor {
-            label: Self::TASK_NAME.to_string() + " bind group layout",
-            entries: vec![
-                crate::wgpu::BindGroupLayoutEntry {
+        target: TextureViewId,
+        target_size: [u32; 2],
+        config: RectangleTaskConfig,
+    ) -> Result<Self, ()> {
+        if let Err(error) = config.validate() {
+            log::error!(target: "RectangleTask","Invalid configuration: {}",error);
+            return Err(());
+        }
+        let target_descriptor = update_context
+            .texture_view_descriptor_ref(&target)
+            .ok_or(())?;
+        let (device, format) = (target_descriptor.device, target_descriptor.format);
+        let RectangleTaskConfig {
+            sampler,
+            max_surfaces,
+        } = config;
+
+        let shader_module =
+            update_context.add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string() + " shader",
+                device,
+                source: ShaderSource::Wgsl(SHADER.to_string()),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })?;
+        let sampler = update_context.add_sampler_descriptor(sampler)?;
+
+        let projection_buffer = update_context.add_buffer_descriptor(BufferDescriptor {
+            label: Self::TASK_NAME.to_string() + " projection",
+            device,
+            size: std::mem::size_of::<Projection>() as u64,
+            usage: crate::wgpu::BufferUsage::UNIFORM | crate::wgpu::BufferUsage::COPY_DST,
+        })?;
+        update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+            buffer: projection_buffer,
+            offset: 0,
+            data: bytemuck::bytes_of(&Projection::new(target_size, max_surfaces)).to_vec(),
+        })]);
+        let projection_layout =
+            update_context.add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " projection layout",
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry::uniform_buffer(
+                    0,
+                    crate::wgpu::ShaderStage::VERTEX,
+                )],
+            })?;
+        let projection_bind_group =
+            update_context.add_bind_group_descriptor(BindGroupDescriptor {
+                label: Self::TASK_NAME.to_string() + " projection bind group",
+                device,
+                layout: projection_layout,
+                entries: vec![BindGroupEntry {
                     binding: 0,
-                    visibility: crate::wgpu::ShaderStage::FRAGMENT,
-                    ty: crate::wgpu::BindingType::Texture {
-                        sample_type: crate::wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: crate::wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: NonZeroU32::new(0),
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: projection_buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                }],
+            })?;
+        let image_layout =
+            update_context.add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " image layout",
+                device,
+                entries: vec![
+                    crate::wgpu::BindGroupLayoutEntry::texture(
+                        0,
+                        crate::wgpu::ShaderStage::FRAGMENT,
+                    ),
+                    crate::wgpu::BindGroupLayoutEntry::sampler(1),
+                ],
+            })?;
+        let pipeline_layout =
+            update_context.add_pipeline_layout_descriptor(PipelineLayoutDescriptor {
+                label: Self::TASK_NAME.to_string() + " pipeline layout",
+                device,
+                bind_group_layouts: vec![projection_layout, image_layout],
+                push_constant_ranges: Vec::new(),
+            })?;
+        let render_pipeline =
+            update_context.add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                label: Self::TASK_NAME.to_string() + " pipeline",
+                device,
+                layout: Some(pipeline_layout),
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: vec![VertexBufferLayout {
+                        array_stride: std::mem::size_of::<Rectangle>()
+                            as crate::wgpu::BufferAddress,
+                        step_mode: crate::wgpu::InputStepMode::Instance,
+                        attributes: crate::wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2]
+                            .to_vec(),
+                    }],
                 },
-                crate::wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: crate::wgpu::ShaderStage::FRAGMENT,
-                    ty: crate::wgpu::BindingType::Sampler {
-                        comparison: false,
-                        filtering: true,
-                    },
-                    count: None,
+                primitive: crate::wgpu::PrimitiveState {
+                    topology: crate::wgpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
                 },
-            ],
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: String::from("fs_main"),
+                    targets: vec![crate::wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: crate::wgpu::ColorWrite::ALL,
+                    }],
+                }),
+            })?;
+        let vertex_buffer = update_context.add_buffer_descriptor(BufferDescriptor {
+            label: Self::TASK_NAME.to_string() + " rectangles",
+            device,
+            size: (std::mem::size_of::<Rectangle>() * max_surfaces as usize) as u64,
+            usage: crate::wgpu::BufferUsage::VERTEX | crate::wgpu::BufferUsage::COPY_DST,
+        })?;
+
+        let pass = RectanglePass {
+            device,
+            target,
+            render_pipeline,
+            projection_bind_group,
+            vertex_buffer,
+            images: Vec::new(),
         };
-        let bind_group_layout_id = update_context.add_resource_descriptor(bind_group_layout).unwrap();
-
-        let aligned_size = ((std::mem::size_of::<PushConstants>() + 4 - 1) / 4) * 4;
-        let pipeline_layout_descriptor = PipelineLayoutDescriptor {
-            label: task_name.clone(),
-            bind_group_layouts: vec![bind_group_layout_id],
-            push_constant_ranges: vec![crate::wgpu::PushConstantRange {
-                stages: crate::wgpu::ShaderStage::VERTEX,
-                range: 0..aligned_size as u32,
-            }],
-        };
-        let pipeline_layout_id = update_context
-            .add_resource_descriptor(pipeline_layout_descriptor)
-            .unwrap();
+        let command_buffer = update_context.add_command_buffer_descriptor(pass.descriptor())?;
+        Ok(Self {
+            max_surfaces,
+            sampler,
+            image_layout,
+            rectangles: Vec::new(),
+            pass,
+            command_buffer,
+        })
+    }
 
-        let render_pipeline_descriptor = RenderPipelineDescriptor {
-            label: task_name,
-            layout: Some(pipeline_layout_id),
-            vertex: VertexState {
-                module: vertex_shader_id,
-                entry_point: String::from("main"),
-                buffers: vec![VertexBufferLayout {
-                    array_stride: std::mem::size_of::<Rectangle>() as crate::wgpu::BufferAddress,
-                    step_mode: crate::wgpu::InputStepMode::Instance,
-                    attributes: crate::wgpu::vertex_attr_array![
-                        0 => Float32x3,
-                        1 => Float32x2,
-                        2 => Uint32,
-                    ]
-                    .to_vec(),
-                }],
-            },
-            primitive: crate::wgpu::PrimitiveState {
-                //front_face: crate::wgpu::FrontFace::Ccw,
-                topology: crate::wgpu::PrimitiveTopology::TriangleStrip,
-                ..Default::default()
-            },
-            multisample: crate::wgpu::MultisampleState::default(),
-            fragment: Some(FragmentState {
-                module: fragment_shader_id,
-                entry_point: String::from("main"),
-                targets: Vec::new(),
-            }),
-            surface_id: target_surface,
+    /**
+    Add a rectangle at `position` (in pixels, with the depth as third coordinate) of `size` pixels,
+    showing the RGBA8 `image` of `image_size` texels. Fails if `max_surfaces` rectangles have been added already.
+    */
+    pub fn add_rectangle(
+        &mut self,
+        update_context: &mut UpdateContext,
+        position: [u32; 3],
+        size: [u32; 2],
+        image_size: [u32; 2],
+        image: Vec<u8>,
+    ) -> Result<(), ()> {
+        if self.rectangles.len() >= self.max_surfaces as usize {
+            log::error!(target: "RectangleTask","Failed to add a rectangle: the {} rectangles of the configuration are in use",self.max_surfaces);
+            return Err(());
+        }
+        let index = self.rectangles.len();
+        let label = format!("{} rectangle {}", Self::TASK_NAME, index);
+        let format = crate::wgpu::TextureFormat::Rgba8UnormSrgb;
+        let extent = crate::wgpu::Extent3d {
+            width: image_size[0],
+            height: image_size[1],
+            depth_or_array_layers: 1,
         };
-        let render_pipeline_id = update_context
-            .add_resource_descriptor(render_pipeline_descriptor)
-            .unwrap();
 
-        let bind_group_descriptor = BindGroupDescriptor {
-            label: Self::TASK_NAME.to_string() + " bind group",
+        let device = self.pass.device;
+        let texture = update_context.add_texture_descriptor(TextureDescriptor {
+            label: label.clone() + " image",
+            device,
+            source: TextureSource::Local,
+            usage: crate::wgpu::TextureUsage::SAMPLED | crate::wgpu::TextureUsage::COPY_DST,
+            size: extent,
+            format,
+            dimension: crate::wgpu::TextureDimension::D2,
+            mip_level_count: 1,
+            sample_count: 1,
+        })?;
+        update_context.write_resource(&mut vec![ResourceWrite::Texture(TextureWrite {
+            texture,
+            mip_level: 0,
+            origin: crate::wgpu::Origin3d::ZERO,
+            data: image,
+            layout: crate::wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(image_size[0] * 4),
+                rows_per_image: std::num::NonZeroU32::new(image_size[1]),
+            },
+            size: extent,
+        })]);
+        let view = update_context.add_texture_view_descriptor(TextureViewDescriptor::layer(
+            label.clone() + " image view",
+            device,
+            texture,
+            format,
+            0,
+        ))?;
+        let bind_group = update_context.add_bind_group_descriptor(BindGroupDescriptor {
+            label: label + " bind group",
+            device,
+            layout: self.image_layout,
             entries: vec![
                 BindGroupEntry {
                     binding: 0,
-                    resource: BindingResource::TextureViewArray(Vec::new()),
+                    resource: BindingResource::TextureView(view),
                 },
                 BindGroupEntry {
                     binding: 1,
-                    resource: BindingResource::Sampler(sampler_id),
+                    resource: BindingResource::Sampler(self.sampler),
                 },
             ],
-            layout: bind_group_layout_id,
-        };
-        let bind_group_id = update_context.add_resource_descriptor(bind_group_descriptor).unwrap();
+        })?;
 
-        let command_buffer_descriptor = CommandBufferDescriptor {
-            label: Self::TASK_NAME.to_string() + " command buffer",
-            commands: vec![Command::RenderPass(target_surface, Vec::new())],
+        let rectangle = Rectangle {
+            position: [position[0] as f32, position[1] as f32, position[2] as f32],
+            size: [size[0] as f32, size[1] as f32],
         };
-        let command_buffer_id = update_context
-            .add_resource_descriptor(command_buffer_descriptor)
-            .unwrap();
-
-        let data_copy_command_buffer_descriptor = CommandBufferDescriptor {
-            label: Self::TASK_NAME.to_string() + " data copy command buffer",
-            commands: vec![],
-        };
-        let data_copy_command_buffer_id = update_context
-            .add_resource_descriptor(data_copy_command_buffer_descriptor)
-            .unwrap();
-
-        let command_buffers_to_execute = Vec::new();
-
-        let rectangle_manager = RectangleManager::new(update_context);
-
-        Self {
-            id,
-
-            pending_events,
-            rectangle_manager,
-
-            target_surface,
-            target_surface_size,
-
-            vertex_shader_id,
-            fragment_shader_id,
-            sampler_id,
+        update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+            buffer: self.pass.vertex_buffer,
+            offset: (index * std::mem::size_of::<Rectangle>()) as u64,
+            data: bytemuck::bytes_of(&rectangle).to_vec(),
+        })]);
+        self.rectangles.push(rectangle);
+        self.pass.images.push(bind_group);
+
+        let descriptor = self.pass.descriptor();
+        if update_context.update_command_buffer_descriptor(&mut self.command_buffer, descriptor) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
 
-            bind_group_layout_id,
-            pipeline_layout_id,
-            render_pipeline_id,
-            bind_group_id,
-            command_buffer_id,
+impl RectanglePass {
+    /// Command buffer clearing the target and drawing every rectangle with its own image.
+    fn descriptor(&self) -> CommandBufferDescriptor {
+        let mut commands = vec![
+            RenderCommand::SetPipeline {
+                pipeline: self.render_pipeline,
+            },
+            RenderCommand::SetBindGroup {
+                index: 0,
+                bind_group: self.projection_bind_group,
+                offsets: Vec::new(),
+            },
+            RenderCommand::SetVertexBuffer {
+                slot: 0,
+                buffer: self.vertex_buffer,
+                slice: Slice::from(..),
+            },
+        ];
+        for (index, bind_group) in self.images.iter().enumerate() {
+            let index = index as u32;
+            commands.push(RenderCommand::SetBindGroup {
+                index: 1,
+                bind_group: *bind_group,
+                offsets: Vec::new(),
+            });
+            commands.push(RenderCommand::Draw {
+                vertices: 0..4,
+                instances: index..index + 1,
+            });
+        }
 
-            data_copy_command_buffer_id,
-            command_buffers_to_execute,
+        CommandBufferDescriptor {
+            label: Self::TASK_NAME.to_string() + " command buffer",
+            device: self.device,
+            commands: vec![Command::RenderPass {
+                label: Self::TASK_NAME.to_string(),
+                depth_stencil: None,
+                color_attachments: vec![RenderPassColorAttachment::clear(
+                    ColorView::TextureView(self.target),
+                    crate::wgpu::Color::TRANSPARENT,
+                )],
+                commands,
+            }],
+            max_commands: None,
         }
     }
+}
 
-    pub fn create_surface(
-        &mut self,
-        label: String,
-        source: SurfaceSource,
-        position: [u32; 3],
-        size: [u32; 2],
-    ) -> usize {
-        let id = self.rectangle_manager.book_id();
-        self.pending_events.push(TaskEvent::CreateSurface {
-            id,
-            label,
-            source,
-            position,
-            size,
-        });
-        id
+impl TaskTrait for RectangleTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, _update_context: &mut UpdateContext) {}
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        vec![self.command_buffer]
     }
+}
 
-    pub fn resize_surface(&mut self) {}
+const SIZE: u32 = 8;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+const FORMAT: crate::wgpu::TextureFormat = crate::wgpu::TextureFormat::Rgba8UnormSrgb;
+/// 2x2 image with a red, green, blue and white texel.
+const IMAGE: [[u8; 4]; 4] = [
+    [255, 0, 0, 255],
+    [0, 255, 0, 255],
+    [0, 0, 255, 255],
+    [255, 255, 255, 255],
+];
+
+/// [RectangleTask] drawing a single rectangle over its whole target, then reading the target back.
+pub struct RectangleReadbackTask {
+    rectangles: RectangleTask,
+    readback: BufferId,
+    readback_command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
 
-    fn update_bind_group_and_command_buffer(
-        &mut self,
+impl RectangleReadbackTask {
+    const TASK_NAME: &'static str = "RectangleReadbackTask";
+
+    /// Task using the configuration returned by `config` for the device.
+    pub fn new(
         update_context: &mut UpdateContext,
-        push_constants: Vec<u8>,
-    ) {
-        let bind_group_layout = BindGroupLayoutDescriptor {
-            label: Self::TASK_NAME.to_string() + " bind group layout",
-            entries: vec![
-                crate::wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: crate::wgpu::ShaderStage::FRAGMENT,
-                    ty: crate::wgpu::BindingType::Texture {
-                        sample_type: crate::wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: crate::wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: NonZeroU32::new(self.rectangle_manager.len() as u32),
-                },
-                crate::wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: crate::wgpu::ShaderStage::FRAGMENT,
-                    ty: crate::wgpu::BindingType::Sampler {
-                        comparison: false,
-                        filtering: true,
-                    },
-                    count: None,
+        config: impl FnOnce(DeviceId) -> RectangleTaskConfig,
+    ) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let target = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " target",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT
+                    | crate::wgpu::TextureUsage::COPY_SRC,
+                size: crate::wgpu::Extent3d {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: 1,
                 },
-            ],
-        };
-        update_context
-            .update_resource(&self.bind_group_layout_id, bind_group_layout)
+                format: FORMAT,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
             .unwrap();
-
-        let bind_group_descriptor = BindGroupDescriptor {
-            label: Self::TASK_NAME.to_string() + " bind group",
-            entries: vec![
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureViewArray(
-                        self.rectangle_manager.rectangle_views(),
-                    ),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Sampler(self.sampler_id),
-                },
-            ],
-            layout: self.bind_group_layout_id,
-        };
-        update_context
-            .update_resource(&self.bind_group_id, bind_group_descriptor)
+        let target_view = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                Self::TASK_NAME.to_string() + " target view",
+                device,
+                target,
+                FORMAT,
+                0,
+            ))
             .unwrap();
-
-        let command_buffer_descriptor = CommandBufferDescriptor {
-            label: Self::TASK_NAME.to_string() + " command buffer",
-            commands: vec![Command::RenderPass(
-                self.target_surface,
-                vec![
-                    RenderCommand::SetPipeline {
-                        pipeline: self.render_pipeline_id,
-                    },
-                    RenderCommand::SetPushConstants {
-                        stages: crate::wgpu::ShaderStage::VERTEX,
-                        offset: 0,
-                        data: push_constants,
-                    },
-                    RenderCommand::SetBindGroup {
-                        index: 0,
-                        bind_group: self.bind_group_id,
-                        offsets: Vec::new(),
-                    },
-                    RenderCommand::SetVertexBuffer {
-                        slot: 0,
-                        buffer: *self.rectangle_manager.buffer_id(),
-                        slice: Slice::from(..),
-                    },
-                    RenderCommand::Draw {
-                        vertices: 0..4,
-                        instances: 0..self.rectangle_manager.len() as u32,
-                    },
-                ],
-            )],
-        };
-        update_context
-            .update_resource(&self.command_buffer_id, command_buffer_descriptor)
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let readback_command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback command buffer",
+                device,
+                commands: vec![texture_readback(
+                    target,
+                    readback,
+                    (SIZE, SIZE),
+                    BYTES_PER_ROW,
+                )],
+                max_commands: None,
+            })
             .unwrap();
-    }
-
-    fn elaborate_events(&mut self, update_context: &mut UpdateContext) {
-        let mut update = false;
-        for event in self.pending_events.drain(..) {
-            match event {
-                TaskEvent::CreateSurface {
-                    id,
-                    label,
-                    source,
-                    position,
-                    size,
-                } => {
-                    self.rectangle_manager.create_surface(
-                        update_context,
-                        label,
-                        id,
-                        source,
-                        position,
-                        size,
-                    );
-                    update = true;
-                }
-            }
-        }
 
-        if update {
-            let push_constants = PushConstants::new(self.target_surface_size, 1024);
-            self.update_bind_group_and_command_buffer(
+        let mut rectangles =
+            RectangleTask::new(update_context, target_view, [SIZE, SIZE], config(device)).unwrap();
+        rectangles
+            .add_rectangle(
                 update_context,
-                bytemuck::bytes_of(&push_constants).to_vec(),
-            );
-        }
-
-        let mut command_buffers_to_execute = Vec::new();
-        command_buffers_to_execute.push(self.command_buffer_id);
+                [0, 0, 0],
+                [SIZE, SIZE],
+                [2, 2],
+                IMAGE.concat(),
+            )
+            .unwrap();
 
-        let commands = self.rectangle_manager.update(update_context);
-        if !commands.is_empty() {
-            let data_copy_command_buffer_descriptor = CommandBufferDescriptor {
-                label: Self::TASK_NAME.to_string() + " data copy command buffer",
-                commands,
-            };
-            update_context
-                .update_resource(
-                    &self.data_copy_command_buffer_id,
-                    data_copy_command_buffer_descriptor,
-                )
-                .unwrap();
-            command_buffers_to_execute.push(self.data_copy_command_buffer_id);
+        Self {
+            rectangles,
+            readback,
+            readback_command_buffer,
+            frame: 0,
+            result: None,
         }
-
-        self.command_buffers_to_execute = command_buffers_to_execute;
     }
 }
 
-impl TaskTrait for RectangleTaskTrait {
+impl TaskTrait for RectangleReadbackTask {
     fn name(&self) -> String {
         Self::TASK_NAME.to_string()
     }
-
     fn update_resources(&mut self, update_context: &mut UpdateContext) {
-        self.elaborate_events(update_context);
+        self.rectangles.update_resources(update_context);
+        if self.frame == 1 {
+            self.result = update_context
+                .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE) as u64)
+                .ok();
+        }
+        self.frame += 1;
     }
-    fn command_buffers(&self) -> Vec<EntityId> {
-        self.command_buffers_to_execute.clone()
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        if self.frame == 1 {
+            let mut command_buffers = self.rectangles.command_buffers();
+            command_buffers.push(self.readback_command_buffer);
+            command_buffers
+        } else {
+            Vec::new()
+        }
     }
 }
 
-impl AsRef<EntityId> for RectangleTask {
-    fn as_ref(&self) -> &EntityId {
-        &self.id
+/// Draw the [IMAGE] rectangle with the configuration returned by `config`, and read back the target.
+fn rectangle_texels(
+    config: impl 'static + Fn(DeviceId) -> RectangleTaskConfig,
+) -> impl Fn(u32, u32) -> Vec<u8> {
+    let (mut wgpu_engine, task) = single_task_engine(
+        RectangleReadbackTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        move |update_context| RectangleReadbackTask::new(update_context, &config),
+    );
+    let data = run_task(
+        &mut wgpu_engine,
+        &task,
+        2,
+        |task: &mut RectangleReadbackTask| task.result.clone(),
+    )
+    .unwrap();
+    move |x, y| {
+        let offset = (y * BYTES_PER_ROW + x * 4) as usize;
+        data[offset..offset + 4].to_vec()
     }
 }
 
 #[test]
-fn rectangle_task() {
-    use std::collections::HashMap;
-    env_logger::init();
-    use crate::WGpuEngine;
-    use pal::definitions::*;
-
-    let features = crate::wgpu::Features::EXTERNAL_MEMORY
-        | crate::wgpu::Features::PUSH_CONSTANTS
-        | crate::wgpu::Features::UNSIZED_BINDING_ARRAY
-        | crate::wgpu::Features::SAMPLED_TEXTURE_BINDING_ARRAY
-        | crate::wgpu::Features::SAMPLED_TEXTURE_ARRAY_DYNAMIC_INDEXING
-        | crate::wgpu::Features::SAMPLED_TEXTURE_ARRAY_NON_UNIFORM_INDEXING;
-
-    let mut limits = crate::wgpu::Limits::default();
-    limits.max_push_constant_size = std::mem::size_of::<PushConstants>() as u32;
-
-    let mut wgpu_engine = WGpuEngine::new(features, limits.clone());
-
-    let mut platform = pal::Platform::new(vec![Box::new(wgpu_engine.wgpu_context())]);
-    platform.request(vec![Request::from(SurfaceRequest::Create(None))]);
-
-    let surfaces: HashMap<SurfaceId, (EntityId, [u32; 2])> = platform
-        .events()
-        .into_iter()
-        .filter_map(|event| match event {
-            pal::Event::Surface(ref surface_event) => {
-                let surface_id = surface_event.id;
-                match &surface_event.event_type {
-                    pal::SurfaceEventType::Added(surface_info) => {
-                        if let Surface::WGpu(surface) = &surface_info.surface {
-                            let resource_id = wgpu_engine.create_surface(
-                                String::from("MainSurface"),
-                                surface.clone(),
-                                surface_info.size.width,
-                                surface_info.size.height,
-                            );
-
-                            match resource_id {
-                                Ok(resource_id) => Some((
-                                    surface_id,
-                                    (
-                                        resource_id,
-                                        [surface_info.size.width, surface_info.size.height],
-                                    ),
-                                )),
-                                Err(_) => None,
-                            }
-                        } else {
-                            panic!("It is not of WGpu type");
-                        }
-                    }
-                    _ => None,
-                }
-            }
-            _ => None,
-        })
-        .collect();
-
-    let target_surface = *surfaces.values().next().unwrap();
-
-    let mut task = wgpu_engine
-        .create_task(
-            "RectangleTask".into(),
-            features,
-            limits,
-            move |context, resources| {
-                RectangleTask::new(context, resources, target_surface.0, target_surface.1)
+fn rectangle_task_nearest_config() {
+    let texel = rectangle_texels(|device| {
+        let config = RectangleTaskConfig::new(device);
+        RectangleTaskConfig {
+            sampler: SamplerDescriptor {
+                label: String::from("Pixel art sampler"),
+                anisotropy_clamp: None,
+                min_filter: crate::wgpu::FilterMode::Nearest,
+                mag_filter: crate::wgpu::FilterMode::Nearest,
+                ..config.sampler
             },
-        )
-        .unwrap();
-
-    task.create_surface(
-        String::from("Surface"),
-        SurfaceSource::File {
-            path: PathBuf::from("/home/fabio/wgpu_engine/src/logo.png"),
-        },
-        [150, 150, 0],
-        [100, 100],
-    );
-    //task.create_surface_from_file(String::from("/home/fabio/wgpu_engine/src/logo.png"));
-
-    let mut tasks = vec![task];
-    'main_loop: loop {
-        for event in platform.events() {
-            match event {
-                pal::Event::Surface(ref surface_event) => {
-                    let surface_id = surface_event.id;
-                    let resource_id = match surfaces.get(&surface_id) {
-                        Some(resource_id) => resource_id.0,
-                        None => continue,
-                    };
-                    match &surface_event.event_type {
-                        pal::SurfaceEventType::Added(surface_info) => {
-                            if let Surface::WGpu(surface) = &surface_info.surface {
-                                wgpu_engine
-                                    .create_surface(
-                                        String::from("MainSurface"),
-                                        surface.clone(),
-                                        surface_info.size.width,
-                                        surface_info.size.height,
-                                    )
-                                    .unwrap();
-                            } else {
-                                panic!("It is not of WGpu type");
-                            }
-                        }
-                        pal::SurfaceEventType::Resized(size) => {
-                            wgpu_engine
-                                .resize_surface(&resource_id, size.width, size.height)
-                                .unwrap();
-                        }
-                        pal::SurfaceEventType::Removed => {
-                            wgpu_engine.destroy_surface(resource_id);
-                            if wgpu_engine.surface_count() == 0 {
-                                break 'main_loop;
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            }
+            max_surfaces: 16,
         }
+    });
+    // Every image texel covers a 4x4 block, with sharp edges even next to the other texels.
+    assert_eq!(texel(0, 0), IMAGE[0].to_vec());
+    assert_eq!(texel(3, 0), IMAGE[0].to_vec());
+    assert_eq!(texel(4, 0), IMAGE[1].to_vec());
+    assert_eq!(texel(3, 4), IMAGE[2].to_vec());
+    assert_eq!(texel(7, 7), IMAGE[3].to_vec());
+}
 
-        wgpu_engine.dispatch_tasks(&mut tasks);
-        std::thread::sleep(std::time::Duration::from_secs(3));
-    }
+#[test]
+fn rectangle_task_default_config_filters_linearly() {
+    let texel = rectangle_texels(RectangleTaskConfig::new);
+    // Next to the other texels the default linear sampler blends them.
+    assert_ne!(texel(3, 0), IMAGE[0].to_vec());
+    assert_ne!(texel(3, 0), IMAGE[1].to_vec());
+}
+
+#[test]
+fn rectangle_task_config_needs_a_surface() {
+    let device = DeviceId::new(crate::entity_manager::EntityId::new(0));
+    let config = RectangleTaskConfig::new(device);
+    assert!(config.validate().is_ok());
+    let none = RectangleTaskConfig {
+        max_surfaces: 0,
+        ..config
+    };
+    assert!(none.validate().is_err());
 }
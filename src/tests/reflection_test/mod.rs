@@ -0,0 +1,245 @@
+use crate::*;
+
+/**
+Module with the set 0 bindings of the rectangle fragment shader: a binding array of `texture_count` textures
+(unsized if `None`) and a sampler, both accessed by the fragment entry point through a sampling function.
+The naga SPIR-V frontend rejects indexing binding arrays (SPV_EXT_descriptor_indexing), so the module is built directly.
+*/
+fn rectangle_fragment_module(texture_count: Option<u32>) -> naga::Module {
+    let mut module = naga::Module::default();
+    let image = module.types.fetch_or_append(naga::Type {
+        name: None,
+        inner: naga::TypeInner::Image {
+            dim: naga::ImageDimension::D2,
+            arrayed: false,
+            class: naga::ImageClass::Sampled {
+                kind: naga::ScalarKind::Float,
+                multi: false,
+            },
+        },
+    });
+    let size = match texture_count {
+        Some(count) => naga::ArraySize::Constant(module.constants.append(naga::Constant {
+            name: None,
+            specialization: None,
+            inner: naga::ConstantInner::Scalar {
+                width: 4,
+                value: naga::ScalarValue::Uint(count as u64),
+            },
+        })),
+        None => naga::ArraySize::Dynamic,
+    };
+    let textures = module.types.fetch_or_append(naga::Type {
+        name: None,
+        inner: naga::TypeInner::Array {
+            base: image,
+            size,
+            stride: 0,
+        },
+    });
+    let sampler = module.types.fetch_or_append(naga::Type {
+        name: None,
+        inner: naga::TypeInner::Sampler { comparison: false },
+    });
+
+    let mut sample = naga::Function::default();
+    for (binding, ty) in [(0, textures), (1, sampler)].iter() {
+        let global = module.global_variables.append(naga::GlobalVariable {
+            name: None,
+            class: naga::StorageClass::Handle,
+            binding: Some(naga::ResourceBinding {
+                group: 0,
+                binding: *binding,
+            }),
+            ty: *ty,
+            init: None,
+            storage_access: naga::StorageAccess::empty(),
+        });
+        sample
+            .expressions
+            .append(naga::Expression::GlobalVariable(global));
+    }
+    let sample = module.functions.append(sample);
+
+    let mut main = naga::Function::default();
+    main.body.push(naga::Statement::Call {
+        function: sample,
+        arguments: Vec::new(),
+        result: None,
+    });
+    module.entry_points.push(naga::EntryPoint {
+        name: String::from("main"),
+        stage: naga::ShaderStage::Fragment,
+        early_depth_test: None,
+        workgroup_size: [0; 3],
+        function: main,
+    });
+    module
+}
+
+#[test]
+fn reflect_rectangle_fragment_bindings() {
+    let texture_entry = |count| crate::wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: crate::wgpu::ShaderStage::FRAGMENT,
+        ty: crate::wgpu::BindingType::Texture {
+            sample_type: crate::wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: crate::wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count,
+    };
+    let sampler_entry = crate::wgpu::BindGroupLayoutEntry {
+        binding: 1,
+        visibility: crate::wgpu::ShaderStage::FRAGMENT,
+        ty: crate::wgpu::BindingType::Sampler {
+            comparison: false,
+            filtering: true,
+        },
+        count: None,
+    };
+
+    // The runtime sized array of the rectangle shader.
+    let module = rectangle_fragment_module(None);
+    assert_eq!(
+        crate::utils::reflection::module_bind_group_layout(&module, 0).unwrap(),
+        vec![texture_entry(None), sampler_entry]
+    );
+    assert!(
+        crate::utils::reflection::module_bind_group_layout(&module, 1)
+            .unwrap()
+            .is_empty()
+    );
+
+    let module = rectangle_fragment_module(Some(16));
+    assert_eq!(
+        crate::utils::reflection::module_bind_group_layout(&module, 0).unwrap(),
+        vec![texture_entry(std::num::NonZeroU32::new(16)), sampler_entry]
+    );
+}
+
+/// Rectangle vertex shader, compiled from the GLSL source of the rectangle test.
+const RECTANGLE_VERTEX_SHADER: &[u8] = include_bytes!("rectangle.vert.spv");
+
+#[test]
+fn infer_rectangle_vertex_layout() {
+    let source = ShaderSource::SpirV(
+        RECTANGLE_VERTEX_SHADER
+            .chunks(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect(),
+    );
+    let layout = RenderPipelineDescriptor::infer_vertex_layout(
+        &source,
+        "main",
+        crate::wgpu::InputStepMode::Instance,
+    )
+    .unwrap();
+
+    let expected = VertexBufferLayout {
+        array_stride: (std::mem::size_of::<[f32; 3]>()
+            + std::mem::size_of::<[f32; 2]>()
+            + std::mem::size_of::<u32>()) as crate::wgpu::BufferAddress,
+        step_mode: crate::wgpu::InputStepMode::Instance,
+        attributes: crate::wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x2,
+            2 => Uint32,
+        ]
+        .to_vec(),
+    };
+    assert_eq!(layout, expected);
+    // The projection matrix is a push constant, so the shader has no bindings.
+    assert!(reflect_bind_group_layout(&source, 0).unwrap().is_empty());
+
+    assert_eq!(
+        RenderPipelineDescriptor::infer_vertex_layout(
+            &source,
+            "missing",
+            crate::wgpu::InputStepMode::Vertex,
+        ),
+        Err(ReflectionError::MissingEntryPoint(String::from("missing")))
+    );
+}
+
+/// Shader sampling the same texture in both stages.
+const SHARED_TEXTURE_SHADER: &str = r#"
+[[group(0), binding(0)]]
+var texture: texture_2d<f32>;
+[[group(0), binding(1)]]
+var samp: sampler;
+
+[[stage(vertex)]]
+fn vs_main([[location(0)]] position: vec2<f32>) -> [[builtin(position)]] vec4<f32> {
+    let height = textureSampleLevel(texture, samp, position, 0.0).r;
+    return vec4<f32>(position, height, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return textureSample(texture, samp, vec2<f32>(0.5, 0.5));
+}
+"#;
+
+#[test]
+fn visibility_mismatch() {
+    let source = ShaderSource::Wgsl(SHARED_TEXTURE_SHADER.to_string());
+    let mut entries = reflect_bind_group_layout(&source, 0).unwrap();
+    assert!(
+        check_visibility(&source, &["vs_main", "fs_main"], 0, &entries)
+            .unwrap()
+            .is_empty()
+    );
+
+    // Visibility declared for the fragment stage only, like forgetting the vertex usage.
+    entries
+        .iter_mut()
+        .for_each(|entry| entry.visibility = crate::wgpu::ShaderStage::FRAGMENT);
+    assert!(check_visibility(&source, &["fs_main"], 0, &entries)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        check_visibility(&source, &["vs_main", "fs_main"], 0, &entries).unwrap(),
+        vec![
+            (0, crate::wgpu::ShaderStage::VERTEX),
+            (1, crate::wgpu::ShaderStage::VERTEX)
+        ]
+    );
+
+    // A binding missing from the layout reports every stage using it.
+    entries.truncate(1);
+    assert_eq!(
+        check_visibility(&source, &["fs_main"], 0, &entries).unwrap(),
+        vec![(1, crate::wgpu::ShaderStage::FRAGMENT)]
+    );
+}
+
+#[test]
+fn compute_visibility_mismatch() {
+    let source = ShaderSource::Wgsl(String::from(
+        r#"
+[[block]]
+struct Values {
+    values: [[stride(4)]] array<u32>;
+};
+[[group(0), binding(0)]]
+var<storage> values: [[access(read_write)]] Values;
+
+[[stage(compute), workgroup_size(64)]]
+fn cs_main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    values.values[id.x] = values.values[id.x] * 2u;
+}
+"#,
+    ));
+    let mut entries = reflect_bind_group_layout(&source, 0).unwrap();
+    assert!(check_visibility(&source, &["cs_main"], 0, &entries)
+        .unwrap()
+        .is_empty());
+
+    // A storage buffer shared with a render pipeline, but declared visible to the fragment stage only.
+    entries[0].visibility = crate::wgpu::ShaderStage::FRAGMENT;
+    assert_eq!(
+        check_visibility(&source, &["cs_main"], 0, &entries).unwrap(),
+        vec![(0, crate::wgpu::ShaderStage::COMPUTE)]
+    );
+}
@@ -0,0 +1,293 @@
+use crate::entity_manager::UpdateContext;
+use crate::*;
+
+const SIZE: u32 = 4;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+const FORMAT: crate::wgpu::TextureFormat = crate::wgpu::TextureFormat::Rgba8Unorm;
+const SHADER: &str = "
+[[stage(vertex)]]
+fn vs_main([[location(0)]] position: vec2<f32>) -> [[builtin(position)]] vec4<f32> {
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_red() -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_green() -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+}
+";
+
+/// Two quads covering the left and the right half of the target.
+fn vertices() -> Vec<[f32; 2]> {
+    let quad = |left: f32, right: f32| {
+        vec![
+            [left, -1.0],
+            [right, -1.0],
+            [right, 1.0],
+            [left, -1.0],
+            [right, 1.0],
+            [left, 1.0],
+        ]
+    };
+    quad(-1.0, 0.0).into_iter().chain(quad(0.0, 1.0)).collect()
+}
+
+pub struct RenderBundleTask {
+    vertex_buffer: BufferId,
+    readback: BufferId,
+    bundles: Vec<RenderBundleId>,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl RenderBundleTask {
+    const TASK_NAME: &'static str = "RenderBundleTask";
+
+    fn pipeline(
+        update_context: &mut UpdateContext,
+        device: DeviceId,
+        shader_module: ShaderModuleId,
+        entry_point: &str,
+    ) -> RenderPipelineId {
+        update_context
+            .add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                label: Self::TASK_NAME.to_string() + " " + entry_point,
+                device,
+                layout: None,
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: vec![VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 2]>() as u64,
+                        step_mode: crate::wgpu::InputStepMode::Vertex,
+                        attributes: vec![crate::wgpu::VertexAttribute {
+                            format: crate::wgpu::VertexFormat::Float32x2,
+                            offset: 0,
+                            shader_location: 0,
+                        }],
+                    }],
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: entry_point.to_string(),
+                    targets: vec![crate::wgpu::ColorTargetState {
+                        format: FORMAT,
+                        blend: None,
+                        write_mask: crate::wgpu::ColorWrite::ALL,
+                    }],
+                }),
+            })
+            .unwrap()
+    }
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let target = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " target",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT
+                    | crate::wgpu::TextureUsage::COPY_SRC,
+                size: crate::wgpu::Extent3d {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: 1,
+                },
+                format: FORMAT,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        let target_view = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                Self::TASK_NAME.to_string() + " target view",
+                device,
+                target,
+                FORMAT,
+                0,
+            ))
+            .unwrap();
+        let vertex_buffer = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " vertex buffer",
+                device,
+                size: (vertices().len() * std::mem::size_of::<[f32; 2]>()) as u64,
+                usage: crate::wgpu::BufferUsage::VERTEX | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        let shader_module = update_context
+            .add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                source: ShaderSource::Wgsl(SHADER.to_string()),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })
+            .unwrap();
+        let red = Self::pipeline(update_context, device, shader_module, "fs_red");
+        let green = Self::pipeline(update_context, device, shader_module, "fs_green");
+
+        // Every half is recorded by its own closure.
+        let closures: Vec<_> = vec![(red, 0..6), (green, 6..12)]
+            .into_iter()
+            .map(|(pipeline, vertices)| {
+                move || {
+                    vec![
+                        RenderCommand::SetPipeline { pipeline },
+                        RenderCommand::SetVertexBuffer {
+                            slot: 0,
+                            buffer: vertex_buffer,
+                            slice: Slice::from(..),
+                        },
+                        RenderCommand::Draw {
+                            vertices,
+                            instances: 0..1,
+                        },
+                    ]
+                }
+            })
+            .collect();
+        let bundles = update_context.record_bundle_parallel(device, FORMAT, closures);
+        assert_eq!(bundles.len(), 2);
+
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![
+                    Command::RenderPass {
+                        label: Self::TASK_NAME.to_string(),
+                        depth_stencil: None,
+                        color_attachments: vec![RenderPassColorAttachment::clear(
+                            ColorView::TextureView(target_view),
+                            crate::wgpu::Color::BLACK,
+                        )],
+                        commands: vec![RenderCommand::ExecuteBundles {
+                            bundles: bundles.clone(),
+                        }],
+                    },
+                    Command::TextureToBuffer(TextureToBufferCopy {
+                        src_texture: target,
+                        src_mip_level: 0,
+                        src_origin: crate::wgpu::Origin3d::ZERO,
+                        dst_buffer: readback,
+                        dst_layout: crate::wgpu::ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: std::num::NonZeroU32::new(BYTES_PER_ROW),
+                            rows_per_image: std::num::NonZeroU32::new(SIZE),
+                        },
+                        copy_size: crate::wgpu::Extent3d {
+                            width: SIZE,
+                            height: SIZE,
+                            depth_or_array_layers: 1,
+                        },
+                    }),
+                ],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            vertex_buffer,
+            readback,
+            bundles,
+            command_buffer,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for RenderBundleTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                buffer: self.vertex_buffer,
+                offset: 0,
+                data: bytemuck::cast_slice(&vertices()).to_vec(),
+            })]),
+            1 => {
+                assert!(self
+                    .bundles
+                    .iter()
+                    .all(|bundle| update_context.render_bundle_handle_ref(bundle).is_some()));
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE) as u64)
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        if self.frame == 1 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn parallel_bundles_executed_in_a_pass() {
+    let mut wgpu_engine = WGpuEngine::new((
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    ))
+    .unwrap();
+
+    let task = wgpu_engine
+        .create_task(
+            RenderBundleTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| RenderBundleTask::new(update_context),
+        )
+        .unwrap();
+
+    for _ in 0..2 {
+        wgpu_engine.dispatch_tasks();
+    }
+
+    let data = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut RenderBundleTask| task.result.clone())
+        .unwrap()
+        .unwrap();
+    let texel = |x: u32, y: u32| {
+        let offset = (y * BYTES_PER_ROW + x * 4) as usize;
+        data[offset..offset + 4].to_vec()
+    };
+    // Each bundle draws its own half with its own pipeline.
+    for y in 0..SIZE {
+        for x in 0..SIZE / 2 {
+            assert_eq!(texel(x, y), vec![255, 0, 0, 255]);
+        }
+        for x in SIZE / 2..SIZE {
+            assert_eq!(texel(x, y), vec![0, 255, 0, 255]);
+        }
+    }
+}
@@ -0,0 +1,2419 @@
+use crate::*;
+
+fn instance(resource_manager: &mut ResourceManager, task: TaskId) -> InstanceId {
+    resource_manager
+        .add_instance(
+            task,
+            InstanceDescriptor {
+                label: String::from("ResourceManagerTest instance"),
+                backend: crate::wgpu::BackendBit::empty(),
+            },
+            None,
+        )
+        .unwrap()
+}
+
+#[test]
+fn touch_rebuilds_resource() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+
+    let id = instance(&mut resource_manager, task);
+    assert!(resource_manager.is_damaged(id.id_ref()));
+    resource_manager.commit_resources();
+    assert!(!resource_manager.is_damaged(id.id_ref()));
+    let first = resource_manager.instance_handle_ref(&id).unwrap().clone();
+
+    assert!(resource_manager.touch_resource(id));
+    assert!(resource_manager.is_damaged(id.id_ref()));
+    resource_manager.commit_resources();
+    assert!(!resource_manager.is_damaged(id.id_ref()));
+    let second = resource_manager.instance_handle_ref(&id).unwrap();
+    assert!(!std::sync::Arc::ptr_eq(&first, second));
+}
+
+#[test]
+fn damage_cleared_after_commit() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+
+    let id = instance(&mut resource_manager, task);
+    assert_eq!(resource_manager.damaged_entities(), vec![*id.id_ref()]);
+    assert!(resource_manager.commit_resources());
+    assert!(resource_manager.damaged_entities().is_empty());
+}
+
+fn device(resource_manager: &mut ResourceManager, task: TaskId) -> DeviceId {
+    let instance = instance(resource_manager, task);
+    resource_manager
+        .add_device(
+            task,
+            DeviceDescriptor {
+                label: String::from("ResourceManagerTest device"),
+                instance,
+                backend: crate::wgpu::BackendBit::empty(),
+                pci_id: 0,
+                features: crate::wgpu::Features::empty(),
+                limits: crate::wgpu::Limits::default(),
+            },
+            None,
+        )
+        .unwrap()
+}
+
+#[test]
+fn shared_depth_target() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let first_task = TaskId::new(EntityId::new(0));
+    let second_task = TaskId::new(EntityId::new(1));
+    let device = device(&mut resource_manager, first_task);
+    let format = crate::wgpu::TextureFormat::Depth32Float;
+
+    let first = resource_manager
+        .create_depth_target(first_task, device, 800, 600, format)
+        .unwrap();
+    let second = resource_manager
+        .create_depth_target(second_task, device, 800, 600, format)
+        .unwrap();
+    assert_eq!(first, second);
+    assert_eq!(resource_manager.textures().count(), 1);
+
+    resource_manager
+        .release_depth_target(&first_task, &first)
+        .unwrap();
+    assert_eq!(resource_manager.textures().count(), 1);
+    resource_manager
+        .release_depth_target(&second_task, &second)
+        .unwrap();
+    assert_eq!(resource_manager.textures().count(), 0);
+}
+
+#[test]
+fn missing_swapchain_is_not_a_frame_error() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let resource_manager = ResourceManager::new(runtime.handle().clone());
+    let swapchain = SwapchainId::new(EntityId::new(42));
+
+    // A swapchain without an acquired frame needs a surface, so only the missing resource path is covered here.
+    let result = ColorViewBuilder::new(&resource_manager, &ColorView::Swapchain(swapchain));
+    assert!(matches!(
+        result,
+        Err(ResourceBuilderError::MissingDependencies)
+    ));
+}
+
+#[cfg(not(feature = "wgpu_custom"))]
+#[test]
+fn external_memory_requires_custom_wgpu() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let resource_manager = ResourceManager::new(runtime.handle().clone());
+    let descriptor = TextureDescriptor {
+        label: String::from("Dmabuf import"),
+        device: DeviceId::new(EntityId::new(0)),
+        source: TextureSource::DmaBuf {
+            fd: 0,
+            drm_properties: None,
+            offset: 0,
+        },
+        usage: crate::wgpu::TextureUsage::SAMPLED,
+        size: crate::wgpu::Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        format: crate::wgpu::TextureFormat::Rgba8Unorm,
+        dimension: crate::wgpu::TextureDimension::D2,
+        mip_level_count: 1,
+        sample_count: 1,
+    };
+
+    // Refused before looking for the device, so no adapter is needed.
+    let result = TextureBuilder::new(
+        &resource_manager,
+        TextureId::new(EntityId::new(1)),
+        &descriptor,
+    );
+    assert!(matches!(
+        result,
+        Err(ResourceBuilderError::ExternalMemoryUnsupported)
+    ));
+}
+
+#[test]
+fn device_alignments_match_limits() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let limits = resource_manager
+        .device_descriptor_ref(&device)
+        .unwrap()
+        .limits
+        .clone();
+
+    let alignments = resource_manager.device_alignments(device).unwrap();
+    assert_eq!(
+        alignments.max_push_constant_size,
+        limits.max_push_constant_size
+    );
+    assert_eq!(alignments.copy_buffer, crate::wgpu::COPY_BUFFER_ALIGNMENT);
+    assert_eq!(
+        alignments.copy_bytes_per_row,
+        crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+    );
+    assert_eq!(
+        alignments.buffer_binding,
+        crate::wgpu::BIND_BUFFER_ALIGNMENT
+    );
+    assert_eq!(
+        alignments.push_constant,
+        crate::wgpu::PUSH_CONSTANT_ALIGNMENT
+    );
+
+    assert_eq!(alignments.align_copy_size(6), 8);
+    assert_eq!(alignments.padded_bytes_per_row(4 * 65), 512);
+    assert_eq!(alignments.padded_bytes_per_row(256), 256);
+    assert_eq!(alignments.align_binding_offset(1), 256);
+    assert_eq!(alignments.align_binding_offset(256), 256);
+    assert_eq!(alignments.align_copy_size(0), 0);
+    assert_eq!(alignments.align_copy_size(8), 8);
+
+    assert_eq!(DeviceAlignments::align_to(0, 4), 0);
+    assert_eq!(DeviceAlignments::align_to(1, 4), 4);
+    assert_eq!(DeviceAlignments::align_to(257, 256), 512);
+    // The push constant helper rounds like the device alignment.
+    for size in 0..=16 {
+        assert_eq!(
+            align_push_constant_size(size) as u64,
+            DeviceAlignments::align_to(size as u64, alignments.push_constant as u64)
+        );
+    }
+
+    resource_manager.remove_device(&task, &device).unwrap();
+    assert_eq!(resource_manager.device_alignments(device), None);
+}
+
+#[test]
+fn task_restricted_to_one_device() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let restricted_task = TaskId::new(EntityId::new(0));
+    let other_task = TaskId::new(EntityId::new(1));
+    let first_device = device(&mut resource_manager, other_task);
+    let instance = instance(&mut resource_manager, other_task);
+    let second_device = resource_manager
+        .add_device(
+            other_task,
+            DeviceDescriptor {
+                label: String::from("ResourceManagerTest second device"),
+                instance,
+                backend: crate::wgpu::BackendBit::empty(),
+                pci_id: 1,
+                features: crate::wgpu::Features::empty(),
+                limits: crate::wgpu::Limits::default(),
+            },
+            None,
+        )
+        .unwrap();
+    assert_ne!(first_device, second_device);
+    let buffer = |device| BufferDescriptor {
+        label: String::from("ResourceManagerTest buffer"),
+        device,
+        size: 256,
+        usage: crate::wgpu::BufferUsage::STORAGE,
+    };
+
+    resource_manager.set_task_devices(restricted_task, vec![first_device]);
+    assert!(resource_manager
+        .add_buffer(restricted_task, buffer(first_device), None)
+        .is_ok());
+    assert!(resource_manager
+        .add_buffer(restricted_task, buffer(second_device), None)
+        .is_err());
+    assert!(resource_manager
+        .add_buffer(other_task, buffer(second_device), None)
+        .is_ok());
+    assert_eq!(resource_manager.buffers().count(), 2);
+
+    let descriptor = TaskDescriptor::new(
+        String::from("ResourceManagerTest task"),
+        Vec::new(),
+        vec![first_device],
+    );
+    assert!(descriptor.uses_device(&first_device));
+    assert!(!descriptor.uses_device(&second_device));
+
+    resource_manager.set_task_devices(restricted_task, Vec::new());
+    assert!(resource_manager
+        .add_buffer(restricted_task, buffer(second_device), None)
+        .is_ok());
+}
+
+#[test]
+fn shared_sampler_update_migrates_ownership() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let first_task = TaskId::new(EntityId::new(0));
+    let second_task = TaskId::new(EntityId::new(1));
+    let device = device(&mut resource_manager, first_task);
+    let descriptor = SamplerDescriptor {
+        label: String::from("ResourceManagerTest sampler"),
+        device,
+        ..Default::default()
+    };
+
+    let first = resource_manager
+        .add_sampler(first_task, descriptor.clone(), None)
+        .unwrap();
+    let mut second = resource_manager
+        .add_sampler(second_task, descriptor.clone(), None)
+        .unwrap();
+    assert_eq!(first, second);
+    assert!(resource_manager.is_shared(first));
+
+    let linear = SamplerDescriptor {
+        mag_filter: crate::wgpu::FilterMode::Linear,
+        ..descriptor.clone()
+    };
+    assert!(resource_manager.update_sampler_descriptor(&second_task, &mut second, linear));
+    assert_ne!(first, second);
+    assert!(!resource_manager.is_shared(first));
+    assert!(!resource_manager.is_shared(second));
+    assert_eq!(
+        resource_manager.sampler_descriptor_ref(&first),
+        Some(&descriptor)
+    );
+    assert_eq!(resource_manager.samplers().count(), 2);
+
+    assert!(resource_manager.update_sampler_descriptor(&second_task, &mut second, descriptor));
+    assert_eq!(first, second);
+    assert!(resource_manager.is_shared(first));
+    assert_eq!(resource_manager.samplers().count(), 1);
+}
+
+#[test]
+fn samplers_not_shared_without_deduplication() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let descriptor = SamplerDescriptor {
+        label: String::from("ResourceManagerTest sampler"),
+        device,
+        ..Default::default()
+    };
+
+    resource_manager.set_deduplication(false);
+    assert!(!resource_manager.deduplication());
+    let first = resource_manager
+        .add_sampler(task, descriptor.clone(), None)
+        .unwrap();
+    let second = resource_manager
+        .add_sampler(task, descriptor, None)
+        .unwrap();
+    assert_ne!(first, second);
+    assert!(!resource_manager.is_shared(first));
+    assert_eq!(resource_manager.samplers().count(), 2);
+}
+
+#[test]
+fn parallel_command_recording() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let mut events = Vec::new();
+    let update_context = UpdateContext::new(
+        TaskId::new(EntityId::new(0)),
+        &mut resource_manager,
+        &mut events,
+    );
+
+    let closures: Vec<_> = (0..2u32)
+        .map(|index| {
+            move || {
+                (0..100)
+                    .map(|instance| RenderCommand::Draw {
+                        vertices: 0..3,
+                        instances: index * 100 + instance..index * 100 + instance + 1,
+                    })
+                    .collect::<Vec<_>>()
+            }
+        })
+        .collect();
+    let recorded = update_context.record_commands_parallel(closures);
+    assert_eq!(recorded.len(), 2);
+
+    let commands: Vec<_> = recorded.into_iter().flatten().collect();
+    assert_eq!(commands.len(), 200);
+    for (index, command) in commands.iter().enumerate() {
+        let index = index as u32;
+        assert_eq!(
+            command,
+            &RenderCommand::Draw {
+                vertices: 0..3,
+                instances: index..index + 1,
+            }
+        );
+    }
+}
+
+#[test]
+fn max_anisotropy_caps_samplers() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let descriptor = SamplerDescriptor {
+        label: String::from("ResourceManagerTest anisotropic sampler"),
+        device,
+        mag_filter: crate::wgpu::FilterMode::Linear,
+        min_filter: crate::wgpu::FilterMode::Linear,
+        mipmap_filter: crate::wgpu::FilterMode::Linear,
+        anisotropy_clamp: std::num::NonZeroU8::new(16),
+        ..Default::default()
+    };
+    let sampler = resource_manager
+        .add_sampler(task, descriptor.clone(), None)
+        .unwrap();
+
+    resource_manager.set_max_anisotropy(4);
+    assert!(resource_manager.is_damaged(sampler.id_ref()));
+    assert_eq!(
+        descriptor.capped_anisotropy(resource_manager.max_anisotropy()),
+        std::num::NonZeroU8::new(4)
+    );
+
+    resource_manager.set_max_anisotropy(0);
+    assert_eq!(
+        descriptor.capped_anisotropy(resource_manager.max_anisotropy()),
+        std::num::NonZeroU8::new(16)
+    );
+}
+
+#[test]
+fn sampler_built_with_capped_anisotropy() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+
+    // A real device, so that the sampler actually gets built.
+    let adapter_info = crate::wgpu::Instance::new(crate::wgpu::BackendBit::PRIMARY)
+        .enumerate_adapters(crate::wgpu::BackendBit::PRIMARY)
+        .next()
+        .unwrap()
+        .get_info();
+    let backend = crate::wgpu::BackendBit::from(adapter_info.backend);
+    let instance = resource_manager
+        .add_instance(
+            task,
+            InstanceDescriptor {
+                label: String::from("ResourceManagerTest instance"),
+                backend,
+            },
+            None,
+        )
+        .unwrap();
+    let device = resource_manager
+        .add_device(
+            task,
+            DeviceDescriptor {
+                label: adapter_info.name,
+                instance,
+                backend,
+                pci_id: adapter_info.device,
+                features: crate::wgpu::Features::empty(),
+                limits: crate::wgpu::Limits::default(),
+            },
+            None,
+        )
+        .unwrap();
+    let descriptor = SamplerDescriptor {
+        label: String::from("ResourceManagerTest anisotropic sampler"),
+        device,
+        mag_filter: crate::wgpu::FilterMode::Linear,
+        min_filter: crate::wgpu::FilterMode::Linear,
+        mipmap_filter: crate::wgpu::FilterMode::Linear,
+        anisotropy_clamp: std::num::NonZeroU8::new(16),
+        ..Default::default()
+    };
+    let sampler = resource_manager
+        .add_sampler(task, descriptor.clone(), None)
+        .unwrap();
+
+    resource_manager.set_max_anisotropy(4);
+    assert!(resource_manager.commit_resources());
+    assert!(resource_manager.sampler_handle_ref(&sampler).is_some());
+    // The builder passes the capped value to wgpu, not the requested one.
+    let builder = SamplerBuilder::new(&resource_manager, sampler, &descriptor).unwrap();
+    assert_eq!(builder.anisotropy_clamp, std::num::NonZeroU8::new(4));
+
+    // Removing the cap rebuilds the sampler with the requested value.
+    resource_manager.set_max_anisotropy(0);
+    assert!(resource_manager.is_damaged(sampler.id_ref()));
+    assert!(resource_manager.commit_resources());
+    assert!(resource_manager.sampler_handle_ref(&sampler).is_some());
+    let builder = SamplerBuilder::new(&resource_manager, sampler, &descriptor).unwrap();
+    assert_eq!(builder.anisotropy_clamp, std::num::NonZeroU8::new(16));
+}
+
+#[test]
+fn estimated_memory_per_device() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+
+    resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest buffer"),
+                device,
+                size: 1024,
+                usage: crate::wgpu::BufferUsage::UNIFORM,
+            },
+            None,
+        )
+        .unwrap();
+    resource_manager
+        .add_texture(
+            task,
+            TextureDescriptor {
+                label: String::from("ResourceManagerTest texture"),
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::SAMPLED,
+                size: crate::wgpu::Extent3d {
+                    width: 64,
+                    height: 64,
+                    depth_or_array_layers: 1,
+                },
+                format: crate::wgpu::TextureFormat::Rgba8Unorm,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 7,
+                sample_count: 1,
+            },
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(resource_manager.resources_on_device(device).len(), 2);
+    // 64x64 RGBA8 with a full mip chain: 4 * (4096 + 1024 + 256 + 64 + 16 + 4 + 1) bytes.
+    let expected = 1024 + 4 * 5461;
+    let estimate = resource_manager.estimated_memory(device);
+    assert!((estimate as i64 - expected as i64).abs() <= expected as i64 / 100);
+}
+
+#[test]
+fn transient_resources_freed_after_frame() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+
+    resource_manager
+        .add_transient(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest scratch buffer"),
+                device,
+                size: 256,
+                usage: crate::wgpu::BufferUsage::STORAGE,
+            },
+        )
+        .unwrap();
+    assert_eq!(resource_manager.buffers().count(), 1);
+
+    resource_manager.free_transients();
+    assert_eq!(resource_manager.buffers().count(), 0);
+}
+
+#[test]
+fn free_resources_by_tag() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let first_task = TaskId::new(EntityId::new(0));
+    let second_task = TaskId::new(EntityId::new(1));
+    let device = device(&mut resource_manager, first_task);
+    let buffer = |label: &str, size| BufferDescriptor {
+        label: label.to_string(),
+        device,
+        size,
+        usage: crate::wgpu::BufferUsage::VERTEX,
+    };
+    let sampler = SamplerDescriptor {
+        label: String::from("ResourceManagerTest level sampler"),
+        device,
+        ..Default::default()
+    };
+
+    resource_manager
+        .add_tagged(first_task, buffer("Level 1 vertices", 256), "level1")
+        .unwrap();
+    resource_manager
+        .add_tagged(first_task, buffer("Level 1 indices", 128), "level1")
+        .unwrap();
+    let shared_sampler = resource_manager
+        .add_tagged(first_task, sampler.clone(), "level1")
+        .unwrap();
+    resource_manager
+        .add_sampler(second_task, sampler, None)
+        .unwrap();
+    resource_manager
+        .add_tagged(first_task, buffer("Level 2 vertices", 512), "level2")
+        .unwrap();
+    assert_eq!(resource_manager.buffers().count(), 3);
+
+    assert_eq!(resource_manager.free_tagged("level1"), 3);
+    assert_eq!(resource_manager.buffers().count(), 1);
+    // The sampler is still used by the second task.
+    assert_eq!(resource_manager.samplers().count(), 1);
+    assert!(!resource_manager.is_shared(shared_sampler));
+
+    assert_eq!(resource_manager.free_tagged("level1"), 0);
+    assert_eq!(resource_manager.free_tagged("level2"), 1);
+    assert_eq!(resource_manager.buffers().count(), 0);
+}
+
+#[test]
+fn released_resources_leave_no_orphans() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let first_task = TaskId::new(EntityId::new(0));
+    let second_task = TaskId::new(EntityId::new(1));
+    let device = device(&mut resource_manager, first_task);
+    let descriptor = SamplerDescriptor {
+        label: String::from("ResourceManagerTest orphan sampler"),
+        device,
+        ..Default::default()
+    };
+
+    let sampler = resource_manager
+        .add_sampler(first_task, descriptor.clone(), None)
+        .unwrap();
+    resource_manager
+        .add_sampler(second_task, descriptor, None)
+        .unwrap();
+    resource_manager.commit_resources();
+    assert!(resource_manager.find_orphans().is_empty());
+
+    resource_manager
+        .remove_sampler(&first_task, &sampler)
+        .unwrap();
+    resource_manager
+        .remove_sampler(&second_task, &sampler)
+        .unwrap();
+    assert_eq!(resource_manager.samplers().count(), 0);
+    assert!(resource_manager.find_orphans().is_empty());
+    resource_manager.commit_resources();
+}
+
+#[test]
+fn layer_views_of_array_texture() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let format = crate::wgpu::TextureFormat::Rgba8Unorm;
+    let descriptor = TextureDescriptor {
+        label: String::from("ResourceManagerTest array texture"),
+        device,
+        source: TextureSource::Local,
+        usage: crate::wgpu::TextureUsage::SAMPLED,
+        size: crate::wgpu::Extent3d {
+            width: 64,
+            height: 64,
+            depth_or_array_layers: 4,
+        },
+        format,
+        dimension: crate::wgpu::TextureDimension::D2,
+        mip_level_count: 1,
+        sample_count: 1,
+    };
+    let texture = resource_manager
+        .add_texture(task, descriptor.clone(), None)
+        .unwrap();
+
+    let views: Vec<_> = (0..4)
+        .map(|layer| {
+            let view = TextureViewDescriptor::layer(
+                format!("Layer {}", layer),
+                device,
+                texture,
+                format,
+                layer,
+            );
+            assert!(view.is_within(&descriptor));
+            resource_manager.add_texture_view(task, view, None).unwrap()
+        })
+        .collect();
+    // Views of different layers are different resources, even if they share the texture.
+    assert_eq!(resource_manager.texture_views().count(), 4);
+
+    let array = BindingResource::TextureViewArray(views.clone());
+    let dependencies = array.dependencies();
+    assert_eq!(dependencies.len(), 4);
+    assert!(views.iter().all(|view| resource_manager
+        .texture_view_descriptor_ref(view)
+        .unwrap()
+        .texture
+        == texture));
+
+    let out_of_range =
+        TextureViewDescriptor::layer(String::from("Layer 4"), device, texture, format, 4);
+    assert!(!out_of_range.is_within(&descriptor));
+    let too_many_layers = TextureViewDescriptor {
+        dimension: crate::wgpu::TextureViewDimension::D2Array,
+        base_array_layer: 2,
+        array_layer_count: std::num::NonZeroU32::new(3),
+        ..TextureViewDescriptor::layer(String::from("Layers 2..5"), device, texture, format, 2)
+    };
+    assert!(!too_many_layers.is_within(&descriptor));
+    // The end of the range would overflow.
+    let overflowing = TextureViewDescriptor {
+        dimension: crate::wgpu::TextureViewDimension::D2Array,
+        array_layer_count: std::num::NonZeroU32::new(2),
+        ..TextureViewDescriptor::layer(
+            String::from("Overflowing layers"),
+            device,
+            texture,
+            format,
+            u32::MAX,
+        )
+    };
+    assert!(!overflowing.is_within(&descriptor));
+}
+
+#[test]
+fn await_resource_readiness() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+
+    let id = instance(&mut resource_manager, task);
+    assert!(!resource_manager.is_ready(id));
+    let ready = runtime.spawn(resource_manager.when_ready(id));
+
+    resource_manager.commit_resources();
+    assert!(resource_manager.is_ready(id));
+    runtime.block_on(ready).unwrap();
+
+    // Already built resources resolve immediately.
+    runtime.block_on(resource_manager.when_ready(id));
+}
+
+#[test]
+fn infer_missing_usages() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let format = crate::wgpu::TextureFormat::Rgba8Unorm;
+
+    // The texture is used as a color attachment, but has been created only to be sampled.
+    let texture = resource_manager
+        .add_texture(
+            task,
+            TextureDescriptor {
+                label: String::from("ResourceManagerTest target"),
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::SAMPLED,
+                size: crate::wgpu::Extent3d {
+                    width: 64,
+                    height: 64,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            },
+            None,
+        )
+        .unwrap();
+    let view = resource_manager
+        .add_texture_view(
+            task,
+            TextureViewDescriptor::layer(
+                String::from("ResourceManagerTest target view"),
+                device,
+                texture,
+                format,
+                0,
+            ),
+            None,
+        )
+        .unwrap();
+    let buffer = resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest buffer"),
+                device,
+                size: 256,
+                usage: crate::wgpu::BufferUsage::VERTEX | crate::wgpu::BufferUsage::COPY_DST,
+            },
+            None,
+        )
+        .unwrap();
+
+    let descriptor = CommandBufferDescriptor {
+        label: String::from("ResourceManagerTest command buffer"),
+        device,
+        commands: vec![Command::RenderPass {
+            label: String::from("ResourceManagerTest pass"),
+            depth_stencil: None,
+            color_attachments: vec![RenderPassColorAttachment {
+                view: ColorView::TextureView(view),
+                resolve_target: None,
+                ops: crate::wgpu::Operations::default(),
+            }],
+            commands: vec![RenderCommand::SetVertexBuffer {
+                slot: 0,
+                buffer,
+                slice: Slice::from(..),
+            }],
+        }],
+        max_commands: None,
+    };
+    assert_eq!(
+        resource_manager.required_usages(&descriptor),
+        vec![
+            RequiredUsage::Texture(texture, crate::wgpu::TextureUsage::RENDER_ATTACHMENT),
+            RequiredUsage::Buffer(buffer, crate::wgpu::BufferUsage::VERTEX),
+        ]
+    );
+
+    let missing = vec![RequiredUsage::Texture(
+        texture,
+        crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+    )];
+    assert_eq!(resource_manager.missing_usages(&descriptor), missing);
+    assert_eq!(resource_manager.augment_usages(&task, &descriptor), missing);
+    assert!(resource_manager.missing_usages(&descriptor).is_empty());
+    assert_eq!(
+        resource_manager
+            .texture_descriptor_ref(&texture)
+            .unwrap()
+            .usage,
+        crate::wgpu::TextureUsage::SAMPLED | crate::wgpu::TextureUsage::RENDER_ATTACHMENT
+    );
+}
+
+#[test]
+fn dispatch_indirect_requires_indirect_usage() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    // Written by a previous pass, but created without INDIRECT.
+    let args = resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest dispatch arguments"),
+                device,
+                size: 12,
+                usage: crate::wgpu::BufferUsage::STORAGE,
+            },
+            None,
+        )
+        .unwrap();
+
+    let dispatch = Command::ComputePass(vec![ComputeCommand::DispatchIndirect {
+        buffer: args,
+        offset: 0,
+    }]);
+    assert!(dispatch.dependencies().contains(args.id_ref()));
+
+    let descriptor = CommandBufferDescriptor {
+        label: String::from("ResourceManagerTest indirect dispatch"),
+        device,
+        commands: vec![dispatch],
+        max_commands: None,
+    };
+    let missing = vec![RequiredUsage::Buffer(
+        args,
+        crate::wgpu::BufferUsage::INDIRECT,
+    )];
+    assert_eq!(resource_manager.missing_usages(&descriptor), missing);
+    assert_eq!(resource_manager.augment_usages(&task, &descriptor), missing);
+    assert!(resource_manager
+        .buffer_descriptor_ref(&args)
+        .unwrap()
+        .validate_indirect(0, 12));
+}
+
+#[test]
+fn switch_index_format_mid_pass() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let buffer = resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest index buffer"),
+                device,
+                size: 24,
+                usage: crate::wgpu::BufferUsage::INDEX,
+            },
+            None,
+        )
+        .unwrap();
+    let set_index_buffer = |index_format| RenderCommand::SetIndexBuffer {
+        index_format,
+        buffer,
+        slice: Slice::from(..),
+    };
+    let draw_indexed = |count| RenderCommand::DrawIndexed {
+        indices: 0..count,
+        base_vertex: 0,
+        instances: 0..1,
+    };
+
+    // 24 bytes hold 12 16-bit indices, but only 6 32-bit ones.
+    let mut commands = vec![
+        set_index_buffer(crate::wgpu::IndexFormat::Uint16),
+        draw_indexed(12),
+        set_index_buffer(crate::wgpu::IndexFormat::Uint32),
+        draw_indexed(6),
+    ];
+    assert!(resource_manager.validate_indexed_draws(&commands));
+
+    commands.push(draw_indexed(12));
+    assert!(!resource_manager.validate_indexed_draws(&commands));
+
+    assert!(!resource_manager.validate_indexed_draws(&[draw_indexed(3)]));
+}
+
+#[test]
+fn compute_writes_before_render_reads() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let particles = resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest particles"),
+                device,
+                size: 1024,
+                usage: crate::wgpu::BufferUsage::VERTEX | crate::wgpu::BufferUsage::STORAGE,
+            },
+            None,
+        )
+        .unwrap();
+
+    let layout = resource_manager
+        .add_bind_group_layout(
+            task,
+            BindGroupLayoutDescriptor {
+                label: String::from("ResourceManagerTest simulation layout"),
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry::storage_buffer(
+                    0,
+                    crate::wgpu::ShaderStage::COMPUTE,
+                    false,
+                )],
+            },
+            None,
+        )
+        .unwrap();
+    let bind_group = resource_manager
+        .add_bind_group(
+            task,
+            BindGroupDescriptor {
+                label: String::from("ResourceManagerTest simulation bind group"),
+                device,
+                layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: particles,
+                        offset: 0,
+                        size: None,
+                    }),
+                }],
+            },
+            None,
+        )
+        .unwrap();
+
+    let simulate = Command::ComputePass(vec![
+        ComputeCommand::SetBindGroup {
+            index: 0,
+            bind_group,
+            offsets: Vec::new(),
+        },
+        ComputeCommand::Dispatch { x: 64, y: 1, z: 1 },
+    ]);
+    let draw = Command::RenderPass {
+        label: String::from("ResourceManagerTest draw"),
+        depth_stencil: None,
+        color_attachments: Vec::new(),
+        commands: vec![RenderCommand::SetVertexBuffer {
+            slot: 0,
+            buffer: particles,
+            slice: Slice::from(..),
+        }],
+    };
+    let mut descriptor = CommandBufferDescriptor {
+        label: String::from("ResourceManagerTest particles"),
+        device,
+        commands: vec![simulate, draw],
+        max_commands: None,
+    };
+    assert!(resource_manager
+        .check_compute_render_order(&descriptor)
+        .is_empty());
+
+    // Drawing before simulating reads the particles of the previous frame.
+    descriptor.commands.reverse();
+    assert_eq!(
+        resource_manager.check_compute_render_order(&descriptor),
+        vec![particles]
+    );
+}
+
+#[test]
+fn validate_broken_command_buffer() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let format = crate::wgpu::TextureFormat::Rgba8Unorm;
+
+    // Created without RENDER_ATTACHMENT, and with a different format than the pipeline target.
+    let texture = resource_manager
+        .add_texture(
+            task,
+            TextureDescriptor {
+                label: String::from("ResourceManagerTest target"),
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::SAMPLED,
+                size: crate::wgpu::Extent3d {
+                    width: 64,
+                    height: 64,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            },
+            None,
+        )
+        .unwrap();
+    let view = resource_manager
+        .add_texture_view(
+            task,
+            TextureViewDescriptor::layer(
+                String::from("ResourceManagerTest target view"),
+                device,
+                texture,
+                format,
+                0,
+            ),
+            None,
+        )
+        .unwrap();
+    let shader_module = resource_manager
+        .add_shader_module(
+            task,
+            ShaderModuleDescriptor {
+                label: String::from("ResourceManagerTest shader"),
+                device,
+                source: ShaderSource::Wgsl(String::new()),
+                flags: crate::wgpu::ShaderFlags::empty(),
+            },
+            None,
+        )
+        .unwrap();
+    let pipeline = resource_manager
+        .add_render_pipeline(
+            task,
+            RenderPipelineDescriptor {
+                label: String::from("ResourceManagerTest pipeline"),
+                device,
+                layout: None,
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: vec![VertexBufferLayout {
+                        array_stride: 16,
+                        step_mode: crate::wgpu::InputStepMode::Vertex,
+                        attributes: Vec::new(),
+                    }],
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: String::from("fs_main"),
+                    targets: vec![crate::wgpu::ColorTargetState {
+                        format: crate::wgpu::TextureFormat::Bgra8Unorm,
+                        blend: None,
+                        write_mask: crate::wgpu::ColorWrite::ALL,
+                    }],
+                }),
+            },
+            None,
+        )
+        .unwrap();
+    // Holds 4 vertices of 16 bytes.
+    let vertex_buffer = resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest vertices"),
+                device,
+                size: 64,
+                usage: crate::wgpu::BufferUsage::VERTEX,
+            },
+            None,
+        )
+        .unwrap();
+    let uniform_buffer = resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest uniforms"),
+                device,
+                size: 64,
+                usage: crate::wgpu::BufferUsage::UNIFORM,
+            },
+            None,
+        )
+        .unwrap();
+    let layout = resource_manager
+        .add_bind_group_layout(
+            task,
+            BindGroupLayoutDescriptor {
+                label: String::from("ResourceManagerTest layout"),
+                device,
+                entries: vec![
+                    BindGroupLayoutEntry::uniform_buffer(0, crate::wgpu::ShaderStage::VERTEX),
+                    BindGroupLayoutEntry::sampler(1),
+                ],
+            },
+            None,
+        )
+        .unwrap();
+    // The sampler binding is not provided.
+    let bind_group = resource_manager
+        .add_bind_group(
+            task,
+            BindGroupDescriptor {
+                label: String::from("ResourceManagerTest bind group"),
+                device,
+                layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: uniform_buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                }],
+            },
+            None,
+        )
+        .unwrap();
+    let removed_bind_group = BindGroupId::new(EntityId::new(1000));
+
+    let descriptor = CommandBufferDescriptor {
+        label: String::from("ResourceManagerTest command buffer"),
+        device,
+        commands: vec![Command::RenderPass {
+            label: String::from("ResourceManagerTest pass"),
+            depth_stencil: None,
+            color_attachments: vec![RenderPassColorAttachment::clear(
+                ColorView::TextureView(view),
+                crate::wgpu::Color::BLACK,
+            )],
+            commands: vec![
+                RenderCommand::SetPipeline { pipeline },
+                RenderCommand::SetBindGroup {
+                    index: 0,
+                    bind_group,
+                    offsets: Vec::new(),
+                },
+                RenderCommand::SetBindGroup {
+                    index: 1,
+                    bind_group: removed_bind_group,
+                    offsets: Vec::new(),
+                },
+                RenderCommand::SetVertexBuffer {
+                    slot: 0,
+                    buffer: vertex_buffer,
+                    slice: Slice::from(..),
+                },
+                RenderCommand::Draw {
+                    vertices: 0..6,
+                    instances: 0..1,
+                },
+                RenderCommand::DrawIndexed {
+                    indices: 0..3,
+                    base_vertex: 0,
+                    instances: 0..1,
+                },
+                // The target is 64x64.
+                RenderCommand::SetViewport {
+                    x: 32.0,
+                    y: 0.0,
+                    width: 64.0,
+                    height: 64.0,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                },
+                RenderCommand::SetScissorRect {
+                    x: 0,
+                    y: 16,
+                    width: 64,
+                    height: 64,
+                },
+                RenderCommand::SetViewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 64.0,
+                    height: 64.0,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                },
+                RenderCommand::SetScissorRect {
+                    x: 16,
+                    y: 16,
+                    width: 48,
+                    height: 48,
+                },
+            ],
+        }],
+        max_commands: None,
+    };
+
+    let pass = String::from("ResourceManagerTest pass");
+    let errors = descriptor.validate(&resource_manager).unwrap_err();
+    let expected = vec![
+        ValidationError::IncompleteBindGroup {
+            bind_group,
+            missing: vec![1],
+        },
+        ValidationError::MissingResource(*removed_bind_group.id_ref()),
+        ValidationError::MissingUsage(RequiredUsage::Texture(
+            texture,
+            crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+        )),
+        ValidationError::AttachmentFormatMismatch {
+            pass: pass.clone(),
+            pipeline,
+            attachment: 0,
+            attachment_format: format,
+            target_format: crate::wgpu::TextureFormat::Bgra8Unorm,
+        },
+        ValidationError::DrawOutOfBounds {
+            pass: pass.clone(),
+            slot: 0,
+            required: 6,
+            capacity: 4,
+        },
+        ValidationError::ViewportOutOfBounds {
+            pass: pass.clone(),
+            viewport: [32.0, 0.0, 64.0, 64.0, 0.0, 1.0],
+            attachment_size: Some((64, 64)),
+        },
+        ValidationError::ScissorOutOfBounds {
+            pass: pass.clone(),
+            rect: [0, 16, 64, 64],
+            attachment_size: (64, 64),
+        },
+        ValidationError::InvalidIndexedDraw { pass },
+    ];
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn linked_depth_target_follows_swapchain_size() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let format = crate::wgpu::TextureFormat::Depth32Float;
+    // Swapchains require a window, so only the resize notification is emulated.
+    let swapchain = SwapchainId::new(EntityId::new(1000));
+    let other_swapchain = SwapchainId::new(EntityId::new(1001));
+
+    let view = resource_manager
+        .create_depth_target(task, device, 640, 480, format)
+        .unwrap();
+    let texture = resource_manager
+        .texture_view_descriptor_ref(&view)
+        .unwrap()
+        .texture;
+    assert!(resource_manager.link_to_swapchain_size(task, texture, swapchain));
+    resource_manager.commit_resources();
+
+    assert!(resource_manager
+        .resize_linked_textures(&other_swapchain, 800, 600)
+        .is_empty());
+    assert_eq!(
+        resource_manager.resize_linked_textures(&swapchain, 800, 600),
+        vec![texture]
+    );
+    let size = resource_manager
+        .texture_descriptor_ref(&texture)
+        .unwrap()
+        .size;
+    assert_eq!((size.width, size.height), (800, 600));
+    assert!(resource_manager.is_damaged(texture.id_ref()));
+    assert!(resource_manager.is_damaged(view.id_ref()));
+    // The shared depth target is found by its new size.
+    assert_eq!(
+        resource_manager.create_depth_target(task, device, 800, 600, format),
+        Ok(view)
+    );
+
+    // Resizing to the current size does nothing.
+    assert!(resource_manager
+        .resize_linked_textures(&swapchain, 800, 600)
+        .is_empty());
+
+    assert!(resource_manager.unlink_swapchain_size(&texture));
+    assert!(resource_manager
+        .resize_linked_textures(&swapchain, 1024, 768)
+        .is_empty());
+}
+
+#[test]
+fn resizing_shared_depth_target_detaches_the_task() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let other_task = TaskId::new(EntityId::new(1));
+    let device = device(&mut resource_manager, task);
+    let format = crate::wgpu::TextureFormat::Depth32Float;
+    // Swapchains require a window, so only the resize notification is emulated.
+    let swapchain = SwapchainId::new(EntityId::new(1000));
+
+    let view = resource_manager
+        .create_depth_target(task, device, 640, 480, format)
+        .unwrap();
+    assert_eq!(
+        resource_manager.create_depth_target(other_task, device, 640, 480, format),
+        Ok(view)
+    );
+    let texture = resource_manager
+        .texture_view_descriptor_ref(&view)
+        .unwrap()
+        .texture;
+    assert!(resource_manager.link_to_swapchain_size(task, texture, swapchain));
+    resource_manager.commit_resources();
+
+    let resized = resource_manager.resize_linked_textures(&swapchain, 800, 600);
+    assert_eq!(resized.len(), 1);
+    assert_ne!(resized[0], texture);
+    let detached = resource_manager
+        .take_detached_depth_target(&task, &view)
+        .unwrap();
+    assert_eq!(
+        resource_manager
+            .texture_view_descriptor_ref(&detached)
+            .unwrap()
+            .texture,
+        resized[0]
+    );
+    assert_eq!(
+        resource_manager.take_detached_depth_target(&task, &view),
+        None
+    );
+
+    // The other task keeps the target of the old size, no longer shared.
+    let size = resource_manager
+        .texture_descriptor_ref(&texture)
+        .unwrap()
+        .size;
+    assert_eq!((size.width, size.height), (640, 480));
+    assert!(!resource_manager.is_shared(texture));
+    assert_eq!(
+        resource_manager.create_depth_target(other_task, device, 640, 480, format),
+        Ok(view)
+    );
+    assert_eq!(
+        resource_manager.create_depth_target(other_task, device, 800, 600, format),
+        Ok(detached)
+    );
+
+    // The link follows the task on its new target.
+    assert!(resource_manager.unlink_swapchain_size(&resized[0]));
+    assert!(!resource_manager.unlink_swapchain_size(&texture));
+}
+
+#[test]
+fn resources_of_other_devices_rejected() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let first_device = device(&mut resource_manager, task);
+    let instance = instance(&mut resource_manager, task);
+    let second_device = resource_manager
+        .add_device(
+            task,
+            DeviceDescriptor {
+                label: String::from("ResourceManagerTest second device"),
+                instance,
+                backend: crate::wgpu::BackendBit::empty(),
+                pci_id: 1,
+                features: crate::wgpu::Features::empty(),
+                limits: crate::wgpu::Limits::default(),
+            },
+            None,
+        )
+        .unwrap();
+    assert_ne!(first_device, second_device);
+
+    let mut buffer = |label: &str, device, usage| {
+        resource_manager
+            .add_buffer(
+                task,
+                BufferDescriptor {
+                    label: String::from(label),
+                    device,
+                    size: 256,
+                    usage,
+                },
+                None,
+            )
+            .unwrap()
+    };
+    let src_buffer = buffer(
+        "ResourceManagerTest source",
+        first_device,
+        crate::wgpu::BufferUsage::COPY_SRC,
+    );
+    let dst_buffer = buffer(
+        "ResourceManagerTest destination",
+        second_device,
+        crate::wgpu::BufferUsage::COPY_DST,
+    );
+
+    let descriptor = CommandBufferDescriptor {
+        label: String::from("ResourceManagerTest copy"),
+        device: first_device,
+        commands: vec![Command::BufferToBuffer(BufferToBufferCopy {
+            src_buffer,
+            src_offset: 0,
+            dst_buffer,
+            dst_offset: 0,
+            size: 256,
+        })],
+        max_commands: None,
+    };
+    assert_eq!(
+        resource_manager.foreign_resources(&descriptor),
+        vec![(*dst_buffer.id_ref(), second_device)]
+    );
+    assert_eq!(
+        descriptor.validate(&resource_manager),
+        Err(vec![ValidationError::DeviceMismatch {
+            resource: *dst_buffer.id_ref(),
+            device: second_device,
+        }])
+    );
+}
+
+#[test]
+fn dependency_graph_edges() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+
+    let shader_module = resource_manager
+        .add_shader_module(
+            task,
+            ShaderModuleDescriptor {
+                label: String::from("ResourceManagerTest shader"),
+                device,
+                source: ShaderSource::Wgsl(String::new()),
+                flags: crate::wgpu::ShaderFlags::empty(),
+            },
+            None,
+        )
+        .unwrap();
+    let pipeline = resource_manager
+        .add_render_pipeline(
+            task,
+            RenderPipelineDescriptor {
+                label: String::from("ResourceManagerTest pipeline"),
+                device,
+                layout: None,
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: Vec::new(),
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: None,
+            },
+            None,
+        )
+        .unwrap();
+    let command_buffer = resource_manager
+        .add_command_buffer(
+            task,
+            CommandBufferDescriptor {
+                label: String::from("ResourceManagerTest command buffer"),
+                device,
+                commands: vec![Command::RenderPass {
+                    label: String::from("ResourceManagerTest pass"),
+                    depth_stencil: None,
+                    color_attachments: Vec::new(),
+                    commands: vec![RenderCommand::SetPipeline { pipeline }],
+                }],
+                max_commands: None,
+            },
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(
+        resource_manager.dependents_of(shader_module),
+        vec![*pipeline.id_ref()]
+    );
+    assert_eq!(
+        resource_manager.dependents_of(pipeline),
+        vec![*command_buffer.id_ref()]
+    );
+    assert!(resource_manager.dependents_of(command_buffer).is_empty());
+
+    let mut expected = vec![*device.id_ref(), *pipeline.id_ref()];
+    expected.sort();
+    assert_eq!(resource_manager.dependencies_of(command_buffer), expected);
+    assert_eq!(
+        resource_manager.dependents_of(device),
+        vec![
+            *shader_module.id_ref(),
+            *pipeline.id_ref(),
+            *command_buffer.id_ref()
+        ]
+    );
+}
+
+#[test]
+fn uniform_bind_group_in_one_call() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let mut events = Vec::new();
+    let mut update_context = UpdateContext::new(task, &mut resource_manager, &mut events);
+
+    let color = [0.25f32, 0.5, 0.75];
+    let (buffer, layout, bind_group) = uniform_bind_group(
+        &mut update_context,
+        device,
+        "ResourceManagerTest",
+        crate::wgpu::ShaderStage::FRAGMENT,
+        &color,
+    )
+    .unwrap();
+
+    let buffer_descriptor = update_context.buffer_descriptor_ref(&buffer).unwrap();
+    assert_eq!(buffer_descriptor.size, 12);
+    assert!(buffer_descriptor
+        .usage
+        .contains(crate::wgpu::BufferUsage::UNIFORM | crate::wgpu::BufferUsage::COPY_DST));
+    assert_eq!(
+        update_context
+            .bind_group_layout_descriptor_ref(&layout)
+            .unwrap()
+            .entries,
+        vec![BindGroupLayoutEntry::uniform_buffer(
+            0,
+            crate::wgpu::ShaderStage::FRAGMENT
+        )]
+    );
+    let bind_group_descriptor = update_context
+        .bind_group_descriptor_ref(&bind_group)
+        .unwrap();
+    assert_eq!(bind_group_descriptor.layout, layout);
+    assert_eq!(
+        bind_group_descriptor.entries[0].resource,
+        BindingResource::Buffer(BufferBinding {
+            buffer,
+            offset: 0,
+            size: None,
+        })
+    );
+
+    assert_eq!(
+        update_context.into_resource_writes(),
+        vec![ResourceWrite::Buffer(BufferWrite {
+            buffer,
+            offset: 0,
+            data: bytemuck::cast_slice(&color).to_vec(),
+        })]
+    );
+}
+
+#[test]
+fn swapchain_claims() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let presenting_task = TaskId::new(EntityId::new(0));
+    let other_task = TaskId::new(EntityId::new(1));
+    let swapchain = SwapchainId::new(EntityId::new(2));
+
+    assert_eq!(resource_manager.swapchain_owner(&swapchain), None);
+    assert!(resource_manager.claim_swapchain(presenting_task, swapchain));
+    // Claiming again is a no-op for the owner, but fails for other tasks.
+    assert!(resource_manager.claim_swapchain(presenting_task, swapchain));
+    assert!(!resource_manager.claim_swapchain(other_task, swapchain));
+    assert_eq!(
+        resource_manager.swapchain_owner(&swapchain),
+        Some(presenting_task)
+    );
+
+    assert!(!resource_manager.release_swapchain(&other_task, &swapchain));
+    assert!(resource_manager.release_swapchain(&presenting_task, &swapchain));
+    assert_eq!(resource_manager.swapchain_owner(&swapchain), None);
+    assert!(resource_manager.claim_swapchain(other_task, swapchain));
+}
+
+#[test]
+fn bind_groups_of_compatible_layouts() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+
+    let uniform = BindGroupLayoutEntry::uniform_buffer(0, crate::wgpu::ShaderStage::VERTEX);
+    let sampler = BindGroupLayoutEntry::sampler(1);
+    let layout_descriptor = |label: &str, entries| BindGroupLayoutDescriptor {
+        label: String::from(label),
+        device,
+        entries,
+    };
+    let expected_descriptor = layout_descriptor("ResourceManagerTest expected", vec![uniform]);
+    // Same entries of the expected layout, but a different layout.
+    let compatible_descriptor = layout_descriptor("ResourceManagerTest compatible", vec![uniform]);
+    let incompatible_descriptor =
+        layout_descriptor("ResourceManagerTest incompatible", vec![uniform, sampler]);
+    assert!(expected_descriptor.is_compatible_with(&compatible_descriptor));
+    assert!(!expected_descriptor.is_compatible_with(&incompatible_descriptor));
+    // The order of the entries does not matter.
+    assert!(layout_descriptor("", vec![uniform, sampler])
+        .is_compatible_with(&layout_descriptor("", vec![sampler, uniform])));
+
+    let mut add_layout = |descriptor: BindGroupLayoutDescriptor| {
+        resource_manager
+            .add_bind_group_layout(task, descriptor, None)
+            .unwrap()
+    };
+    let expected = add_layout(expected_descriptor);
+    let compatible = add_layout(compatible_descriptor);
+    let incompatible = add_layout(incompatible_descriptor);
+
+    let pipeline_layout = resource_manager
+        .add_pipeline_layout(
+            task,
+            PipelineLayoutDescriptor {
+                label: String::from("ResourceManagerTest pipeline layout"),
+                device,
+                bind_group_layouts: vec![expected],
+                push_constant_ranges: Vec::new(),
+            },
+            None,
+        )
+        .unwrap();
+    let shader_module = resource_manager
+        .add_shader_module(
+            task,
+            ShaderModuleDescriptor {
+                label: String::from("ResourceManagerTest shader"),
+                device,
+                source: ShaderSource::Wgsl(String::new()),
+                flags: crate::wgpu::ShaderFlags::empty(),
+            },
+            None,
+        )
+        .unwrap();
+    let pipeline = resource_manager
+        .add_render_pipeline(
+            task,
+            RenderPipelineDescriptor {
+                label: String::from("ResourceManagerTest pipeline"),
+                device,
+                layout: Some(pipeline_layout),
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: Vec::new(),
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: None,
+            },
+            None,
+        )
+        .unwrap();
+    let uniform_buffer = resource_manager
+        .add_buffer(
+            task,
+            BufferDescriptor {
+                label: String::from("ResourceManagerTest uniforms"),
+                device,
+                size: 64,
+                usage: crate::wgpu::BufferUsage::UNIFORM,
+            },
+            None,
+        )
+        .unwrap();
+    let sampler = resource_manager
+        .add_sampler(
+            task,
+            SamplerDescriptor {
+                label: String::from("ResourceManagerTest sampler"),
+                device,
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+
+    let uniform_entry = BindGroupEntry {
+        binding: 0,
+        resource: BindingResource::Buffer(BufferBinding {
+            buffer: uniform_buffer,
+            offset: 0,
+            size: None,
+        }),
+    };
+    let mut add_bind_group = |layout, entries| {
+        resource_manager
+            .add_bind_group(
+                task,
+                BindGroupDescriptor {
+                    label: String::from("ResourceManagerTest bind group"),
+                    device,
+                    layout,
+                    entries,
+                },
+                None,
+            )
+            .unwrap()
+    };
+    let compatible_bind_group = add_bind_group(compatible, vec![uniform_entry.clone()]);
+    let incompatible_bind_group = add_bind_group(
+        incompatible,
+        vec![
+            uniform_entry,
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(sampler),
+            },
+        ],
+    );
+
+    assert!(resource_manager.is_bind_group_compatible(&pipeline_layout, 0, &compatible_bind_group));
+    assert!(!resource_manager.is_bind_group_compatible(
+        &pipeline_layout,
+        0,
+        &incompatible_bind_group
+    ));
+    // The pipeline layout has no bind group at index 1.
+    assert!(!resource_manager.is_bind_group_compatible(
+        &pipeline_layout,
+        1,
+        &compatible_bind_group
+    ));
+
+    // A second pipeline expecting the incompatible layout.
+    let other_pipeline_layout = resource_manager
+        .add_pipeline_layout(
+            task,
+            PipelineLayoutDescriptor {
+                label: String::from("ResourceManagerTest other pipeline layout"),
+                device,
+                bind_group_layouts: vec![incompatible],
+                push_constant_ranges: Vec::new(),
+            },
+            None,
+        )
+        .unwrap();
+    let other_pipeline = resource_manager
+        .add_render_pipeline(
+            task,
+            RenderPipelineDescriptor {
+                label: String::from("ResourceManagerTest other pipeline"),
+                device,
+                layout: Some(other_pipeline_layout),
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: Vec::new(),
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: None,
+            },
+            None,
+        )
+        .unwrap();
+
+    let set_bind_group = |bind_group| RenderCommand::SetBindGroup {
+        index: 0,
+        bind_group,
+        offsets: Vec::new(),
+    };
+    let draw = RenderCommand::Draw {
+        vertices: 0..3,
+        instances: 0..1,
+    };
+    let incompatible_draw =
+        |commands: Vec<RenderCommand>| resource_manager.incompatible_render_bind_group(&commands);
+    // A bind group replaced before the draw is never used.
+    assert_eq!(
+        incompatible_draw(vec![
+            RenderCommand::SetPipeline { pipeline },
+            set_bind_group(incompatible_bind_group),
+            set_bind_group(compatible_bind_group),
+            draw.clone(),
+        ]),
+        None
+    );
+    // The bind groups can be set before the pipeline.
+    assert_eq!(
+        incompatible_draw(vec![
+            set_bind_group(incompatible_bind_group),
+            RenderCommand::SetPipeline { pipeline },
+            draw.clone(),
+        ]),
+        Some((pipeline, 0, incompatible_bind_group))
+    );
+    // A bind group left bound is checked again against the new pipeline.
+    assert_eq!(
+        incompatible_draw(vec![
+            RenderCommand::SetPipeline { pipeline },
+            set_bind_group(compatible_bind_group),
+            draw.clone(),
+            RenderCommand::SetPipeline {
+                pipeline: other_pipeline,
+            },
+            draw.clone(),
+        ]),
+        Some((other_pipeline, 0, compatible_bind_group))
+    );
+
+    let compute_pipeline = resource_manager
+        .add_compute_pipeline(
+            task,
+            ComputePipelineDescriptor {
+                label: String::from("ResourceManagerTest compute pipeline"),
+                device,
+                layout: Some(pipeline_layout),
+                module: shader_module,
+                entry_point: String::from("main"),
+            },
+            None,
+        )
+        .unwrap();
+    let dispatch = |bind_group| {
+        vec![
+            ComputeCommand::SetBindGroup {
+                index: 0,
+                bind_group,
+                offsets: Vec::new(),
+            },
+            ComputeCommand::SetPipeline {
+                pipeline: compute_pipeline,
+            },
+            ComputeCommand::Dispatch { x: 1, y: 1, z: 1 },
+        ]
+    };
+    assert_eq!(
+        resource_manager.incompatible_compute_bind_group(&dispatch(compatible_bind_group)),
+        None
+    );
+    assert_eq!(
+        resource_manager.incompatible_compute_bind_group(&dispatch(incompatible_bind_group)),
+        Some((compute_pipeline, 0, incompatible_bind_group))
+    );
+
+    let pass = String::from("ResourceManagerTest pass");
+    let descriptor = CommandBufferDescriptor {
+        label: String::from("ResourceManagerTest command buffer"),
+        device,
+        commands: vec![Command::RenderPass {
+            label: pass.clone(),
+            depth_stencil: None,
+            color_attachments: Vec::new(),
+            commands: vec![
+                RenderCommand::SetPipeline { pipeline },
+                set_bind_group(compatible_bind_group),
+                set_bind_group(incompatible_bind_group),
+                draw,
+            ],
+        }],
+        max_commands: None,
+    };
+    assert_eq!(
+        descriptor.validate(&resource_manager),
+        Err(vec![ValidationError::IncompatibleBindGroup {
+            pass,
+            pipeline,
+            index: 0,
+            bind_group: incompatible_bind_group,
+        }])
+    );
+}
+
+#[test]
+fn batch_rolled_back_on_failure() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+
+    let buffer = BufferDescriptor {
+        label: String::from("ResourceManagerTest batch buffer"),
+        device,
+        size: 16,
+        usage: crate::wgpu::BufferUsage::UNIFORM,
+    };
+    let layout = BindGroupLayoutDescriptor {
+        label: String::from("ResourceManagerTest batch layout"),
+        device,
+        entries: vec![BindGroupLayoutEntry::uniform_buffer(
+            0,
+            crate::wgpu::ShaderStage::VERTEX,
+        )],
+    };
+    // The bind group references a layout removed before the batch.
+    let removed_layout = resource_manager
+        .add_bind_group_layout(task, layout.clone(), None)
+        .unwrap();
+    resource_manager
+        .remove_bind_group_layout(&task, &removed_layout)
+        .unwrap();
+    let bind_group = BindGroupDescriptor {
+        label: String::from("ResourceManagerTest batch bind group"),
+        device,
+        layout: removed_layout,
+        entries: Vec::new(),
+    };
+
+    let result = resource_manager.add_batch(
+        task,
+        vec![
+            buffer.clone().into(),
+            layout.clone().into(),
+            bind_group.into(),
+        ],
+    );
+    match result {
+        Err((index, error)) => {
+            assert_eq!(index, 2);
+            assert_eq!(error, ResourceBuilderError::MissingDependencies);
+        }
+        Ok(_) => panic!("Expected the batch to fail on the missing layout"),
+    }
+    assert_eq!(resource_manager.buffers().count(), 0);
+    assert_eq!(resource_manager.bind_group_layouts().count(), 0);
+    assert_eq!(resource_manager.bind_groups().count(), 0);
+
+    // A resource of a device that is not one of the task devices.
+    let other_task = TaskId::new(EntityId::new(1));
+    let other_device = self::device(&mut resource_manager, other_task);
+    resource_manager.set_task_devices(task, vec![device]);
+    let result = resource_manager.add_batch(
+        task,
+        vec![
+            buffer.clone().into(),
+            BufferDescriptor {
+                device: other_device,
+                ..buffer.clone()
+            }
+            .into(),
+        ],
+    );
+    assert_eq!(
+        result.err(),
+        Some((1, ResourceBuilderError::NotTaskDevice(other_device)))
+    );
+    assert_eq!(resource_manager.buffers().count(), 0);
+
+    let ids = resource_manager
+        .add_batch(task, vec![layout.into(), buffer.into()])
+        .ok()
+        .unwrap();
+    assert!(matches!(ids[0], ResourceId::BindGroupLayout(_)));
+    assert!(matches!(ids[1], ResourceId::Buffer(_)));
+}
+
+#[test]
+fn batch_items_reference_previous_items() {
+    use std::convert::TryInto;
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+
+    let ids = resource_manager
+        .add_batch(
+            task,
+            vec![
+                ShaderModuleDescriptor {
+                    label: String::from("ResourceManagerTest batch shader"),
+                    device,
+                    source: ShaderSource::Wgsl(String::new()),
+                    flags: crate::wgpu::ShaderFlags::empty(),
+                }
+                .into(),
+                PipelineLayoutDescriptor {
+                    label: String::from("ResourceManagerTest batch pipeline layout"),
+                    device,
+                    bind_group_layouts: Vec::new(),
+                    push_constant_ranges: Vec::new(),
+                }
+                .into(),
+                // The pipeline uses the shader and the layout added by the same batch.
+                BatchItem::with_ids(move |ids| RenderPipelineDescriptor {
+                    label: String::from("ResourceManagerTest batch pipeline"),
+                    device,
+                    layout: Some(ids[1].try_into().unwrap()),
+                    vertex: VertexState {
+                        module: ids[0].try_into().unwrap(),
+                        entry_point: String::from("vs_main"),
+                        buffers: Vec::new(),
+                    },
+                    primitive: crate::wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: crate::wgpu::MultisampleState::default(),
+                    fragment: None,
+                }),
+            ],
+        )
+        .ok()
+        .unwrap();
+
+    let shader_module: ShaderModuleId = ids[0].try_into().unwrap();
+    let layout: PipelineLayoutId = ids[1].try_into().unwrap();
+    let pipeline: RenderPipelineId = ids[2].try_into().unwrap();
+    let descriptor = resource_manager
+        .render_pipeline_descriptor_ref(&pipeline)
+        .unwrap();
+    assert_eq!(descriptor.layout, Some(layout));
+    assert_eq!(descriptor.vertex.module, shader_module);
+    assert_eq!(resource_manager.render_pipelines().count(), 1);
+}
+
+#[test]
+fn pipelines_share_a_pipeline_layout() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let other_task = TaskId::new(EntityId::new(1));
+    let device = device(&mut resource_manager, task);
+    let bind_group_layout = resource_manager
+        .add_bind_group_layout(
+            task,
+            BindGroupLayoutDescriptor {
+                label: String::from("ResourceManagerTest layout"),
+                device,
+                entries: vec![BindGroupLayoutEntry::uniform_buffer(
+                    0,
+                    crate::wgpu::ShaderStage::VERTEX,
+                )],
+            },
+            None,
+        )
+        .unwrap();
+    let shader_module = resource_manager
+        .add_shader_module(
+            task,
+            ShaderModuleDescriptor {
+                label: String::from("ResourceManagerTest shader"),
+                device,
+                source: ShaderSource::Wgsl(String::new()),
+                flags: crate::wgpu::ShaderFlags::empty(),
+            },
+            None,
+        )
+        .unwrap();
+
+    let mut pipelines = Vec::new();
+    for (task, entry_point) in [(task, "vs_first"), (other_task, "vs_second")] {
+        let mut events = Vec::new();
+        let mut update_context = UpdateContext::new(task, &mut resource_manager, &mut events);
+        let layout = shared_pipeline_layout(
+            &mut update_context,
+            device,
+            &[bind_group_layout],
+            Vec::new(),
+        )
+        .unwrap();
+        let pipeline = update_context
+            .add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                label: String::from("ResourceManagerTest pipeline"),
+                device,
+                layout: Some(layout),
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from(entry_point),
+                    buffers: Vec::new(),
+                },
+                primitive: crate::wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: None,
+            })
+            .unwrap();
+        pipelines.push((layout, pipeline));
+    }
+
+    assert_eq!(pipelines[0].0, pipelines[1].0);
+    assert_ne!(pipelines[0].1, pipelines[1].1);
+    assert_eq!(resource_manager.pipeline_layouts().count(), 1);
+    assert!(resource_manager.is_shared(pipelines[0].0));
+}
+
+#[test]
+fn identical_descriptor_not_rebuilt() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+
+    let descriptor = BufferDescriptor {
+        label: String::from("ResourceManagerTest buffer"),
+        device,
+        size: 256,
+        usage: crate::wgpu::BufferUsage::UNIFORM,
+    };
+    let mut id = resource_manager
+        .add_buffer(task, descriptor.clone(), None)
+        .unwrap();
+    resource_manager.commit_resources();
+    let first = resource_manager.buffer_handle_ref(&id).unwrap().clone();
+
+    assert!(resource_manager.update_buffer_descriptor(&task, &mut id, descriptor.clone()));
+    assert!(!resource_manager.is_damaged(id.id_ref()));
+    resource_manager.commit_resources();
+    let second = resource_manager.buffer_handle_ref(&id).unwrap();
+    assert!(std::sync::Arc::ptr_eq(&first, second));
+
+    let mut changed = descriptor;
+    changed.size = 512;
+    assert!(resource_manager.update_buffer_descriptor(&task, &mut id, changed));
+    assert!(resource_manager.is_damaged(id.id_ref()));
+}
+
+#[test]
+fn resource_log_level() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    assert!(resource_manager.resource_log_enabled(log::Level::Trace));
+
+    resource_manager.set_resource_log_level(log::LevelFilter::Warn);
+    assert!(resource_manager.resource_log_enabled(log::Level::Warn));
+    assert!(!resource_manager.resource_log_enabled(log::Level::Info));
+    assert!(!resource_manager.resource_log_enabled(log::Level::Debug));
+
+    resource_manager.set_resource_log_level(log::LevelFilter::Off);
+    assert!(!resource_manager.resource_log_enabled(log::Level::Error));
+
+    // Building with the logs suppressed works as usual.
+    let task = TaskId::new(EntityId::new(0));
+    let id = instance(&mut resource_manager, task);
+    assert!(resource_manager.commit_resources());
+    assert!(resource_manager.instance_handle_ref(&id).is_some());
+}
+
+thread_local! {
+    /// Records logged on the current thread while capturing, see [capture_logs].
+    static CAPTURED_LOGS: std::cell::RefCell<Option<Vec<(log::Level, String, String)>>> =
+        std::cell::RefCell::new(None);
+}
+
+/// Logger keeping the records of the capturing threads only, so that the tests running in parallel do not interfere.
+struct CapturingLogger;
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+    fn log(&self, record: &log::Record) {
+        CAPTURED_LOGS.with(|captured| {
+            if let Some(captured) = captured.borrow_mut().as_mut() {
+                captured.push((
+                    record.level(),
+                    record.target().to_string(),
+                    record.args().to_string(),
+                ));
+            }
+        });
+    }
+    fn flush(&self) {}
+}
+
+/**
+Run `f` and return the records it logged on the current thread, as (level, target, message).
+Returns `None` if another logger has been installed before, like the `env_logger` of the windowed tests.
+*/
+fn capture_logs(f: impl FnOnce()) -> Option<Vec<(log::Level, String, String)>> {
+    static LOGGER: CapturingLogger = CapturingLogger;
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    static INSTALLED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+    INSTALL.call_once(|| {
+        if log::set_logger(&LOGGER).is_ok() {
+            log::set_max_level(log::LevelFilter::Trace);
+            INSTALLED.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    });
+    if !INSTALLED.load(std::sync::atomic::Ordering::SeqCst) {
+        return None;
+    }
+
+    CAPTURED_LOGS.with(|captured| *captured.borrow_mut() = Some(Vec::new()));
+    f();
+    CAPTURED_LOGS.with(|captured| captured.borrow_mut().take())
+}
+
+#[test]
+fn resource_logs_follow_the_level() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let id = instance(&mut resource_manager, task);
+    let building = format!("Building {}", id);
+    // The logs of wgpu itself do not follow the resource log level.
+    let engine_levels = |logs: Vec<(log::Level, String, String)>| -> Vec<(log::Level, String)> {
+        logs.into_iter()
+            .filter(|(_level, target, _message)| target == "EntityManager" || target == "Engine")
+            .map(|(level, _target, message)| (level, message))
+            .collect()
+    };
+
+    resource_manager.set_resource_log_level(log::LevelFilter::Warn);
+    let logs = match capture_logs(|| assert!(resource_manager.commit_resources())) {
+        Some(logs) => engine_levels(logs),
+        None => return,
+    };
+    // Neither the builders nor the commit log below the resource log level.
+    assert!(logs
+        .iter()
+        .all(|(level, _message)| *level <= log::Level::Warn));
+
+    resource_manager.set_resource_log_level(log::LevelFilter::Info);
+    assert!(resource_manager.touch_resource(id));
+    let logs =
+        engine_levels(capture_logs(|| assert!(resource_manager.commit_resources())).unwrap());
+    assert!(logs.contains(&(log::Level::Info, building)));
+    assert!(logs
+        .iter()
+        .all(|(level, _message)| *level <= log::Level::Info));
+}
+
+#[test]
+fn frame_not_acquired_is_not_a_failed_build() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let command_buffer = EntityId::new(1);
+    let swapchain = SwapchainId::new(EntityId::new(2));
+
+    // Transient, so neither reported in strict mode nor to the tasks.
+    assert!(!resource_manager.record_build_failure(
+        command_buffer,
+        Some(ResourceBuilderError::SwapchainFrameNotAcquired(swapchain))
+    ));
+    assert!(resource_manager.failed_builds().is_empty());
+    assert!(resource_manager.build_errors().is_empty());
+
+    assert!(resource_manager.record_build_failure(
+        command_buffer,
+        Some(ResourceBuilderError::MissingDependencies)
+    ));
+    assert_eq!(resource_manager.failed_builds(), &[command_buffer]);
+    assert_eq!(
+        resource_manager.build_errors(),
+        &[(command_buffer, ResourceBuilderError::MissingDependencies)]
+    );
+}
+
+#[test]
+fn patch_draw_instances_in_place() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let first_task = TaskId::new(EntityId::new(0));
+    let second_task = TaskId::new(EntityId::new(1));
+    let device = device(&mut resource_manager, first_task);
+    let draw = |instances| RenderCommand::Draw {
+        vertices: 0..3,
+        instances,
+    };
+    let descriptor = CommandBufferDescriptor {
+        label: String::from("ResourceManagerTest command buffer"),
+        device,
+        commands: vec![Command::RenderPass {
+            label: String::from("ResourceManagerTest pass"),
+            depth_stencil: None,
+            color_attachments: Vec::new(),
+            commands: vec![draw(0..1), draw(0..1)],
+        }],
+        max_commands: None,
+    };
+    let draws = |resource_manager: &ResourceManager, id: &CommandBufferId| match &resource_manager
+        .command_buffer_descriptor_ref(id)
+        .unwrap()
+        .commands[0]
+    {
+        Command::RenderPass { commands, .. } => commands.clone(),
+        _ => panic!("Expected a render pass"),
+    };
+
+    let mut first = resource_manager
+        .add_command_buffer(first_task, descriptor.clone(), None)
+        .unwrap();
+    let mut second = resource_manager
+        .add_command_buffer(second_task, descriptor, None)
+        .unwrap();
+    assert_eq!(first, second);
+
+    // The shared command buffer is forked, the other task keeps the original draw.
+    assert!(resource_manager.patch_draw_instances(&second_task, &mut second, 0, 1, 0..4));
+    assert_ne!(first, second);
+    assert_eq!(
+        draws(&resource_manager, &first),
+        vec![draw(0..1), draw(0..1)]
+    );
+    assert_eq!(
+        draws(&resource_manager, &second),
+        vec![draw(0..1), draw(0..4)]
+    );
+
+    // Once owned by a single task, the draw is patched without changing the id.
+    let before = first;
+    assert!(resource_manager.patch_draw_instances(&first_task, &mut first, 0, 0, 0..8));
+    assert_eq!(first, before);
+    assert_eq!(
+        draws(&resource_manager, &first),
+        vec![draw(0..8), draw(0..1)]
+    );
+    assert_eq!(resource_manager.command_buffers().count(), 2);
+
+    // Only the owners can patch, and only draws.
+    assert!(!resource_manager.patch_draw_instances(&second_task, &mut first, 0, 0, 0..2));
+    assert!(!resource_manager.patch_draw_instances(&first_task, &mut first, 0, 2, 0..2));
+    assert_eq!(
+        draws(&resource_manager, &first),
+        vec![draw(0..8), draw(0..1)]
+    );
+}
+
+#[test]
+fn device_rebuilt_with_reduced_limits() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+
+    let adapter_info = crate::wgpu::Instance::new(crate::wgpu::BackendBit::PRIMARY)
+        .enumerate_adapters(crate::wgpu::BackendBit::PRIMARY)
+        .next()
+        .unwrap()
+        .get_info();
+    let backend = crate::wgpu::BackendBit::from(adapter_info.backend);
+    let instance = resource_manager
+        .add_instance(
+            task,
+            InstanceDescriptor {
+                label: String::from("ResourceManagerTest instance"),
+                backend,
+            },
+            None,
+        )
+        .unwrap();
+    let descriptor = DeviceDescriptor {
+        label: adapter_info.name,
+        instance,
+        backend,
+        pci_id: adapter_info.device,
+        features: crate::wgpu::Features::empty(),
+        limits: crate::wgpu::Limits::default(),
+    };
+    let mut device = resource_manager
+        .add_device(task, descriptor.clone(), None)
+        .unwrap();
+    assert!(resource_manager.commit_resources());
+    assert!(resource_manager.device_handle_ref(&device).is_some());
+    assert_eq!(resource_manager.device_regression(&device), None);
+
+    // Like an adapter offering less after a hot-plug: the device gets rebuilt with fewer bind groups.
+    let reduced = DeviceDescriptor {
+        limits: crate::wgpu::Limits {
+            max_bind_groups: 2,
+            ..crate::wgpu::Limits::default()
+        },
+        ..descriptor.clone()
+    };
+    assert!(resource_manager.update_device_descriptor(&task, &mut device, reduced));
+    assert!(resource_manager.commit_resources());
+    assert_eq!(
+        resource_manager.device_regression(&device),
+        Some(&DeviceRegression {
+            features: crate::wgpu::Features::empty(),
+            limits: vec![(
+                "max_bind_groups",
+                crate::wgpu::Limits::default().max_bind_groups,
+                2
+            )],
+        })
+    );
+
+    // Getting the capabilities back clears the regression.
+    assert!(resource_manager.update_device_descriptor(&task, &mut device, descriptor));
+    assert!(resource_manager.commit_resources());
+    assert_eq!(resource_manager.device_regression(&device), None);
+}
+
+#[cfg(feature = "debug_text")]
+#[test]
+fn debug_text_rolled_back_on_failure() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut resource_manager = ResourceManager::new(runtime.handle().clone());
+    let task = TaskId::new(EntityId::new(0));
+    let device = device(&mut resource_manager, task);
+    let mut events = Vec::new();
+    let mut update_context = UpdateContext::new(task, &mut resource_manager, &mut events);
+
+    // The target does not exist, so the command buffer, created last, fails.
+    let target = ColorView::TextureView(TextureViewId::new(EntityId::new(100)));
+    assert!(DebugTextTask::new(
+        &mut update_context,
+        device,
+        target,
+        crate::wgpu::TextureFormat::Rgba8Unorm,
+        64,
+        64,
+        1,
+    )
+    .is_err());
+    assert_eq!(resource_manager.shader_modules().count(), 0);
+    assert_eq!(resource_manager.buffers().count(), 0);
+    assert_eq!(resource_manager.textures().count(), 0);
+    assert_eq!(resource_manager.texture_views().count(), 0);
+    assert_eq!(resource_manager.bind_group_layouts().count(), 0);
+    assert_eq!(resource_manager.bind_groups().count(), 0);
+    assert_eq!(resource_manager.pipeline_layouts().count(), 0);
+    assert_eq!(resource_manager.render_pipelines().count(), 0);
+}
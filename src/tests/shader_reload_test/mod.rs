@@ -0,0 +1,177 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+pub struct ShaderReloadTask {
+    device: DeviceId,
+    shader_modules: [ShaderModuleId; 2],
+    render_pipelines: [RenderPipelineId; 2],
+    command_buffers: [CommandBufferId; 2],
+    frame: usize,
+    damaged: Option<Vec<EntityId>>,
+}
+
+impl ShaderReloadTask {
+    const TASK_NAME: &'static str = "ShaderReloadTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let shader_modules = [0, 1].map(|index| {
+            update_context
+                .add_shader_module_descriptor(Self::shader_module_descriptor(device, index, ""))
+                .unwrap()
+        });
+        let render_pipelines = [0, 1].map(|index| {
+            update_context
+                .add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                    label: format!("{} {}", Self::TASK_NAME, index),
+                    device,
+                    layout: None,
+                    vertex: VertexState {
+                        module: shader_modules[index],
+                        entry_point: String::from("vs_main"),
+                        buffers: Vec::new(),
+                    },
+                    primitive: crate::wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: crate::wgpu::MultisampleState::default(),
+                    fragment: Some(FragmentState {
+                        module: shader_modules[index],
+                        entry_point: String::from("fs_main"),
+                        targets: vec![crate::wgpu::ColorTargetState {
+                            format: crate::wgpu::TextureFormat::Rgba8Unorm,
+                            blend: None,
+                            write_mask: crate::wgpu::ColorWrite::ALL,
+                        }],
+                    }),
+                })
+                .unwrap()
+        });
+
+        let texture = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " target",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::RENDER_ATTACHMENT,
+                size: crate::wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                format: crate::wgpu::TextureFormat::Rgba8Unorm,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        let view = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                Self::TASK_NAME.to_string() + " target view",
+                device,
+                texture,
+                crate::wgpu::TextureFormat::Rgba8Unorm,
+                0,
+            ))
+            .unwrap();
+        // One command buffer per pipeline, so reloading a shader rebuilds only the one drawing with it.
+        let command_buffers = [0, 1].map(|index| {
+            update_context
+                .add_command_buffer_descriptor(CommandBufferDescriptor {
+                    label: format!("{} command buffer {}", Self::TASK_NAME, index),
+                    device,
+                    commands: vec![Command::RenderPass {
+                        label: format!("{} pass {}", Self::TASK_NAME, index),
+                        depth_stencil: None,
+                        color_attachments: vec![RenderPassColorAttachment::clear(
+                            ColorView::TextureView(view),
+                            crate::wgpu::Color::BLACK,
+                        )],
+                        commands: vec![
+                            RenderCommand::SetPipeline {
+                                pipeline: render_pipelines[index],
+                            },
+                            RenderCommand::Draw {
+                                vertices: 0..3,
+                                instances: 0..1,
+                            },
+                        ],
+                    }],
+                    max_commands: None,
+                })
+                .unwrap()
+        });
+
+        Self {
+            device,
+            shader_modules,
+            render_pipelines,
+            command_buffers,
+            frame: 0,
+            damaged: None,
+        }
+    }
+
+    fn shader_module_descriptor(
+        device: DeviceId,
+        index: usize,
+        suffix: &str,
+    ) -> ShaderModuleDescriptor {
+        ShaderModuleDescriptor {
+            label: format!("{} {}{}", Self::TASK_NAME, index, suffix),
+            device,
+            source: ShaderSource::Wgsl(include_str!("../triangle_test/shader.wgsl").to_string()),
+            flags: crate::wgpu::ShaderFlags::VALIDATION,
+        }
+    }
+}
+
+impl TaskTrait for ShaderReloadTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        if self.frame == 1 {
+            assert!(update_context.update_shader_module_descriptor(
+                &mut self.shader_modules[0],
+                Self::shader_module_descriptor(self.device, 0, " reloaded"),
+            ));
+            self.damaged = Some(update_context.damaged_entities());
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn reload_rebuilds_only_dependents() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        ShaderReloadTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        ShaderReloadTask::new,
+    );
+
+    let (shader_modules, render_pipelines, command_buffers, damaged) =
+        run_task(&mut wgpu_engine, &task, 2, |task: &mut ShaderReloadTask| {
+            (
+                task.shader_modules,
+                task.render_pipelines,
+                task.command_buffers,
+                task.damaged.clone(),
+            )
+        });
+
+    // The command buffer drawing with the other pipeline is left as it is.
+    let mut expected = vec![
+        *shader_modules[0].id_ref(),
+        *render_pipelines[0].id_ref(),
+        *command_buffers[0].id_ref(),
+    ];
+    expected.sort();
+    assert_eq!(damaged, Some(expected));
+}
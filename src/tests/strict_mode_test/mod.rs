@@ -0,0 +1,195 @@
+use crate::entity_manager::UpdateContext;
+use crate::*;
+
+pub struct StrictModeTask {
+    view: TextureViewId,
+    bind_group: BindGroupId,
+    buffer: BufferId,
+    build_errors: Vec<(EntityId, ResourceBuilderError)>,
+    frame_numbers: Vec<u64>,
+    result: Option<Vec<u8>>,
+}
+
+impl StrictModeTask {
+    const TASK_NAME: &'static str = "StrictModeTask";
+    const DATA: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let format = crate::wgpu::TextureFormat::Rgba8Unorm;
+        let texture = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::SAMPLED,
+                size: crate::wgpu::Extent3d {
+                    width: 16,
+                    height: 16,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        // The texture has a single layer, so the view fails to build.
+        let view = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                Self::TASK_NAME.to_string() + " view",
+                device,
+                texture,
+                format,
+                2,
+            ))
+            .unwrap();
+        let layout = update_context
+            .add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                entries: vec![BindGroupLayoutEntry::texture(
+                    0,
+                    crate::wgpu::ShaderStage::FRAGMENT,
+                )],
+            })
+            .unwrap();
+        // The bind group depends on the view, so it cannot be built either.
+        let bind_group = update_context
+            .add_bind_group_descriptor(BindGroupDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(view),
+                }],
+            })
+            .unwrap();
+        // Builds fine, to check that its writes survive an aborted dispatch.
+        let buffer = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " buffer",
+                device,
+                size: Self::DATA.len() as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        Self {
+            view,
+            bind_group,
+            buffer,
+            build_errors: Vec::new(),
+            frame_numbers: Vec::new(),
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for StrictModeTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        self.build_errors = update_context.last_build_errors();
+        if self.frame_numbers.is_empty() {
+            update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                buffer: self.buffer,
+                offset: 0,
+                data: Self::DATA.to_vec(),
+            })]);
+        } else {
+            self.result = update_context
+                .read_buffer(&self.buffer, 0, Self::DATA.len() as u64)
+                .ok();
+        }
+        self.frame_numbers.push(update_context.frame_number());
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn strict_mode_surfaces_missing_dependencies() {
+    let mut wgpu_engine = WGpuEngine::new((
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    ))
+    .unwrap();
+    wgpu_engine.set_strict(true);
+    let task = wgpu_engine
+        .create_task(
+            StrictModeTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| StrictModeTask::new(update_context),
+        )
+        .unwrap();
+    let (view, bind_group) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut StrictModeTask| {
+            (task.view, task.bind_group)
+        })
+        .unwrap();
+
+    match wgpu_engine.try_dispatch_tasks() {
+        Err(WGpuEngineError::BuildFailed(failed_builds)) => {
+            assert!(failed_builds.contains(view.id_ref()));
+            assert!(failed_builds.contains(bind_group.id_ref()));
+        }
+        result => panic!("Expected a build failure, got {:?}", result),
+    }
+
+    // Without the strict mode, the failure is only logged.
+    wgpu_engine.set_strict(false);
+    assert!(wgpu_engine.try_dispatch_tasks().is_ok());
+
+    // The aborted dispatch still uploaded the writes and ended its frame.
+    let (frame_numbers, result) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut StrictModeTask| {
+            (task.frame_numbers.clone(), task.result.clone())
+        })
+        .unwrap();
+    assert_eq!(frame_numbers.len(), 2);
+    assert_eq!(frame_numbers[1], frame_numbers[0] + 1);
+    assert_eq!(result, Some(StrictModeTask::DATA.to_vec()));
+}
+
+#[test]
+fn build_errors_reported_to_tasks() {
+    let mut wgpu_engine = WGpuEngine::new((
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    ))
+    .unwrap();
+    let task = wgpu_engine
+        .create_task(
+            StrictModeTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, update_context| StrictModeTask::new(update_context),
+        )
+        .unwrap();
+
+    // The errors of a commit are available to the tasks on the next dispatch.
+    wgpu_engine.dispatch_tasks();
+    wgpu_engine.dispatch_tasks();
+
+    let (view, bind_group, build_errors) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut StrictModeTask| {
+            (task.view, task.bind_group, task.build_errors.clone())
+        })
+        .unwrap();
+    assert!(build_errors.contains(&(
+        *view.id_ref(),
+        ResourceBuilderError::InvalidTextureViewRange
+    )));
+    assert!(build_errors.contains(&(
+        *bind_group.id_ref(),
+        ResourceBuilderError::MissingDependencies
+    )));
+}
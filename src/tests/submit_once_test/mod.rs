@@ -0,0 +1,106 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+const DATA: [u8; 16] = [16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1];
+
+pub struct SubmitOnceTask {
+    device: DeviceId,
+    upload: BufferId,
+    readback: BufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl SubmitOnceTask {
+    const TASK_NAME: &'static str = "SubmitOnceTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+
+        let upload = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " upload buffer",
+                device,
+                size: DATA.len() as u64,
+                usage: crate::wgpu::BufferUsage::MAP_WRITE | crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: DATA.len() as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+
+        Self {
+            device,
+            upload,
+            readback,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for SubmitOnceTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            1 => {
+                update_context
+                    .write_buffer_mapped(&self.upload, 0, &DATA)
+                    .unwrap();
+            }
+            2 => {
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, DATA.len() as u64)
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn one_off_buffer_copy() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        SubmitOnceTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        SubmitOnceTask::new,
+    );
+
+    let (device, upload, readback) =
+        run_task(&mut wgpu_engine, &task, 2, |task: &mut SubmitOnceTask| {
+            (task.device, task.upload, task.readback)
+        });
+    wgpu_engine
+        .submit_once(CommandBufferDescriptor {
+            label: String::from("One-off copy"),
+            device,
+            commands: vec![Command::BufferToBuffer(BufferToBufferCopy {
+                src_buffer: upload,
+                src_offset: 0,
+                dst_buffer: readback,
+                dst_offset: 0,
+                size: DATA.len() as u64,
+            })],
+            max_commands: None,
+        })
+        .unwrap();
+
+    let result = run_task(&mut wgpu_engine, &task, 1, |task: &mut SubmitOnceTask| {
+        task.result.clone()
+    });
+    assert_eq!(result, Some(DATA.to_vec()));
+}
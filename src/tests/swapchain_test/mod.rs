@@ -0,0 +1,703 @@
+//! Tests on the swapchains of real windows: they need a display, so they are ignored by default.
+
+use crate::tests::run_on_surfaces;
+use crate::*;
+
+/// Task recording the events it receives, and whether the swapchain of the event still existed when received.
+struct SwapchainWatcherTask {
+    events: Vec<(ResourceEvent, bool)>,
+    /// Destroyed swapchains found removed on a later dispatch.
+    removed: Vec<SwapchainId>,
+    /// State of the first created swapchain seen by every dispatch.
+    dispatches: Vec<DispatchRecord>,
+}
+
+#[derive(Debug, Clone)]
+struct DispatchRecord {
+    size: (u32, u32),
+    /// Whether a frame has been acquired for the dispatch.
+    acquired: bool,
+    /// Whether something has rendered on the swapchain, and how many of its frames it cleared by itself.
+    rendered: bool,
+    cleared_frames: usize,
+    presented_frames: usize,
+    /// Operations of the passes clearing the frames, with the clear color of the surface.
+    clear_operations: Option<crate::wgpu::Operations<crate::wgpu::Color>>,
+    events: Vec<ResourceEvent>,
+}
+
+impl SwapchainWatcherTask {
+    const TASK_NAME: &'static str = "SwapchainWatcherTask";
+
+    fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            removed: Vec::new(),
+            dispatches: Vec::new(),
+        }
+    }
+
+    fn count(&self, filter: impl Fn(&ResourceEvent) -> bool) -> usize {
+        self.events
+            .iter()
+            .filter(|(event, _)| filter(event))
+            .count()
+    }
+}
+
+impl TaskTrait for SwapchainWatcherTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        let first_swapchain = self.events.iter().find_map(|(event, _)| match event {
+            ResourceEvent::SwapchainCreated { swapchain, .. } => Some(*swapchain),
+            _ => None,
+        });
+        for event in update_context.events().clone() {
+            let exists = event
+                .swapchain()
+                .map(|swapchain| {
+                    update_context
+                        .swapchain_descriptor_ref(&swapchain)
+                        .is_some()
+                })
+                .unwrap_or(false);
+            self.events.push((event, exists));
+        }
+        for (event, _) in &self.events {
+            if let ResourceEvent::SwapchainDestroyed(swapchain) = event {
+                if update_context.swapchain_descriptor_ref(swapchain).is_none()
+                    && !self.removed.contains(swapchain)
+                {
+                    self.removed.push(*swapchain);
+                }
+            }
+        }
+        let first_swapchain = first_swapchain.or_else(|| {
+            update_context
+                .events()
+                .iter()
+                .find_map(|event| match event {
+                    ResourceEvent::SwapchainCreated { swapchain, .. } => Some(*swapchain),
+                    _ => None,
+                })
+        });
+        if let Some(swapchain) = first_swapchain {
+            let size = update_context
+                .swapchain_descriptor_ref(&swapchain)
+                .map(|descriptor| (descriptor.width, descriptor.height));
+            let handle = update_context.swapchain_handle_ref(&swapchain);
+            let acquired = handle
+                .map(|handle| handle.current_frame().is_some())
+                .unwrap_or(false);
+            let rendered = handle.map(|handle| handle.is_rendered()).unwrap_or(false);
+            let cleared_frames = handle.map(|handle| handle.cleared_frames()).unwrap_or(0);
+            let presented_frames = handle.map(|handle| handle.presented_frames()).unwrap_or(0);
+            let clear_operations = handle.map(|handle| handle.clear_operations());
+            if let Some(size) = size {
+                self.dispatches.push(DispatchRecord {
+                    size,
+                    acquired,
+                    rendered,
+                    cleared_frames,
+                    presented_frames,
+                    clear_operations,
+                    events: update_context.events().clone(),
+                });
+            }
+        }
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+fn new_engine() -> WGpuEngine {
+    WGpuEngine::new((
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+    ))
+    .unwrap()
+}
+
+fn create_watcher(wgpu_engine: &mut WGpuEngine) -> TaskId {
+    wgpu_engine
+        .create_task(
+            SwapchainWatcherTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, _update_context| SwapchainWatcherTask::new(),
+        )
+        .unwrap()
+}
+
+#[test]
+#[ignore]
+fn destroyed_swapchain_outlives_its_event() {
+    let mut wgpu_engine = new_engine();
+    let watchers = vec![
+        create_watcher(&mut wgpu_engine),
+        create_watcher(&mut wgpu_engine),
+    ];
+
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |wgpu_engine, surfaces, frame| {
+            if frame == 1 {
+                wgpu_engine.destroy_surface(surfaces[0]);
+            }
+            frame < 3
+        },
+    );
+
+    for watcher in &watchers {
+        let (events, removed) = wgpu_engine
+            .task_handle_cast_mut(watcher, |task: &mut SwapchainWatcherTask| {
+                (task.events.clone(), task.removed.clone())
+            })
+            .unwrap();
+        let destroyed: Vec<_> = events
+            .iter()
+            .filter(|(event, _)| matches!(event, ResourceEvent::SwapchainDestroyed(_)))
+            .collect();
+        // Every task gets the event while the swapchain can still be inspected.
+        assert_eq!(destroyed.len(), 1);
+        assert!(destroyed[0].1);
+        // And the swapchain is removed on the next dispatch.
+        assert_eq!(removed, vec![destroyed[0].0.swapchain().unwrap()]);
+    }
+    assert_eq!(wgpu_engine.surface_count(), 0);
+}
+
+#[test]
+#[ignore]
+fn all_surfaces_ready_once() {
+    let mut wgpu_engine = new_engine();
+    let watcher = create_watcher(&mut wgpu_engine);
+
+    let mut ready_frames = Vec::new();
+    run_on_surfaces(
+        &mut wgpu_engine,
+        2,
+        crate::wgpu::Color::BLACK,
+        |wgpu_engine, _surfaces, frame| {
+            if wgpu_engine.surfaces_ready() {
+                ready_frames.push(frame);
+            }
+            frame < 4
+        },
+    );
+
+    // Once both swapchains are built, they stay ready.
+    assert!(!ready_frames.is_empty());
+    assert_eq!(ready_frames, (ready_frames[0]..=4).collect::<Vec<_>>());
+    let (created, ready, ready_after_created) = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            let ready_index = task
+                .events
+                .iter()
+                .position(|(event, _)| event == &ResourceEvent::AllSurfacesReady);
+            let last_created_index = task
+                .events
+                .iter()
+                .rposition(|(event, _)| matches!(event, ResourceEvent::SwapchainCreated { .. }));
+            (
+                task.count(|event| matches!(event, ResourceEvent::SwapchainCreated { .. })),
+                task.count(|event| event == &ResourceEvent::AllSurfacesReady),
+                ready_index > last_created_index,
+            )
+        })
+        .unwrap();
+    assert_eq!(created, 2);
+    assert_eq!(ready, 1);
+    assert!(ready_after_created);
+}
+
+#[test]
+#[ignore]
+fn resize_applied_at_frame_boundary() {
+    let mut wgpu_engine = new_engine();
+    let watcher = create_watcher(&mut wgpu_engine);
+
+    let mut resized_at = None;
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |wgpu_engine, surfaces, frame| {
+            if frame == 2 {
+                resized_at = wgpu_engine
+                    .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+                        task.dispatches.len()
+                    });
+                wgpu_engine.resize_surface(surfaces[0], 64, 48);
+            }
+            frame < 5
+        },
+    );
+
+    let resized_at = resized_at.unwrap();
+    let dispatches = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            task.dispatches.clone()
+        })
+        .unwrap();
+    let old_size = dispatches[resized_at - 1].size;
+    assert_ne!(old_size, (64, 48));
+    // The dispatch right after the request still builds and presents the frame of the old swapchain.
+    assert_eq!(dispatches[resized_at].size, old_size);
+    assert!(dispatches[resized_at].acquired);
+    assert!(!dispatches[resized_at]
+        .events
+        .iter()
+        .any(|event| matches!(event, ResourceEvent::SwapchainUpdated(_))));
+    // The resize is applied once that frame has been presented, so the next dispatch rebuilds the swapchain.
+    assert!(dispatches[resized_at + 1]
+        .events
+        .iter()
+        .any(|event| matches!(event, ResourceEvent::SwapchainUpdated(_))));
+    assert!(dispatches[resized_at + 1..]
+        .iter()
+        .all(|dispatch| dispatch.size == (64, 48)));
+    // From then on the frames are acquired from the rebuilt swapchain.
+    assert!(dispatches[resized_at + 2..]
+        .iter()
+        .all(|dispatch| dispatch.acquired));
+}
+
+#[test]
+#[ignore]
+fn zero_size_suspends_and_resumes() {
+    let mut wgpu_engine = new_engine();
+    let watcher = create_watcher(&mut wgpu_engine);
+
+    let mut minimized_at = None;
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |wgpu_engine, surfaces, frame| {
+            match frame {
+                // Like a minimized window.
+                2 => {
+                    minimized_at = wgpu_engine
+                        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+                            task.dispatches.len()
+                        });
+                    wgpu_engine.resize_surface(surfaces[0], 0, 0);
+                }
+                5 => wgpu_engine.resize_surface(surfaces[0], 64, 48),
+                _ => {}
+            }
+            frame < 8
+        },
+    );
+
+    let minimized_at = minimized_at.unwrap();
+    let dispatches = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            task.dispatches.clone()
+        })
+        .unwrap();
+    let find = |filter: fn(&ResourceEvent) -> bool| {
+        dispatches
+            .iter()
+            .enumerate()
+            .filter(|(_index, dispatch)| dispatch.events.iter().any(filter))
+            .map(|(index, _dispatch)| index)
+            .collect::<Vec<_>>()
+    };
+    let suspended = find(|event| matches!(event, ResourceEvent::SwapchainSuspended(_)));
+    let resumed = find(|event| matches!(event, ResourceEvent::SwapchainResumed(_)));
+    assert_eq!(suspended, vec![minimized_at + 1]);
+    assert_eq!(resumed.len(), 1);
+    assert!(resumed[0] > suspended[0]);
+
+    // While suspended the swapchain keeps its last size and no frame is acquired.
+    let old_size = dispatches[minimized_at - 1].size;
+    for dispatch in &dispatches[suspended[0]..resumed[0]] {
+        assert_eq!(dispatch.size, old_size);
+        assert!(!dispatch.acquired);
+    }
+    // Once resumed it gets reconfigured with the new size and rendered again.
+    let last = dispatches.last().unwrap();
+    assert_eq!(last.size, (64, 48));
+    assert!(last.acquired);
+}
+
+/// Task clearing the first created swapchain with its own render pass, on every dispatch or only on the first `dispatches`.
+struct SwapchainPassTask {
+    command_buffer: Option<CommandBufferId>,
+    build_errors: Vec<(EntityId, ResourceBuilderError)>,
+    dispatches: Option<usize>,
+}
+
+impl SwapchainPassTask {
+    const TASK_NAME: &'static str = "SwapchainPassTask";
+
+    fn new(dispatches: Option<usize>) -> Self {
+        Self {
+            command_buffer: None,
+            build_errors: Vec::new(),
+            dispatches,
+        }
+    }
+}
+
+impl TaskTrait for SwapchainPassTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        self.build_errors.extend(update_context.last_build_errors());
+        if self.command_buffer.is_some() {
+            self.dispatches = self
+                .dispatches
+                .map(|dispatches| dispatches.saturating_sub(1));
+            return;
+        }
+        let swapchain = update_context
+            .events()
+            .iter()
+            .find_map(|event| match event {
+                ResourceEvent::SwapchainCreated { swapchain, .. } => Some(*swapchain),
+                _ => None,
+            });
+        if let Some(swapchain) = swapchain {
+            let device = update_context.devices().next().unwrap();
+            self.command_buffer = update_context
+                .add_command_buffer_descriptor(CommandBufferDescriptor {
+                    label: Self::TASK_NAME.to_string(),
+                    device,
+                    commands: vec![Command::RenderPass {
+                        label: Self::TASK_NAME.to_string(),
+                        depth_stencil: None,
+                        color_attachments: vec![RenderPassColorAttachment::clear(
+                            ColorView::Swapchain(swapchain),
+                            crate::wgpu::Color::WHITE,
+                        )],
+                        commands: Vec::new(),
+                    }],
+                    max_commands: None,
+                })
+                .ok();
+        }
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        if self.dispatches == Some(0) {
+            return Vec::new();
+        }
+        self.command_buffer.into_iter().collect()
+    }
+}
+
+#[test]
+#[ignore]
+fn suspended_swapchain_pass_is_not_a_failed_build() {
+    let mut wgpu_engine = new_engine();
+    // Strict mode panics on the first failed build.
+    wgpu_engine.set_strict(true);
+    let watcher = create_watcher(&mut wgpu_engine);
+    let task = wgpu_engine
+        .create_task(
+            SwapchainPassTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, _update_context| SwapchainPassTask::new(None),
+        )
+        .unwrap();
+
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |wgpu_engine, surfaces, frame| {
+            match frame {
+                2 => wgpu_engine.resize_surface(surfaces[0], 0, 0),
+                5 => wgpu_engine.resize_surface(surfaces[0], 64, 48),
+                _ => {}
+            }
+            frame < 8
+        },
+    );
+
+    // The pass has no frame to render on while suspended, but that is not reported as a build error.
+    let (command_buffer, build_errors) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut SwapchainPassTask| {
+            (task.command_buffer, task.build_errors.clone())
+        })
+        .unwrap();
+    assert!(command_buffer.is_some());
+    assert!(build_errors.is_empty());
+    let suspended = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            task.count(|event| matches!(event, ResourceEvent::SwapchainSuspended(_)))
+        })
+        .unwrap();
+    assert_eq!(suspended, 1);
+}
+
+#[test]
+#[ignore]
+fn untouched_swapchain_first_frame_cleared() {
+    let mut wgpu_engine = new_engine();
+    let watcher = create_watcher(&mut wgpu_engine);
+
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |_wgpu_engine, _surfaces, frame| frame < 4,
+    );
+
+    let dispatches = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            task.dispatches.clone()
+        })
+        .unwrap();
+    assert!(dispatches.len() >= 2);
+    // Nothing renders on the swapchain, so its first frame is cleared instead of presenting undefined contents.
+    assert_eq!(dispatches[0].cleared_frames, 0);
+    assert_eq!(dispatches[1].cleared_frames, 1);
+    assert!(dispatches.iter().all(|dispatch| !dispatch.rendered));
+}
+
+#[test]
+#[ignore]
+fn rendered_swapchain_not_cleared_again() {
+    let mut wgpu_engine = new_engine();
+    let watcher = create_watcher(&mut wgpu_engine);
+    wgpu_engine
+        .create_task(
+            SwapchainPassTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, _update_context| SwapchainPassTask::new(None),
+        )
+        .unwrap();
+
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |_wgpu_engine, _surfaces, frame| frame < 5,
+    );
+
+    let dispatches = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            task.dispatches.clone()
+        })
+        .unwrap();
+    // Once the pass renders on it, the frames are presented as they are.
+    let first_rendered = dispatches
+        .iter()
+        .position(|dispatch| dispatch.rendered)
+        .unwrap();
+    let cleared_frames = dispatches[first_rendered].cleared_frames;
+    assert!(dispatches[first_rendered..]
+        .iter()
+        .all(|dispatch| dispatch.rendered && dispatch.cleared_frames == cleared_frames));
+}
+
+#[test]
+#[ignore]
+fn surface_clear_color_reaches_the_clears() {
+    let red = crate::wgpu::Color::RED;
+    let mut wgpu_engine = new_engine();
+    let watcher = create_watcher(&mut wgpu_engine);
+
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        red,
+        |_wgpu_engine, _surfaces, frame| frame < 4,
+    );
+
+    let dispatches = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            task.dispatches.clone()
+        })
+        .unwrap();
+    assert!(dispatches.len() >= 2);
+    // Both the first frame clear and the clear pass of every frame use the color of the surface, and store it.
+    let expected = crate::wgpu::Operations {
+        load: crate::wgpu::LoadOp::Clear(red),
+        store: true,
+    };
+    assert!(dispatches
+        .iter()
+        .all(|dispatch| dispatch.clear_operations == Some(expected)));
+    // The untouched swapchain presents its first frame cleared to it.
+    assert_eq!(dispatches[1].cleared_frames, 1);
+}
+
+#[test]
+#[ignore]
+fn throttled_present_skips_untouched_frames() {
+    let mut wgpu_engine = new_engine();
+    wgpu_engine.set_max_skipped_presents(Some(2));
+    let watcher = create_watcher(&mut wgpu_engine);
+    wgpu_engine
+        .create_task(
+            SwapchainPassTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            |_id, _tokio, _update_context| SwapchainPassTask::new(Some(2)),
+        )
+        .unwrap();
+
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |_wgpu_engine, _surfaces, frame| frame < 16,
+    );
+
+    let dispatches = wgpu_engine
+        .task_handle_cast_mut(&watcher, |task: &mut SwapchainWatcherTask| {
+            task.dispatches.clone()
+        })
+        .unwrap();
+    let first_rendered = dispatches
+        .iter()
+        .position(|dispatch| dispatch.rendered)
+        .unwrap();
+    // Once the pass stops rendering, the swapchain is untouched.
+    let untouched = &dispatches[first_rendered + 3..];
+    assert!(untouched.len() >= 9);
+    // Every untouched frame presented shows the clear color, never undefined contents.
+    let presented = untouched.last().unwrap().presented_frames - untouched[0].presented_frames;
+    let cleared = untouched.last().unwrap().cleared_frames - untouched[0].cleared_frames;
+    assert_eq!(cleared, presented);
+    // Some presents are skipped, but never more than 2 in a row.
+    assert!(presented < untouched.len() - 1);
+    assert!(untouched
+        .windows(4)
+        .all(|window| window[3].presented_frames > window[0].presented_frames));
+}
+
+/// Task claiming the first created swapchain, rendering on it and presenting it on every dispatch except `skipped_dispatch`.
+struct PresentingTask {
+    skipped_dispatch: usize,
+    swapchain: Option<SwapchainId>,
+    command_buffer: Option<CommandBufferId>,
+    /// Result of the present and frames presented by the swapchain, for every dispatch after the claim.
+    presents: Vec<(Option<Result<(), ()>>, usize)>,
+}
+
+impl PresentingTask {
+    const TASK_NAME: &'static str = "PresentingTask";
+}
+
+impl TaskTrait for PresentingTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        let swapchain = match self.swapchain {
+            Some(swapchain) => swapchain,
+            None => {
+                let swapchain = update_context
+                    .events()
+                    .iter()
+                    .find_map(|event| match event {
+                        ResourceEvent::SwapchainCreated { swapchain, .. } => Some(*swapchain),
+                        _ => None,
+                    });
+                if let Some(swapchain) = swapchain {
+                    assert!(update_context.claim_swapchain(swapchain));
+                    let device = update_context.devices().next().unwrap();
+                    self.command_buffer = update_context
+                        .add_command_buffer_descriptor(CommandBufferDescriptor {
+                            label: Self::TASK_NAME.to_string(),
+                            device,
+                            commands: vec![Command::RenderPass {
+                                label: Self::TASK_NAME.to_string(),
+                                depth_stencil: None,
+                                color_attachments: vec![RenderPassColorAttachment::clear(
+                                    ColorView::Swapchain(swapchain),
+                                    crate::wgpu::Color::WHITE,
+                                )],
+                                commands: Vec::new(),
+                            }],
+                            max_commands: None,
+                        })
+                        .ok();
+                    self.swapchain = Some(swapchain);
+                }
+                return;
+            }
+        };
+
+        // The frame rendered by the previous dispatch is still waiting for this task to present it.
+        let present = if self.presents.len() == self.skipped_dispatch {
+            None
+        } else {
+            Some(update_context.present_swapchain(&swapchain))
+        };
+        let presented_frames = update_context
+            .swapchain_handle_ref(&swapchain)
+            .map(|handle| handle.presented_frames())
+            .unwrap_or(0);
+        self.presents.push((present, presented_frames));
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        self.command_buffer.into_iter().collect()
+    }
+}
+
+#[test]
+#[ignore]
+fn claimed_swapchain_presented_by_its_task() {
+    let skipped_dispatch = 2;
+    let mut wgpu_engine = new_engine();
+    let task = wgpu_engine
+        .create_task(
+            PresentingTask::TASK_NAME.to_string(),
+            (
+                crate::wgpu::Features::empty(),
+                crate::wgpu::Limits::default(),
+            ),
+            move |_id, _tokio, _update_context| PresentingTask {
+                skipped_dispatch,
+                swapchain: None,
+                command_buffer: None,
+                presents: Vec::new(),
+            },
+        )
+        .unwrap();
+
+    run_on_surfaces(
+        &mut wgpu_engine,
+        1,
+        crate::wgpu::Color::BLACK,
+        |_wgpu_engine, _surfaces, frame| frame < 6,
+    );
+
+    let presents = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut PresentingTask| task.presents.clone())
+        .unwrap();
+    assert!(presents.len() > skipped_dispatch + 1);
+    // Neither the batches rendering on the swapchain nor the engine present it: only the task does.
+    for (dispatch, window) in presents.windows(2).enumerate() {
+        let ((_, previous), (present, presented_frames)) = (window[0], window[1]);
+        if dispatch + 1 == skipped_dispatch {
+            assert_eq!(present, None);
+            assert_eq!(presented_frames, previous);
+        } else {
+            assert_eq!(present, Some(Ok(())));
+            assert_eq!(presented_frames, previous + 1);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::*;
+
+#[test]
+fn dirty_regions_produce_minimal_writes() {
+    let texture = TextureId::new(EntityId::new(0));
+    let mut writer = TextureRegionWriter::new(texture, 256, 256, 4);
+
+    assert!(writer.write(TextureRegion::new(0, 0, 4, 4), &[1; 4 * 4 * 4]));
+    assert!(writer.write(TextureRegion::new(100, 100, 8, 2), &[2; 8 * 2 * 4]));
+    let writes = writer.take_writes();
+    assert_eq!(writes.len(), 2);
+
+    assert_eq!(writes[0].origin, crate::wgpu::Origin3d::ZERO);
+    assert_eq!(writes[0].size.width, 4);
+    assert_eq!(writes[0].size.height, 4);
+    let bytes_per_row = writes[0].layout.bytes_per_row.unwrap().get();
+    assert_eq!(bytes_per_row % crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT, 0);
+    assert_eq!(&writes[0].data[0..16], &[1; 16]);
+
+    assert_eq!(
+        writes[1].origin,
+        crate::wgpu::Origin3d {
+            x: 100,
+            y: 100,
+            z: 0
+        }
+    );
+    assert_eq!(writes[1].size.width, 8);
+    assert_eq!(writes[1].size.height, 2);
+    assert!(writer.dirty_regions().is_empty());
+
+    assert!(writer.mark_dirty(TextureRegion::new(0, 0, 4, 4)));
+    assert!(writer.mark_dirty(TextureRegion::new(4, 0, 4, 4)));
+    assert_eq!(writer.dirty_regions(), &[TextureRegion::new(0, 0, 8, 4)]);
+    assert!(!writer.mark_dirty(TextureRegion::new(254, 0, 4, 4)));
+}
@@ -0,0 +1,163 @@
+use crate::entity_manager::UpdateContext;
+use crate::*;
+
+const LAYERS: u32 = 4;
+const FORMAT: crate::wgpu::TextureFormat = crate::wgpu::TextureFormat::Rgba8Unorm;
+
+pub struct TextureViewArrayTask {
+    supported: bool,
+    bind_group: BindGroupId,
+    out_of_range: TextureViewId,
+    frame: usize,
+    built: Option<bool>,
+    build_errors: Vec<(EntityId, ResourceBuilderError)>,
+}
+
+impl TextureViewArrayTask {
+    const TASK_NAME: &'static str = "TextureViewArrayTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let supported = update_context
+            .device_descriptor_ref(&device)
+            .unwrap()
+            .features
+            .contains(crate::wgpu::Features::SAMPLED_TEXTURE_BINDING_ARRAY);
+
+        let texture = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " array texture",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::SAMPLED,
+                size: crate::wgpu::Extent3d {
+                    width: 16,
+                    height: 16,
+                    depth_or_array_layers: LAYERS,
+                },
+                format: FORMAT,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        // One view per layer, all of the same texture.
+        let views: Vec<_> = (0..LAYERS)
+            .map(|layer| {
+                update_context
+                    .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                        format!("{} layer {}", Self::TASK_NAME, layer),
+                        device,
+                        texture,
+                        FORMAT,
+                        layer,
+                    ))
+                    .unwrap()
+            })
+            .collect();
+        let out_of_range = update_context
+            .add_texture_view_descriptor(TextureViewDescriptor::layer(
+                format!("{} layer {}", Self::TASK_NAME, LAYERS),
+                device,
+                texture,
+                FORMAT,
+                LAYERS,
+            ))
+            .unwrap();
+
+        let layout = update_context
+            .add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: crate::wgpu::ShaderStage::FRAGMENT,
+                    ty: crate::wgpu::BindingType::Texture {
+                        sample_type: crate::wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: crate::wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: std::num::NonZeroU32::new(LAYERS),
+                }],
+            })
+            .unwrap();
+        let bind_group = update_context
+            .add_bind_group_descriptor(BindGroupDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureViewArray(views),
+                }],
+            })
+            .unwrap();
+
+        Self {
+            supported,
+            bind_group,
+            out_of_range,
+            frame: 0,
+            built: None,
+            build_errors: Vec::new(),
+        }
+    }
+}
+
+impl TaskTrait for TextureViewArrayTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        self.build_errors.extend(update_context.last_build_errors());
+        if self.frame == 1 {
+            self.built = Some(
+                update_context
+                    .bind_group_handle_ref(&self.bind_group)
+                    .is_some(),
+            );
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn bind_group_of_layer_views() {
+    let requirements = (
+        crate::wgpu::Features::SAMPLED_TEXTURE_BINDING_ARRAY,
+        crate::wgpu::Limits::default(),
+    );
+    let mut wgpu_engine = WGpuEngine::new(requirements.clone()).unwrap();
+
+    let task = wgpu_engine
+        .create_task(
+            TextureViewArrayTask::TASK_NAME.to_string(),
+            requirements,
+            |_id, _tokio, update_context| TextureViewArrayTask::new(update_context),
+        )
+        .unwrap();
+    for _ in 0..2 {
+        wgpu_engine.dispatch_tasks();
+    }
+
+    let (supported, built, out_of_range, build_errors) = wgpu_engine
+        .task_handle_cast_mut(&task, |task: &mut TextureViewArrayTask| {
+            (
+                task.supported,
+                task.built,
+                task.out_of_range,
+                task.build_errors.clone(),
+            )
+        })
+        .unwrap();
+    // The views of the layers of a single texture are bound as one array.
+    assert_eq!(built, Some(supported));
+    // The view past the last layer is rejected, instead of failing inside wgpu.
+    assert!(build_errors.contains(&(
+        *out_of_range.id_ref(),
+        ResourceBuilderError::InvalidTextureViewRange
+    )));
+}
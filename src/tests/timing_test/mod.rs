@@ -0,0 +1,116 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+const COPY_SIZE: u64 = 4 * 1024 * 1024;
+
+pub struct TimingTask {
+    supported: bool,
+    query_set: QuerySetId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    elapsed: Option<std::time::Duration>,
+}
+
+impl TimingTask {
+    const TASK_NAME: &'static str = "TimingTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let supported = update_context
+            .device_descriptor_ref(&device)
+            .unwrap()
+            .features
+            .contains(crate::wgpu::Features::TIMESTAMP_QUERY);
+
+        let src_buffer = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " source buffer",
+                device,
+                size: COPY_SIZE,
+                usage: crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let dst_buffer = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " destination buffer",
+                device,
+                size: COPY_SIZE,
+                usage: crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let query_set = update_context
+            .add_query_set_descriptor(QuerySetDescriptor::timestamps(
+                Self::TASK_NAME.to_string() + " query set",
+                device,
+                2,
+            ))
+            .unwrap();
+
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(
+                CommandBufferDescriptor {
+                    label: Self::TASK_NAME.to_string() + " command buffer",
+                    device,
+                    commands: vec![Command::BufferToBuffer(BufferToBufferCopy {
+                        src_buffer,
+                        src_offset: 0,
+                        dst_buffer,
+                        dst_offset: 0,
+                        size: COPY_SIZE,
+                    })],
+                    max_commands: None,
+                }
+                .with_timing(query_set),
+            )
+            .unwrap();
+
+        Self {
+            supported,
+            query_set,
+            command_buffer,
+            frame: 0,
+            elapsed: None,
+        }
+    }
+}
+
+impl TaskTrait for TimingTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        if self.frame == 1 {
+            self.elapsed = update_context.read_elapsed_time(&self.query_set).ok();
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        // Timed once, on the first dispatch.
+        if self.frame == 1 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn command_buffer_timing() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        TimingTask::TASK_NAME,
+        crate::wgpu::Features::TIMESTAMP_QUERY,
+        crate::wgpu::Limits::default(),
+        TimingTask::new,
+    );
+
+    let (supported, elapsed) = run_task(&mut wgpu_engine, &task, 2, |task: &mut TimingTask| {
+        (task.supported, task.elapsed)
+    });
+    if supported {
+        assert!(elapsed.unwrap() > std::time::Duration::from_nanos(0));
+    } else {
+        // Without the feature the query set is not built, so there is nothing to read.
+        assert_eq!(elapsed, None);
+    }
+}
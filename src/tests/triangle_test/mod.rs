@@ -163,6 +163,7 @@ impl TriangleTask {
             label: String::from("TriangleTask"),
             device,
             commands,
+            max_commands: None,
         }
     }
 
@@ -199,7 +200,17 @@ impl TaskTrait for TriangleTask {
     fn update_resources(&mut self, update_context: &mut UpdateContext) {
         for event in update_context.events().clone() {
             match event {
-                ResourceEvent::SwapchainCreated(swapchain) => {
+                ResourceEvent::SwapchainCreated {
+                    swapchain, format, ..
+                } => {
+                    // The pipeline targets the descriptor format, that must be the reported one.
+                    assert_eq!(
+                        update_context
+                            .swapchain_descriptor_ref(&swapchain)
+                            .unwrap()
+                            .format,
+                        format
+                    );
                     let device = update_context.entity_device_id(swapchain).unwrap();
                     match self.devices.entry(device) {
                         Entry::Vacant(vacant) => {
@@ -256,16 +267,18 @@ impl TaskTrait for TriangleTask {
 
 #[test]
 fn triangle_task() {
-    env_logger::init();
+    let _ = env_logger::try_init();
     quick_run(
         2,
         crate::wgpu::Features::default(),
         crate::wgpu::Limits::default(),
         |_id, _tokio_runtime, update_context| TriangleTask::new(update_context),
+        |_task| {},
     )
+    .unwrap();
     /*
     use std::collections::HashSet;
-    env_logger::init();
+    let _ = env_logger::try_init();
     use crate::WGpuEngine;
     use pal::definitions::*;
 
@@ -304,7 +317,9 @@ fn triangle_task() {
                                     surface.clone(),
                                     surface_info.size.width,
                                     surface_info.size.height,
-                                );
+                                    crate::wgpu::Color::BLACK,
+                                )
+                                .unwrap();
                                 surfaces.insert(surface_id.id() as usize);
                             } else {
                                 panic!("It is not of WGpu type");
@@ -0,0 +1,162 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine, texture_readback, unpad_rows};
+use crate::*;
+
+const SIZE: u32 = 4;
+const BYTES_PER_ROW: u32 = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+const FORMAT: crate::wgpu::TextureFormat = crate::wgpu::TextureFormat::Rgba8Unorm;
+
+pub struct UsageUpgradeTask {
+    texture: TextureId,
+    /// Whether the texture also gets resized after the usage upgrade, in the same frame.
+    resize: bool,
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl UsageUpgradeTask {
+    const TASK_NAME: &'static str = "UsageUpgradeTask";
+
+    fn texels() -> Vec<u8> {
+        (0..SIZE * SIZE * 4).map(|value| value as u8).collect()
+    }
+
+    pub fn new(update_context: &mut UpdateContext, resize: bool) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let size = crate::wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = update_context
+            .add_texture_descriptor(TextureDescriptor {
+                label: Self::TASK_NAME.to_string() + " texture",
+                device,
+                source: TextureSource::Local,
+                usage: crate::wgpu::TextureUsage::COPY_SRC | crate::wgpu::TextureUsage::COPY_DST,
+                size,
+                format: FORMAT,
+                dimension: crate::wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size: (BYTES_PER_ROW * SIZE) as u64,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![texture_readback(
+                    texture,
+                    readback,
+                    (SIZE, SIZE),
+                    BYTES_PER_ROW,
+                )],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            texture,
+            resize,
+            readback,
+            command_buffer,
+            frame: 0,
+            result: None,
+        }
+    }
+}
+
+impl TaskTrait for UsageUpgradeTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => update_context.write_resource(&mut vec![ResourceWrite::Texture(TextureWrite {
+                texture: self.texture,
+                mip_level: 0,
+                origin: crate::wgpu::Origin3d::ZERO,
+                data: Self::texels(),
+                layout: crate::wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(SIZE * 4),
+                    rows_per_image: std::num::NonZeroU32::new(SIZE),
+                },
+                size: crate::wgpu::Extent3d {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: 1,
+                },
+            })]),
+            // The texture needs to be sampled too, once its contents have been written.
+            1 => {
+                let mut descriptor = update_context
+                    .texture_descriptor_ref(&self.texture)
+                    .unwrap()
+                    .clone();
+                descriptor.usage |= crate::wgpu::TextureUsage::SAMPLED;
+                assert!(
+                    update_context.update_texture_descriptor(&mut self.texture, descriptor.clone())
+                );
+                if self.resize {
+                    descriptor.size.width *= 2;
+                    descriptor.size.height *= 2;
+                    assert!(update_context.update_texture_descriptor(&mut self.texture, descriptor));
+                }
+            }
+            3 => {
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, (BYTES_PER_ROW * SIZE) as u64)
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        // Read back once the upgraded texture has been rebuilt.
+        if self.frame == 3 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Run a [UsageUpgradeTask] and return the texels read back from the upgraded texture.
+fn upgraded_texels(resize: bool) -> Vec<u8> {
+    let (mut wgpu_engine, task) = single_task_engine(
+        UsageUpgradeTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        |update_context| UsageUpgradeTask::new(update_context, resize),
+    );
+
+    let data = run_task(&mut wgpu_engine, &task, 4, |task: &mut UsageUpgradeTask| {
+        task.result.clone()
+    })
+    .unwrap();
+    unpad_rows(&data, (SIZE * 4) as usize, BYTES_PER_ROW as usize)
+}
+
+#[test]
+fn usage_upgrade_preserves_contents() {
+    assert_eq!(upgraded_texels(false), UsageUpgradeTask::texels());
+}
+
+#[test]
+fn resize_after_usage_upgrade_drops_contents() {
+    // The resize is not a usage upgrade, so nothing gets copied from the smaller texture.
+    assert!(upgraded_texels(true).iter().all(|texel| *texel == 0));
+}
@@ -0,0 +1,103 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+pub struct WireframeTask {
+    supported: bool,
+    render_pipeline: RenderPipelineId,
+    frame: usize,
+    built: Option<bool>,
+}
+
+impl WireframeTask {
+    const TASK_NAME: &'static str = "WireframeTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let supported = update_context
+            .device_descriptor_ref(&device)
+            .unwrap()
+            .features
+            .contains(crate::wgpu::Features::NON_FILL_POLYGON_MODE);
+
+        let shader_module = update_context
+            .add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                source: ShaderSource::Wgsl(
+                    include_str!("../triangle_test/shader.wgsl").to_string(),
+                ),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })
+            .unwrap();
+        let render_pipeline = update_context
+            .add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                label: Self::TASK_NAME.to_string(),
+                device,
+                layout: None,
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: Vec::new(),
+                },
+                primitive: crate::wgpu::PrimitiveState {
+                    polygon_mode: crate::wgpu::PolygonMode::Line,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: String::from("fs_main"),
+                    targets: vec![crate::wgpu::ColorTargetState {
+                        format: crate::wgpu::TextureFormat::Rgba8Unorm,
+                        blend: None,
+                        write_mask: crate::wgpu::ColorWrite::ALL,
+                    }],
+                }),
+            })
+            .unwrap();
+
+        Self {
+            supported,
+            render_pipeline,
+            frame: 0,
+            built: None,
+        }
+    }
+}
+
+impl TaskTrait for WireframeTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        if self.frame == 1 {
+            self.built = Some(
+                update_context
+                    .render_pipeline_handle_ref(&self.render_pipeline)
+                    .is_some(),
+            );
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn wireframe_pipeline() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        WireframeTask::TASK_NAME,
+        crate::wgpu::Features::NON_FILL_POLYGON_MODE,
+        crate::wgpu::Limits::default(),
+        WireframeTask::new,
+    );
+
+    let (supported, built) = run_task(&mut wgpu_engine, &task, 2, |task: &mut WireframeTask| {
+        (task.supported, task.built)
+    });
+    // Without the feature the pipeline must fail to build, instead of failing inside wgpu.
+    assert_eq!(built, Some(supported));
+}
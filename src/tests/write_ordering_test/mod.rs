@@ -0,0 +1,118 @@
+use crate::entity_manager::UpdateContext;
+use crate::tests::single_task_engine;
+use crate::tests::{run_task, single_task_engine};
+use crate::*;
+
+const FIRST: [u32; 4] = [1, 1, 1, 1];
+const SECOND: [u32; 4] = [2, 2, 2, 2];
+
+pub struct WriteOrderingTask {
+    uniform: BufferId,
+    readback: BufferId,
+    command_buffer: CommandBufferId,
+    frame: usize,
+    result: Option<Vec<u8>>,
+}
+
+impl WriteOrderingTask {
+    const TASK_NAME: &'static str = "WriteOrderingTask";
+
+    pub fn new(update_context: &mut UpdateContext) -> Self {
+        let device = update_context.devices().next().unwrap();
+        let size = std::mem::size_of_val(&FIRST) as u64;
+
+        let uniform = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " uniform buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::UNIFORM
+                    | crate::wgpu::BufferUsage::COPY_DST
+                    | crate::wgpu::BufferUsage::COPY_SRC,
+            })
+            .unwrap();
+        let readback = update_context
+            .add_buffer_descriptor(BufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " readback buffer",
+                device,
+                size,
+                usage: crate::wgpu::BufferUsage::MAP_READ | crate::wgpu::BufferUsage::COPY_DST,
+            })
+            .unwrap();
+        let command_buffer = update_context
+            .add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: Self::TASK_NAME.to_string() + " command buffer",
+                device,
+                commands: vec![Command::BufferToBuffer(BufferToBufferCopy {
+                    src_buffer: uniform,
+                    src_offset: 0,
+                    dst_buffer: readback,
+                    dst_offset: 0,
+                    size,
+                })],
+                max_commands: None,
+            })
+            .unwrap();
+
+        Self {
+            uniform,
+            readback,
+            command_buffer,
+            frame: 0,
+            result: None,
+        }
+    }
+
+    fn write(&self, update_context: &mut UpdateContext, data: &[u32; 4]) {
+        update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+            buffer: self.uniform,
+            offset: 0,
+            data: bytemuck::cast_slice(data).to_vec(),
+        })]);
+    }
+}
+
+impl TaskTrait for WriteOrderingTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        match self.frame {
+            0 => self.write(update_context, &FIRST),
+            1 => self.write(update_context, &SECOND),
+            2 => {
+                self.result = update_context
+                    .read_buffer(&self.readback, 0, std::mem::size_of_val(&SECOND) as u64)
+                    .ok();
+            }
+            _ => {}
+        }
+        self.frame += 1;
+    }
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        if self.frame <= 2 {
+            vec![self.command_buffer]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn writes_flushed_before_command_buffers() {
+    let (mut wgpu_engine, task) = single_task_engine(
+        WriteOrderingTask::TASK_NAME,
+        crate::wgpu::Features::empty(),
+        crate::wgpu::Limits::default(),
+        WriteOrderingTask::new,
+    );
+
+    // The copy submitted on the second frame must see the value written on the same frame.
+    let result = run_task(
+        &mut wgpu_engine,
+        &task,
+        3,
+        |task: &mut WriteOrderingTask| task.result.clone(),
+    );
+    assert_eq!(result, Some(bytemuck::cast_slice(&SECOND).to_vec()));
+}
@@ -0,0 +1,230 @@
+use crate::BindGroupDescriptor;
+use crate::BindGroupEntry;
+use crate::BindGroupId;
+use crate::BindGroupLayoutDescriptor;
+use crate::BindGroupLayoutEntryExt;
+use crate::BindGroupLayoutId;
+use crate::BindingResource;
+use crate::ColorView;
+use crate::Command;
+use crate::FragmentState;
+use crate::PipelineLayoutDescriptor;
+use crate::PipelineLayoutId;
+use crate::RenderCommand;
+use crate::RenderPassColorAttachment;
+use crate::RenderPipelineDescriptor;
+use crate::RenderPipelineId;
+use crate::SamplerId;
+use crate::ShaderModuleDescriptor;
+use crate::ShaderModuleId;
+use crate::ShaderSource;
+use crate::TextureViewId;
+use crate::UpdateContext;
+use crate::VertexState;
+
+/// Full screen triangle sampling the source texture, with the uvs going from 0 to 1 on the visible area.
+const BLIT_SHADER: &str = r#"
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var output: VertexOutput;
+    output.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    output.uv = uv;
+    return output;
+}
+
+[[group(0), binding(0)]]
+var source: texture_2d<f32>;
+[[group(0), binding(1)]]
+var source_sampler: sampler;
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return textureSample(source, source_sampler, input.uv);
+}
+"#;
+
+#[derive(Debug)]
+/**
+Resources of a blit pass, created by [blit][blit]. The pass can be recorded multiple times
+with [command][Blit::command], and follows the contents of the source view.
+*/
+pub struct Blit {
+    label: String,
+    shader_module: ShaderModuleId,
+    bind_group_layout: BindGroupLayoutId,
+    bind_group: BindGroupId,
+    pipeline_layout: PipelineLayoutId,
+    render_pipeline: RenderPipelineId,
+    dst_view: TextureViewId,
+}
+impl Blit {
+    /// Render pass drawing the source view on the whole destination view.
+    pub fn command(&self) -> Command {
+        Command::RenderPass {
+            label: self.label.clone(),
+            depth_stencil: None,
+            color_attachments: vec![RenderPassColorAttachment::clear(
+                ColorView::TextureView(self.dst_view),
+                crate::wgpu::Color::TRANSPARENT,
+            )],
+            commands: vec![
+                RenderCommand::SetPipeline {
+                    pipeline: self.render_pipeline,
+                },
+                RenderCommand::SetBindGroup {
+                    index: 0,
+                    bind_group: self.bind_group,
+                    offsets: Vec::new(),
+                },
+                RenderCommand::Draw {
+                    vertices: 0..3,
+                    instances: 0..1,
+                },
+            ],
+        }
+    }
+
+    /// Remove the resources of the blit. The source and destination views and the sampler are left untouched.
+    pub fn remove(self, update_context: &mut UpdateContext) -> Result<(), ()> {
+        update_context.remove_render_pipeline(&self.render_pipeline)?;
+        update_context.remove_pipeline_layout(&self.pipeline_layout)?;
+        update_context.remove_bind_group(&self.bind_group)?;
+        update_context.remove_bind_group_layout(&self.bind_group_layout)?;
+        update_context.remove_shader_module(&self.shader_module)
+    }
+}
+
+/**
+Create a blit pass sampling `src_view` on the whole `dst_view` with a full screen triangle, for the copies
+that [TextureToTextureCopy][crate::TextureToTextureCopy] cannot do, like between different formats
+(e.g. from a sRGB render to a linear float texture) or sizes. The filters of `sampler` select the resampling.
+The source must be a 2D view of a filterable float format, while the destination needs the
+`RENDER_ATTACHMENT` usage. The pass is recorded with [Blit::command][Blit::command].
+If any creation fails, the already created resources are removed.
+*/
+pub fn blit(
+    update_context: &mut UpdateContext,
+    label: &str,
+    src_view: TextureViewId,
+    dst_view: TextureViewId,
+    sampler: SamplerId,
+) -> Result<Blit, ()> {
+    let (device, format) = match update_context.texture_view_descriptor_ref(&dst_view) {
+        Some(descriptor) => (descriptor.device, descriptor.format),
+        None => {
+            log::error!(target: "Blit","Failed to create blit `{}`: destination {} not found",label,dst_view);
+            return Err(());
+        }
+    };
+
+    let shader_module = update_context.add_shader_module_descriptor(ShaderModuleDescriptor {
+        label: label.to_string() + " shader",
+        device,
+        source: ShaderSource::Wgsl(BLIT_SHADER.to_string()),
+        flags: crate::wgpu::ShaderFlags::VALIDATION,
+    })?;
+
+    let bind_group_layout =
+        match update_context.add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+            label: label.to_string() + " bind group layout",
+            device,
+            entries: vec![
+                crate::wgpu::BindGroupLayoutEntry::texture(0, crate::wgpu::ShaderStage::FRAGMENT),
+                crate::wgpu::BindGroupLayoutEntry::sampler(1),
+            ],
+        }) {
+            Ok(bind_group_layout) => bind_group_layout,
+            Err(()) => {
+                let _ = update_context.remove_shader_module(&shader_module);
+                return Err(());
+            }
+        };
+
+    let bind_group = match update_context.add_bind_group_descriptor(BindGroupDescriptor {
+        label: label.to_string() + " bind group",
+        device,
+        layout: bind_group_layout,
+        entries: vec![
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(src_view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(sampler),
+            },
+        ],
+    }) {
+        Ok(bind_group) => bind_group,
+        Err(()) => {
+            let _ = update_context.remove_bind_group_layout(&bind_group_layout);
+            let _ = update_context.remove_shader_module(&shader_module);
+            return Err(());
+        }
+    };
+
+    let pipeline_layout =
+        match update_context.add_pipeline_layout_descriptor(PipelineLayoutDescriptor {
+            label: label.to_string() + " pipeline layout",
+            device,
+            bind_group_layouts: vec![bind_group_layout],
+            push_constant_ranges: Vec::new(),
+        }) {
+            Ok(pipeline_layout) => pipeline_layout,
+            Err(()) => {
+                let _ = update_context.remove_bind_group(&bind_group);
+                let _ = update_context.remove_bind_group_layout(&bind_group_layout);
+                let _ = update_context.remove_shader_module(&shader_module);
+                return Err(());
+            }
+        };
+
+    let render_pipeline =
+        match update_context.add_render_pipeline_descriptor(RenderPipelineDescriptor {
+            label: label.to_string() + " pipeline",
+            device,
+            layout: Some(pipeline_layout),
+            vertex: VertexState {
+                module: shader_module,
+                entry_point: String::from("vs_main"),
+                buffers: Vec::new(),
+            },
+            primitive: crate::wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: crate::wgpu::MultisampleState::default(),
+            fragment: Some(FragmentState {
+                module: shader_module,
+                entry_point: String::from("fs_main"),
+                targets: vec![crate::wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: crate::wgpu::ColorWrite::ALL,
+                }],
+            }),
+        }) {
+            Ok(render_pipeline) => render_pipeline,
+            Err(()) => {
+                let _ = update_context.remove_pipeline_layout(&pipeline_layout);
+                let _ = update_context.remove_bind_group(&bind_group);
+                let _ = update_context.remove_bind_group_layout(&bind_group_layout);
+                let _ = update_context.remove_shader_module(&shader_module);
+                return Err(());
+            }
+        };
+
+    Ok(Blit {
+        label: label.to_string(),
+        shader_module,
+        bind_group_layout,
+        bind_group,
+        pipeline_layout,
+        render_pipeline,
+        dst_view,
+    })
+}
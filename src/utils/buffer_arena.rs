@@ -0,0 +1,226 @@
+use crate::BufferId;
+use crate::BufferWrite;
+use crate::DeviceAlignments;
+use crate::ResourceWrite;
+use crate::Slice;
+use crate::UpdateContext;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Handle of an allocation of a [BufferArena][BufferArena]. It stays valid across defragmentations.
+pub struct ArenaHandle(usize);
+
+#[derive(Debug)]
+/**
+Helper structure for variable size allocations within a single buffer (like a pool of meshes).
+Allocations are placed with a first-fit strategy on a free list, and are aligned to
+[COPY_BUFFER_ALIGNMENT][crate::wgpu::COPY_BUFFER_ALIGNMENT].
+A CPU copy of the buffer contents is kept, so that the allocations can be compacted by
+[defragment][BufferArena::defragment] and uploaded again.
+*/
+pub struct BufferArena {
+    buffer: BufferId,
+    shadow: Vec<u8>,
+    allocations: HashMap<ArenaHandle, (u64, u64)>,
+    // Free blocks as (offset, size), sorted by offset and never adjacent.
+    free_blocks: Vec<(u64, u64)>,
+    dirty_ranges: Vec<(u64, u64)>,
+    next_handle: usize,
+}
+impl BufferArena {
+    pub fn new(buffer: BufferId, size: u64) -> Self {
+        let alignment = DeviceAlignments::default().copy_buffer;
+        let size = size / alignment * alignment;
+        let shadow = vec![0; size as usize];
+        let allocations = HashMap::new();
+        let free_blocks = if size > 0 {
+            vec![(0, size)]
+        } else {
+            Vec::new()
+        };
+        let dirty_ranges = Vec::new();
+        Self {
+            buffer,
+            shadow,
+            allocations,
+            free_blocks,
+            dirty_ranges,
+            next_handle: 0,
+        }
+    }
+
+    /// Id of the underlying buffer.
+    pub fn id(&self) -> &BufferId {
+        &self.buffer
+    }
+    /// Total size of the arena.
+    pub fn size(&self) -> u64 {
+        self.shadow.len() as u64
+    }
+    /// Size not allocated, possibly split in multiple blocks.
+    pub fn free_space(&self) -> u64 {
+        self.free_blocks.iter().map(|(_offset, size)| size).sum()
+    }
+    /// Size of the largest free block, that is the largest allocation that can currently succeed.
+    pub fn largest_free_block(&self) -> u64 {
+        self.free_blocks
+            .iter()
+            .map(|(_offset, size)| *size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /**
+    Allocate `size` bytes, rounded up to [COPY_BUFFER_ALIGNMENT][crate::wgpu::COPY_BUFFER_ALIGNMENT].
+    Returns `None` if no free block is large enough, which can happen because of fragmentation
+    even if the free space is enough: in that case [defragment][BufferArena::defragment] can help.
+    */
+    pub fn alloc(&mut self, size: u64) -> Option<ArenaHandle> {
+        let alignment = DeviceAlignments::default().copy_buffer;
+        let size = DeviceAlignments::align_to(size, alignment).max(alignment);
+
+        let index = self
+            .free_blocks
+            .iter()
+            .position(|(_offset, free_size)| *free_size >= size)?;
+        let (offset, free_size) = self.free_blocks[index];
+        if free_size == size {
+            self.free_blocks.remove(index);
+        } else {
+            self.free_blocks[index] = (offset + size, free_size - size);
+        }
+
+        let handle = ArenaHandle(self.next_handle);
+        self.next_handle += 1;
+        self.allocations.insert(handle, (offset, size));
+        Some(handle)
+    }
+
+    /// Free an allocation, merging its space with the adjacent free blocks. Returns false if the handle is unknown.
+    pub fn free(&mut self, handle: ArenaHandle) -> bool {
+        let (offset, size) = match self.allocations.remove(&handle) {
+            Some(allocation) => allocation,
+            None => return false,
+        };
+
+        let index = self
+            .free_blocks
+            .iter()
+            .position(|(free_offset, _size)| *free_offset > offset)
+            .unwrap_or(self.free_blocks.len());
+        self.free_blocks.insert(index, (offset, size));
+
+        if index + 1 < self.free_blocks.len() {
+            let (next_offset, next_size) = self.free_blocks[index + 1];
+            if offset + size == next_offset {
+                self.free_blocks[index].1 += next_size;
+                self.free_blocks.remove(index + 1);
+            }
+        }
+        if index > 0 {
+            let (previous_offset, previous_size) = self.free_blocks[index - 1];
+            if previous_offset + previous_size == offset {
+                self.free_blocks[index - 1].1 += self.free_blocks[index].1;
+                self.free_blocks.remove(index);
+            }
+        }
+        true
+    }
+
+    /// Offset and (aligned) size of an allocation.
+    pub fn range(&self, handle: ArenaHandle) -> Option<(u64, u64)> {
+        self.allocations.get(&handle).copied()
+    }
+    /// Range of an allocation, to be used in the [RenderCommand][crate::RenderCommand] setting vertex or index buffers.
+    pub fn slice(&self, handle: ArenaHandle) -> Option<Slice<crate::wgpu::BufferAddress>> {
+        self.range(handle)
+            .map(|(offset, size)| Slice::from(offset..offset + size))
+    }
+
+    /**
+    Write `data` at the start of an allocation and mark it as dirty.
+    Returns false if the handle is unknown or the data does not fit the allocation.
+    */
+    pub fn write(&mut self, handle: ArenaHandle, data: &[u8]) -> bool {
+        let (offset, size) = match self.range(handle) {
+            Some(range) => range,
+            None => return false,
+        };
+        if data.len() as u64 > size {
+            log::error!(target: "Buffer Arena","Failed write: {} bytes do not fit an allocation of {} bytes",data.len(),size);
+            return false;
+        }
+        let start = offset as usize;
+        self.shadow[start..start + data.len()].copy_from_slice(data);
+        self.dirty_ranges.push((offset, size));
+        true
+    }
+
+    /**
+    Compact the allocations at the start of the buffer, preserving their order, so that all the free space
+    becomes a single block. Moved allocations are marked as dirty.
+    Returns true if any allocation has been moved.
+    */
+    pub fn defragment(&mut self) -> bool {
+        let mut allocations: Vec<_> = self
+            .allocations
+            .iter()
+            .map(|(handle, range)| (*handle, *range))
+            .collect();
+        allocations.sort_by_key(|(_handle, (offset, _size))| *offset);
+
+        let mut moved = false;
+        let mut end = 0;
+        for (handle, (offset, size)) in allocations {
+            if offset != end {
+                self.shadow
+                    .copy_within(offset as usize..(offset + size) as usize, end as usize);
+                self.allocations.insert(handle, (end, size));
+                self.dirty_ranges.push((end, size));
+                moved = true;
+            }
+            end += size;
+        }
+
+        self.free_blocks.clear();
+        if end < self.size() {
+            self.free_blocks.push((end, self.size() - end));
+        }
+        moved
+    }
+
+    /// Generate a write for every dirty range and clear them. Overlapping or adjacent ranges are merged.
+    pub fn take_writes(&mut self) -> Vec<BufferWrite> {
+        let mut dirty_ranges: Vec<_> = self.dirty_ranges.drain(..).collect();
+        dirty_ranges.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = Vec::new();
+        for (offset, size) in dirty_ranges {
+            match merged.last_mut() {
+                Some((last_offset, last_size)) if *last_offset + *last_size >= offset => {
+                    *last_size = (*last_size).max(offset + size - *last_offset);
+                }
+                _ => merged.push((offset, size)),
+            }
+        }
+
+        merged
+            .into_iter()
+            .map(|(offset, size)| BufferWrite {
+                buffer: self.buffer,
+                offset,
+                data: self.shadow[offset as usize..(offset + size) as usize].to_vec(),
+            })
+            .collect()
+    }
+
+    /// Submit the writes of the dirty ranges.
+    pub fn update(&mut self, update_context: &mut UpdateContext) {
+        let mut writes: Vec<_> = self
+            .take_writes()
+            .into_iter()
+            .map(ResourceWrite::Buffer)
+            .collect();
+        update_context.write_resource(&mut writes);
+    }
+}
@@ -5,20 +5,103 @@ use crate::BufferWrite;
 use crate::Command;
 use crate::CommandBufferDescriptor;
 use crate::CommandBufferId;
+use crate::DeviceAlignments;
 use crate::DeviceId;
 use crate::ResourceWrite;
 use crate::UpdateContext;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Strategy used by a [BufferManager][BufferManager] to upload its pending writes.
+pub enum WriteStrategy {
+    /// Every changed element is written separately.
+    PerElement,
+    /// The whole dirty span is written at once.
+    SingleSpan,
+}
+
+/**
+Coalesce the pending writes of a buffer. If more than half of the span between the first and the last
+dirty byte is dirty, the writes are replaced by a single write of the whole span, taken from `shadow`
+(the CPU copy of the buffer contents, with the writes already applied). Otherwise the writes are returned unchanged.
+*/
+pub fn coalesce_writes(
+    buffer: BufferId,
+    shadow: &[u8],
+    mut writes: Vec<BufferWrite>,
+) -> (WriteStrategy, Vec<BufferWrite>) {
+    if writes.len() < 2 {
+        return (WriteStrategy::PerElement, writes);
+    }
+
+    let mut ranges: Vec<_> = writes
+        .iter()
+        .map(|write| {
+            (
+                write.offset as usize,
+                write.offset as usize + write.data.len(),
+            )
+        })
+        .collect();
+    ranges.sort_unstable();
+
+    let start = ranges[0].0;
+    let mut end = start;
+    let mut dirty = 0;
+    for (range_start, range_end) in ranges {
+        let range_start = range_start.max(end);
+        if range_end > range_start {
+            dirty += range_end - range_start;
+            end = range_end;
+        }
+    }
+
+    if dirty * 2 > end - start {
+        let alignment = crate::wgpu::COPY_BUFFER_ALIGNMENT as usize;
+        let start = start / alignment * alignment;
+        let end = ((end + alignment - 1) / alignment * alignment).min(shadow.len());
+        writes.clear();
+        writes.push(BufferWrite {
+            buffer,
+            offset: start as u64,
+            data: shadow[start..end].to_vec(),
+        });
+        (WriteStrategy::SingleSpan, writes)
+    } else {
+        (WriteStrategy::PerElement, writes)
+    }
+}
+
+/**
+Distance in bytes between two consecutive elements of type `D` in a buffer of the passed usages.
+Elements of uniform buffers are padded to [buffer_binding][DeviceAlignments::buffer_binding], so that every
+element can be bound with a dynamic offset; otherwise the elements are tightly packed.
+*/
+pub fn element_stride<D: Sized>(
+    usages: crate::wgpu::BufferUsage,
+    alignments: &DeviceAlignments,
+) -> usize {
+    let size = std::mem::size_of::<D>();
+    if usages.contains(crate::wgpu::BufferUsage::UNIFORM) {
+        alignments.align_binding_offset(size as u64) as usize
+    } else {
+        size
+    }
+}
+
 #[derive(Debug)]
-/// Helper structure to suballocate a buffer while keeping the data synchronized.
+/**
+Helper structure to suballocate a buffer while keeping the data synchronized.
+With the `UNIFORM` usage, the slots are padded to the [element_stride][element_stride], see [dynamic_offset][BufferManager::dynamic_offset].
+*/
 pub struct BufferManager<D: bytemuck::Pod + Sized, A> {
     label: String,
     phantom: PhantomData<D>,
     device: DeviceId,
     buffer: BufferId,
     descriptor: BufferDescriptor,
+    stride: usize,
     need_rebuild: bool,
 
     id_map: HashMap<usize, (usize, A)>,
@@ -26,6 +109,8 @@ pub struct BufferManager<D: bytemuck::Pod + Sized, A> {
     command_buffer: CommandBufferId,
     pending_copies: Vec<Command>,
     pending_writes: Vec<BufferWrite>,
+    shadow: Vec<u8>,
+    write_strategy: Option<WriteStrategy>,
 
     support_buffer: BufferId,
 }
@@ -37,10 +122,12 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
         capacity: usize,
         usages: crate::wgpu::BufferUsage,
     ) -> Self {
+        let alignments = update_context.device_alignments(device).unwrap_or_default();
+        let stride = element_stride::<D>(usages, &alignments);
         let descriptor = BufferDescriptor {
             label: label.clone() + " buffer",
             device,
-            size: (capacity * std::mem::size_of::<D>()) as u64,
+            size: (capacity * stride) as u64,
             usage: crate::wgpu::BufferUsage::COPY_SRC | crate::wgpu::BufferUsage::COPY_DST | usages,
         };
 
@@ -64,6 +151,7 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
                 label: label.clone() + " command buffer",
                 device,
                 commands: Vec::new(),
+                max_commands: None,
             })
             .unwrap();
 
@@ -73,17 +161,22 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
 
         let pending_copies = Vec::new();
         let pending_writes = Vec::new();
+        let shadow = vec![0; descriptor.size as usize];
+        let write_strategy = None;
         Self {
             label,
             phantom,
             device,
             buffer,
             descriptor,
+            stride,
             need_rebuild,
             id_map,
             command_buffer,
             pending_copies,
             pending_writes,
+            shadow,
+            write_strategy,
 
             support_buffer,
         }
@@ -92,6 +185,15 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
     pub fn id(&self) -> &BufferId {
         &self.buffer
     }
+    /// Distance in bytes between two consecutive slots, see [element_stride][element_stride].
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+    /// Offset of the slot of a suballocation, to be used as dynamic offset in [SetBindGroup][crate::RenderCommand::SetBindGroup].
+    pub fn dynamic_offset(&self, buffer_index: &usize) -> Option<crate::wgpu::DynamicOffset> {
+        self.data_slot(buffer_index)
+            .map(|slot| (slot * self.stride) as crate::wgpu::DynamicOffset)
+    }
     /// Number of buffer suballocations.
     pub fn len(&self) -> usize {
         self.id_map.len()
@@ -102,11 +204,11 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
     }
     /// Returns the space occupied by the sum of all suballocations.
     pub fn size(&self) -> usize {
-        self.id_map.len() * std::mem::size_of::<D>()
+        self.id_map.len() * self.stride
     }
     /// Returns the maximum number of possible suballocations with the current buffer size.
     pub fn capacity(&self) -> usize {
-        self.descriptor.size as usize / std::mem::size_of::<D>()
+        self.descriptor.size as usize / self.stride
     }
     /// Returns the index of the next available suballocation slot.
     pub fn next_slot(&self) -> usize {
@@ -175,7 +277,7 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
             let mut commands = vec![
                 Command::BufferToBuffer(BufferToBufferCopy {
                     src_buffer: self.buffer,
-                    src_offset: (last_slot * std::mem::size_of::<D>()) as u64,
+                    src_offset: (last_slot * self.stride) as u64,
                     dst_buffer: self.support_buffer,
                     dst_offset: 0,
                     size: std::mem::size_of::<D>() as u64,
@@ -184,12 +286,18 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
                     src_buffer: self.support_buffer,
                     src_offset: 0,
                     dst_buffer: self.buffer,
-                    dst_offset: (removed_slot * std::mem::size_of::<D>()) as u64,
+                    dst_offset: (removed_slot * self.stride) as u64,
                     size: std::mem::size_of::<D>() as u64,
                 }),
             ];
             self.pending_copies.append(&mut commands);
 
+            let size = std::mem::size_of::<D>();
+            self.shadow.copy_within(
+                last_slot * self.stride..last_slot * self.stride + size,
+                removed_slot * self.stride,
+            );
+
             //println!("Associated data from middle is some: true");
             Some(removed_element.1)
         }
@@ -231,9 +339,11 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
 
         let (offset, data) = callback();
         if offset + data.len() <= std::mem::size_of::<D>() {
+            let offset = slot * self.stride + offset;
+            self.shadow[offset..offset + data.len()].copy_from_slice(&data);
             let write = BufferWrite {
                 buffer: self.buffer,
-                offset: (slot * std::mem::size_of::<D>() + offset) as u64,
+                offset: offset as u64,
                 data,
             };
             self.pending_writes.push(write);
@@ -246,7 +356,8 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
 
     fn extend(&mut self) {
         let new_capacity = self.capacity() + 32;
-        self.descriptor.size = (new_capacity * std::mem::size_of::<D>()) as u64;
+        self.descriptor.size = (new_capacity * self.stride) as u64;
+        self.shadow.resize(self.descriptor.size as usize, 0);
         self.need_rebuild = true;
     }
 
@@ -257,9 +368,13 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
             self.need_rebuild = false;
         }
 
-        let mut writes: Vec<_> = self
-            .pending_writes
-            .drain(..)
+        let pending_writes = self.pending_writes.drain(..).collect();
+        let (write_strategy, pending_writes) =
+            coalesce_writes(self.buffer, &self.shadow, pending_writes);
+        self.write_strategy = Some(write_strategy);
+
+        let mut writes: Vec<_> = pending_writes
+            .into_iter()
             .map(ResourceWrite::Buffer)
             .collect();
         update_context.write_resource(&mut writes);
@@ -267,6 +382,11 @@ impl<D: bytemuck::Pod + Sized, A: std::fmt::Debug> BufferManager<D, A> {
         self.pending_copies.drain(..).collect()
     }
 
+    /// Strategy used to upload the writes on the last [update][BufferManager::update] call.
+    pub fn write_strategy(&self) -> Option<WriteStrategy> {
+        self.write_strategy
+    }
+
     /// Get a reference of the associated data of a suballocation.
     pub fn associated_data(&self, buffer_index: &usize) -> Option<&A> {
         self.id_map
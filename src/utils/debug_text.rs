@@ -0,0 +1,593 @@
+use crate::BindGroupDescriptor;
+use crate::BindGroupEntry;
+use crate::BindGroupLayoutDescriptor;
+use crate::BindGroupLayoutEntryExt;
+use crate::BindingResource;
+use crate::BufferDescriptor;
+use crate::BufferId;
+use crate::BufferWrite;
+use crate::ColorView;
+use crate::Command;
+use crate::CommandBufferDescriptor;
+use crate::CommandBufferId;
+use crate::DeviceId;
+use crate::FragmentState;
+use crate::PipelineLayoutDescriptor;
+use crate::RenderCommand;
+use crate::RenderPassColorAttachment;
+use crate::RenderPipelineDescriptor;
+use crate::ResourceId;
+use crate::ResourceWrite;
+use crate::ShaderModuleDescriptor;
+use crate::ShaderSource;
+use crate::Slice;
+use crate::TaskTrait;
+use crate::TextureDescriptor;
+use crate::TextureSource;
+use crate::TextureViewDescriptor;
+use crate::TextureWrite;
+use crate::UpdateContext;
+use crate::VertexBufferLayout;
+use crate::VertexState;
+
+/// Width of a glyph of the debug font, in texels.
+pub const DEBUG_GLYPH_WIDTH: u32 = 3;
+/// Height of a glyph of the debug font, in texels.
+pub const DEBUG_GLYPH_HEIGHT: u32 = 5;
+
+/**
+Glyphs of the debug font, as rows of [DEBUG_GLYPH_WIDTH][DEBUG_GLYPH_WIDTH] bits with the most significant one on the left.
+Lowercase letters are drawn with the uppercase glyphs, while the missing characters with the `?` glyph.
+*/
+pub const DEBUG_FONT: &[(char, [u8; DEBUG_GLYPH_HEIGHT as usize])] = &[
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+    ('!', [0b010, 0b010, 0b010, 0b000, 0b010]),
+    ('"', [0b101, 0b101, 0b000, 0b000, 0b000]),
+    ('#', [0b101, 0b111, 0b101, 0b111, 0b101]),
+    ('%', [0b101, 0b001, 0b010, 0b100, 0b101]),
+    ('\'', [0b010, 0b010, 0b000, 0b000, 0b000]),
+    ('(', [0b001, 0b010, 0b010, 0b010, 0b001]),
+    (')', [0b100, 0b010, 0b010, 0b010, 0b100]),
+    ('*', [0b000, 0b101, 0b010, 0b101, 0b000]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    (';', [0b000, 0b010, 0b000, 0b010, 0b100]),
+    ('<', [0b001, 0b010, 0b100, 0b010, 0b001]),
+    ('=', [0b000, 0b111, 0b000, 0b111, 0b000]),
+    ('>', [0b100, 0b010, 0b001, 0b010, 0b100]),
+    ('?', [0b111, 0b001, 0b010, 0b000, 0b010]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('[', [0b110, 0b100, 0b100, 0b100, 0b110]),
+    (']', [0b011, 0b001, 0b001, 0b001, 0b011]),
+    ('_', [0b000, 0b000, 0b000, 0b000, 0b111]),
+];
+
+/// Index of the glyph drawing `character` in [DEBUG_FONT][DEBUG_FONT].
+pub fn debug_glyph(character: char) -> u32 {
+    let character = character.to_ascii_uppercase();
+    let position = |character| {
+        DEBUG_FONT
+            .iter()
+            .position(|(glyph_character, _rows)| *glyph_character == character)
+    };
+    position(character).or_else(|| position('?')).unwrap_or(0) as u32
+}
+
+/**
+Texels of the glyph atlas, one byte per texel, with the glyphs placed side by side
+in the order of [DEBUG_FONT][DEBUG_FONT]. Rows are padded to `bytes_per_row`.
+*/
+fn atlas_texels(bytes_per_row: u32) -> Vec<u8> {
+    let mut data = vec![0; (bytes_per_row * DEBUG_GLYPH_HEIGHT) as usize];
+    for (index, (_character, rows)) in DEBUG_FONT.iter().enumerate() {
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..DEBUG_GLYPH_WIDTH {
+                if row & (1 << (DEBUG_GLYPH_WIDTH - 1 - x)) != 0 {
+                    let texel = y * bytes_per_row as usize + index * DEBUG_GLYPH_WIDTH as usize;
+                    data[texel + x as usize] = 255;
+                }
+            }
+        }
+    }
+    data
+}
+
+/// Quad of every glyph, stretched on the instance rectangle and reading its coverage from the atlas.
+const DEBUG_TEXT_SHADER: &str = r#"
+[[block]]
+struct Screen {
+    size: vec2<f32>;
+    glyph_size: vec2<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> screen: Screen;
+[[group(1), binding(0)]]
+var atlas: texture_2d<f32>;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] atlas_coords: vec2<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] index: u32,
+    [[location(0)]] origin: vec2<f32>,
+    [[location(1)]] glyph: u32
+) -> VertexOutput {
+    let corner = vec2<f32>(f32(index >> 1u), f32(index & 1u));
+    let pixel = origin + corner * screen.glyph_size;
+    var output: VertexOutput;
+    output.position = vec4<f32>(
+        pixel.x / screen.size.x * 2.0 - 1.0,
+        1.0 - pixel.y / screen.size.y * 2.0,
+        0.0,
+        1.0
+    );
+    output.atlas_coords = (vec2<f32>(f32(glyph), 0.0) + corner) * vec2<f32>(3.0, 5.0);
+    return output;
+}
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let texel = vec2<i32>(i32(input.atlas_coords.x), i32(input.atlas_coords.y));
+    let coverage = textureLoad(atlas, texel, 0).r;
+    return vec4<f32>(1.0, 1.0, 1.0, coverage);
+}
+"#;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct ScreenUniform {
+    size: [f32; 2],
+    glyph_size: [f32; 2],
+}
+impl ScreenUniform {
+    fn new(width: u32, height: u32, scale: u32) -> Self {
+        Self {
+            size: [width as f32, height as f32],
+            glyph_size: [
+                (DEBUG_GLYPH_WIDTH * scale) as f32,
+                (DEBUG_GLYPH_HEIGHT * scale) as f32,
+            ],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+/// Instance of a drawn glyph, with the position of its top left corner in pixels.
+pub struct GlyphInstance {
+    pub position: [f32; 2],
+    pub glyph: u32,
+}
+impl GlyphInstance {
+    /// Layout of an instance buffer of [GlyphInstance][GlyphInstance], with the position at location 0 and the glyph at location 1.
+    pub fn layout() -> VertexBufferLayout {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as crate::wgpu::BufferAddress,
+            step_mode: crate::wgpu::InputStepMode::Instance,
+            attributes: vec![
+                crate::wgpu::VertexAttribute {
+                    format: crate::wgpu::VertexFormat::Float32x2,
+                    offset: 0,
+                    shader_location: 0,
+                },
+                crate::wgpu::VertexAttribute {
+                    format: crate::wgpu::VertexFormat::Uint32,
+                    offset: std::mem::size_of::<[f32; 2]>() as crate::wgpu::BufferAddress,
+                    shader_location: 1,
+                },
+            ],
+        }
+    }
+}
+
+/**
+Lay out `text` starting from the top left corner at `(x, y)`, in pixels, with every glyph scaled by `scale`.
+Glyphs are separated by a column and lines by a row, while `\n` starts a new line.
+*/
+pub fn layout_debug_text(x: f32, y: f32, scale: u32, text: &str) -> Vec<GlyphInstance> {
+    let advance = ((DEBUG_GLYPH_WIDTH + 1) * scale) as f32;
+    let line_height = ((DEBUG_GLYPH_HEIGHT + 1) * scale) as f32;
+    let space = debug_glyph(' ');
+
+    let mut instances = Vec::new();
+    let mut position = [x, y];
+    for character in text.chars() {
+        if character == '\n' {
+            position = [x, position[1] + line_height];
+            continue;
+        }
+        let glyph = debug_glyph(character);
+        if glyph != space {
+            instances.push(GlyphInstance { position, glyph });
+        }
+        position[0] += advance;
+    }
+    instances
+}
+
+#[derive(Debug)]
+/**
+Task drawing strings with a monospace bitmap font on a target, meant for debug overlays (like frame timings).
+Strings are queued with [push_text][DebugTextTask::push_text] and drawn only on the next frame,
+so they have to be pushed again every frame. Every glyph is an instanced quad reading its coverage
+from a glyph atlas, blended in white over the current target contents: the target is loaded, not cleared,
+so the task has to run after the tasks rendering on it.
+*/
+pub struct DebugTextTask {
+    device: DeviceId,
+    scale: u32,
+    uniform_buffer: BufferId,
+    instance_buffer: BufferId,
+    instance_capacity: usize,
+    instance_count: u32,
+    command_buffer: CommandBufferId,
+    texts: Vec<(f32, f32, String)>,
+}
+
+impl DebugTextTask {
+    pub const TASK_NAME: &'static str = "DebugTextTask";
+    const INITIAL_CAPACITY: usize = 256;
+    // Position of the glyphs draw in the command buffer: the last command of its only render pass.
+    const DRAW_PASS: usize = 0;
+    const DRAW_COMMAND: usize = 4;
+
+    /**
+    Create the task drawing on `target`, of the passed format and size in pixels.
+    Glyphs are magnified by `scale`, so every glyph covers `3 * scale` by `5 * scale` pixels.
+    If any creation fails, the already created resources are removed.
+    */
+    pub fn new(
+        update_context: &mut UpdateContext,
+        device: DeviceId,
+        target: ColorView,
+        format: crate::wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        scale: u32,
+    ) -> Result<Self, ()> {
+        let mut created = Vec::new();
+        let task = Self::create(
+            update_context,
+            &mut created,
+            device,
+            target,
+            format,
+            width,
+            height,
+            scale,
+        );
+        if task.is_err() {
+            // Dependents first, so every resource is removed with nothing depending on it.
+            for id in created.iter().rev() {
+                let _ = update_context.remove_resource(id);
+            }
+        }
+        task
+    }
+
+    // Create the resources of the task, recording them in `created` so that they can be removed on failure.
+    fn create(
+        update_context: &mut UpdateContext,
+        created: &mut Vec<ResourceId>,
+        device: DeviceId,
+        target: ColorView,
+        format: crate::wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        scale: u32,
+    ) -> Result<Self, ()> {
+        let label = Self::TASK_NAME;
+        let scale = scale.max(1);
+
+        let shader_module =
+            update_context.add_shader_module_descriptor(ShaderModuleDescriptor {
+                label: label.to_string() + " shader",
+                device,
+                source: ShaderSource::Wgsl(DEBUG_TEXT_SHADER.to_string()),
+                flags: crate::wgpu::ShaderFlags::VALIDATION,
+            })?;
+        created.push(shader_module.into());
+
+        let (uniform_buffer, uniform_layout, uniform_bind_group) = crate::uniform_bind_group(
+            update_context,
+            device,
+            label,
+            crate::wgpu::ShaderStage::VERTEX,
+            &ScreenUniform::new(width, height, scale),
+        )?;
+        created.push(uniform_buffer.into());
+        created.push(uniform_layout.into());
+        created.push(uniform_bind_group.into());
+
+        let atlas_width = DEBUG_GLYPH_WIDTH * DEBUG_FONT.len() as u32;
+        let atlas_format = crate::wgpu::TextureFormat::R8Unorm;
+        let atlas = update_context.add_texture_descriptor(TextureDescriptor {
+            label: label.to_string() + " glyph atlas",
+            device,
+            source: TextureSource::Local,
+            usage: crate::wgpu::TextureUsage::SAMPLED | crate::wgpu::TextureUsage::COPY_DST,
+            size: crate::wgpu::Extent3d {
+                width: atlas_width,
+                height: DEBUG_GLYPH_HEIGHT,
+                depth_or_array_layers: 1,
+            },
+            format: atlas_format,
+            dimension: crate::wgpu::TextureDimension::D2,
+            mip_level_count: 1,
+            sample_count: 1,
+        })?;
+        created.push(atlas.into());
+        let atlas_view =
+            update_context.add_texture_view_descriptor(TextureViewDescriptor::layer(
+                label.to_string() + " glyph atlas view",
+                device,
+                atlas,
+                atlas_format,
+                0,
+            ))?;
+        created.push(atlas_view.into());
+
+        let atlas_layout =
+            update_context.add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+                label: label.to_string() + " glyph atlas bind group layout",
+                device,
+                entries: vec![crate::wgpu::BindGroupLayoutEntry::texture(
+                    0,
+                    crate::wgpu::ShaderStage::FRAGMENT,
+                )],
+            })?;
+        created.push(atlas_layout.into());
+        let atlas_bind_group = update_context.add_bind_group_descriptor(BindGroupDescriptor {
+            label: label.to_string() + " glyph atlas bind group",
+            device,
+            layout: atlas_layout,
+            entries: vec![BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(atlas_view),
+            }],
+        })?;
+        created.push(atlas_bind_group.into());
+
+        let pipeline_layout =
+            update_context.add_pipeline_layout_descriptor(PipelineLayoutDescriptor {
+                label: label.to_string() + " pipeline layout",
+                device,
+                bind_group_layouts: vec![uniform_layout, atlas_layout],
+                push_constant_ranges: Vec::new(),
+            })?;
+        created.push(pipeline_layout.into());
+        let render_pipeline =
+            update_context.add_render_pipeline_descriptor(RenderPipelineDescriptor {
+                label: label.to_string() + " pipeline",
+                device,
+                layout: Some(pipeline_layout),
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: String::from("vs_main"),
+                    buffers: vec![GlyphInstance::layout()],
+                },
+                primitive: crate::wgpu::PrimitiveState {
+                    topology: crate::wgpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: crate::wgpu::MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: String::from("fs_main"),
+                    targets: vec![crate::wgpu::ColorTargetState {
+                        format,
+                        blend: Some(crate::wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: crate::wgpu::ColorWrite::ALL,
+                    }],
+                }),
+            })?;
+        created.push(render_pipeline.into());
+
+        let instance_capacity = Self::INITIAL_CAPACITY;
+        let instance_buffer = update_context
+            .add_buffer_descriptor(Self::instance_buffer_descriptor(device, instance_capacity))?;
+        created.push(instance_buffer.into());
+
+        let command_buffer =
+            update_context.add_command_buffer_descriptor(CommandBufferDescriptor {
+                label: label.to_string() + " command buffer",
+                device,
+                commands: vec![Command::RenderPass {
+                    label: label.to_string() + " render pass",
+                    depth_stencil: None,
+                    color_attachments: vec![RenderPassColorAttachment {
+                        view: target,
+                        resolve_target: None,
+                        ops: crate::wgpu::Operations {
+                            load: crate::wgpu::LoadOp::Load,
+                            store: true,
+                        },
+                    }],
+                    commands: vec![
+                        RenderCommand::SetPipeline {
+                            pipeline: render_pipeline,
+                        },
+                        RenderCommand::SetBindGroup {
+                            index: 0,
+                            bind_group: uniform_bind_group,
+                            offsets: Vec::new(),
+                        },
+                        RenderCommand::SetBindGroup {
+                            index: 1,
+                            bind_group: atlas_bind_group,
+                            offsets: Vec::new(),
+                        },
+                        RenderCommand::SetVertexBuffer {
+                            slot: 0,
+                            buffer: instance_buffer,
+                            slice: Slice::from(..),
+                        },
+                        RenderCommand::Draw {
+                            vertices: 0..4,
+                            instances: 0..0,
+                        },
+                    ],
+                }],
+                max_commands: None,
+            })?;
+
+        // Uploaded only once everything has been created, so no write is left for a removed atlas.
+        let bytes_per_row = update_context
+            .device_alignments(device)
+            .unwrap_or_default()
+            .padded_bytes_per_row(atlas_width);
+        update_context.write_resource(&mut vec![ResourceWrite::Texture(TextureWrite {
+            texture: atlas,
+            mip_level: 0,
+            origin: crate::wgpu::Origin3d::ZERO,
+            data: atlas_texels(bytes_per_row),
+            layout: crate::wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(bytes_per_row),
+                rows_per_image: std::num::NonZeroU32::new(DEBUG_GLYPH_HEIGHT),
+            },
+            size: crate::wgpu::Extent3d {
+                width: atlas_width,
+                height: DEBUG_GLYPH_HEIGHT,
+                depth_or_array_layers: 1,
+            },
+        })]);
+
+        Ok(Self {
+            device,
+            scale,
+            uniform_buffer,
+            instance_buffer,
+            instance_capacity,
+            instance_count: 0,
+            command_buffer,
+            texts: Vec::new(),
+        })
+    }
+
+    fn instance_buffer_descriptor(device: DeviceId, capacity: usize) -> BufferDescriptor {
+        BufferDescriptor {
+            label: Self::TASK_NAME.to_string() + " instance buffer",
+            device,
+            size: (capacity * std::mem::size_of::<GlyphInstance>()) as u64,
+            usage: crate::wgpu::BufferUsage::VERTEX | crate::wgpu::BufferUsage::COPY_DST,
+        }
+    }
+
+    /// Queue `text` to be drawn on the next frame, with its top left corner at `(x, y)` in pixels.
+    pub fn push_text(&mut self, x: f32, y: f32, text: &str) {
+        self.texts.push((x, y, text.to_string()));
+    }
+
+    /// Update the size of the target, in pixels, so that the text keeps its size after a resize.
+    pub fn resize(&mut self, update_context: &mut UpdateContext, width: u32, height: u32) {
+        update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+            buffer: self.uniform_buffer,
+            offset: 0,
+            data: bytemuck::bytes_of(&ScreenUniform::new(width, height, self.scale)).to_vec(),
+        })]);
+    }
+}
+
+impl TaskTrait for DebugTextTask {
+    fn name(&self) -> String {
+        Self::TASK_NAME.to_string()
+    }
+
+    fn update_resources(&mut self, update_context: &mut UpdateContext) {
+        let scale = self.scale;
+        let instances: Vec<GlyphInstance> = self
+            .texts
+            .drain(..)
+            .flat_map(|(x, y, text)| layout_debug_text(x, y, scale, &text))
+            .collect();
+
+        if instances.len() > self.instance_capacity {
+            let capacity = instances.len().next_power_of_two();
+            if !update_context.update_buffer_descriptor(
+                &mut self.instance_buffer,
+                Self::instance_buffer_descriptor(self.device, capacity),
+            ) {
+                log::error!(target: "DebugTextTask","Failed to grow the instance buffer to {} glyphs",capacity);
+                self.instance_count = 0;
+                return;
+            }
+            self.instance_capacity = capacity;
+        }
+
+        if !instances.is_empty() {
+            update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+                buffer: self.instance_buffer,
+                offset: 0,
+                data: bytemuck::cast_slice(&instances).to_vec(),
+            })]);
+        }
+
+        let instance_count = instances.len() as u32;
+        if instance_count != self.instance_count
+            && !update_context.patch_draw_instances(
+                &mut self.command_buffer,
+                Self::DRAW_PASS,
+                Self::DRAW_COMMAND,
+                0..instance_count,
+            )
+        {
+            log::error!(target: "DebugTextTask","Failed to update the command buffer for {} glyphs",instance_count);
+            self.instance_count = 0;
+            return;
+        }
+        self.instance_count = instance_count;
+    }
+
+    fn command_buffers(&self) -> Vec<CommandBufferId> {
+        if self.instance_count == 0 {
+            Vec::new()
+        } else {
+            vec![self.command_buffer]
+        }
+    }
+}
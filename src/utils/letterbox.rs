@@ -0,0 +1,141 @@
+use crate::RenderCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Region of the attachments the normalized device coordinates are mapped to, see [SetViewport][RenderCommand::SetViewport].
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub min_depth: f32,
+    pub max_depth: f32,
+}
+impl Viewport {
+    /// [SetViewport][RenderCommand::SetViewport] command using the viewport.
+    pub fn command(&self) -> RenderCommand {
+        RenderCommand::SetViewport {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            min_depth: self.min_depth,
+            max_depth: self.max_depth,
+        }
+    }
+}
+impl From<ScissorRect> for Viewport {
+    fn from(rect: ScissorRect) -> Self {
+        Self {
+            x: rect.x as f32,
+            y: rect.y as f32,
+            width: rect.width as f32,
+            height: rect.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Region of the attachments in pixels, see [SetScissorRect][RenderCommand::SetScissorRect].
+pub struct ScissorRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+impl ScissorRect {
+    /// [SetScissorRect][RenderCommand::SetScissorRect] command using the rectangle.
+    pub fn command(&self) -> RenderCommand {
+        RenderCommand::SetScissorRect {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+    /// Returns true if the rectangle covers no pixel, like the borders of a letterbox matching the swapchain.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/**
+Compute the largest viewport with the `content_aspect` (width / height) ratio that fits in a swapchain of `swapchain_size`,
+centered on it. Also returns the two borders left uncovered: the left and right ones if the swapchain is wider than the content,
+the top and bottom ones otherwise. The borders are empty if the aspect ratios match.
+The viewport and the borders are aligned to the pixels and cover the whole swapchain without overlapping.
+*/
+pub fn letterbox_viewport(
+    swapchain_size: (u32, u32),
+    content_aspect: f32,
+) -> (Viewport, [ScissorRect; 2]) {
+    let (width, height) = swapchain_size;
+    if width as f32 > height as f32 * content_aspect {
+        let content_width = ((height as f32 * content_aspect).round() as u32).min(width);
+        let x = (width - content_width) / 2;
+        let content = ScissorRect {
+            x,
+            y: 0,
+            width: content_width,
+            height,
+        };
+        let left = ScissorRect {
+            x: 0,
+            y: 0,
+            width: x,
+            height,
+        };
+        let right = ScissorRect {
+            x: x + content_width,
+            y: 0,
+            width: width - x - content_width,
+            height,
+        };
+        (content.into(), [left, right])
+    } else {
+        let content_height = ((width as f32 / content_aspect).round() as u32).min(height);
+        let y = (height - content_height) / 2;
+        let content = ScissorRect {
+            x: 0,
+            y,
+            width,
+            height: content_height,
+        };
+        let top = ScissorRect {
+            x: 0,
+            y: 0,
+            width,
+            height: y,
+        };
+        let bottom = ScissorRect {
+            x: 0,
+            y: y + content_height,
+            width,
+            height: height - y - content_height,
+        };
+        (content.into(), [top, bottom])
+    }
+}
+
+/**
+Prepend to the commands of a render pass on a swapchain of `swapchain_size` the viewport and the scissor rectangle
+of the [letterbox][letterbox_viewport] with the `content_aspect` ratio, so that the content is drawn only in it.
+The borders keep the color the pass clears the attachments with, so the pass must use [LoadOp::Clear][crate::wgpu::LoadOp::Clear].
+*/
+pub fn letterbox_commands(
+    swapchain_size: (u32, u32),
+    content_aspect: f32,
+    commands: Vec<RenderCommand>,
+) -> Vec<RenderCommand> {
+    let (viewport, _borders) = letterbox_viewport(swapchain_size, content_aspect);
+    let scissor = ScissorRect {
+        x: viewport.x as u32,
+        y: viewport.y as u32,
+        width: viewport.width as u32,
+        height: viewport.height as u32,
+    };
+    let mut letterboxed = vec![viewport.command(), scissor.command()];
+    letterboxed.extend(commands);
+    letterboxed
+}
@@ -1,25 +1,112 @@
 //! Utility functions and structures.
 
+pub mod blit;
+pub use blit::*;
+
+pub mod buffer_arena;
+pub use buffer_arena::*;
+
 pub mod buffer_manager;
 pub use buffer_manager::*;
 
+#[cfg(feature = "debug_text")]
+pub mod debug_text;
+#[cfg(feature = "debug_text")]
+pub use debug_text::*;
+
+pub mod letterbox;
+pub use letterbox::*;
+
+pub mod ping_pong_targets;
+pub use ping_pong_targets::*;
+
+pub mod primitives;
+
+pub mod push_constants;
+pub use push_constants::*;
+
+pub mod reflection;
+pub use reflection::*;
+
+pub mod texture_region_writer;
+pub use texture_region_writer::*;
+
+pub mod uniforms;
+pub use uniforms::*;
+
 use crate::common::tasks::TaskTrait;
 use crate::TaskId;
 use crate::UpdateContext;
 
+use crate::Requirements;
 use crate::WGpuEngine;
+use crate::WGpuEngineError;
 use pal::definitions::*;
 
-/// Allow to quickly initialize the engine and the event loop to run a single task.
+/**
+Run a future to completion on the passed runtime. If the current thread is already running inside a runtime
+(like when the engine is used from an async application), the blocking is moved out of the worker with
+[block_in_place][tokio::task::block_in_place], which requires a multi threaded runtime.
+*/
+pub(crate) fn block_on<F: std::future::Future>(
+    tokio: &tokio::runtime::Handle,
+    future: F,
+) -> F::Output {
+    match tokio::runtime::Handle::try_current() {
+        Ok(_) => tokio::task::block_in_place(|| tokio.block_on(future)),
+        Err(_) => tokio.block_on(future),
+    }
+}
+
+/**
+Round the size of a push constant structure up to [PUSH_CONSTANT_ALIGNMENT][crate::wgpu::PUSH_CONSTANT_ALIGNMENT],
+as required by the ranges of a [PipelineLayoutDescriptor][crate::PipelineLayoutDescriptor].
+*/
+pub fn align_push_constant_size(size: usize) -> u32 {
+    crate::DeviceAlignments::align_to(size as u64, crate::wgpu::PUSH_CONSTANT_ALIGNMENT as u64)
+        as u32
+}
+
+/**
+Create a pipeline layout for the passed bind group layouts and push constant ranges, or reuse the existing one:
+every layout created by this function has the same label, so the [deduplication][crate::ResourceManager::set_deduplication]
+of the stateless resources shares a single layout among all the callers (and tasks) asking for identical layouts.
+The intended pattern is to pass the returned id to every [RenderPipelineDescriptor][crate::RenderPipelineDescriptor]
+using those bind group layouts. With the deduplication disabled, every call creates a new layout.
+*/
+pub fn shared_pipeline_layout(
+    update_context: &mut UpdateContext,
+    device: crate::DeviceId,
+    bind_group_layouts: &[crate::BindGroupLayoutId],
+    push_constant_ranges: Vec<crate::wgpu::PushConstantRange>,
+) -> Result<crate::PipelineLayoutId, ()> {
+    update_context.add_pipeline_layout_descriptor(crate::PipelineLayoutDescriptor {
+        label: String::from("Shared pipeline layout"),
+        device,
+        bind_group_layouts: bind_group_layouts.to_vec(),
+        push_constant_ranges,
+    })
+}
+
+/**
+Allow to quickly initialize the engine and the event loop to run a single task.
+The limits are required as minimum limits, so an adapter that does not satisfy them fails the run.
+It returns when all the surfaces have been removed, or with an error as soon as the engine,
+the task or a surface cannot be created, instead of looping without surfaces.
+*/
 pub fn quick_run<T: TaskTrait, C: Fn(TaskId, &tokio::runtime::Handle, &mut UpdateContext) -> T>(
     surface_count: usize,
     features: crate::wgpu::Features,
     limits: crate::wgpu::Limits,
     task_callback: C,
     mut loop_callback: impl FnMut(&mut T),
-) {
-    let mut wgpu_engine = WGpuEngine::new((features.clone(), limits.clone()))
-        .expect("Failed to initialize the engine: {}");
+) -> Result<(), WGpuEngineError> {
+    let requirements =
+        Requirements::from((features, limits.clone())).with_minimum_limits(limits.clone());
+    let mut wgpu_engine = WGpuEngine::new(requirements).map_err(|err| {
+        log::error!(target: "Engine","Quick run: failed to initialize the engine: {:?}",err);
+        err
+    })?;
 
     let mut platform = pal::Platform::new(vec![Box::new(wgpu_engine.wgpu_context())]);
     (0..surface_count).for_each(|_| {
@@ -28,9 +115,13 @@ pub fn quick_run<T: TaskTrait, C: Fn(TaskId, &tokio::runtime::Handle, &mut Updat
         }]);
     });
 
-    let task = wgpu_engine
-        .create_task("Task".into(), (features, limits), task_callback)
-        .unwrap();
+    let task = match wgpu_engine.create_task("Task".into(), (features, limits), task_callback) {
+        Some(task) => task,
+        None => {
+            log::error!(target: "Engine","Quick run: failed to create the task");
+            return Err(WGpuEngineError::InitializationFailed);
+        }
+    };
 
     use std::os::unix::io::AsRawFd;
     let mut event_loop = calloop::EventLoop::try_new().unwrap();
@@ -55,16 +146,23 @@ pub fn quick_run<T: TaskTrait, C: Fn(TaskId, &tokio::runtime::Handle, &mut Updat
             match event {
                 pal::Event::Surface { time: _, id, event } => match &event {
                     pal::SurfaceEvent::Added(surface_info) => {
-                        if let Surface::WGpu(surface) = &surface_info.surface {
-                            wgpu_engine.create_surface(
-                                id.into(),
-                                String::from("MainSurface"),
-                                surface.clone(),
-                                surface_info.size.width,
-                                surface_info.size.height,
-                            );
-                        } else {
-                            panic!("It is not of WGpu type");
+                        let surface = match &surface_info.surface {
+                            Surface::WGpu(surface) => surface.clone(),
+                            _ => {
+                                log::error!(target: "Engine","Quick run: the surface is not of WGpu type");
+                                return Err(WGpuEngineError::SurfaceCreationFailed(id.into()));
+                            }
+                        };
+                        if let Err(err) = wgpu_engine.create_surface(
+                            id.into(),
+                            String::from("MainSurface"),
+                            surface,
+                            surface_info.size.width,
+                            surface_info.size.height,
+                            crate::SurfaceOptions::default(),
+                        ) {
+                            log::error!(target: "Engine","Quick run: {:?}, stopping",err);
+                            return Err(err);
                         }
                     }
                     pal::SurfaceEvent::Resized(size) => {
@@ -85,4 +183,5 @@ pub fn quick_run<T: TaskTrait, C: Fn(TaskId, &tokio::runtime::Handle, &mut Updat
         wgpu_engine.dispatch_tasks();
         wgpu_engine.task_handle_cast_mut(&task, |task| loop_callback(task));
     }
+    Ok(())
 }
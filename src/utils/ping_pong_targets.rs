@@ -0,0 +1,97 @@
+use crate::TextureDescriptor;
+use crate::TextureId;
+use crate::TextureViewDescriptor;
+use crate::TextureViewId;
+use crate::UpdateContext;
+
+#[derive(Debug)]
+/**
+Helper structure holding two render targets with identical descriptors, for iterative passes
+(like blurs or simulations) that read from one target while writing on the other.
+Both targets get the `RENDER_ATTACHMENT` and `SAMPLED` usages in addition to the requested ones.
+*/
+pub struct PingPongTargets {
+    descriptor: TextureDescriptor,
+    targets: [(TextureId, TextureViewId); 2],
+    read: usize,
+}
+impl PingPongTargets {
+    pub fn new(
+        update_context: &mut UpdateContext,
+        mut descriptor: TextureDescriptor,
+    ) -> Result<Self, ()> {
+        descriptor.usage |=
+            crate::wgpu::TextureUsage::RENDER_ATTACHMENT | crate::wgpu::TextureUsage::SAMPLED;
+
+        let first = Self::create_target(update_context, &descriptor, 0)?;
+        let second = match Self::create_target(update_context, &descriptor, 1) {
+            Ok(second) => second,
+            Err(()) => {
+                let _ = update_context.remove_texture_view(&first.1);
+                let _ = update_context.remove_texture(&first.0);
+                return Err(());
+            }
+        };
+
+        Ok(Self {
+            descriptor,
+            targets: [first, second],
+            read: 0,
+        })
+    }
+
+    fn create_target(
+        update_context: &mut UpdateContext,
+        descriptor: &TextureDescriptor,
+        index: usize,
+    ) -> Result<(TextureId, TextureViewId), ()> {
+        let mut descriptor = descriptor.clone();
+        descriptor.label = format!("{} {}", descriptor.label, index);
+        let texture = update_context.add_texture_descriptor(descriptor.clone())?;
+        let view = update_context.add_texture_view_descriptor(TextureViewDescriptor {
+            label: descriptor.label + " view",
+            device: descriptor.device,
+            texture,
+            format: descriptor.format,
+            dimension: crate::wgpu::TextureViewDimension::D2,
+            aspect: crate::wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: None,
+            base_array_layer: 0,
+            array_layer_count: None,
+        });
+        match view {
+            Ok(view) => Ok((texture, view)),
+            Err(()) => {
+                let _ = update_context.remove_texture(&texture);
+                Err(())
+            }
+        }
+    }
+
+    /// Descriptor shared by both targets.
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
+    /// Target to read from in the current iteration.
+    pub fn read(&self) -> (TextureId, TextureViewId) {
+        self.targets[self.read]
+    }
+    /// Target to write on in the current iteration.
+    pub fn write(&self) -> (TextureId, TextureViewId) {
+        self.targets[1 - self.read]
+    }
+    /// Swap the targets, so that the last written target becomes the one to read from.
+    pub fn swap(&mut self) {
+        self.read = 1 - self.read;
+    }
+
+    /// Remove both targets.
+    pub fn remove(self, update_context: &mut UpdateContext) -> Result<(), ()> {
+        for (texture, view) in self.targets.iter() {
+            update_context.remove_texture_view(view)?;
+            update_context.remove_texture(texture)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! Basic meshes, with counter-clockwise front faces.
+
+use crate::BufferDescriptor;
+use crate::BufferId;
+use crate::BufferWrite;
+use crate::DeviceId;
+use crate::ResourceWrite;
+use crate::UpdateContext;
+use crate::VertexBufferLayout;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+/// Vertex of the generated primitives.
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+}
+impl Vertex {
+    pub fn new(position: [f32; 3], uv: [f32; 2]) -> Self {
+        Self { position, uv }
+    }
+    /// Layout of a vertex buffer of [Vertex][Vertex], with the position at location 0 and the uv at location 1.
+    pub fn layout() -> VertexBufferLayout {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as crate::wgpu::BufferAddress,
+            step_mode: crate::wgpu::InputStepMode::Vertex,
+            attributes: vec![
+                crate::wgpu::VertexAttribute {
+                    format: crate::wgpu::VertexFormat::Float32x3,
+                    offset: 0,
+                    shader_location: 0,
+                },
+                crate::wgpu::VertexAttribute {
+                    format: crate::wgpu::VertexFormat::Float32x2,
+                    offset: std::mem::size_of::<[f32; 3]>() as crate::wgpu::BufferAddress,
+                    shader_location: 1,
+                },
+            ],
+        }
+    }
+}
+
+/// Quad of side 1 on the XY plane, centered on the origin and facing +Z.
+pub fn unit_quad() -> (Vec<Vertex>, Vec<u16>) {
+    let vertices = vec![
+        Vertex::new([-0.5, -0.5, 0.0], [0.0, 1.0]),
+        Vertex::new([0.5, -0.5, 0.0], [1.0, 1.0]),
+        Vertex::new([0.5, 0.5, 0.0], [1.0, 0.0]),
+        Vertex::new([-0.5, 0.5, 0.0], [0.0, 0.0]),
+    ];
+    let indices = vec![0, 1, 2, 0, 2, 3];
+    (vertices, indices)
+}
+
+/// Cube of side 1 centered on the origin, with 4 vertices per face so that every face has its own uvs.
+pub fn unit_cube() -> (Vec<Vertex>, Vec<u16>) {
+    // Normal and the two tangent axes of every face, with `u x v = normal`.
+    let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ];
+
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+    for (normal, u, v) in faces.iter() {
+        let base = vertices.len() as u16;
+        for (su, sv, uv) in [
+            (-1.0, -1.0, [0.0, 1.0]),
+            (1.0, -1.0, [1.0, 1.0]),
+            (1.0, 1.0, [1.0, 0.0]),
+            (-1.0, 1.0, [0.0, 0.0]),
+        ] {
+            let mut position = [0.0; 3];
+            for axis in 0..3 {
+                position[axis] = 0.5 * (normal[axis] + su * u[axis] + sv * v[axis]);
+            }
+            vertices.push(Vertex::new(position, uv));
+        }
+        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    (vertices, indices)
+}
+
+/// Single triangle covering the whole clip space, with uvs going from 0 to 1 on the visible area.
+pub fn fullscreen_triangle() -> Vec<Vertex> {
+    vec![
+        Vertex::new([-1.0, -1.0, 0.0], [0.0, 1.0]),
+        Vertex::new([3.0, -1.0, 0.0], [2.0, 1.0]),
+        Vertex::new([-1.0, 3.0, 0.0], [0.0, -1.0]),
+    ]
+}
+
+/**
+Create a vertex buffer and an index buffer for the passed mesh and queue the upload of their data.
+The index data is padded to the [copy alignment][crate::DeviceAlignments::copy_buffer] of the device.
+*/
+pub fn upload_mesh(
+    update_context: &mut UpdateContext,
+    device: DeviceId,
+    label: &str,
+    vertices: &[Vertex],
+    indices: &[u16],
+) -> Result<(BufferId, BufferId), ()> {
+    let alignments = update_context.device_alignments(device).ok_or(())?;
+
+    let vertex_data = bytemuck::cast_slice(vertices).to_vec();
+    let vertex_buffer = update_context.add_buffer_descriptor(BufferDescriptor {
+        label: label.to_string() + " vertex buffer",
+        device,
+        size: vertex_data.len() as u64,
+        usage: crate::wgpu::BufferUsage::VERTEX | crate::wgpu::BufferUsage::COPY_DST,
+    })?;
+
+    let mut index_data = bytemuck::cast_slice(indices).to_vec();
+    index_data.resize(
+        alignments.align_copy_size(index_data.len() as u64) as usize,
+        0,
+    );
+    let index_buffer = match update_context.add_buffer_descriptor(BufferDescriptor {
+        label: label.to_string() + " index buffer",
+        device,
+        size: index_data.len() as u64,
+        usage: crate::wgpu::BufferUsage::INDEX | crate::wgpu::BufferUsage::COPY_DST,
+    }) {
+        Ok(index_buffer) => index_buffer,
+        Err(()) => {
+            let _ = update_context.remove_buffer(&vertex_buffer);
+            return Err(());
+        }
+    };
+
+    update_context.write_resource(&mut vec![
+        ResourceWrite::Buffer(BufferWrite {
+            buffer: vertex_buffer,
+            offset: 0,
+            data: vertex_data,
+        }),
+        ResourceWrite::Buffer(BufferWrite {
+            buffer: index_buffer,
+            offset: 0,
+            data: index_data,
+        }),
+    ]);
+    Ok((vertex_buffer, index_buffer))
+}
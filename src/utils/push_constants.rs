@@ -0,0 +1,122 @@
+use crate::align_push_constant_size;
+use crate::ComputeCommand;
+use crate::RenderCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Segment of a [PushConstantLayout][PushConstantLayout], holding a single value visible to some shader stages.
+pub struct PushConstantSegment {
+    pub stages: crate::wgpu::ShaderStage,
+    pub offset: u32,
+    pub size: u32,
+}
+impl PushConstantSegment {
+    /// Data of the segment, padded to its aligned size.
+    fn data<T: bytemuck::Pod>(&self, value: &T) -> Vec<u8> {
+        assert!(
+            std::mem::size_of::<T>() <= self.size as usize,
+            "A value of {} bytes does not fit in a push constant segment of {} bytes",
+            std::mem::size_of::<T>(),
+            self.size
+        );
+        let mut data = bytemuck::bytes_of(value).to_vec();
+        data.resize(self.size as usize, 0);
+        data
+    }
+    /**
+    [SetPushConstants][RenderCommand::SetPushConstants] writing `value` in the segment.
+    Panics if `value` is bigger than the segment, since it would overwrite the following ones.
+    */
+    pub fn render_command<T: bytemuck::Pod>(&self, value: &T) -> RenderCommand {
+        RenderCommand::SetPushConstants {
+            stages: self.stages,
+            offset: self.offset,
+            data: self.data(value),
+        }
+    }
+    /**
+    [SetPushConstants][ComputeCommand::SetPushConstants] writing `value` in the segment.
+    Panics if `value` is bigger than the segment, since it would overwrite the following ones.
+    */
+    pub fn compute_command<T: bytemuck::Pod>(&self, value: &T) -> ComputeCommand {
+        ComputeCommand::SetPushConstants {
+            stages: self.stages,
+            offset: self.offset,
+            data: self.data(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/**
+Builder of the push constant ranges of a [PipelineLayoutDescriptor][crate::PipelineLayoutDescriptor].
+Every segment is placed after the previous one, aligned to [PUSH_CONSTANT_ALIGNMENT][crate::wgpu::PUSH_CONSTANT_ALIGNMENT].
+Since wgpu allows every stage in a single range only, consecutive segments with the same stages share a range,
+while segments whose stages overlap the ones of another range are refused.
+*/
+pub struct PushConstantLayout {
+    segments: Vec<PushConstantSegment>,
+    ranges: Vec<crate::wgpu::PushConstantRange>,
+}
+impl PushConstantLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Add a segment holding a `T` visible to `stages`. The returned segment carries the offset to be used
+    when setting the push constants.
+    */
+    pub fn add<T: bytemuck::Pod>(
+        &mut self,
+        stages: crate::wgpu::ShaderStage,
+    ) -> Result<PushConstantSegment, ()> {
+        if stages.is_empty() {
+            log::error!(target: "Push Constant Layout","Failed to add a segment: no stages specified");
+            return Err(());
+        }
+        let last = self.ranges.len().checked_sub(1);
+        if let Some((index, range)) = self
+            .ranges
+            .iter()
+            .enumerate()
+            .find(|(_index, range)| range.stages.intersects(stages))
+        {
+            if range.stages != stages || Some(index) != last {
+                log::error!(target: "Push Constant Layout","Failed to add a segment: stages {:?} overlap the range {:?} of stages {:?}",stages,range.range,range.stages);
+                return Err(());
+            }
+        }
+
+        let segment = PushConstantSegment {
+            stages,
+            offset: self.size(),
+            size: align_push_constant_size(std::mem::size_of::<T>()),
+        };
+        let end = segment.offset + segment.size;
+        match self.ranges.last_mut() {
+            Some(range) if range.stages == stages => range.range.end = end,
+            _ => self.ranges.push(crate::wgpu::PushConstantRange {
+                stages,
+                range: segment.offset..end,
+            }),
+        }
+        self.segments.push(segment);
+        Ok(segment)
+    }
+
+    /// Segments in the order they have been added.
+    pub fn segments(&self) -> &[PushConstantSegment] {
+        &self.segments
+    }
+    /// Total size of the push constants.
+    pub fn size(&self) -> u32 {
+        self.segments
+            .last()
+            .map(|segment| segment.offset + segment.size)
+            .unwrap_or(0)
+    }
+    /// Ranges to be used in the [PipelineLayoutDescriptor][crate::PipelineLayoutDescriptor].
+    pub fn ranges(&self) -> Vec<crate::wgpu::PushConstantRange> {
+        self.ranges.clone()
+    }
+}
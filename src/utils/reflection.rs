@@ -0,0 +1,436 @@
+//! Shader reflection helpers.
+
+use crate::ShaderSource;
+use crate::VertexBufferLayout;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Possible errors of the shader reflection.
+pub enum ReflectionError {
+    /// The shader source failed to parse.
+    Parse(String),
+    /// The binding uses a type that cannot be expressed as a bind group layout entry.
+    UnsupportedBinding(u32),
+    /// The vertex input at the location uses a type that cannot be expressed as a vertex format.
+    UnsupportedVertexInput(u32),
+    /// The shader has no vertex entry point with the passed name.
+    MissingEntryPoint(String),
+}
+
+fn parse(source: &ShaderSource) -> Result<naga::Module, ReflectionError> {
+    match source {
+        ShaderSource::SpirV(spirv) => naga::front::spv::parse_u8_slice(
+            bytemuck::cast_slice(spirv.as_slice()),
+            &naga::front::spv::Options::default(),
+        )
+        .map_err(|err| ReflectionError::Parse(format!("{:?}", err))),
+        ShaderSource::Wgsl(wgsl) => naga::front::wgsl::parse_str(wgsl)
+            .map_err(|err| ReflectionError::Parse(format!("{:?}", err))),
+    }
+}
+
+/// Global variables accessed by a function, including the ones accessed by the functions it calls.
+fn used_globals(
+    module: &naga::Module,
+    function: &naga::Function,
+) -> HashSet<naga::Handle<naga::GlobalVariable>> {
+    let mut globals = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut functions = vec![function];
+    while let Some(function) = functions.pop() {
+        globals.extend(
+            function
+                .expressions
+                .iter()
+                .filter_map(|(_handle, expression)| match expression {
+                    naga::Expression::GlobalVariable(global) => Some(*global),
+                    _ => None,
+                }),
+        );
+        let mut calls = Vec::new();
+        called_functions(&function.body, &mut calls);
+        for call in calls {
+            if visited.insert(call) {
+                functions.push(&module.functions[call]);
+            }
+        }
+    }
+    globals
+}
+
+fn called_functions(block: &naga::Block, calls: &mut Vec<naga::Handle<naga::Function>>) {
+    for statement in block.iter() {
+        match statement {
+            naga::Statement::Block(block) => called_functions(block, calls),
+            naga::Statement::If { accept, reject, .. } => {
+                called_functions(accept, calls);
+                called_functions(reject, calls);
+            }
+            naga::Statement::Switch { cases, default, .. } => {
+                for case in cases {
+                    called_functions(&case.body, calls);
+                }
+                called_functions(default, calls);
+            }
+            naga::Statement::Loop { body, continuing } => {
+                called_functions(body, calls);
+                called_functions(continuing, calls);
+            }
+            naga::Statement::Call { function, .. } => calls.push(*function),
+            _ => {}
+        }
+    }
+}
+
+/**
+Reflect the bind group layout entries of the passed descriptor set (`group`) from a shader source.
+The visibility of every entry is the set of entry point stages using it.
+Binding arrays get their size as count, while unsized binding arrays get `None`.
+The returned entries are sorted by binding index.
+The module is not validated, since the naga validator in use rejects the binding arrays:
+the shader is validated anyway when the shader module is built.
+*/
+pub fn reflect_bind_group_layout(
+    source: &ShaderSource,
+    group: u32,
+) -> Result<Vec<crate::wgpu::BindGroupLayoutEntry>, ReflectionError> {
+    let module = parse(source)?;
+    module_bind_group_layout(&module, group)
+}
+
+/// Reflect the bind group layout entries of a parsed module, see [reflect_bind_group_layout][reflect_bind_group_layout].
+pub(crate) fn module_bind_group_layout(
+    module: &naga::Module,
+    group: u32,
+) -> Result<Vec<crate::wgpu::BindGroupLayoutEntry>, ReflectionError> {
+    let used_globals: Vec<_> = module
+        .entry_points
+        .iter()
+        .map(|entry_point| used_globals(module, &entry_point.function))
+        .collect();
+
+    let mut entries = Vec::new();
+    for (handle, variable) in module.global_variables.iter() {
+        let binding = match &variable.binding {
+            Some(binding) if binding.group == group => binding.binding,
+            _ => continue,
+        };
+
+        let visibility = module.entry_points.iter().zip(used_globals.iter()).fold(
+            crate::wgpu::ShaderStage::NONE,
+            |visibility, (entry_point, used_globals)| {
+                if used_globals.contains(&handle) {
+                    visibility | shader_stage(entry_point.stage)
+                } else {
+                    visibility
+                }
+            },
+        );
+
+        let (ty, count) =
+            binding_type(module, variable).ok_or(ReflectionError::UnsupportedBinding(binding))?;
+
+        entries.push(crate::wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty,
+            count,
+        });
+    }
+    entries.sort_by_key(|entry| entry.binding);
+    Ok(entries)
+}
+
+/**
+Check that the declared visibility of the bind group layout `entries` (for the descriptor set `group`) covers
+the stages actually accessing them in the passed entry points of the shader source.
+Returns the bindings with the stages missing from their visibility, sorted by binding index:
+a binding used by the shader but not declared at all is returned with every stage using it.
+*/
+pub fn check_visibility(
+    source: &ShaderSource,
+    entry_points: &[&str],
+    group: u32,
+    entries: &[crate::wgpu::BindGroupLayoutEntry],
+) -> Result<Vec<(u32, crate::wgpu::ShaderStage)>, ReflectionError> {
+    let module = parse(source)?;
+
+    let mut mismatches = Vec::new();
+    for (handle, variable) in module.global_variables.iter() {
+        let binding = match &variable.binding {
+            Some(binding) if binding.group == group => binding.binding,
+            _ => continue,
+        };
+
+        let used = module
+            .entry_points
+            .iter()
+            .filter(|entry_point| entry_points.contains(&entry_point.name.as_str()))
+            .filter(|entry_point| used_globals(&module, &entry_point.function).contains(&handle))
+            .fold(crate::wgpu::ShaderStage::NONE, |visibility, entry_point| {
+                visibility | shader_stage(entry_point.stage)
+            });
+        let declared = entries
+            .iter()
+            .find(|entry| entry.binding == binding)
+            .map(|entry| entry.visibility)
+            .unwrap_or(crate::wgpu::ShaderStage::NONE);
+
+        let missing = used - declared;
+        if !missing.is_empty() {
+            mismatches.push((binding, missing));
+        }
+    }
+    mismatches.sort_by_key(|(binding, _missing)| *binding);
+    Ok(mismatches)
+}
+
+/**
+Reflect the vertex buffer layout from the `location` inputs of the vertex entry point `entry_point`,
+including the ones inside input structures. Attributes are sorted by location and tightly packed in that order,
+like [vertex_attr_array][crate::wgpu::vertex_attr_array] does, so the stride is the sum of their sizes.
+Since the shader cannot tell if the inputs are per vertex or per instance, the step mode has to be passed.
+*/
+pub fn reflect_vertex_layout(
+    source: &ShaderSource,
+    entry_point: &str,
+    step_mode: crate::wgpu::InputStepMode,
+) -> Result<VertexBufferLayout, ReflectionError> {
+    let module = parse(source)?;
+    let entry_point = module
+        .entry_points
+        .iter()
+        .find(|entry| entry.stage == naga::ShaderStage::Vertex && entry.name == entry_point)
+        .ok_or_else(|| ReflectionError::MissingEntryPoint(entry_point.to_string()))?;
+
+    let mut inputs = Vec::new();
+    for argument in entry_point.function.arguments.iter() {
+        match (&argument.binding, &module.types[argument.ty].inner) {
+            (Some(binding), _) => inputs.push((binding, argument.ty)),
+            (None, naga::TypeInner::Struct { members, .. }) => {
+                inputs.extend(members.iter().filter_map(|member| {
+                    member.binding.as_ref().map(|binding| (binding, member.ty))
+                }))
+            }
+            (None, _) => {}
+        }
+    }
+
+    let mut locations = Vec::new();
+    for (binding, ty) in inputs {
+        if let naga::Binding::Location { location, .. } = binding {
+            let format = vertex_format(&module.types[ty].inner)
+                .ok_or(ReflectionError::UnsupportedVertexInput(*location))?;
+            locations.push((*location, format));
+        }
+    }
+    locations.sort_by_key(|(location, _format)| *location);
+
+    let mut offset = 0;
+    let attributes = locations
+        .into_iter()
+        .map(|(shader_location, format)| {
+            let attribute = crate::wgpu::VertexAttribute {
+                format,
+                offset,
+                shader_location,
+            };
+            offset += format.size();
+            attribute
+        })
+        .collect();
+
+    Ok(VertexBufferLayout {
+        array_stride: offset,
+        step_mode,
+        attributes,
+    })
+}
+
+fn vertex_format(inner: &naga::TypeInner) -> Option<crate::wgpu::VertexFormat> {
+    use crate::wgpu::VertexFormat as Vf;
+    let (kind, width, size) = match inner {
+        naga::TypeInner::Scalar { kind, width } => (*kind, *width, 1),
+        naga::TypeInner::Vector { size, kind, width } => (*kind, *width, *size as u8),
+        _ => return None,
+    };
+    match (kind, width, size) {
+        (naga::ScalarKind::Float, 4, 1) => Some(Vf::Float32),
+        (naga::ScalarKind::Float, 4, 2) => Some(Vf::Float32x2),
+        (naga::ScalarKind::Float, 4, 3) => Some(Vf::Float32x3),
+        (naga::ScalarKind::Float, 4, 4) => Some(Vf::Float32x4),
+        (naga::ScalarKind::Float, 8, 1) => Some(Vf::Float64),
+        (naga::ScalarKind::Float, 8, 2) => Some(Vf::Float64x2),
+        (naga::ScalarKind::Float, 8, 3) => Some(Vf::Float64x3),
+        (naga::ScalarKind::Float, 8, 4) => Some(Vf::Float64x4),
+        (naga::ScalarKind::Uint, 4, 1) => Some(Vf::Uint32),
+        (naga::ScalarKind::Uint, 4, 2) => Some(Vf::Uint32x2),
+        (naga::ScalarKind::Uint, 4, 3) => Some(Vf::Uint32x3),
+        (naga::ScalarKind::Uint, 4, 4) => Some(Vf::Uint32x4),
+        (naga::ScalarKind::Sint, 4, 1) => Some(Vf::Sint32),
+        (naga::ScalarKind::Sint, 4, 2) => Some(Vf::Sint32x2),
+        (naga::ScalarKind::Sint, 4, 3) => Some(Vf::Sint32x3),
+        (naga::ScalarKind::Sint, 4, 4) => Some(Vf::Sint32x4),
+        _ => None,
+    }
+}
+
+fn shader_stage(stage: naga::ShaderStage) -> crate::wgpu::ShaderStage {
+    match stage {
+        naga::ShaderStage::Vertex => crate::wgpu::ShaderStage::VERTEX,
+        naga::ShaderStage::Fragment => crate::wgpu::ShaderStage::FRAGMENT,
+        naga::ShaderStage::Compute => crate::wgpu::ShaderStage::COMPUTE,
+    }
+}
+
+fn binding_type(
+    module: &naga::Module,
+    variable: &naga::GlobalVariable,
+) -> Option<(crate::wgpu::BindingType, Option<std::num::NonZeroU32>)> {
+    match variable.class {
+        naga::StorageClass::Uniform => Some((
+            crate::wgpu::BindingType::Buffer {
+                ty: crate::wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            None,
+        )),
+        naga::StorageClass::Storage => Some((
+            crate::wgpu::BindingType::Buffer {
+                ty: crate::wgpu::BufferBindingType::Storage {
+                    read_only: !variable.storage_access.contains(naga::StorageAccess::STORE),
+                },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            None,
+        )),
+        naga::StorageClass::Handle => match &module.types[variable.ty].inner {
+            naga::TypeInner::Array { base, size, .. } => {
+                let count = match size {
+                    naga::ArraySize::Constant(constant) => {
+                        match module.constants[*constant].inner {
+                            naga::ConstantInner::Scalar {
+                                value: naga::ScalarValue::Uint(count),
+                                ..
+                            } => std::num::NonZeroU32::new(count as u32),
+                            naga::ConstantInner::Scalar {
+                                value: naga::ScalarValue::Sint(count),
+                                ..
+                            } => std::num::NonZeroU32::new(count as u32),
+                            _ => return None,
+                        }
+                    }
+                    naga::ArraySize::Dynamic => None,
+                };
+                handle_type(&module.types[*base].inner, variable.storage_access)
+                    .map(|ty| (ty, count))
+            }
+            inner => handle_type(inner, variable.storage_access).map(|ty| (ty, None)),
+        },
+        _ => None,
+    }
+}
+
+fn handle_type(
+    inner: &naga::TypeInner,
+    storage_access: naga::StorageAccess,
+) -> Option<crate::wgpu::BindingType> {
+    match inner {
+        naga::TypeInner::Sampler { comparison } => Some(crate::wgpu::BindingType::Sampler {
+            comparison: *comparison,
+            filtering: true,
+        }),
+        naga::TypeInner::Image {
+            dim,
+            arrayed,
+            class,
+        } => {
+            let view_dimension = match (dim, arrayed) {
+                (naga::ImageDimension::D1, _) => crate::wgpu::TextureViewDimension::D1,
+                (naga::ImageDimension::D2, false) => crate::wgpu::TextureViewDimension::D2,
+                (naga::ImageDimension::D2, true) => crate::wgpu::TextureViewDimension::D2Array,
+                (naga::ImageDimension::D3, _) => crate::wgpu::TextureViewDimension::D3,
+                (naga::ImageDimension::Cube, false) => crate::wgpu::TextureViewDimension::Cube,
+                (naga::ImageDimension::Cube, true) => crate::wgpu::TextureViewDimension::CubeArray,
+            };
+            match class {
+                naga::ImageClass::Sampled { kind, multi } => {
+                    let sample_type = match kind {
+                        naga::ScalarKind::Float => {
+                            crate::wgpu::TextureSampleType::Float { filterable: true }
+                        }
+                        naga::ScalarKind::Sint => crate::wgpu::TextureSampleType::Sint,
+                        naga::ScalarKind::Uint => crate::wgpu::TextureSampleType::Uint,
+                        naga::ScalarKind::Bool => return None,
+                    };
+                    Some(crate::wgpu::BindingType::Texture {
+                        sample_type,
+                        view_dimension,
+                        multisampled: *multi,
+                    })
+                }
+                naga::ImageClass::Depth => Some(crate::wgpu::BindingType::Texture {
+                    sample_type: crate::wgpu::TextureSampleType::Depth,
+                    view_dimension,
+                    multisampled: false,
+                }),
+                naga::ImageClass::Storage(format) => {
+                    let access = if !storage_access.contains(naga::StorageAccess::LOAD) {
+                        crate::wgpu::StorageTextureAccess::WriteOnly
+                    } else if !storage_access.contains(naga::StorageAccess::STORE) {
+                        crate::wgpu::StorageTextureAccess::ReadOnly
+                    } else {
+                        crate::wgpu::StorageTextureAccess::ReadWrite
+                    };
+                    Some(crate::wgpu::BindingType::StorageTexture {
+                        access,
+                        format: storage_format(*format),
+                        view_dimension,
+                    })
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+fn storage_format(format: naga::StorageFormat) -> crate::wgpu::TextureFormat {
+    use crate::wgpu::TextureFormat as Tf;
+    use naga::StorageFormat as Sf;
+    match format {
+        Sf::R8Unorm => Tf::R8Unorm,
+        Sf::R8Snorm => Tf::R8Snorm,
+        Sf::R8Uint => Tf::R8Uint,
+        Sf::R8Sint => Tf::R8Sint,
+        Sf::R16Uint => Tf::R16Uint,
+        Sf::R16Sint => Tf::R16Sint,
+        Sf::R16Float => Tf::R16Float,
+        Sf::Rg8Unorm => Tf::Rg8Unorm,
+        Sf::Rg8Snorm => Tf::Rg8Snorm,
+        Sf::Rg8Uint => Tf::Rg8Uint,
+        Sf::Rg8Sint => Tf::Rg8Sint,
+        Sf::R32Uint => Tf::R32Uint,
+        Sf::R32Sint => Tf::R32Sint,
+        Sf::R32Float => Tf::R32Float,
+        Sf::Rg16Uint => Tf::Rg16Uint,
+        Sf::Rg16Sint => Tf::Rg16Sint,
+        Sf::Rg16Float => Tf::Rg16Float,
+        Sf::Rgba8Unorm => Tf::Rgba8Unorm,
+        Sf::Rgba8Snorm => Tf::Rgba8Snorm,
+        Sf::Rgba8Uint => Tf::Rgba8Uint,
+        Sf::Rgba8Sint => Tf::Rgba8Sint,
+        Sf::Rgb10a2Unorm => Tf::Rgb10a2Unorm,
+        Sf::Rg11b10Float => Tf::Rg11b10Float,
+        Sf::Rg32Uint => Tf::Rg32Uint,
+        Sf::Rg32Sint => Tf::Rg32Sint,
+        Sf::Rg32Float => Tf::Rg32Float,
+        Sf::Rgba16Uint => Tf::Rgba16Uint,
+        Sf::Rgba16Sint => Tf::Rgba16Sint,
+        Sf::Rgba16Float => Tf::Rgba16Float,
+        Sf::Rgba32Uint => Tf::Rgba32Uint,
+        Sf::Rgba32Sint => Tf::Rgba32Sint,
+        Sf::Rgba32Float => Tf::Rgba32Float,
+    }
+}
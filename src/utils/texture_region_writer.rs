@@ -0,0 +1,186 @@
+use crate::ResourceWrite;
+use crate::TextureId;
+use crate::TextureWrite;
+use crate::UpdateContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Rectangular region of a texture, in texels.
+pub struct TextureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+impl TextureRegion {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    /// Returns true if the regions overlap or share an edge (or a corner).
+    pub fn touches(&self, other: &Self) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+    /// Returns the smallest region containing both regions.
+    pub fn union(&self, other: &Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Self::new(x, y, right - x, bottom - y)
+    }
+}
+
+#[derive(Debug)]
+/**
+Helper structure to partially update a texture. It keeps a CPU copy of the texture contents
+and tracks the dirty regions, so that only the changed regions are uploaded.
+Overlapping or adjacent dirty regions are merged into a single write.
+*/
+pub struct TextureRegionWriter {
+    texture: TextureId,
+    width: u32,
+    height: u32,
+    bytes_per_texel: u32,
+    shadow: Vec<u8>,
+    dirty_regions: Vec<TextureRegion>,
+}
+impl TextureRegionWriter {
+    pub fn new(texture: TextureId, width: u32, height: u32, bytes_per_texel: u32) -> Self {
+        let shadow = vec![0; (width * height * bytes_per_texel) as usize];
+        let dirty_regions = Vec::new();
+        Self {
+            texture,
+            width,
+            height,
+            bytes_per_texel,
+            shadow,
+            dirty_regions,
+        }
+    }
+
+    /// Id of the underlying texture.
+    pub fn id(&self) -> &TextureId {
+        &self.texture
+    }
+
+    /**
+    Write the tightly packed `data` on the passed region and mark it as dirty.
+    Returns false if the region is out of the texture bounds or the data size does not match.
+    */
+    pub fn write(&mut self, region: TextureRegion, data: &[u8]) -> bool {
+        if !self.contains(&region) {
+            log::error!(target: "Texture Region Writer","Failed write: {:?} out of the texture bounds",region);
+            return false;
+        }
+        let row_size = (region.width * self.bytes_per_texel) as usize;
+        if data.len() != row_size * region.height as usize {
+            log::error!(target: "Texture Region Writer","Failed write: data size {} does not match {:?}",data.len(),region);
+            return false;
+        }
+
+        for (row, src) in data.chunks_exact(row_size.max(1)).enumerate() {
+            let offset = self.texel_offset(region.x, region.y + row as u32);
+            self.shadow[offset..offset + row_size].copy_from_slice(src);
+        }
+        self.mark_dirty(region)
+    }
+
+    /// Mark a region as dirty without changing its contents.
+    pub fn mark_dirty(&mut self, region: TextureRegion) -> bool {
+        if !self.contains(&region) {
+            log::error!(target: "Texture Region Writer","Failed to mark dirty: {:?} out of the texture bounds",region);
+            return false;
+        }
+        if region.width == 0 || region.height == 0 {
+            return true;
+        }
+
+        let mut region = region;
+        // Merging can make the region touch regions that were previously disjoint, so repeat until stable.
+        while let Some(index) = self
+            .dirty_regions
+            .iter()
+            .position(|dirty| dirty.touches(&region))
+        {
+            region = region.union(&self.dirty_regions.swap_remove(index));
+        }
+        self.dirty_regions.push(region);
+        true
+    }
+
+    /// Currently dirty regions.
+    pub fn dirty_regions(&self) -> &[TextureRegion] {
+        &self.dirty_regions
+    }
+
+    /**
+    Generate a write for every dirty region and clear them.
+    Rows are padded to [COPY_BYTES_PER_ROW_ALIGNMENT][crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT].
+    */
+    pub fn take_writes(&mut self) -> Vec<TextureWrite> {
+        let alignment = crate::wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let mut dirty_regions: Vec<_> = self.dirty_regions.drain(..).collect();
+        dirty_regions.sort_by_key(|region| (region.y, region.x));
+
+        dirty_regions
+            .into_iter()
+            .map(|region| {
+                let row_size = region.width * self.bytes_per_texel;
+                let bytes_per_row = (row_size + alignment - 1) / alignment * alignment;
+                let mut data = vec![0; (bytes_per_row * region.height) as usize];
+                for row in 0..region.height {
+                    let src = self.texel_offset(region.x, region.y + row);
+                    let dst = (row * bytes_per_row) as usize;
+                    data[dst..dst + row_size as usize]
+                        .copy_from_slice(&self.shadow[src..src + row_size as usize]);
+                }
+
+                TextureWrite {
+                    texture: self.texture,
+                    mip_level: 0,
+                    origin: crate::wgpu::Origin3d {
+                        x: region.x,
+                        y: region.y,
+                        z: 0,
+                    },
+                    data,
+                    layout: crate::wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: std::num::NonZeroU32::new(bytes_per_row),
+                        rows_per_image: std::num::NonZeroU32::new(region.height),
+                    },
+                    size: crate::wgpu::Extent3d {
+                        width: region.width,
+                        height: region.height,
+                        depth_or_array_layers: 1,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Submit the writes of the dirty regions.
+    pub fn update(&mut self, update_context: &mut UpdateContext) {
+        let mut writes: Vec<_> = self
+            .take_writes()
+            .into_iter()
+            .map(ResourceWrite::Texture)
+            .collect();
+        update_context.write_resource(&mut writes);
+    }
+
+    fn contains(&self, region: &TextureRegion) -> bool {
+        region.x + region.width <= self.width && region.y + region.height <= self.height
+    }
+
+    fn texel_offset(&self, x: u32, y: u32) -> usize {
+        ((y * self.width + x) * self.bytes_per_texel) as usize
+    }
+}
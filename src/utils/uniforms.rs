@@ -0,0 +1,85 @@
+use crate::BindGroupDescriptor;
+use crate::BindGroupEntry;
+use crate::BindGroupId;
+use crate::BindGroupLayoutDescriptor;
+use crate::BindGroupLayoutEntryExt;
+use crate::BindGroupLayoutId;
+use crate::BindingResource;
+use crate::BufferBinding;
+use crate::BufferDescriptor;
+use crate::BufferId;
+use crate::BufferWrite;
+use crate::DeviceAlignments;
+use crate::DeviceId;
+use crate::ResourceWrite;
+use crate::UpdateContext;
+
+/**
+Create a uniform buffer holding `data`, a layout with a single uniform entry at binding 0 and a bind group
+binding the whole buffer, then queue the upload of the data. The layout is meant for the pipeline layout,
+while the bind group for the commands. The buffer has the `COPY_DST` usage, so it can be updated with
+[write_resource][UpdateContext::write_resource]. If any creation fails, the already created resources are removed.
+*/
+pub fn uniform_bind_group<T: bytemuck::Pod>(
+    update_context: &mut UpdateContext,
+    device: DeviceId,
+    label: &str,
+    visibility: crate::wgpu::ShaderStage,
+    data: &T,
+) -> Result<(BufferId, BindGroupLayoutId, BindGroupId), ()> {
+    let alignments: DeviceAlignments = update_context.device_alignments(device).unwrap_or_default();
+    let mut data = bytemuck::bytes_of(data).to_vec();
+    let size = alignments
+        .align_copy_size(data.len() as u64)
+        .max(alignments.copy_buffer);
+    data.resize(size as usize, 0);
+
+    let buffer = update_context.add_buffer_descriptor(BufferDescriptor {
+        label: label.to_string() + " uniform buffer",
+        device,
+        size,
+        usage: crate::wgpu::BufferUsage::UNIFORM | crate::wgpu::BufferUsage::COPY_DST,
+    })?;
+
+    let layout = match update_context.add_bind_group_layout_descriptor(BindGroupLayoutDescriptor {
+        label: label.to_string() + " bind group layout",
+        device,
+        entries: vec![crate::wgpu::BindGroupLayoutEntry::uniform_buffer(
+            0, visibility,
+        )],
+    }) {
+        Ok(layout) => layout,
+        Err(()) => {
+            let _ = update_context.remove_buffer(&buffer);
+            return Err(());
+        }
+    };
+
+    let bind_group = match update_context.add_bind_group_descriptor(BindGroupDescriptor {
+        label: label.to_string() + " bind group",
+        device,
+        layout,
+        entries: vec![BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer,
+                offset: 0,
+                size: None,
+            }),
+        }],
+    }) {
+        Ok(bind_group) => bind_group,
+        Err(()) => {
+            let _ = update_context.remove_bind_group_layout(&layout);
+            let _ = update_context.remove_buffer(&buffer);
+            return Err(());
+        }
+    };
+
+    update_context.write_resource(&mut vec![ResourceWrite::Buffer(BufferWrite {
+        buffer,
+        offset: 0,
+        data,
+    })]);
+    Ok((buffer, layout, bind_group))
+}